@@ -0,0 +1,8 @@
+drop table if exists rate_limit_buckets;
+drop table if exists audit_events;
+drop table if exists scoped_tokens;
+drop table if exists device_authorizations;
+drop table if exists profiles;
+drop table if exists user_roles;
+drop table if exists users;
+drop table if exists login_providers;
@@ -0,0 +1,81 @@
+create table login_providers (
+  user_id text not null,
+  kind varchar(255) not null,
+  id_in_provider varchar(255) not null,
+  updated_at timestamp without time zone not null,
+  PRIMARY KEY (kind, id_in_provider)
+);
+
+create table users (
+  id varchar(255) not null,
+  status varchar(255) not null default 'Active',
+  created_at timestamp without time zone not null default NOW(),
+  updated_at timestamp without time zone not null,
+  PRIMARY KEY (id)
+);
+
+create table user_roles (
+  user_id varchar(255) not null,
+  role varchar(255) not null,
+  updated_at timestamp without time zone not null,
+  PRIMARY KEY (user_id, role)
+);
+
+create table profiles (
+  user_id varchar(255) not null,
+  name varchar(20) not null,
+  display_name text not null,
+  avatar_url text not null,
+  updated_at timestamp without time zone not null,
+  primary key (user_id)
+);
+
+
+create table device_authorizations (
+  device_code varchar(255) not null,
+  user_code varchar(255) not null,
+  client_id varchar(255) not null,
+  scope text not null,
+  status varchar(255) not null,
+  user_id varchar(255),
+  interval_seconds bigint not null,
+  expires_at timestamp without time zone not null,
+  last_polled_at timestamp without time zone,
+  updated_at timestamp without time zone not null,
+  PRIMARY KEY (device_code)
+);
+
+create unique index device_authorizations_user_code_idx on device_authorizations (user_code);
+
+create table scoped_tokens (
+  token_hash varchar(255) not null,
+  user_id varchar(255) not null,
+  scope text not null,
+  expires_at timestamp without time zone not null,
+  updated_at timestamp without time zone not null,
+  PRIMARY KEY (token_hash)
+);
+
+create table audit_events (
+  id varchar(255) not null,
+  actor_id varchar(255),
+  action varchar(255) not null,
+  target text,
+  ip_address varchar(255),
+  user_agent text,
+  outcome varchar(255) not null,
+  failure_reason text,
+  occurred_at timestamp without time zone not null,
+  PRIMARY KEY (id)
+);
+
+create index audit_events_occurred_at_idx on audit_events (occurred_at);
+create index audit_events_actor_id_idx on audit_events (actor_id);
+
+create table rate_limit_buckets (
+  key varchar(255) not null,
+  window_start bigint not null,
+  count bigint not null,
+  expires_at timestamp without time zone not null,
+  PRIMARY KEY (key, window_start)
+);
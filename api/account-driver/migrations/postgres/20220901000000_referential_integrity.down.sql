@@ -0,0 +1,17 @@
+drop index if exists scoped_tokens_user_id_idx;
+drop index if exists device_authorizations_user_id_idx;
+drop index if exists login_providers_user_id_idx;
+
+alter table scoped_tokens drop constraint if exists scoped_tokens_user_id_fkey;
+alter table device_authorizations drop constraint if exists device_authorizations_user_id_fkey;
+alter table profiles drop constraint if exists profiles_user_id_fkey;
+alter table user_roles drop constraint if exists user_roles_user_id_fkey;
+alter table login_providers drop constraint if exists login_providers_user_id_fkey;
+
+alter table scoped_tokens drop column if exists created_at;
+alter table device_authorizations drop column if exists created_at;
+alter table profiles drop column if exists created_at;
+alter table user_roles drop column if exists created_at;
+alter table login_providers drop column if exists created_at;
+
+alter table login_providers alter column user_id type text;
@@ -0,0 +1,29 @@
+-- Earlier writes could leave rows behind for users that were never stored.
+delete from login_providers where user_id not in (select id from users);
+delete from user_roles where user_id not in (select id from users);
+delete from profiles where user_id not in (select id from users);
+delete from scoped_tokens where user_id not in (select id from users);
+delete from device_authorizations where user_id is not null and user_id not in (select id from users);
+
+alter table login_providers alter column user_id type varchar(255);
+
+alter table login_providers add column created_at timestamp without time zone not null default NOW();
+alter table user_roles add column created_at timestamp without time zone not null default NOW();
+alter table profiles add column created_at timestamp without time zone not null default NOW();
+alter table device_authorizations add column created_at timestamp without time zone not null default NOW();
+alter table scoped_tokens add column created_at timestamp without time zone not null default NOW();
+
+alter table login_providers
+  add constraint login_providers_user_id_fkey foreign key (user_id) references users (id) on delete cascade;
+alter table user_roles
+  add constraint user_roles_user_id_fkey foreign key (user_id) references users (id) on delete cascade;
+alter table profiles
+  add constraint profiles_user_id_fkey foreign key (user_id) references users (id) on delete cascade;
+alter table device_authorizations
+  add constraint device_authorizations_user_id_fkey foreign key (user_id) references users (id) on delete cascade;
+alter table scoped_tokens
+  add constraint scoped_tokens_user_id_fkey foreign key (user_id) references users (id) on delete cascade;
+
+create index login_providers_user_id_idx on login_providers (user_id);
+create index device_authorizations_user_id_idx on device_authorizations (user_id);
+create index scoped_tokens_user_id_idx on scoped_tokens (user_id);
@@ -0,0 +1 @@
+alter table profiles drop column if exists locale;
@@ -0,0 +1,3 @@
+drop index if exists rate_limit_buckets_expires_at_idx;
+alter table rate_limit_buckets drop constraint rate_limit_buckets_pkey;
+alter table rate_limit_buckets add primary key (key, window_start);
@@ -0,0 +1,8 @@
+-- One row per key, reset in place when a new window starts, so that a hit is counted by a single
+-- upsert. Only the latest window of each key is kept.
+delete from rate_limit_buckets stale
+    using rate_limit_buckets newer
+    where stale.key = newer.key and stale.window_start < newer.window_start;
+alter table rate_limit_buckets drop constraint rate_limit_buckets_pkey;
+alter table rate_limit_buckets add primary key (key);
+create index rate_limit_buckets_expires_at_idx on rate_limit_buckets (expires_at);
@@ -0,0 +1,4 @@
+drop index if exists device_authorizations_expires_at_idx;
+-- A digest cannot be turned back into its device code, so the pending authorizations are dropped.
+delete from device_authorizations;
+alter table device_authorizations rename column device_code_hash to device_code;
@@ -0,0 +1,5 @@
+-- Device codes are bearer secrets until exchanged, so only their SHA-256 digest is kept, like
+-- the token_hash of scoped_tokens.
+update device_authorizations set device_code = encode(sha256(convert_to(device_code, 'UTF8')), 'hex');
+alter table device_authorizations rename column device_code to device_code_hash;
+create index device_authorizations_expires_at_idx on device_authorizations (expires_at);
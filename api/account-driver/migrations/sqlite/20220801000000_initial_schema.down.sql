@@ -0,0 +1,7 @@
+drop table if exists audit_events;
+drop table if exists scoped_tokens;
+drop table if exists device_authorizations;
+drop table if exists profiles;
+drop table if exists user_roles;
+drop table if exists login_providers;
+drop table if exists users;
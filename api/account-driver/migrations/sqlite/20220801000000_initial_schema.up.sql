@@ -0,0 +1,84 @@
+-- Mirrors the postgres schema with timestamps stored as unix seconds.
+-- Rate limit buckets are not created: a single node keeps them in memory.
+create table users (
+  id varchar(255) not null,
+  status varchar(255) not null default 'Active',
+  created_at integer not null default (strftime('%s', 'now')),
+  updated_at integer not null,
+  primary key (id)
+);
+
+create table login_providers (
+  user_id varchar(255) not null references users (id) on delete cascade,
+  kind varchar(255) not null,
+  id_in_provider varchar(255) not null,
+  created_at integer not null default (strftime('%s', 'now')),
+  updated_at integer not null,
+  primary key (kind, id_in_provider)
+);
+
+create index login_providers_user_id_idx on login_providers (user_id);
+
+create table user_roles (
+  user_id varchar(255) not null references users (id) on delete cascade,
+  role varchar(255) not null,
+  created_at integer not null default (strftime('%s', 'now')),
+  updated_at integer not null,
+  primary key (user_id, role)
+);
+
+create table profiles (
+  user_id varchar(255) not null references users (id) on delete cascade,
+  name varchar(20) not null,
+  display_name text not null,
+  avatar_url text not null,
+  created_at integer not null default (strftime('%s', 'now')),
+  updated_at integer not null,
+  primary key (user_id)
+);
+
+create table device_authorizations (
+  device_code varchar(255) not null,
+  user_code varchar(255) not null,
+  client_id varchar(255) not null,
+  scope text not null,
+  status varchar(255) not null,
+  user_id varchar(255) references users (id) on delete cascade,
+  interval_seconds integer not null,
+  expires_at integer not null,
+  last_polled_at integer,
+  created_at integer not null default (strftime('%s', 'now')),
+  updated_at integer not null,
+  primary key (device_code)
+);
+
+create unique index device_authorizations_user_code_idx on device_authorizations (user_code);
+create index device_authorizations_user_id_idx on device_authorizations (user_id);
+
+create table scoped_tokens (
+  token_hash varchar(255) not null,
+  user_id varchar(255) not null references users (id) on delete cascade,
+  scope text not null,
+  expires_at integer not null,
+  created_at integer not null default (strftime('%s', 'now')),
+  updated_at integer not null,
+  primary key (token_hash)
+);
+
+create index scoped_tokens_user_id_idx on scoped_tokens (user_id);
+
+create table audit_events (
+  id varchar(255) not null,
+  actor_id varchar(255),
+  action varchar(255) not null,
+  target text,
+  ip_address varchar(255),
+  user_agent text,
+  outcome varchar(255) not null,
+  failure_reason text,
+  occurred_at integer not null,
+  primary key (id)
+);
+
+create index audit_events_occurred_at_idx on audit_events (occurred_at);
+create index audit_events_actor_id_idx on audit_events (actor_id);
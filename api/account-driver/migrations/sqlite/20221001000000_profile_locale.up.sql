@@ -0,0 +1,2 @@
+-- Language chosen by the user; null leaves it to the Accept-Language of each request.
+alter table profiles add column locale varchar(255);
@@ -0,0 +1,3 @@
+drop index if exists device_authorizations_expires_at_idx;
+delete from device_authorizations;
+alter table device_authorizations rename column device_code_hash to device_code;
@@ -0,0 +1,78 @@
+use account::adapter::firebase_auth::{
+    AccessToken, FirebaseAuthDriver, FullName, LocalId, VerifyError, VerifyResult,
+};
+use account::effect::config::HaveConfig;
+use async_trait::async_trait;
+use derive_more::Constructor;
+
+use crate::config::DefaultConfig;
+
+/// Prefix of the tokens accepted by `FakeFirebaseAuthAdapter`.
+pub const FAKE_TOKEN_PREFIX: &str = "fake:";
+
+/// Accepts `fake:<uid>` as the ID token of the Firebase user `<uid>`, without calling Google.
+/// Any other token fails to decode. Only for tests and local runs.
+#[derive(Debug, Constructor, Clone)]
+pub struct FakeFirebaseAuthAdapter(DefaultConfig);
+
+impl FakeFirebaseAuthAdapter {
+    pub fn token(uid: &str) -> String {
+        format!("{}{}", FAKE_TOKEN_PREFIX, uid)
+    }
+}
+
+impl HaveConfig for FakeFirebaseAuthAdapter {
+    type Config = DefaultConfig;
+    fn config(&self) -> &Self::Config {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl FirebaseAuthDriver for FakeFirebaseAuthAdapter {
+    async fn verify(&self, token: AccessToken) -> Result<VerifyResult, VerifyError> {
+        match token.0.strip_prefix(FAKE_TOKEN_PREFIX) {
+            Some(uid) if !uid.is_empty() => Ok(VerifyResult::new(
+                LocalId::new(uid.to_string()),
+                FullName::new(uid.to_string()),
+                None,
+            )),
+            _ => Err(VerifyError::DecodeError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FakeFirebaseAuthAdapter;
+    use crate::config::DefaultConfig;
+    use account::adapter::firebase_auth::{AccessToken, FirebaseAuthDriver, VerifyError};
+
+    fn adapter() -> FakeFirebaseAuthAdapter {
+        FakeFirebaseAuthAdapter::new(DefaultConfig::new(
+            "project".to_string(),
+            "http://localhost/device".to_string(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn fake_firebase_auth_adapter_verify_fake_token() {
+        let result = adapter()
+            .verify(AccessToken::new(FakeFirebaseAuthAdapter::token("foo")))
+            .await
+            .unwrap();
+
+        assert_eq!(result.uid.0, "foo");
+        assert_eq!(result.full_name.0, "foo");
+        assert!(result.expires_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn fake_firebase_auth_adapter_reject_other_token() {
+        for token in ["fake:", "eyJhbGciOiJSUzI1NiJ9.e30.sig"] {
+            let result = adapter().verify(AccessToken::new(token.to_string())).await;
+
+            assert!(matches!(result, Err(VerifyError::DecodeError)));
+        }
+    }
+}
@@ -8,11 +8,13 @@ use async_trait::async_trait;
 use derive_more::Constructor;
 use jsonwebtoken::jwk::{AlgorithmParameters, JwkSet};
 use jsonwebtoken::{decode, decode_header, DecodingKey, Validation};
-use reqwest::get;
-use tracing::info;
+use time::OffsetDateTime;
+use tracing::{info, info_span, Instrument};
 
 use crate::cache::{Cache, HaveCache};
 use crate::config::DefaultConfig;
+use crate::metrics;
+use crate::trace_context::trace_headers;
 
 use std::collections::HashMap;
 
@@ -32,44 +34,60 @@ impl HaveCache<JwkSet> for DefaultFirebaseAuthAdapter {
     }
 }
 
+const JWKS_URL: &str =
+    "https://www.googleapis.com/service_accounts/v1/jwk/securetoken@system.gserviceaccount.com";
+
+async fn fetch_jwks() -> Result<JwkSet, VerifyError> {
+    let jwks = reqwest::Client::new()
+        .get(JWKS_URL)
+        .headers(trace_headers())
+        .send()
+        .await
+        .with_context(|| VerifyError::GetSecurityTokenError)?
+        .json::<JwkSet>()
+        .await
+        .with_context(|| VerifyError::SecurityTokenDeserializeError)?;
+    Ok(jwks)
+}
+
 #[async_trait]
 impl FirebaseAuthDriver for DefaultFirebaseAuthAdapter {
     #[tracing::instrument(skip(token, self))]
     async fn verify(&self, token: AccessToken) -> Result<VerifyResult, VerifyError> {
-        let is_exist = self
+        let result = self.verify_token(token).await;
+        metrics::record_verification(&result);
+        result
+    }
+}
+
+impl DefaultFirebaseAuthAdapter {
+    /// The signing keys of Firebase ID tokens: cached, or fetched from Google and cached.
+    pub async fn jwks(&self) -> Result<JwkSet, VerifyError> {
+        let cached = self
             .cache()
             .lock()
             .map_err(|_| VerifyError::GetCacheStoreLockError)?
-            .contains_key("jwks");
-        if !is_exist {
-            info!("Fetch jwks from Google");
-            let jwks = get("https://www.googleapis.com/service_accounts/v1/jwk/securetoken@system.gserviceaccount.com")
-                .await
-                .with_context(|| VerifyError::GetSecurityTokenError)?
-                .json::<JwkSet>()
-                .await
-                .with_context(|| VerifyError::SecurityTokenDeserializeError)?;
-            info!("Fetched jwks from Google");
-            self.cache()
-                .lock()
-                .map_err(|_| VerifyError::GetCacheStoreLockError)?
-                .insert("jwks".to_string(), jwks);
-            info!("Writed jwks in memory-cache ");
+            .get("jwks")
+            .cloned();
+        if let Some(jwks) = cached {
+            metrics::record_jwks_cache_hit();
+            return Ok(jwks);
         }
-
-        let jwks = match self
-            .cache()
+        info!("Fetch jwks from Google");
+        let jwks = metrics::observe_jwks_fetch(fetch_jwks())
+            .instrument(info_span!("fetch_jwks"))
+            .await?;
+        info!("Fetched jwks from Google");
+        self.cache()
             .lock()
             .map_err(|_| VerifyError::GetCacheStoreLockError)?
-            .get("jwks")
-        {
-            Some(j) => j.clone(),
-            None => {
-                return Err(VerifyError::Unexpected(anyhow::anyhow!(
-                    "jwks is not found in cache"
-                )))
-            }
-        };
+            .insert("jwks".to_string(), jwks.clone());
+        info!("Writed jwks in memory-cache ");
+        Ok(jwks)
+    }
+
+    async fn verify_token(&self, token: AccessToken) -> Result<VerifyResult, VerifyError> {
+        let jwks = self.jwks().await?;
 
         let header = decode_header(&token).with_context(|| VerifyError::TokenHeaderDecodeError)?;
         let kid = match header.kid {
@@ -114,9 +132,17 @@ impl FirebaseAuthDriver for DefaultFirebaseAuthAdapter {
                         Some(v) => v,
                         None => return Err(VerifyError::IdentifyNotFoundError),
                     };
+                    let expires_at = decoded_token
+                        .claims
+                        .get("exp")
+                        .and_then(|v| v.as_i64())
+                        .map(OffsetDateTime::from_unix_timestamp)
+                        .transpose()
+                        .context(VerifyError::DecodeError)?;
                     Ok(VerifyResult::new(
                         LocalId::new(uid.to_string()),
                         FullName(name.to_string()),
+                        expires_at,
                     ))
                 }
                 _ => Err(VerifyError::Unexpected(anyhow!("Unsupported algorithm"))),
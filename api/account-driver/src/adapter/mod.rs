@@ -1 +1,3 @@
+#[cfg(any(test, feature = "test-util"))]
+pub mod fake_firebase_auth_adapter;
 pub mod firebase_auth_adapter;
@@ -0,0 +1,433 @@
+use account::effect::audit_log::{AuditEventCondition, AuditLog, AuditLogError};
+use account::model::audit_event::{
+    AuditAction, AuditContext, AuditEvent, AuditEventId, AuditOutcome,
+};
+use account::model::user::UserId;
+use account::repository::meta::{Page, Pagination};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use indoc::indoc;
+use sqlx::{query, query_as, PgPool, SqlitePool};
+use time::OffsetDateTime;
+
+use crate::db_conn::{DBBackend, HaveDBConnection, HaveSqliteConnection};
+use crate::metrics;
+use crate::repository::in_memory_database::InMemoryDatabase;
+
+#[derive(Constructor, Debug, Clone)]
+pub struct PostgresAuditLog {
+    conn: PgPool,
+}
+
+impl HaveDBConnection for PostgresAuditLog {
+    fn db_connection(&self) -> &PgPool {
+        &self.conn
+    }
+}
+
+const AUDIT_EVENTS_CONDITION: &str = indoc! {"
+    FROM audit_events
+    WHERE ($1::varchar IS NULL OR actor_id = $1)
+        AND ($2::varchar IS NULL OR action = $2)
+"};
+
+#[derive(sqlx::FromRow)]
+struct AuditEventRow {
+    id: String,
+    actor_id: Option<String>,
+    action: String,
+    target: Option<String>,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+    outcome: String,
+    failure_reason: Option<String>,
+    occurred_at: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct CountRow {
+    total: i64,
+}
+
+impl TryFrom<AuditEventRow> for AuditEvent {
+    type Error = anyhow::Error;
+    fn try_from(row: AuditEventRow) -> Result<Self, Self::Error> {
+        let outcome = match row.outcome.as_str() {
+            "Success" => AuditOutcome::Success,
+            "Failure" => AuditOutcome::Failure(row.failure_reason.unwrap_or_default()),
+            outcome => return Err(anyhow!("Unknown audit outcome: {}", outcome)),
+        };
+        Ok(AuditEvent::new(
+            AuditEventId::new(row.id),
+            row.actor_id.map(UserId::new),
+            AuditAction::try_from(row.action).context("AuditAction")?,
+            row.target,
+            AuditContext::new(row.ip_address, row.user_agent),
+            outcome,
+            OffsetDateTime::from_unix_timestamp(row.occurred_at).context("occurred_at")?,
+        ))
+    }
+}
+
+fn outcome_columns(outcome: &AuditOutcome) -> (&'static str, Option<&str>) {
+    match outcome {
+        AuditOutcome::Success => ("Success", None),
+        AuditOutcome::Failure(reason) => ("Failure", Some(reason)),
+    }
+}
+
+#[async_trait]
+impl AuditLog for PostgresAuditLog {
+    #[tracing::instrument(skip(self))]
+    async fn record(&self, event: &AuditEvent) -> Result<(), AuditLogError> {
+        let (outcome, failure_reason) = outcome_columns(&event.outcome);
+        query(indoc! {"
+            INSERT INTO audit_events (id, actor_id, action, target, ip_address, user_agent, outcome, failure_reason, occurred_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, to_timestamp($9) AT TIME ZONE 'UTC');
+        "})
+        .bind(&event.id.0)
+        .bind(event.actor.as_ref().map(|a| &a.0))
+        .bind(String::from(&event.action))
+        .bind(&event.target)
+        .bind(&event.context.ip_address)
+        .bind(&event.context.user_agent)
+        .bind(outcome)
+        .bind(failure_reason)
+        .bind(event.occurred_at.unix_timestamp())
+        .execute(self.db_connection())
+        .await
+        .context("Failed record audit event")?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn search(
+        &self,
+        condition: &AuditEventCondition,
+        pagination: &Pagination,
+    ) -> Result<Page<AuditEvent>, AuditLogError> {
+        let actor_id = condition.actor.as_ref().map(|a| a.0.clone());
+        let action = condition.action.as_ref().map(String::from);
+
+        let count = query_as::<_, CountRow>(
+            format!("SELECT COUNT(*) AS total {};", AUDIT_EVENTS_CONDITION).as_str(),
+        )
+        .bind(&actor_id)
+        .bind(&action)
+        .fetch_one(self.db_connection())
+        .await
+        .context("Failed count audit events query")?;
+        let rows = query_as::<_, AuditEventRow>(
+            format!(
+                indoc! {"
+                    SELECT id, actor_id, action, target, ip_address, user_agent, outcome, failure_reason,
+                        CAST(EXTRACT(EPOCH FROM occurred_at) AS BIGINT) AS occurred_at
+                    {}
+                    ORDER BY occurred_at DESC, id
+                    LIMIT $3 OFFSET $4;
+                "},
+                AUDIT_EVENTS_CONDITION
+            )
+            .as_str(),
+        )
+        .bind(&actor_id)
+        .bind(&action)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.db_connection())
+        .await
+        .context("Failed search audit events query")?;
+        let events = rows
+            .into_iter()
+            .map(AuditEvent::try_from)
+            .collect::<Result<Vec<AuditEvent>, anyhow::Error>>()?;
+        Ok(Page::new(events, count.total))
+    }
+}
+
+#[derive(Constructor, Debug, Clone)]
+pub struct SqliteAuditLog {
+    conn: SqlitePool,
+}
+
+impl HaveSqliteConnection for SqliteAuditLog {
+    fn sqlite_connection(&self) -> &SqlitePool {
+        &self.conn
+    }
+}
+
+const SQLITE_AUDIT_EVENTS_CONDITION: &str = indoc! {"
+    FROM audit_events
+    WHERE (?1 IS NULL OR actor_id = ?1)
+        AND (?2 IS NULL OR action = ?2)
+"};
+
+#[async_trait]
+impl AuditLog for SqliteAuditLog {
+    #[tracing::instrument(skip(self))]
+    async fn record(&self, event: &AuditEvent) -> Result<(), AuditLogError> {
+        let (outcome, failure_reason) = outcome_columns(&event.outcome);
+        query(indoc! {"
+            INSERT INTO audit_events (id, actor_id, action, target, ip_address, user_agent, outcome, failure_reason, occurred_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9);
+        "})
+        .bind(&event.id.0)
+        .bind(event.actor.as_ref().map(|a| &a.0))
+        .bind(String::from(&event.action))
+        .bind(&event.target)
+        .bind(&event.context.ip_address)
+        .bind(&event.context.user_agent)
+        .bind(outcome)
+        .bind(failure_reason)
+        .bind(event.occurred_at.unix_timestamp())
+        .execute(self.sqlite_connection())
+        .await
+        .context("Failed record audit event")?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn search(
+        &self,
+        condition: &AuditEventCondition,
+        pagination: &Pagination,
+    ) -> Result<Page<AuditEvent>, AuditLogError> {
+        let actor_id = condition.actor.as_ref().map(|a| a.0.clone());
+        let action = condition.action.as_ref().map(String::from);
+
+        let count = query_as::<_, CountRow>(
+            format!(
+                "SELECT COUNT(*) AS total {};",
+                SQLITE_AUDIT_EVENTS_CONDITION
+            )
+            .as_str(),
+        )
+        .bind(&actor_id)
+        .bind(&action)
+        .fetch_one(self.sqlite_connection())
+        .await
+        .context("Failed count audit events query")?;
+        let rows = query_as::<_, AuditEventRow>(
+            format!(
+                indoc! {"
+                    SELECT id, actor_id, action, target, ip_address, user_agent, outcome, failure_reason, occurred_at
+                    {}
+                    ORDER BY occurred_at DESC, id
+                    LIMIT ?3 OFFSET ?4;
+                "},
+                SQLITE_AUDIT_EVENTS_CONDITION
+            )
+            .as_str(),
+        )
+        .bind(&actor_id)
+        .bind(&action)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.sqlite_connection())
+        .await
+        .context("Failed search audit events query")?;
+        let events = rows
+            .into_iter()
+            .map(AuditEvent::try_from)
+            .collect::<Result<Vec<AuditEvent>, anyhow::Error>>()?;
+        Ok(Page::new(events, count.total))
+    }
+}
+
+/// Keeps audit events in process memory, next to the rows of the in-memory repositories.
+#[derive(Constructor, Debug, Clone, Default)]
+pub struct InMemoryAuditLog {
+    db: InMemoryDatabase,
+}
+
+#[async_trait]
+impl AuditLog for InMemoryAuditLog {
+    #[tracing::instrument(skip(self))]
+    async fn record(&self, event: &AuditEvent) -> Result<(), AuditLogError> {
+        let mut tables = self.db.lock()?;
+        if tables.audit_events.iter().any(|e| e.id == event.id) {
+            return Err(anyhow!("Duplicate audit event {}", event.id.0).into());
+        }
+        tables.audit_events.push(event.clone());
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn search(
+        &self,
+        condition: &AuditEventCondition,
+        pagination: &Pagination,
+    ) -> Result<Page<AuditEvent>, AuditLogError> {
+        let tables = self.db.lock()?;
+        let mut found = tables
+            .audit_events
+            .iter()
+            .filter(|e| {
+                condition
+                    .actor
+                    .as_ref()
+                    .is_none_or(|a| e.actor.as_ref() == Some(a))
+            })
+            .filter(|e| condition.action.is_none_or(|a| e.action == a))
+            .collect::<Vec<_>>();
+        found.sort_by(|a, b| {
+            b.occurred_at
+                .cmp(&a.occurred_at)
+                .then_with(|| a.id.0.cmp(&b.id.0))
+        });
+        let events = found
+            .iter()
+            .skip(pagination.offset.max(0) as usize)
+            .take(pagination.limit.max(0) as usize)
+            .map(|e| (*e).clone())
+            .collect();
+        Ok(Page::new(events, found.len() as i64))
+    }
+}
+
+/// Audit log selected at start up by `database.backend`.
+#[derive(Debug, Clone)]
+pub enum DefaultAuditLog {
+    Postgres(PostgresAuditLog),
+    Sqlite(SqliteAuditLog),
+}
+
+impl DefaultAuditLog {
+    fn backend(&self) -> DBBackend {
+        match self {
+            Self::Postgres(_) => DBBackend::Postgres,
+            Self::Sqlite(_) => DBBackend::Sqlite,
+        }
+    }
+}
+
+#[async_trait]
+impl AuditLog for DefaultAuditLog {
+    async fn record(&self, event: &AuditEvent) -> Result<(), AuditLogError> {
+        let query = match self {
+            Self::Postgres(l) => l.record(event),
+            Self::Sqlite(l) => l.record(event),
+        };
+        metrics::observe_query("audit_log", "record", self.backend(), query).await
+    }
+
+    async fn search(
+        &self,
+        condition: &AuditEventCondition,
+        pagination: &Pagination,
+    ) -> Result<Page<AuditEvent>, AuditLogError> {
+        let query = match self {
+            Self::Postgres(l) => l.search(condition, pagination),
+            Self::Sqlite(l) => l.search(condition, pagination),
+        };
+        metrics::observe_query("audit_log", "search", self.backend(), query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InMemoryAuditLog, PostgresAuditLog, SqliteAuditLog};
+    use crate::db_conn::{test_sqlite_pool, TestDBConnection, TestDBInterface};
+    use account::effect::audit_log::{AuditEventCondition, AuditLog};
+    use account::model::audit_event::{
+        AuditAction, AuditContext, AuditEvent, AuditEventId, AuditOutcome,
+    };
+    use account::model::user::UserId;
+    use account::repository::meta::Pagination;
+    use time::OffsetDateTime;
+
+    fn event(id: &str, actor: &str, outcome: AuditOutcome, occurred_at: i64) -> AuditEvent {
+        AuditEvent::new(
+            AuditEventId::new(id.to_string()),
+            Some(UserId::new(actor.to_string())),
+            AuditAction::Authenticated,
+            None,
+            AuditContext::new(Some("127.0.0.1".to_string()), Some("curl".to_string())),
+            outcome,
+            OffsetDateTime::from_unix_timestamp(occurred_at).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_audit_log_record_and_search() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let audit_log = PostgresAuditLog::new(db_conn.conn.clone());
+        let older = event("event1", "foo", AuditOutcome::Success, 1_600_000_000);
+        let newer = event(
+            "event2",
+            "foo",
+            AuditOutcome::Failure("Token expired.".to_string()),
+            1_600_000_001,
+        );
+        let other = event("event3", "bar", AuditOutcome::Success, 1_600_000_002);
+        for e in [&older, &newer, &other] {
+            audit_log.record(e).await.unwrap();
+        }
+        let page = audit_log
+            .search(
+                &AuditEventCondition::new(Some(UserId::new("foo".to_string())), None),
+                &Pagination::default(),
+            )
+            .await;
+        db_conn.flush().await;
+
+        let page = page.unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items, vec![newer, older]);
+    }
+
+    #[tokio::test]
+    async fn sqlite_audit_log_record_and_search() {
+        let audit_log = SqliteAuditLog::new(test_sqlite_pool().await);
+        let older = event("event1", "foo", AuditOutcome::Success, 1_600_000_000);
+        let newer = event(
+            "event2",
+            "foo",
+            AuditOutcome::Failure("Token expired.".to_string()),
+            1_600_000_001,
+        );
+        let other = event("event3", "bar", AuditOutcome::Success, 1_600_000_002);
+        for e in [&older, &newer, &other] {
+            audit_log.record(e).await.unwrap();
+        }
+        let page = audit_log
+            .search(
+                &AuditEventCondition::new(Some(UserId::new("foo".to_string())), None),
+                &Pagination::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items, vec![newer, older]);
+    }
+
+    #[tokio::test]
+    async fn in_memory_audit_log_record_and_search() {
+        let audit_log = InMemoryAuditLog::default();
+        let older = event("event1", "foo", AuditOutcome::Success, 1_600_000_000);
+        let newer = event(
+            "event2",
+            "foo",
+            AuditOutcome::Failure("Token expired.".to_string()),
+            1_600_000_001,
+        );
+        let other = event("event3", "bar", AuditOutcome::Success, 1_600_000_002);
+        for e in [&older, &newer, &other] {
+            audit_log.record(e).await.unwrap();
+        }
+        let page = audit_log
+            .search(
+                &AuditEventCondition::new(Some(UserId::new("foo".to_string())), None),
+                &Pagination::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items, vec![newer, older]);
+        assert!(audit_log.record(&other).await.is_err());
+    }
+}
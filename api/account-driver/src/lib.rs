@@ -1,9 +1,17 @@
 pub mod adapter;
+pub mod audit_log;
 pub mod cache;
 pub mod config;
 pub mod db_conn;
 pub mod id_generator;
+pub mod metrics;
+pub mod migration;
+pub mod rate_limit_store;
+pub mod replica;
 pub mod repository;
+pub mod trace_context;
+pub mod user_code_generator;
+pub mod verified_token_cache;
 
 #[cfg(test)]
 mod tests {
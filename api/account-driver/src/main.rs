@@ -4,7 +4,6 @@ use account_driver::config::DefaultConfig;
 use std::collections::HashMap;
 use std::env;
 use std::sync::{Arc, Mutex};
-use tracing_subscriber;
 
 #[tokio::main]
 async fn main() {
@@ -24,10 +23,9 @@ async fn main() {
             unreachable!("token is not found")
         }
     };
-    let config = DefaultConfig::new(project_id.clone(), 5);
+    let config = DefaultConfig::new(project_id.clone(), String::new());
     let cache = Arc::new(Mutex::new(HashMap::new()));
     let adapter = DefaultFirebaseAuthAdapter::new(config, cache);
     let verify_result = adapter.verify(AccessToken::new(token.clone())).await;
-    let verify_result = adapter.verify(AccessToken::new(token.clone())).await;
     println!("{:?}", verify_result);
 }
@@ -0,0 +1,311 @@
+//! Prometheus metrics of the drivers, registered in the default registry that `account-http`
+//! renders at `/metrics`.
+use account::adapter::firebase_auth::{VerifyError, VerifyResult};
+use prometheus::core::{Collector, Desc};
+use prometheus::proto::MetricFamily;
+use prometheus::{
+    register_histogram, register_histogram_vec, register_int_counter_vec, register_int_gauge_vec,
+    Histogram, HistogramVec, IntCounterVec, IntGaugeVec, Opts,
+};
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use crate::db_conn::{DBBackend, DBPool};
+
+struct Metrics {
+    query_seconds: HistogramVec,
+    queries_in_flight: IntGaugeVec,
+    verifications: IntCounterVec,
+    jwks_cache: IntCounterVec,
+    jwks_fetch_seconds: Histogram,
+    verified_token_cache: IntCounterVec,
+}
+
+fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics {
+        query_seconds: register_histogram_vec!(
+            "db_query_duration_seconds",
+            "Time a repository operation took, waiting for a pooled connection included.",
+            &["repository", "operation", "backend"]
+        )
+        .unwrap(),
+        queries_in_flight: register_int_gauge_vec!(
+            "db_queries_in_flight",
+            "Repository operations running or waiting for a pooled connection.",
+            &["backend"]
+        )
+        .unwrap(),
+        verifications: register_int_counter_vec!(
+            "firebase_token_verifications_total",
+            "Firebase ID token verifications, by `ok` or the `VerifyError` they failed with.",
+            &["result"]
+        )
+        .unwrap(),
+        jwks_cache: register_int_counter_vec!(
+            "jwks_cache_total",
+            "Lookups of the Google JWKS, served by the cache (`hit`) or fetched (`refresh`).",
+            &["result"]
+        )
+        .unwrap(),
+        jwks_fetch_seconds: register_histogram!(
+            "jwks_fetch_duration_seconds",
+            "Time fetching the Google JWKS took."
+        )
+        .unwrap(),
+        verified_token_cache: register_int_counter_vec!(
+            "verified_token_cache_requests_total",
+            "Lookups of verified ID tokens, served by the cache (`hit`) or verified again (`miss`).",
+            &["result"]
+        )
+        .unwrap(),
+    })
+}
+
+/// Registers the driver metrics, so that they are rendered before their first sample.
+pub fn register() {
+    metrics();
+}
+
+fn backend_label(backend: DBBackend) -> &'static str {
+    match backend {
+        DBBackend::Postgres => "postgres",
+        DBBackend::Sqlite => "sqlite",
+    }
+}
+
+/// Leaves `db_queries_in_flight` on drop, so that a cancelled operation is not left counted.
+struct InFlight(&'static str);
+
+impl InFlight {
+    fn enter(backend: &'static str) -> Self {
+        metrics()
+            .queries_in_flight
+            .with_label_values(&[backend])
+            .inc();
+        InFlight(backend)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        metrics()
+            .queries_in_flight
+            .with_label_values(&[self.0])
+            .dec();
+    }
+}
+
+/// Runs the `operation` of `repository`, recording how long it took on `backend`.
+pub async fn observe_query<F: Future>(
+    repository: &'static str,
+    operation: &'static str,
+    backend: DBBackend,
+    query: F,
+) -> F::Output {
+    let backend = backend_label(backend);
+    let _in_flight = InFlight::enter(backend);
+    let start = Instant::now();
+    let output = query.await;
+    metrics()
+        .query_seconds
+        .with_label_values(&[repository, operation, backend])
+        .observe(start.elapsed().as_secs_f64());
+    output
+}
+
+/// The `result` label of a verification failing with `error`.
+pub fn verify_error_label(error: &VerifyError) -> &'static str {
+    match error {
+        VerifyError::TokenExpired => "token_expired",
+        VerifyError::UserDisabled(_) => "user_disabled",
+        VerifyError::UserNotFound(_) => "user_not_found",
+        VerifyError::InvalidatedApiKey => "invalidated_api_key",
+        VerifyError::GetSecurityTokenError => "get_security_token_error",
+        VerifyError::SecurityTokenDeserializeError => "security_token_deserialize_error",
+        VerifyError::TokenHeaderDecodeError => "token_header_decode_error",
+        VerifyError::DecodeError => "decode_error",
+        VerifyError::IdentifyNotFoundError => "identify_not_found_error",
+        VerifyError::GetCacheStoreLockError => "get_cache_store_lock_error",
+        VerifyError::Unexpected(_) => "unexpected",
+    }
+}
+
+pub fn record_verification(result: &Result<VerifyResult, VerifyError>) {
+    let label = match result {
+        Ok(_) => "ok",
+        Err(e) => verify_error_label(e),
+    };
+    metrics().verifications.with_label_values(&[label]).inc();
+}
+
+pub fn record_jwks_cache_hit() {
+    metrics().jwks_cache.with_label_values(&["hit"]).inc();
+}
+
+/// Runs `fetch`, counted as a `refresh` of the JWKS cache.
+pub async fn observe_jwks_fetch<F: Future>(fetch: F) -> F::Output {
+    metrics().jwks_cache.with_label_values(&["refresh"]).inc();
+    let _timer = metrics().jwks_fetch_seconds.start_timer();
+    fetch.await
+}
+
+pub fn record_verified_token_cache(hit: bool) {
+    let label = if hit { "hit" } else { "miss" };
+    metrics()
+        .verified_token_cache
+        .with_label_values(&[label])
+        .inc();
+}
+
+/// Samples the connections of a pool when it is scraped. sqlx does not report how long an
+/// acquire waited, so `db_pool_waiting` counts the repository operations in flight that no
+/// connection in use can account for.
+pub struct PoolMetrics {
+    pool: DBPool,
+    max_connections: u32,
+    connections: IntGaugeVec,
+    idle: IntGaugeVec,
+    max: IntGaugeVec,
+    waiting: IntGaugeVec,
+}
+
+impl PoolMetrics {
+    pub fn new(pool: DBPool, max_connections: u32) -> Self {
+        let gauge =
+            |name: &str, help: &str| IntGaugeVec::new(Opts::new(name, help), &["backend"]).unwrap();
+        PoolMetrics {
+            pool,
+            max_connections,
+            connections: gauge("db_pool_connections", "Connections open in the pool."),
+            idle: gauge("db_pool_idle_connections", "Open connections not in use."),
+            max: gauge("db_pool_max_connections", "Connections the pool may open."),
+            waiting: gauge(
+                "db_pool_waiting",
+                "Repository operations waiting for a pooled connection.",
+            ),
+        }
+    }
+
+    fn gauges(&self) -> [&IntGaugeVec; 4] {
+        [&self.connections, &self.idle, &self.max, &self.waiting]
+    }
+}
+
+impl Collector for PoolMetrics {
+    fn desc(&self) -> Vec<&Desc> {
+        self.gauges().into_iter().flat_map(|g| g.desc()).collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let (backend, size, idle) = match &self.pool {
+            DBPool::Postgres(pool) => (DBBackend::Postgres, pool.size(), pool.num_idle()),
+            DBPool::Sqlite(pool) => (DBBackend::Sqlite, pool.size(), pool.num_idle()),
+        };
+        let backend = backend_label(backend);
+        let in_use = i64::from(size) - idle as i64;
+        let in_flight = metrics()
+            .queries_in_flight
+            .with_label_values(&[backend])
+            .get();
+        let samples = [
+            i64::from(size),
+            idle as i64,
+            i64::from(self.max_connections),
+            (in_flight - in_use).max(0),
+        ];
+        for (gauge, value) in self.gauges().into_iter().zip(samples) {
+            gauge.with_label_values(&[backend]).set(value);
+        }
+        self.gauges()
+            .into_iter()
+            .flat_map(|g| g.collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counter(vec: &IntCounterVec, label: &str) -> u64 {
+        vec.with_label_values(&[label]).get()
+    }
+
+    #[tokio::test]
+    async fn observe_query_record_latency_while_in_flight() {
+        let histogram = || {
+            metrics()
+                .query_seconds
+                .with_label_values(&["user", "resolve", "sqlite"])
+                .get_sample_count()
+        };
+        let before = histogram();
+        let in_flight = observe_query("user", "resolve", DBBackend::Sqlite, async {
+            metrics()
+                .queries_in_flight
+                .with_label_values(&["sqlite"])
+                .get()
+        })
+        .await;
+
+        assert!(in_flight >= 1);
+        assert_eq!(histogram(), before + 1);
+    }
+
+    #[test]
+    fn record_verification_count_each_variant() {
+        let before = counter(&metrics().verifications, "token_expired");
+        record_verification(&Err(VerifyError::TokenExpired));
+        record_verification(&Ok(VerifyResult::default()));
+
+        assert_eq!(
+            counter(&metrics().verifications, "token_expired"),
+            before + 1
+        );
+        assert!(counter(&metrics().verifications, "ok") >= 1);
+    }
+
+    #[test]
+    fn verified_token_cache_count_hits_and_misses() {
+        use crate::verified_token_cache::LruVerifiedTokenCache;
+        use account::effect::verified_token_cache::VerifiedTokenCache;
+        use account::model::user::{User, UserId};
+        use std::num::NonZeroUsize;
+        use time::{Duration, OffsetDateTime};
+
+        let cache =
+            LruVerifiedTokenCache::new(NonZeroUsize::new(1).unwrap(), Duration::seconds(60));
+        let now = OffsetDateTime::now_utc();
+        let hits = counter(&metrics().verified_token_cache, "hit");
+        let misses = counter(&metrics().verified_token_cache, "miss");
+        cache.get("token", now);
+        cache.put(
+            "token",
+            &User::new(UserId::new("foo".to_string()), None),
+            None,
+            now,
+        );
+        cache.get("token", now);
+
+        assert!(counter(&metrics().verified_token_cache, "hit") > hits);
+        assert!(counter(&metrics().verified_token_cache, "miss") > misses);
+    }
+
+    #[tokio::test]
+    async fn pool_metrics_sample_pool_on_collect() {
+        let pool = crate::db_conn::test_sqlite_pool().await;
+        let pool_metrics = PoolMetrics::new(DBPool::Sqlite(pool.clone()), 1);
+        let _conn = pool.acquire().await.unwrap();
+        let families = pool_metrics.collect();
+
+        let value = |name: &str| {
+            let family = families.iter().find(|f| f.get_name() == name).unwrap();
+            family.get_metric()[0].get_gauge().get_value()
+        };
+        assert_eq!(value("db_pool_max_connections"), 1.0);
+        assert_eq!(value("db_pool_connections"), 1.0);
+        assert_eq!(value("db_pool_idle_connections"), 0.0);
+    }
+}
@@ -0,0 +1,238 @@
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::db_conn::DBPool;
+
+/// Schema versions under `account-driver/migrations/postgres`, compiled into the binary.
+pub static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+/// Schema versions under `account-driver/migrations/sqlite`, compiled into the binary.
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+fn migrator(pool: &DBPool) -> &'static Migrator {
+    match pool {
+        DBPool::Postgres(_) => &POSTGRES_MIGRATOR,
+        DBPool::Sqlite(_) => &SQLITE_MIGRATOR,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Pending,
+    Applied,
+    /// Applied, but the file changed since. `run` refuses to continue until it is restored.
+    Modified,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+}
+
+/// Version of the schema `database/account/migrate.sql` created with psqldef, before the
+/// migrations were managed by sqlx.
+pub const LEGACY_SCHEMA_VERSION: i64 = 20220801000000;
+
+/// Records `LEGACY_SCHEMA_VERSION` as applied on a database psqldef created: one with `users`
+/// but no `_sqlx_migrations`. Returns whether it did. Other databases are left untouched, so
+/// it is safe to call before every `run`.
+pub async fn baseline(pool: &DBPool) -> Result<bool, MigrateError> {
+    let pool = match pool {
+        DBPool::Postgres(p) => p,
+        // SQLite databases have always been created by the migrations.
+        DBPool::Sqlite(_) => return Ok(false),
+    };
+    let (legacy,): (bool,) = sqlx::query_as(
+        "SELECT to_regclass('users') IS NOT NULL AND to_regclass('_sqlx_migrations') IS NULL",
+    )
+    .fetch_one(pool)
+    .await?;
+    if !legacy {
+        return Ok(false);
+    }
+    let migration = POSTGRES_MIGRATOR
+        .iter()
+        .find(|m| m.version == LEGACY_SCHEMA_VERSION && !m.migration_type.is_down_migration())
+        .ok_or(MigrateError::VersionMissing(LEGACY_SCHEMA_VERSION))?;
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    // Another instance may baseline at the same time.
+    sqlx::query(
+        "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+         VALUES ($1, $2, TRUE, $3, 0) ON CONFLICT (version) DO NOTHING",
+    )
+    .bind(migration.version)
+    .bind(&*migration.description)
+    .bind(&*migration.checksum)
+    .execute(&mut *conn)
+    .await?;
+    Ok(true)
+}
+
+/// Applies every pending migration, after adopting a database psqldef created with
+/// `baseline`. Concurrent callers wait on a database lock.
+pub async fn run(pool: &DBPool) -> Result<(), MigrateError> {
+    if baseline(pool).await? {
+        info!(
+            version = LEGACY_SCHEMA_VERSION,
+            "recorded schema created by psqldef as migrated"
+        );
+    }
+    match pool {
+        DBPool::Postgres(p) => POSTGRES_MIGRATOR.run(p).await,
+        DBPool::Sqlite(p) => SQLITE_MIGRATOR.run(p).await,
+    }
+}
+
+/// Checksums of the applied migrations, by version. Reads `_sqlx_migrations` without creating
+/// it, so that a status check never runs DDL; a missing table means nothing is applied.
+async fn applied_checksums(pool: &DBPool) -> Result<HashMap<i64, Vec<u8>>, sqlx::Error> {
+    let rows: Vec<(i64, Vec<u8>)> = match pool {
+        DBPool::Postgres(p) => {
+            let (exists,): (bool,) =
+                sqlx::query_as("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+                    .fetch_one(p)
+                    .await?;
+            if !exists {
+                return Ok(HashMap::new());
+            }
+            sqlx::query_as("SELECT version, checksum FROM _sqlx_migrations WHERE success")
+                .fetch_all(p)
+                .await?
+        }
+        DBPool::Sqlite(p) => {
+            let (exists,): (bool,) = sqlx::query_as(
+                "SELECT count(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+            )
+            .fetch_one(p)
+            .await?;
+            if !exists {
+                return Ok(HashMap::new());
+            }
+            sqlx::query_as("SELECT version, checksum FROM _sqlx_migrations WHERE success")
+                .fetch_all(p)
+                .await?
+        }
+    };
+    Ok(rows.into_iter().collect())
+}
+
+pub async fn status(pool: &DBPool) -> Result<Vec<MigrationStatus>, MigrateError> {
+    let applied = applied_checksums(pool).await?;
+    Ok(migrator(pool)
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            state: match applied.get(&m.version) {
+                None => MigrationState::Pending,
+                Some(checksum) if *checksum == *m.checksum => MigrationState::Applied,
+                Some(_) => MigrationState::Modified,
+            },
+        })
+        .collect())
+}
+
+/// Reverts applied migrations newer than `target`, or only the latest one when `target` is
+/// `None`. Returns the version the schema is left at, `0` being an empty schema.
+pub async fn rollback(pool: &DBPool, target: Option<i64>) -> Result<i64, MigrateError> {
+    let target = match target {
+        Some(t) => t,
+        None => {
+            let mut applied = status(pool)
+                .await?
+                .into_iter()
+                .filter(|s| s.state != MigrationState::Pending)
+                .map(|s| s.version)
+                .collect::<Vec<_>>();
+            applied.pop();
+            applied.pop().unwrap_or(0)
+        }
+    };
+    match pool {
+        DBPool::Postgres(p) => POSTGRES_MIGRATOR.undo(p, target).await?,
+        DBPool::Sqlite(p) => SQLITE_MIGRATOR.undo(p, target).await?,
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{baseline, rollback, run, status, MigrationState, LEGACY_SCHEMA_VERSION};
+    use crate::db_conn::{test_sqlite_pool, DBPool, TestDBConnection, TestDBInterface};
+
+    async fn assert_rollback_and_run_again(pool: &DBPool) {
+        assert!(status(pool)
+            .await
+            .unwrap()
+            .iter()
+            .all(|s| s.state == MigrationState::Applied));
+
+        assert_eq!(rollback(pool, Some(0)).await.unwrap(), 0);
+        let after_rollback = status(pool).await.unwrap();
+        run(pool).await.unwrap();
+        let after_run = status(pool).await.unwrap();
+
+        assert!(after_rollback
+            .iter()
+            .all(|s| s.state == MigrationState::Pending));
+        assert!(after_run.iter().all(|s| s.state == MigrationState::Applied));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn migration_rollback_and_run_again() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        assert_rollback_and_run_again(&DBPool::Postgres(db_conn.conn.clone())).await;
+    }
+
+    #[tokio::test]
+    async fn status_report_pending_without_creating_migrations_table() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let pending = status(&DBPool::Sqlite(pool.clone())).await.unwrap();
+        let created = sqlx::query("SELECT 1 FROM _sqlx_migrations")
+            .execute(&pool)
+            .await
+            .is_ok();
+
+        assert!(pending.iter().all(|s| s.state == MigrationState::Pending));
+        assert!(!created);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn migration_run_adopt_schema_created_by_psqldef() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let pool = DBPool::Postgres(db_conn.conn.clone());
+        rollback(&pool, Some(LEGACY_SCHEMA_VERSION)).await.unwrap();
+        sqlx::query("DROP TABLE _sqlx_migrations")
+            .execute(&db_conn.conn)
+            .await
+            .unwrap();
+
+        let adopted = baseline(&pool).await.unwrap();
+        let again = baseline(&pool).await.unwrap();
+        run(&pool).await.unwrap();
+
+        assert!(adopted);
+        assert!(!again);
+        assert!(status(&pool)
+            .await
+            .unwrap()
+            .iter()
+            .all(|s| s.state == MigrationState::Applied));
+    }
+
+    #[tokio::test]
+    async fn sqlite_migration_rollback_and_run_again() {
+        assert_rollback_and_run_again(&DBPool::Sqlite(test_sqlite_pool().await)).await;
+    }
+}
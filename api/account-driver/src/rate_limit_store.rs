@@ -0,0 +1,254 @@
+use crate::db_conn::HaveDBConnection;
+use account::effect::rate_limit_store::{RateLimitStore, RateLimitStoreError};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use indoc::indoc;
+use sqlx::{query, query_as, PgPool};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
+
+/// Number of keys kept before expired windows are swept from memory.
+const IN_MEMORY_SWEEP_THRESHOLD: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    window_start: i64,
+    expires_at: i64,
+    count: i64,
+}
+
+#[derive(Default)]
+struct Buckets {
+    by_key: HashMap<String, Bucket>,
+    /// Window start from which the next sweep may run, so that a map kept over the threshold
+    /// by live keys is scanned once per window rather than on every hit.
+    next_sweep_at: i64,
+}
+
+/// Process local counters. Limits are enforced per replica.
+#[derive(Clone, Default)]
+pub struct InMemoryRateLimitStore {
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+#[async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+    async fn increment(
+        &self,
+        key: &str,
+        window_start: i64,
+        window_seconds: i64,
+    ) -> Result<i64, RateLimitStoreError> {
+        let mut buckets = self
+            .buckets
+            .lock()
+            .map_err(|_| anyhow!("rate limit buckets are poisoned"))?;
+        if buckets.by_key.len() > IN_MEMORY_SWEEP_THRESHOLD && window_start >= buckets.next_sweep_at
+        {
+            buckets.by_key.retain(|_, b| b.expires_at > window_start);
+            buckets.next_sweep_at = window_start + window_seconds;
+        }
+        let bucket = buckets.by_key.entry(key.to_string()).or_insert(Bucket {
+            window_start,
+            expires_at: window_start + window_seconds,
+            count: 0,
+        });
+        if bucket.window_start != window_start {
+            *bucket = Bucket {
+                window_start,
+                expires_at: window_start + window_seconds,
+                count: 0,
+            };
+        }
+        bucket.count += 1;
+        Ok(bucket.count)
+    }
+}
+
+/// Counters in `rate_limit_buckets`, so limits hold across replicas.
+#[derive(Constructor, Debug, Clone)]
+pub struct PostgresRateLimitStore {
+    conn: PgPool,
+}
+
+impl PostgresRateLimitStore {
+    /// Deletes the buckets of keys that were not hit since their window expired.
+    pub async fn sweep_expired(&self) -> anyhow::Result<u64> {
+        let done =
+            query("DELETE FROM rate_limit_buckets WHERE expires_at < NOW() AT TIME ZONE 'UTC';")
+                .execute(self.db_connection())
+                .await
+                .context("Failed sweep rate limit buckets")?;
+        Ok(done.rows_affected())
+    }
+
+    /// Sweeps expired buckets every `interval`. A failed sweep is logged and tried again.
+    pub fn sweep_every(&self, interval: Duration) {
+        let store = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                if let Err(e) = store.sweep_expired().await {
+                    warn!("{:#}", e);
+                }
+            }
+        });
+    }
+}
+
+impl HaveDBConnection for PostgresRateLimitStore {
+    fn db_connection(&self) -> &PgPool {
+        &self.conn
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CountRow {
+    count: i64,
+}
+
+#[async_trait]
+impl RateLimitStore for PostgresRateLimitStore {
+    async fn increment(
+        &self,
+        key: &str,
+        window_start: i64,
+        window_seconds: i64,
+    ) -> Result<i64, RateLimitStoreError> {
+        // A hit in a newer window resets the row in place, so concurrent hits never race
+        // between reading and writing the count.
+        let row = query_as::<_, CountRow>(indoc! {"
+            INSERT INTO rate_limit_buckets (key, window_start, count, expires_at)
+            VALUES ($1, $2, 1, to_timestamp($3) AT TIME ZONE 'UTC')
+            ON CONFLICT ON CONSTRAINT rate_limit_buckets_pkey
+            DO UPDATE SET
+                count=CASE WHEN rate_limit_buckets.window_start < $2
+                    THEN 1 ELSE rate_limit_buckets.count + 1 END,
+                expires_at=CASE WHEN rate_limit_buckets.window_start < $2
+                    THEN EXCLUDED.expires_at ELSE rate_limit_buckets.expires_at END,
+                window_start=GREATEST(rate_limit_buckets.window_start, $2)
+            RETURNING count;
+        "})
+        .bind(key)
+        .bind(window_start)
+        .bind(window_start + window_seconds)
+        .fetch_one(self.db_connection())
+        .await
+        .context("Failed increment rate limit bucket")?;
+        Ok(row.count)
+    }
+}
+
+/// Store selected at start up by `ACCOUNT_RATE_LIMIT_STORE`.
+#[derive(Clone)]
+pub enum DefaultRateLimitStore {
+    InMemory(InMemoryRateLimitStore),
+    Postgres(PostgresRateLimitStore),
+}
+
+#[async_trait]
+impl RateLimitStore for DefaultRateLimitStore {
+    async fn increment(
+        &self,
+        key: &str,
+        window_start: i64,
+        window_seconds: i64,
+    ) -> Result<i64, RateLimitStoreError> {
+        match self {
+            Self::InMemory(s) => s.increment(key, window_start, window_seconds).await,
+            Self::Postgres(s) => s.increment(key, window_start, window_seconds).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InMemoryRateLimitStore, PostgresRateLimitStore, IN_MEMORY_SWEEP_THRESHOLD};
+    use crate::db_conn::{TestDBConnection, TestDBInterface};
+    use account::effect::rate_limit_store::RateLimitStore;
+
+    #[tokio::test]
+    async fn in_memory_rate_limit_store_reset_count_on_new_window() {
+        let store = InMemoryRateLimitStore::default();
+        assert_eq!(store.increment("ip:1", 0, 60).await.unwrap(), 1);
+        assert_eq!(store.increment("ip:1", 0, 60).await.unwrap(), 2);
+        assert_eq!(store.increment("ip:2", 0, 60).await.unwrap(), 1);
+        assert_eq!(store.increment("ip:1", 60, 60).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn in_memory_rate_limit_store_sweep_at_most_once_per_window() {
+        let store = InMemoryRateLimitStore::default();
+        let len = || store.buckets.lock().unwrap().by_key.len();
+        for i in 0..=IN_MEMORY_SWEEP_THRESHOLD {
+            store.increment(&format!("old:{}", i), 0, 60).await.unwrap();
+        }
+        store.increment("a", 60, 60).await.unwrap();
+        let after_sweep = len();
+        for i in 0..=IN_MEMORY_SWEEP_THRESHOLD {
+            store
+                .increment(&format!("short:{}", i), 60, 1)
+                .await
+                .unwrap();
+        }
+        store.increment("b", 61, 1).await.unwrap();
+        let same_window = len();
+        store.increment("c", 120, 60).await.unwrap();
+
+        assert_eq!(after_sweep, 1);
+        assert_eq!(same_window, IN_MEMORY_SWEEP_THRESHOLD + 3);
+        assert_eq!(len(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_rate_limit_store_increment() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let store = PostgresRateLimitStore::new(db_conn.conn.clone());
+        let first = store.increment("ip:1", 0, 60).await.unwrap();
+        let second = store.increment("ip:1", 0, 60).await.unwrap();
+        let next_window = store.increment("ip:1", 60, 60).await.unwrap();
+        db_conn.flush().await;
+
+        assert_eq!((first, second, next_window), (1, 2, 1));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_rate_limit_store_count_concurrent_hits() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let store = PostgresRateLimitStore::new(db_conn.conn.clone());
+        let hits: Vec<_> = (0..10)
+            .map(|_| {
+                let store = store.clone();
+                tokio::spawn(async move { store.increment("ip:1", 0, 60).await.unwrap() })
+            })
+            .collect();
+        let mut counts = Vec::new();
+        for hit in hits {
+            counts.push(hit.await.unwrap());
+        }
+        db_conn.flush().await;
+        counts.sort_unstable();
+
+        assert_eq!(counts, (1..=10).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_rate_limit_store_sweep_expired() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let store = PostgresRateLimitStore::new(db_conn.conn.clone());
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        store.increment("ip:expired", 0, 60).await.unwrap();
+        store.increment("ip:current", now, 60).await.unwrap();
+        let swept = store.sweep_expired().await.unwrap();
+        let current = store.increment("ip:current", now, 60).await.unwrap();
+        db_conn.flush().await;
+
+        assert_eq!((swept, current), (1, 2));
+    }
+}
@@ -0,0 +1,139 @@
+//! Routing of repository reads to a read replica. Reads go to the replica, which may lag
+//! behind the primary, unless the current unit of work wrote or asked for the primary.
+use sqlx::PgPool;
+use std::cell::Cell;
+use std::future::Future;
+
+use crate::db_conn::HaveDBConnection;
+
+#[derive(Default)]
+struct UnitOfWork {
+    /// Whether its reads go to the primary.
+    read_primary: Cell<bool>,
+    wrote: Cell<bool>,
+}
+
+tokio::task_local! {
+    static UNIT_OF_WORK: UnitOfWork;
+}
+
+/// Runs `work`, such as a request, with its reads on the replica until it writes, and on the
+/// primary after, so that it reads its own writes.
+pub async fn track_writes<F: Future>(work: F) -> F::Output {
+    UNIT_OF_WORK.scope(UnitOfWork::default(), work).await
+}
+
+/// Runs `work` with every read on the primary, e.g. for a client that just wrote in a previous
+/// request. Within `track_writes`, the rest of that unit of work reads from the primary too.
+pub async fn read_primary<F: Future>(work: F) -> F::Output {
+    if UNIT_OF_WORK
+        .try_with(|unit| unit.read_primary.set(true))
+        .is_ok()
+    {
+        work.await
+    } else {
+        let unit = UnitOfWork {
+            read_primary: Cell::new(true),
+            ..UnitOfWork::default()
+        };
+        UNIT_OF_WORK.scope(unit, work).await
+    }
+}
+
+/// Recorded by repositories once they wrote.
+pub fn wrote() {
+    let _ = UNIT_OF_WORK.try_with(|unit| {
+        unit.read_primary.set(true);
+        unit.wrote.set(true);
+    });
+}
+
+pub fn reads_from_primary() -> bool {
+    UNIT_OF_WORK
+        .try_with(|unit| unit.read_primary.get())
+        .unwrap_or(false)
+}
+
+/// Whether the current unit of work wrote, unlike `reads_from_primary` which also holds once
+/// it asked for the primary.
+pub fn has_written() -> bool {
+    UNIT_OF_WORK
+        .try_with(|unit| unit.wrote.get())
+        .unwrap_or(false)
+}
+
+/// Repositories whose reads may be served by a replica of their pool.
+pub trait HaveReplicaConnection: HaveDBConnection {
+    /// The replica, or the primary itself when none is configured.
+    fn replica_connection(&self) -> &PgPool;
+
+    /// Pool to read from in the current unit of work.
+    fn read_connection(&self) -> &PgPool {
+        if reads_from_primary() {
+            self.db_connection()
+        } else {
+            self.replica_connection()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_written, read_primary, reads_from_primary, track_writes, wrote};
+
+    #[tokio::test]
+    async fn track_writes_read_primary_once_written() {
+        let (before, after) = track_writes(async {
+            let before = reads_from_primary();
+            wrote();
+            (before, reads_from_primary())
+        })
+        .await;
+
+        assert!(!before);
+        assert!(after);
+    }
+
+    #[tokio::test]
+    async fn track_writes_isolate_units_of_work() {
+        track_writes(async { wrote() }).await;
+        let next = track_writes(async { reads_from_primary() }).await;
+
+        assert!(!next);
+    }
+
+    #[tokio::test]
+    async fn read_primary_outside_and_within_unit_of_work() {
+        let outside = read_primary(async { reads_from_primary() }).await;
+        let within = track_writes(async {
+            read_primary(async {}).await;
+            reads_from_primary()
+        })
+        .await;
+
+        assert!(outside);
+        assert!(within);
+        assert!(!reads_from_primary());
+    }
+
+    #[tokio::test]
+    async fn has_written_only_once_written() {
+        let (asked, written) = track_writes(async {
+            read_primary(async {}).await;
+            let asked = has_written();
+            wrote();
+            (asked, has_written())
+        })
+        .await;
+
+        assert!(!asked);
+        assert!(written);
+    }
+
+    #[test]
+    fn wrote_without_unit_of_work_change_nothing() {
+        wrote();
+
+        assert!(!reads_from_primary());
+    }
+}
@@ -0,0 +1,800 @@
+//! Behaviour every `UserRepository`, `UserProfileRepository`, `DeviceAuthorizationRepository` and
+//! `ScopedTokenRepository` implementation must share.
+//!
+//! Each case is a generic function over a [`RepositoryFixture`];
+//! `repository_contract_tests!` turns all of them into tests for one fixture.
+
+use account::model::device_authorization::{
+    ClientId, DeviceAuthorization, DeviceAuthorizationStatus, DeviceCode, Scope, UserCode,
+};
+use account::model::login_provider::{IdInProvider, LoginProvider, ProviderKind};
+use account::model::profile::avatar::Avatar;
+use account::model::profile::display_name::DisplayName;
+use account::model::profile::entity::Profile;
+use account::model::profile::locale::Locale;
+use account::model::profile::user_name::UserName;
+use account::model::role::Role;
+use account::model::scoped_token::{ScopedToken, ScopedTokenValue};
+use account::model::user::{User, UserId, UserStatus};
+use account::model::user_profile::{UserProfile, UserProfileId};
+use account::repository::device_authorization_repository::DeviceAuthorizationRepository;
+use account::repository::meta::{Pagination, Repository};
+use account::repository::scoped_token_repository::ScopedTokenRepository;
+use account::repository::user_profile_repository::UserProfileRepository;
+use account::repository::user_repository::{UserRepository, UserSearchCondition};
+use async_trait::async_trait;
+use time::{Duration, OffsetDateTime};
+
+#[async_trait]
+pub trait RepositoryFixture: Sized + Send + Sync {
+    type UserRepository: UserRepository + Send + Sync;
+    type UserProfileRepository: UserProfileRepository + Send + Sync;
+    type DeviceAuthorizationRepository: DeviceAuthorizationRepository + Send + Sync;
+    type ScopedTokenRepository: ScopedTokenRepository + Send + Sync;
+
+    /// Repositories over one empty database.
+    async fn setup() -> Self;
+    fn user_repository(&self) -> &Self::UserRepository;
+    fn user_profile_repository(&self) -> &Self::UserProfileRepository;
+    fn device_authorization_repository(&self) -> &Self::DeviceAuthorizationRepository;
+    fn scoped_token_repository(&self) -> &Self::ScopedTokenRepository;
+    /// Removes what a case stored when the database outlives the fixture.
+    async fn teardown(&self) {}
+}
+
+fn google_user(id: &str, id_in_provider: &str) -> User {
+    User::new(
+        UserId::new(id.to_string()),
+        Some(vec![LoginProvider::new(
+            ProviderKind::Google,
+            IdInProvider::new(id_in_provider.to_string()),
+        )]),
+    )
+}
+
+fn user_profile(id: &str, name: &str) -> UserProfile {
+    UserProfile::new(
+        UserProfileId::new(id.to_string()),
+        Profile::new(
+            UserName(name.to_string()),
+            DisplayName(name.to_string()),
+            Avatar::new("https://example.com".to_string()),
+            None,
+        ),
+    )
+}
+
+fn device_authorization(device_code: &str, user_code: &str) -> DeviceAuthorization {
+    DeviceAuthorization::issue(
+        DeviceCode::new(device_code.to_string()),
+        UserCode::new(user_code.to_string()),
+        ClientId::new("cli".to_string()),
+        Scope::new("profile".to_string()),
+        OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap(),
+    )
+}
+
+/// `authorization` as `find_by_user_code` returns it, without its device code.
+fn found_by_user_code(authorization: &DeviceAuthorization) -> DeviceAuthorization {
+    DeviceAuthorization {
+        device_code: DeviceCode::default(),
+        ..authorization.clone()
+    }
+}
+
+pub async fn user_resolve_return_none_when_not_exist<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let resolved = f
+        .user_repository()
+        .resolve(&UserId::new("unknown".to_string()))
+        .await;
+    f.teardown().await;
+
+    assert!(resolved.unwrap().is_none());
+}
+
+pub async fn user_store_and_resolve<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let user = google_user("dummy1", "test1")
+        .with_roles(vec![Role::Admin, Role::Viewer])
+        .with_status(UserStatus::Disabled);
+    let stored = f.user_repository().store(&user).await;
+    let resolved = f.user_repository().resolve(&user.id).await;
+    f.teardown().await;
+
+    assert!(stored.is_ok());
+    assert_eq!(resolved.unwrap(), Some(user));
+}
+
+pub async fn user_store_and_resolve_for_update<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let user = google_user("dummy1", "test1").with_roles(vec![Role::Editor]);
+    f.user_repository().store(&user).await.unwrap();
+    let resolved = f.user_repository().resolve_for_update(&user.id).await;
+    let unknown = f
+        .user_repository()
+        .resolve_for_update(&UserId::new("unknown".to_string()))
+        .await;
+    f.teardown().await;
+
+    assert_eq!(resolved.unwrap(), Some(user));
+    assert!(unknown.unwrap().is_none());
+}
+
+pub async fn user_store_replace_roles_sorted_by_name<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let user = google_user("dummy1", "test1").with_roles(vec![Role::Viewer]);
+    f.user_repository().store(&user).await.unwrap();
+    let user = user.with_roles(vec![Role::Editor, Role::Admin]);
+    f.user_repository().store(&user).await.unwrap();
+    let resolved = f.user_repository().resolve(&user.id).await;
+    f.teardown().await;
+
+    assert_eq!(
+        resolved.unwrap().map(|u| u.roles),
+        Some(vec![Role::Admin, Role::Editor])
+    );
+}
+
+pub async fn user_find_by_id_in_provider<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let user = google_user("dummy1", "test1");
+    f.user_repository().store(&user).await.unwrap();
+    let found = f
+        .user_repository()
+        .find_by_id_in_provider(&IdInProvider::new("test1".to_string()))
+        .await;
+    let not_found = f
+        .user_repository()
+        .find_by_id_in_provider(&IdInProvider::new("test2".to_string()))
+        .await;
+    f.teardown().await;
+
+    assert_eq!(found.unwrap(), Some(user));
+    assert_eq!(not_found.unwrap(), None);
+}
+
+pub async fn user_search_filter_by_provider_kind<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let google = google_user("dummy1", "test1");
+    let other = User::new(UserId::new("dummy2".to_string()), None);
+    f.user_repository().store(&google).await.unwrap();
+    f.user_repository().store(&other).await.unwrap();
+    let condition = UserSearchCondition {
+        provider_kind: Some(ProviderKind::Google),
+        ..Default::default()
+    };
+    let page = f
+        .user_repository()
+        .search(&condition, &Pagination::default())
+        .await;
+    f.teardown().await;
+
+    let page = page.unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items, vec![google]);
+}
+
+pub async fn user_search_filter_by_case_sensitive_name_prefix<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    for (id, name) in [
+        ("dummy1", "Alice"),
+        ("dummy2", "alice_2"),
+        ("dummy3", "alicex"),
+    ] {
+        f.user_repository()
+            .store(&User::new(UserId::new(id.to_string()), None))
+            .await
+            .unwrap();
+        f.user_profile_repository()
+            .store(&user_profile(id, name))
+            .await
+            .unwrap();
+    }
+    let condition = UserSearchCondition {
+        name_prefix: Some("alice_".to_string()),
+        ..Default::default()
+    };
+    let page = f
+        .user_repository()
+        .search(&condition, &Pagination::default())
+        .await;
+    f.teardown().await;
+
+    let page = page.unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(
+        page.items,
+        vec![User::new(UserId::new("dummy2".to_string()), None)]
+    );
+}
+
+pub async fn user_search_filter_by_created_at<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let user = User::new(UserId::new("dummy1".to_string()), None);
+    f.user_repository().store(&user).await.unwrap();
+    let minute_ago = OffsetDateTime::now_utc() - Duration::minutes(1);
+    let after = f
+        .user_repository()
+        .search(
+            &UserSearchCondition {
+                created_after: Some(minute_ago),
+                ..Default::default()
+            },
+            &Pagination::default(),
+        )
+        .await;
+    let before = f
+        .user_repository()
+        .search(
+            &UserSearchCondition {
+                created_before: Some(minute_ago),
+                ..Default::default()
+            },
+            &Pagination::default(),
+        )
+        .await;
+    f.teardown().await;
+
+    assert_eq!(after.unwrap().items, vec![user]);
+    assert_eq!(before.unwrap().total, 0);
+}
+
+pub async fn user_search_paginate_in_creation_order<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let users =
+        ["dummy1", "dummy2", "dummy3"].map(|id| User::new(UserId::new(id.to_string()), None));
+    for user in users.iter() {
+        f.user_repository().store(user).await.unwrap();
+    }
+    let page = f
+        .user_repository()
+        .search(&UserSearchCondition::default(), &Pagination::new(1, 2))
+        .await;
+    f.teardown().await;
+
+    let page = page.unwrap();
+    assert_eq!(page.total, 3);
+    assert_eq!(page.items, users[1..].to_vec());
+}
+
+pub async fn user_search_return_providers_and_roles_of_each_user<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let users = [
+        google_user("dummy1", "test1").with_roles(vec![Role::Admin, Role::Viewer]),
+        User::new(UserId::new("dummy2".to_string()), None).with_status(UserStatus::Disabled),
+        google_user("dummy3", "test3").with_roles(vec![Role::Viewer]),
+    ];
+    for user in users.iter() {
+        f.user_repository().store(user).await.unwrap();
+    }
+    let page = f
+        .user_repository()
+        .search(&UserSearchCondition::default(), &Pagination::default())
+        .await;
+    f.teardown().await;
+
+    assert_eq!(page.unwrap().items, users.to_vec());
+}
+
+pub async fn profile_resolve_return_none_when_user_not_exist<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let resolved = f
+        .user_profile_repository()
+        .resolve(&UserProfileId::new("unknown".to_string()))
+        .await;
+    f.teardown().await;
+
+    assert!(resolved.unwrap().is_none());
+}
+
+pub async fn profile_store_and_update<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    f.user_repository()
+        .store(&User::new(UserId::new("dummy1".to_string()), None))
+        .await
+        .unwrap();
+    let mut profile = user_profile("dummy1", "dummy1");
+    f.user_profile_repository().store(&profile).await.unwrap();
+    profile.profile.display_name = DisplayName("renamed".to_string());
+    profile.profile.locale = Some(Locale::En);
+    let updated = f.user_profile_repository().store(&profile).await;
+    let resolved = f.user_profile_repository().resolve(&profile.id).await;
+    f.teardown().await;
+
+    assert!(updated.is_ok());
+    assert_eq!(resolved.unwrap(), Some(profile));
+}
+
+pub async fn profile_store_and_resolve_for_update<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    f.user_repository()
+        .store(&User::new(UserId::new("dummy1".to_string()), None))
+        .await
+        .unwrap();
+    let profile = user_profile("dummy1", "dummy1");
+    f.user_profile_repository().store(&profile).await.unwrap();
+    let resolved = f
+        .user_profile_repository()
+        .resolve_for_update(&profile.id)
+        .await;
+    f.teardown().await;
+
+    assert_eq!(resolved.unwrap(), Some(profile));
+}
+
+pub async fn profile_store_is_err_when_user_not_exist<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let orphan = user_profile("unknown", "unknown");
+    let stored = f.user_profile_repository().store(&orphan).await;
+    let resolved = f.user_profile_repository().resolve(&orphan.id).await;
+    f.teardown().await;
+
+    assert!(stored.is_err());
+    assert!(resolved.unwrap().is_none());
+}
+
+pub async fn device_authorization_resolve_return_none_when_not_exist<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let resolved = f
+        .device_authorization_repository()
+        .resolve(&DeviceCode::new("unknown".to_string()))
+        .await;
+    let found = f
+        .device_authorization_repository()
+        .find_by_user_code(&UserCode::new("BCDF-GHJK".to_string()))
+        .await;
+    f.teardown().await;
+
+    assert!(resolved.unwrap().is_none());
+    assert!(found.unwrap().is_none());
+}
+
+pub async fn device_authorization_store_and_resolve<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    f.user_repository()
+        .store(&User::new(UserId::new("dummy1".to_string()), None))
+        .await
+        .unwrap();
+    let mut authorization = device_authorization("device1", "BCDF-GHJK");
+    f.device_authorization_repository()
+        .store(&authorization)
+        .await
+        .unwrap();
+    let now = authorization.expires_at - Duration::seconds(1);
+    authorization
+        .approve(UserId::new("dummy1".to_string()), now)
+        .unwrap();
+    authorization.poll(now).unwrap();
+    let updated = f
+        .device_authorization_repository()
+        .store(&authorization)
+        .await;
+    let resolved = f
+        .device_authorization_repository()
+        .resolve(&authorization.device_code)
+        .await;
+    let found = f
+        .device_authorization_repository()
+        .find_by_user_code(&authorization.user_code)
+        .await;
+    f.teardown().await;
+
+    assert!(updated.is_ok());
+    assert_eq!(resolved.unwrap(), Some(authorization.clone()));
+    assert_eq!(found.unwrap(), Some(found_by_user_code(&authorization)));
+}
+
+pub async fn device_authorization_store_if_found_by_user_code<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    f.user_repository()
+        .store(&User::new(UserId::new("dummy1".to_string()), None))
+        .await
+        .unwrap();
+    let issued = device_authorization("device1", "BCDF-GHJK");
+    f.device_authorization_repository()
+        .store(&issued)
+        .await
+        .unwrap();
+    let mut found = f
+        .device_authorization_repository()
+        .find_by_user_code(&issued.user_code)
+        .await
+        .unwrap()
+        .unwrap();
+    found
+        .approve(
+            UserId::new("dummy1".to_string()),
+            issued.expires_at - Duration::seconds(1),
+        )
+        .unwrap();
+    let stored = f
+        .device_authorization_repository()
+        .store_if(&found, &DeviceAuthorizationStatus::Pending)
+        .await;
+    let resolved = f
+        .device_authorization_repository()
+        .resolve(&issued.device_code)
+        .await;
+    f.teardown().await;
+
+    assert!(stored.unwrap());
+    assert_eq!(
+        resolved.unwrap(),
+        Some(DeviceAuthorization {
+            device_code: issued.device_code,
+            ..found
+        })
+    );
+}
+
+pub async fn device_authorization_store_if_is_false_when_status_is_stale<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    f.user_repository()
+        .store(&User::new(UserId::new("dummy1".to_string()), None))
+        .await
+        .unwrap();
+    let mut authorization = device_authorization("device1", "BCDF-GHJK");
+    f.device_authorization_repository()
+        .store(&authorization)
+        .await
+        .unwrap();
+    let now = authorization.expires_at - Duration::seconds(1);
+    let mut denied = authorization.clone();
+    denied.deny(now).unwrap();
+    authorization
+        .approve(UserId::new("dummy1".to_string()), now)
+        .unwrap();
+    let approved = f
+        .device_authorization_repository()
+        .store_if(&authorization, &DeviceAuthorizationStatus::Pending)
+        .await;
+    let denied_after_approval = f
+        .device_authorization_repository()
+        .store_if(&denied, &DeviceAuthorizationStatus::Pending)
+        .await;
+    let unknown = f
+        .device_authorization_repository()
+        .store_if(
+            &device_authorization("device2", "LMNP-QRST"),
+            &DeviceAuthorizationStatus::Pending,
+        )
+        .await;
+    let resolved = f
+        .device_authorization_repository()
+        .resolve(&authorization.device_code)
+        .await;
+    f.teardown().await;
+
+    assert!(approved.unwrap());
+    assert!(!denied_after_approval.unwrap());
+    assert!(!unknown.unwrap());
+    assert_eq!(resolved.unwrap(), Some(authorization));
+}
+
+pub async fn device_authorization_store_is_err_when_user_code_taken<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let first = device_authorization("device1", "BCDF-GHJK");
+    f.device_authorization_repository()
+        .store(&first)
+        .await
+        .unwrap();
+    let stored = f
+        .device_authorization_repository()
+        .store(&device_authorization("device2", "BCDF-GHJK"))
+        .await;
+    let found = f
+        .device_authorization_repository()
+        .find_by_user_code(&first.user_code)
+        .await;
+    f.teardown().await;
+
+    assert!(stored.is_err());
+    assert_eq!(found.unwrap(), Some(found_by_user_code(&first)));
+}
+
+pub async fn device_authorization_store_is_err_when_user_not_exist<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let mut authorization = device_authorization("device1", "BCDF-GHJK");
+    f.device_authorization_repository()
+        .store(&authorization)
+        .await
+        .unwrap();
+    authorization
+        .approve(
+            UserId::new("unknown".to_string()),
+            authorization.expires_at - Duration::seconds(1),
+        )
+        .unwrap();
+    let stored = f
+        .device_authorization_repository()
+        .store(&authorization)
+        .await;
+    let resolved = f
+        .device_authorization_repository()
+        .resolve(&authorization.device_code)
+        .await;
+    f.teardown().await;
+
+    assert!(stored.is_err());
+    assert_eq!(
+        resolved.unwrap().map(|a| a.status),
+        Some(DeviceAuthorizationStatus::Pending)
+    );
+}
+
+pub async fn device_authorization_delete_expired<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let expired = device_authorization("device1", "BCDF-GHJK");
+    let live = DeviceAuthorization {
+        expires_at: expired.expires_at + Duration::minutes(10),
+        ..device_authorization("device2", "LMNP-QRST")
+    };
+    f.device_authorization_repository()
+        .store(&expired)
+        .await
+        .unwrap();
+    f.device_authorization_repository()
+        .store(&live)
+        .await
+        .unwrap();
+    let deleted = f
+        .device_authorization_repository()
+        .delete_expired(expired.expires_at + Duration::seconds(1))
+        .await;
+    let resolved_expired = f
+        .device_authorization_repository()
+        .resolve(&expired.device_code)
+        .await;
+    let resolved_live = f
+        .device_authorization_repository()
+        .resolve(&live.device_code)
+        .await;
+    f.teardown().await;
+
+    assert_eq!(deleted.unwrap(), 1);
+    assert!(resolved_expired.unwrap().is_none());
+    assert_eq!(resolved_live.unwrap(), Some(live));
+}
+
+pub async fn scoped_token_resolve_return_none_when_not_exist<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let resolved = f
+        .scoped_token_repository()
+        .resolve(&ScopedTokenValue::new("unknown".to_string()))
+        .await;
+    f.teardown().await;
+
+    assert!(resolved.unwrap().is_none());
+}
+
+pub async fn scoped_token_store_and_resolve<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    f.user_repository()
+        .store(&User::new(UserId::new("dummy1".to_string()), None))
+        .await
+        .unwrap();
+    let token = ScopedToken::issue(
+        "token1".to_string(),
+        UserId::new("dummy1".to_string()),
+        Scope::new("profile".to_string()),
+        OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap(),
+    );
+    let stored = f.scoped_token_repository().store(&token).await;
+    let resolved = f.scoped_token_repository().resolve(&token.token).await;
+    let other = f
+        .scoped_token_repository()
+        .resolve(&ScopedTokenValue::new(format!("{}x", token.token.0)))
+        .await;
+    f.teardown().await;
+
+    assert!(stored.is_ok());
+    assert_eq!(resolved.unwrap(), Some(token));
+    assert!(other.unwrap().is_none());
+}
+
+pub async fn scoped_token_store_is_err_when_user_not_exist<F: RepositoryFixture>() {
+    let f = F::setup().await;
+    let token = ScopedToken::issue(
+        "token1".to_string(),
+        UserId::new("unknown".to_string()),
+        Scope::new("profile".to_string()),
+        OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap(),
+    );
+    let stored = f.scoped_token_repository().store(&token).await;
+    let resolved = f.scoped_token_repository().resolve(&token.token).await;
+    f.teardown().await;
+
+    assert!(stored.is_err());
+    assert!(resolved.unwrap().is_none());
+}
+
+/// Expands to one `#[tokio::test]` per contract case for `$fixture`, each carrying the given
+/// extra attributes, e.g. `#[ignore]` for fixtures that need a running server.
+macro_rules! repository_contract_tests {
+    ($fixture:ty $(, #[$attr:meta])*) => {
+        repository_contract_tests!(@cases $fixture, [$(#[$attr])*],
+            user_resolve_return_none_when_not_exist,
+            user_store_and_resolve,
+            user_store_and_resolve_for_update,
+            user_store_replace_roles_sorted_by_name,
+            user_find_by_id_in_provider,
+            user_search_filter_by_provider_kind,
+            user_search_filter_by_case_sensitive_name_prefix,
+            user_search_filter_by_created_at,
+            user_search_paginate_in_creation_order,
+            user_search_return_providers_and_roles_of_each_user,
+            profile_resolve_return_none_when_user_not_exist,
+            profile_store_and_update,
+            profile_store_and_resolve_for_update,
+            profile_store_is_err_when_user_not_exist,
+            device_authorization_resolve_return_none_when_not_exist,
+            device_authorization_store_and_resolve,
+            device_authorization_store_if_found_by_user_code,
+            device_authorization_store_if_is_false_when_status_is_stale,
+            device_authorization_store_is_err_when_user_code_taken,
+            device_authorization_store_is_err_when_user_not_exist,
+            device_authorization_delete_expired,
+            scoped_token_resolve_return_none_when_not_exist,
+            scoped_token_store_and_resolve,
+            scoped_token_store_is_err_when_user_not_exist,
+        );
+    };
+    (@cases $fixture:ty, [$(#[$attr:meta])*], $case:ident, $($rest:ident,)*) => {
+        #[tokio::test]
+        $(#[$attr])*
+        async fn $case() {
+            $crate::repository::contract::$case::<$fixture>().await;
+        }
+        repository_contract_tests!(@cases $fixture, [$(#[$attr])*], $($rest,)*);
+    };
+    (@cases $fixture:ty, [$(#[$attr:meta])*], ) => {};
+}
+
+mod in_memory {
+    use super::RepositoryFixture;
+    use crate::repository::in_memory_database::InMemoryDatabase;
+    use crate::repository::in_memory_device_authorization_repository::InMemoryDeviceAuthorizationRepository;
+    use crate::repository::in_memory_scoped_token_repository::InMemoryScopedTokenRepository;
+    use crate::repository::in_memory_user_profile_repository::InMemoryUserProfileRepository;
+    use crate::repository::in_memory_user_repository::InMemoryUserRepository;
+
+    struct InMemoryFixture(
+        InMemoryUserRepository,
+        InMemoryUserProfileRepository,
+        InMemoryDeviceAuthorizationRepository,
+        InMemoryScopedTokenRepository,
+    );
+
+    #[async_trait::async_trait]
+    impl RepositoryFixture for InMemoryFixture {
+        type UserRepository = InMemoryUserRepository;
+        type UserProfileRepository = InMemoryUserProfileRepository;
+        type DeviceAuthorizationRepository = InMemoryDeviceAuthorizationRepository;
+        type ScopedTokenRepository = InMemoryScopedTokenRepository;
+        async fn setup() -> Self {
+            let db = InMemoryDatabase::default();
+            Self(
+                InMemoryUserRepository::new(db.clone()),
+                InMemoryUserProfileRepository::new(db.clone()),
+                InMemoryDeviceAuthorizationRepository::new(db.clone()),
+                InMemoryScopedTokenRepository::new(db),
+            )
+        }
+        fn user_repository(&self) -> &Self::UserRepository {
+            &self.0
+        }
+        fn user_profile_repository(&self) -> &Self::UserProfileRepository {
+            &self.1
+        }
+        fn device_authorization_repository(&self) -> &Self::DeviceAuthorizationRepository {
+            &self.2
+        }
+        fn scoped_token_repository(&self) -> &Self::ScopedTokenRepository {
+            &self.3
+        }
+    }
+
+    repository_contract_tests!(InMemoryFixture);
+}
+
+mod sqlite {
+    use super::RepositoryFixture;
+    use crate::db_conn::test_sqlite_pool;
+    use crate::repository::sqlite_device_authorization_repository::SqliteDeviceAuthorizationRepository;
+    use crate::repository::sqlite_scoped_token_repository::SqliteScopedTokenRepository;
+    use crate::repository::sqlite_user_profile_repository::SqliteUserProfileRepository;
+    use crate::repository::sqlite_user_repository::SqliteUserRepository;
+
+    struct SqliteFixture(
+        SqliteUserRepository,
+        SqliteUserProfileRepository,
+        SqliteDeviceAuthorizationRepository,
+        SqliteScopedTokenRepository,
+    );
+
+    #[async_trait::async_trait]
+    impl RepositoryFixture for SqliteFixture {
+        type UserRepository = SqliteUserRepository;
+        type UserProfileRepository = SqliteUserProfileRepository;
+        type DeviceAuthorizationRepository = SqliteDeviceAuthorizationRepository;
+        type ScopedTokenRepository = SqliteScopedTokenRepository;
+        async fn setup() -> Self {
+            let pool = test_sqlite_pool().await;
+            Self(
+                SqliteUserRepository::new(pool.clone()),
+                SqliteUserProfileRepository::new(pool.clone()),
+                SqliteDeviceAuthorizationRepository::new(pool.clone()),
+                SqliteScopedTokenRepository::new(pool),
+            )
+        }
+        fn user_repository(&self) -> &Self::UserRepository {
+            &self.0
+        }
+        fn user_profile_repository(&self) -> &Self::UserProfileRepository {
+            &self.1
+        }
+        fn device_authorization_repository(&self) -> &Self::DeviceAuthorizationRepository {
+            &self.2
+        }
+        fn scoped_token_repository(&self) -> &Self::ScopedTokenRepository {
+            &self.3
+        }
+    }
+
+    repository_contract_tests!(SqliteFixture);
+}
+
+mod postgres {
+    use super::RepositoryFixture;
+    use crate::db_conn::{TestDBConnection, TestDBInterface};
+    use crate::repository::postgres_device_authorization_repository::PostgresDeviceAuthorizationRepository;
+    use crate::repository::postgres_scoped_token_repository::PostgresScopedTokenRepository;
+    use crate::repository::postgres_user_profile_repository::PostgresUserProfileRepository;
+    use crate::repository::postgres_user_repository::PostgresUserRepository;
+
+    struct PostgresFixture {
+        db_conn: TestDBConnection,
+        user_repo: PostgresUserRepository,
+        user_profile_repo: PostgresUserProfileRepository,
+        device_authorization_repo: PostgresDeviceAuthorizationRepository,
+        scoped_token_repo: PostgresScopedTokenRepository,
+    }
+
+    #[async_trait::async_trait]
+    impl RepositoryFixture for PostgresFixture {
+        type UserRepository = PostgresUserRepository;
+        type UserProfileRepository = PostgresUserProfileRepository;
+        type DeviceAuthorizationRepository = PostgresDeviceAuthorizationRepository;
+        type ScopedTokenRepository = PostgresScopedTokenRepository;
+        async fn setup() -> Self {
+            let db_conn = TestDBConnection::default().await.unwrap();
+            Self {
+                user_repo: PostgresUserRepository::new(db_conn.conn.clone()),
+                user_profile_repo: PostgresUserProfileRepository::new(db_conn.conn.clone()),
+                device_authorization_repo: PostgresDeviceAuthorizationRepository::new(
+                    db_conn.conn.clone(),
+                ),
+                scoped_token_repo: PostgresScopedTokenRepository::new(db_conn.conn.clone()),
+                db_conn,
+            }
+        }
+        fn user_repository(&self) -> &Self::UserRepository {
+            &self.user_repo
+        }
+        fn user_profile_repository(&self) -> &Self::UserProfileRepository {
+            &self.user_profile_repo
+        }
+        fn device_authorization_repository(&self) -> &Self::DeviceAuthorizationRepository {
+            &self.device_authorization_repo
+        }
+        fn scoped_token_repository(&self) -> &Self::ScopedTokenRepository {
+            &self.scoped_token_repo
+        }
+        async fn teardown(&self) {
+            self.db_conn.flush().await;
+        }
+    }
+
+    repository_contract_tests!(PostgresFixture, #[ignore]);
+}
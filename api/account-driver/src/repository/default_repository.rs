@@ -0,0 +1,272 @@
+use account::model::device_authorization::{
+    DeviceAuthorization, DeviceAuthorizationStatus, DeviceCode, UserCode,
+};
+use account::model::login_provider::IdInProvider;
+use account::model::scoped_token::{ScopedToken, ScopedTokenValue};
+use account::model::user::{User, UserId};
+use account::model::user_profile::{UserProfile, UserProfileId};
+use account::repository::device_authorization_repository::{
+    self, DeviceAuthorizationRepository, FindByUserCodeError,
+};
+use account::repository::meta::{Page, Pagination, Repository, ResolveError};
+use account::repository::scoped_token_repository::{self, ScopedTokenRepository};
+use account::repository::user_profile_repository::{self, UserProfileRepository};
+use account::repository::user_repository::{
+    self, FilterByIdInProviderError, SearchError, UserRepository, UserSearchCondition,
+};
+use async_trait::async_trait;
+use time::OffsetDateTime;
+
+use crate::db_conn::DBBackend;
+use crate::metrics;
+
+use super::postgres_device_authorization_repository::PostgresDeviceAuthorizationRepository;
+use super::postgres_scoped_token_repository::PostgresScopedTokenRepository;
+use super::postgres_user_profile_repository::PostgresUserProfileRepository;
+use super::postgres_user_repository::PostgresUserRepository;
+use super::sqlite_device_authorization_repository::SqliteDeviceAuthorizationRepository;
+use super::sqlite_scoped_token_repository::SqliteScopedTokenRepository;
+use super::sqlite_user_profile_repository::SqliteUserProfileRepository;
+use super::sqlite_user_repository::SqliteUserRepository;
+
+/// Repository selected at start up by `database.backend`.
+#[derive(Debug, Clone)]
+pub enum DefaultUserRepository {
+    Postgres(PostgresUserRepository),
+    Sqlite(SqliteUserRepository),
+}
+
+impl DefaultUserRepository {
+    fn backend(&self) -> DBBackend {
+        match self {
+            Self::Postgres(_) => DBBackend::Postgres,
+            Self::Sqlite(_) => DBBackend::Sqlite,
+        }
+    }
+}
+
+#[async_trait]
+impl Repository<UserId, User> for DefaultUserRepository {
+    async fn resolve(&self, id: &UserId) -> Result<Option<User>, ResolveError> {
+        let query = match self {
+            Self::Postgres(r) => r.resolve(id),
+            Self::Sqlite(r) => r.resolve(id),
+        };
+        metrics::observe_query("user", "resolve", self.backend(), query).await
+    }
+}
+
+#[async_trait]
+impl UserRepository for DefaultUserRepository {
+    async fn resolve_for_update(&self, id: &UserId) -> Result<Option<User>, ResolveError> {
+        let query = match self {
+            Self::Postgres(r) => r.resolve_for_update(id),
+            Self::Sqlite(r) => r.resolve_for_update(id),
+        };
+        metrics::observe_query("user", "resolve_for_update", self.backend(), query).await
+    }
+
+    async fn store(&self, u: &User) -> Result<(), user_repository::StoreError> {
+        let query = match self {
+            Self::Postgres(r) => r.store(u),
+            Self::Sqlite(r) => r.store(u),
+        };
+        metrics::observe_query("user", "store", self.backend(), query).await
+    }
+
+    async fn find_by_id_in_provider(
+        &self,
+        id_in_provider: &IdInProvider,
+    ) -> Result<Option<User>, FilterByIdInProviderError> {
+        let query = match self {
+            Self::Postgres(r) => r.find_by_id_in_provider(id_in_provider),
+            Self::Sqlite(r) => r.find_by_id_in_provider(id_in_provider),
+        };
+        metrics::observe_query("user", "find_by_id_in_provider", self.backend(), query).await
+    }
+
+    async fn search(
+        &self,
+        condition: &UserSearchCondition,
+        pagination: &Pagination,
+    ) -> Result<Page<User>, SearchError> {
+        let query = match self {
+            Self::Postgres(r) => r.search(condition, pagination),
+            Self::Sqlite(r) => r.search(condition, pagination),
+        };
+        metrics::observe_query("user", "search", self.backend(), query).await
+    }
+}
+
+/// Repository selected at start up by `database.backend`.
+#[derive(Debug, Clone)]
+pub enum DefaultUserProfileRepository {
+    Postgres(PostgresUserProfileRepository),
+    Sqlite(SqliteUserProfileRepository),
+}
+
+impl DefaultUserProfileRepository {
+    fn backend(&self) -> DBBackend {
+        match self {
+            Self::Postgres(_) => DBBackend::Postgres,
+            Self::Sqlite(_) => DBBackend::Sqlite,
+        }
+    }
+}
+
+#[async_trait]
+impl Repository<UserProfileId, UserProfile> for DefaultUserProfileRepository {
+    async fn resolve(&self, id: &UserProfileId) -> Result<Option<UserProfile>, ResolveError> {
+        let query = match self {
+            Self::Postgres(r) => r.resolve(id),
+            Self::Sqlite(r) => r.resolve(id),
+        };
+        metrics::observe_query("user_profile", "resolve", self.backend(), query).await
+    }
+}
+
+#[async_trait]
+impl UserProfileRepository for DefaultUserProfileRepository {
+    async fn resolve_for_update(
+        &self,
+        id: &UserProfileId,
+    ) -> Result<Option<UserProfile>, ResolveError> {
+        let query = match self {
+            Self::Postgres(r) => r.resolve_for_update(id),
+            Self::Sqlite(r) => r.resolve_for_update(id),
+        };
+        metrics::observe_query("user_profile", "resolve_for_update", self.backend(), query).await
+    }
+
+    async fn store(&self, up: &UserProfile) -> Result<(), user_profile_repository::StoreError> {
+        let query = match self {
+            Self::Postgres(r) => r.store(up),
+            Self::Sqlite(r) => r.store(up),
+        };
+        metrics::observe_query("user_profile", "store", self.backend(), query).await
+    }
+}
+
+/// Repository selected at start up by `database.backend`.
+#[derive(Debug, Clone)]
+pub enum DefaultDeviceAuthorizationRepository {
+    Postgres(PostgresDeviceAuthorizationRepository),
+    Sqlite(SqliteDeviceAuthorizationRepository),
+}
+
+impl DefaultDeviceAuthorizationRepository {
+    fn backend(&self) -> DBBackend {
+        match self {
+            Self::Postgres(_) => DBBackend::Postgres,
+            Self::Sqlite(_) => DBBackend::Sqlite,
+        }
+    }
+}
+
+#[async_trait]
+impl Repository<DeviceCode, DeviceAuthorization> for DefaultDeviceAuthorizationRepository {
+    async fn resolve(&self, id: &DeviceCode) -> Result<Option<DeviceAuthorization>, ResolveError> {
+        let query = match self {
+            Self::Postgres(r) => r.resolve(id),
+            Self::Sqlite(r) => r.resolve(id),
+        };
+        metrics::observe_query("device_authorization", "resolve", self.backend(), query).await
+    }
+}
+
+#[async_trait]
+impl DeviceAuthorizationRepository for DefaultDeviceAuthorizationRepository {
+    async fn store(
+        &self,
+        authorization: &DeviceAuthorization,
+    ) -> Result<(), device_authorization_repository::StoreError> {
+        let query = match self {
+            Self::Postgres(r) => r.store(authorization),
+            Self::Sqlite(r) => r.store(authorization),
+        };
+        metrics::observe_query("device_authorization", "store", self.backend(), query).await
+    }
+
+    async fn store_if(
+        &self,
+        authorization: &DeviceAuthorization,
+        expected: &DeviceAuthorizationStatus,
+    ) -> Result<bool, device_authorization_repository::StoreError> {
+        let query = match self {
+            Self::Postgres(r) => r.store_if(authorization, expected),
+            Self::Sqlite(r) => r.store_if(authorization, expected),
+        };
+        metrics::observe_query("device_authorization", "store_if", self.backend(), query).await
+    }
+
+    async fn find_by_user_code(
+        &self,
+        user_code: &UserCode,
+    ) -> Result<Option<DeviceAuthorization>, FindByUserCodeError> {
+        let query = match self {
+            Self::Postgres(r) => r.find_by_user_code(user_code),
+            Self::Sqlite(r) => r.find_by_user_code(user_code),
+        };
+        metrics::observe_query(
+            "device_authorization",
+            "find_by_user_code",
+            self.backend(),
+            query,
+        )
+        .await
+    }
+    async fn delete_expired(
+        &self,
+        before: OffsetDateTime,
+    ) -> Result<u64, device_authorization_repository::StoreError> {
+        let query = match self {
+            Self::Postgres(r) => r.delete_expired(before),
+            Self::Sqlite(r) => r.delete_expired(before),
+        };
+        metrics::observe_query(
+            "device_authorization",
+            "delete_expired",
+            self.backend(),
+            query,
+        )
+        .await
+    }
+}
+
+/// Repository selected at start up by `database.backend`.
+#[derive(Debug, Clone)]
+pub enum DefaultScopedTokenRepository {
+    Postgres(PostgresScopedTokenRepository),
+    Sqlite(SqliteScopedTokenRepository),
+}
+
+impl DefaultScopedTokenRepository {
+    fn backend(&self) -> DBBackend {
+        match self {
+            Self::Postgres(_) => DBBackend::Postgres,
+            Self::Sqlite(_) => DBBackend::Sqlite,
+        }
+    }
+}
+
+#[async_trait]
+impl Repository<ScopedTokenValue, ScopedToken> for DefaultScopedTokenRepository {
+    async fn resolve(&self, id: &ScopedTokenValue) -> Result<Option<ScopedToken>, ResolveError> {
+        let query = match self {
+            Self::Postgres(r) => r.resolve(id),
+            Self::Sqlite(r) => r.resolve(id),
+        };
+        metrics::observe_query("scoped_token", "resolve", self.backend(), query).await
+    }
+}
+
+#[async_trait]
+impl ScopedTokenRepository for DefaultScopedTokenRepository {
+    async fn store(&self, token: &ScopedToken) -> Result<(), scoped_token_repository::StoreError> {
+        let query = match self {
+            Self::Postgres(r) => r.store(token),
+            Self::Sqlite(r) => r.store(token),
+        };
+        metrics::observe_query("scoped_token", "store", self.backend(), query).await
+    }
+}
@@ -0,0 +1,59 @@
+use account::model::audit_event::AuditEvent;
+use account::model::device_authorization::{DeviceAuthorization, Scope};
+use account::model::login_provider::LoginProvider;
+use account::model::profile::entity::Profile;
+use account::model::role::Role;
+use account::model::user::UserStatus;
+use anyhow::anyhow;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone)]
+pub(crate) struct UserRecord {
+    pub status: UserStatus,
+    pub roles: Vec<Role>,
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ScopedTokenRecord {
+    pub user_id: String,
+    pub scope: Scope,
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct LoginProviderRecord {
+    pub user_id: String,
+    pub provider: LoginProvider,
+}
+
+/// The rows the postgres schema keeps, with the same keys and foreign keys.
+#[derive(Debug, Default)]
+pub(crate) struct Tables {
+    pub users: BTreeMap<String, UserRecord>,
+    /// Keyed by `(kind, id_in_provider)` like `login_providers_pkey`, in insertion order.
+    pub login_providers: Vec<LoginProviderRecord>,
+    pub profiles: HashMap<String, Profile>,
+    /// Keyed by the device code digest like `device_authorizations_pkey`, without the code.
+    pub device_authorizations: HashMap<String, DeviceAuthorization>,
+    /// Keyed by the token digest like `scoped_tokens_pkey`.
+    pub scoped_tokens: HashMap<String, ScopedTokenRecord>,
+    pub audit_events: Vec<AuditEvent>,
+}
+
+/// Shared by the in-memory repositories so that each sees the rows the others store, as
+/// tables of one database would. Clones share the same rows.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryDatabase {
+    tables: Arc<Mutex<Tables>>,
+}
+
+impl InMemoryDatabase {
+    pub(crate) fn lock(&self) -> Result<MutexGuard<'_, Tables>, anyhow::Error> {
+        self.tables
+            .lock()
+            .map_err(|_| anyhow!("in-memory database is poisoned"))
+    }
+}
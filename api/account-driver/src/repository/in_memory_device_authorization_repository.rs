@@ -0,0 +1,227 @@
+use account::model::device_authorization::{
+    DeviceAuthorization, DeviceAuthorizationStatus, DeviceCode, UserCode,
+};
+use account::repository::device_authorization_repository::{
+    DeviceAuthorizationRepository, FindByUserCodeError, StoreError,
+};
+use account::repository::meta::{Repository, ResolveError};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use derive_more::Constructor;
+use time::OffsetDateTime;
+
+use super::in_memory_database::InMemoryDatabase;
+use super::postgres_device_authorization_repository::hash_device_code;
+
+/// Keeps device authorizations in process memory with the keys, unique user code and
+/// `users` foreign key of the postgres schema. Like postgres, it keys them by the digest of the
+/// device code and does not keep the code itself.
+#[derive(Constructor, Debug, Clone, Default)]
+pub struct InMemoryDeviceAuthorizationRepository {
+    db: InMemoryDatabase,
+}
+
+#[async_trait]
+impl Repository<DeviceCode, DeviceAuthorization> for InMemoryDeviceAuthorizationRepository {
+    #[tracing::instrument(skip(self))]
+    async fn resolve(&self, id: &DeviceCode) -> Result<Option<DeviceAuthorization>, ResolveError> {
+        let tables = self.db.lock()?;
+        Ok(tables
+            .device_authorizations
+            .get(&hash_device_code(id))
+            .map(|d| DeviceAuthorization {
+                device_code: id.clone(),
+                ..d.clone()
+            }))
+    }
+}
+
+#[async_trait]
+impl DeviceAuthorizationRepository for InMemoryDeviceAuthorizationRepository {
+    #[tracing::instrument(skip(self))]
+    async fn store(&self, a: &DeviceAuthorization) -> Result<(), StoreError> {
+        let mut tables = self.db.lock()?;
+        if let DeviceAuthorizationStatus::Approved(user_id) = &a.status {
+            if !tables.users.contains_key(&user_id.0) {
+                return Err(anyhow!(
+                    "Failed store device authorization, user {} does not exist",
+                    user_id.0
+                )
+                .into());
+            }
+        }
+        let key = hash_device_code(&a.device_code);
+        let user_code_taken = tables
+            .device_authorizations
+            .iter()
+            .any(|(k, d)| d.user_code == a.user_code && k != &key);
+        if user_code_taken {
+            return Err(anyhow!("Duplicate user code {}", a.user_code.0).into());
+        }
+        match tables.device_authorizations.get_mut(&key) {
+            Some(existing) => {
+                existing.status = a.status.clone();
+                existing.interval = a.interval;
+                existing.last_polled_at = a.last_polled_at;
+            }
+            None => {
+                tables.device_authorizations.insert(
+                    key,
+                    DeviceAuthorization {
+                        device_code: DeviceCode::default(),
+                        ..a.clone()
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn store_if(
+        &self,
+        a: &DeviceAuthorization,
+        expected: &DeviceAuthorizationStatus,
+    ) -> Result<bool, StoreError> {
+        let mut tables = self.db.lock()?;
+        if let DeviceAuthorizationStatus::Approved(user_id) = &a.status {
+            if !tables.users.contains_key(&user_id.0) {
+                return Err(anyhow!(
+                    "Failed store device authorization, user {} does not exist",
+                    user_id.0
+                )
+                .into());
+            }
+        }
+        match tables
+            .device_authorizations
+            .values_mut()
+            .find(|d| d.user_code == a.user_code)
+        {
+            Some(existing) if &existing.status == expected => {
+                existing.status = a.status.clone();
+                existing.interval = a.interval;
+                existing.last_polled_at = a.last_polled_at;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn find_by_user_code(
+        &self,
+        user_code: &UserCode,
+    ) -> Result<Option<DeviceAuthorization>, FindByUserCodeError> {
+        let tables = self.db.lock()?;
+        Ok(tables
+            .device_authorizations
+            .values()
+            .find(|d| &d.user_code == user_code)
+            .cloned())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_expired(&self, before: OffsetDateTime) -> Result<u64, StoreError> {
+        let mut tables = self.db.lock()?;
+        let len = tables.device_authorizations.len();
+        tables
+            .device_authorizations
+            .retain(|_, d| d.expires_at >= before);
+        Ok((len - tables.device_authorizations.len()) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InMemoryDeviceAuthorizationRepository;
+    use crate::repository::in_memory_database::InMemoryDatabase;
+    use crate::repository::in_memory_user_repository::InMemoryUserRepository;
+    use account::model::device_authorization::{
+        ClientId, DeviceAuthorization, DeviceCode, Scope, UserCode,
+    };
+    use account::model::user::{User, UserId};
+    use account::repository::device_authorization_repository::DeviceAuthorizationRepository;
+    use account::repository::meta::Repository;
+    use account::repository::user_repository::UserRepository;
+    use time::OffsetDateTime;
+
+    fn issued(device_code: &str) -> DeviceAuthorization {
+        DeviceAuthorization::issue(
+            DeviceCode::new(device_code.to_string()),
+            UserCode::new("BCDF-GHJK".to_string()),
+            ClientId::new("cli".to_string()),
+            Scope::new("profile".to_string()),
+            OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn in_memory_device_authorization_repository_store_and_resolve() {
+        let db = InMemoryDatabase::default();
+        let repo = InMemoryDeviceAuthorizationRepository::new(db.clone());
+        InMemoryUserRepository::new(db)
+            .store(&User::new(UserId::new("foo".to_string()), None))
+            .await
+            .unwrap();
+        let mut authorization = issued("device");
+        repo.store(&authorization).await.unwrap();
+        authorization
+            .approve(
+                UserId::new("foo".to_string()),
+                authorization.expires_at - time::Duration::seconds(1),
+            )
+            .unwrap();
+        repo.store(&authorization).await.unwrap();
+
+        let resolved = repo.resolve(&authorization.device_code).await.unwrap();
+        let found = repo
+            .find_by_user_code(&authorization.user_code)
+            .await
+            .unwrap();
+
+        assert_eq!(resolved, Some(authorization.clone()));
+        assert_eq!(
+            found,
+            Some(DeviceAuthorization {
+                device_code: DeviceCode::default(),
+                ..authorization
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn in_memory_device_authorization_repository_store_is_err_when_constraint_violated() {
+        let repo = InMemoryDeviceAuthorizationRepository::default();
+        let mut authorization = issued("device");
+        repo.store(&authorization).await.unwrap();
+        let duplicate_user_code = repo.store(&issued("other")).await;
+        authorization
+            .approve(
+                UserId::new("unknown".to_string()),
+                authorization.expires_at - time::Duration::seconds(1),
+            )
+            .unwrap();
+        let unknown_user = repo.store(&authorization).await;
+
+        assert!(duplicate_user_code.is_err());
+        assert!(unknown_user.is_err());
+    }
+
+    #[tokio::test]
+    async fn in_memory_device_authorization_repository_delete_expired() {
+        let repo = InMemoryDeviceAuthorizationRepository::default();
+        let authorization = issued("device");
+        repo.store(&authorization).await.unwrap();
+
+        let before_expiry = repo.delete_expired(authorization.expires_at).await.unwrap();
+        let after_expiry = repo
+            .delete_expired(authorization.expires_at + time::Duration::seconds(1))
+            .await
+            .unwrap();
+        let resolved = repo.resolve(&authorization.device_code).await.unwrap();
+
+        assert_eq!((before_expiry, after_expiry), (0, 1));
+        assert!(resolved.is_none());
+    }
+}
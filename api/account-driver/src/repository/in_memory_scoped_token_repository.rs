@@ -0,0 +1,107 @@
+use account::model::scoped_token::{ScopedToken, ScopedTokenValue};
+use account::model::user::UserId;
+use account::repository::meta::{Repository, ResolveError};
+use account::repository::scoped_token_repository::{ScopedTokenRepository, StoreError};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use derive_more::Constructor;
+
+use super::in_memory_database::{InMemoryDatabase, ScopedTokenRecord};
+use super::postgres_scoped_token_repository::hash_token;
+
+/// Keeps the same SHA-256 digest as the postgres repository, never the token itself.
+#[derive(Constructor, Debug, Clone, Default)]
+pub struct InMemoryScopedTokenRepository {
+    db: InMemoryDatabase,
+}
+
+#[async_trait]
+impl Repository<ScopedTokenValue, ScopedToken> for InMemoryScopedTokenRepository {
+    #[tracing::instrument(skip(self))]
+    async fn resolve(&self, id: &ScopedTokenValue) -> Result<Option<ScopedToken>, ResolveError> {
+        let tables = self.db.lock()?;
+        Ok(tables.scoped_tokens.get(&hash_token(id)).map(|record| {
+            ScopedToken::new(
+                id.clone(),
+                UserId::new(record.user_id.clone()),
+                record.scope.clone(),
+                record.expires_at,
+            )
+        }))
+    }
+}
+
+#[async_trait]
+impl ScopedTokenRepository for InMemoryScopedTokenRepository {
+    #[tracing::instrument(skip(self))]
+    async fn store(&self, t: &ScopedToken) -> Result<(), StoreError> {
+        let mut tables = self.db.lock()?;
+        if !tables.users.contains_key(&t.user_id.0) {
+            return Err(anyhow!(
+                "Failed store scoped token, user {} does not exist",
+                t.user_id.0
+            )
+            .into());
+        }
+        tables.scoped_tokens.insert(
+            hash_token(&t.token),
+            ScopedTokenRecord {
+                user_id: t.user_id.0.clone(),
+                scope: t.scope.clone(),
+                expires_at: t.expires_at,
+            },
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InMemoryScopedTokenRepository;
+    use crate::repository::in_memory_database::InMemoryDatabase;
+    use crate::repository::in_memory_user_repository::InMemoryUserRepository;
+    use account::model::device_authorization::Scope;
+    use account::model::scoped_token::{ScopedToken, ScopedTokenValue};
+    use account::model::user::{User, UserId};
+    use account::repository::meta::Repository;
+    use account::repository::scoped_token_repository::ScopedTokenRepository;
+    use account::repository::user_repository::UserRepository;
+    use time::OffsetDateTime;
+
+    fn token(user_id: &str) -> ScopedToken {
+        ScopedToken::issue(
+            "xxxx".to_string(),
+            UserId::new(user_id.to_string()),
+            Scope::new("profile".to_string()),
+            OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn in_memory_scoped_token_repository_store_and_resolve() {
+        let db = InMemoryDatabase::default();
+        let repo = InMemoryScopedTokenRepository::new(db.clone());
+        InMemoryUserRepository::new(db)
+            .store(&User::new(UserId::new("foo".to_string()), None))
+            .await
+            .unwrap();
+        let token = token("foo");
+        repo.store(&token).await.unwrap();
+        let resolved = repo.resolve(&token.token).await.unwrap();
+        let unknown = repo
+            .resolve(&ScopedTokenValue::new("mtk_unknown".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(resolved, Some(token));
+        assert!(unknown.is_none());
+    }
+
+    #[tokio::test]
+    async fn in_memory_scoped_token_repository_store_is_err_when_user_not_exist() {
+        let repo = InMemoryScopedTokenRepository::default();
+        let result = repo.store(&token("unknown")).await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use account::model::user_profile::{UserProfile, UserProfileId};
+use account::repository::meta::{Repository, ResolveError};
+use account::repository::user_profile_repository::{StoreError, UserProfileRepository};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use derive_more::Constructor;
+
+use super::in_memory_database::InMemoryDatabase;
+
+/// Keeps profiles in process memory. Like the `profiles` foreign key, a profile can only be
+/// stored for a user stored through an `InMemoryUserRepository` on the same database.
+#[derive(Constructor, Debug, Clone, Default)]
+pub struct InMemoryUserProfileRepository {
+    db: InMemoryDatabase,
+}
+
+#[async_trait]
+impl Repository<UserProfileId, UserProfile> for InMemoryUserProfileRepository {
+    #[tracing::instrument(skip(self))]
+    async fn resolve(&self, id: &UserProfileId) -> Result<Option<UserProfile>, ResolveError> {
+        let tables = self.db.lock()?;
+        if !tables.users.contains_key(&id.0) {
+            return Ok(None);
+        }
+        let profile = tables
+            .profiles
+            .get(&id.0)
+            .ok_or_else(|| anyhow!("No profile stored for user {}", id.0))?;
+        Ok(Some(UserProfile::new(id.clone(), profile.clone())))
+    }
+}
+
+#[async_trait]
+impl UserProfileRepository for InMemoryUserProfileRepository {
+    /// Same as `resolve`: there is no replica.
+    #[tracing::instrument(skip(self))]
+    async fn resolve_for_update(
+        &self,
+        id: &UserProfileId,
+    ) -> Result<Option<UserProfile>, ResolveError> {
+        self.resolve(id).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn store(&self, up: &UserProfile) -> Result<(), StoreError> {
+        let mut tables = self.db.lock()?;
+        if !tables.users.contains_key(&up.id.0) {
+            return Err(anyhow!("Failed insert profile, user {} does not exist", up.id.0).into());
+        }
+        tables.profiles.insert(up.id.0.clone(), up.profile.clone());
+        Ok(())
+    }
+}
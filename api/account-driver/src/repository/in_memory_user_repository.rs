@@ -0,0 +1,162 @@
+use account::model::login_provider::IdInProvider;
+use account::model::role::Role;
+use account::model::user::{User, UserId};
+use account::repository::meta::{Page, Pagination, Repository, ResolveError};
+use account::repository::user_repository::{
+    FilterByIdInProviderError, SearchError, StoreError, UserRepository, UserSearchCondition,
+};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use derive_more::Constructor;
+use std::collections::HashSet;
+use time::OffsetDateTime;
+
+use super::in_memory_database::{InMemoryDatabase, LoginProviderRecord, Tables, UserRecord};
+
+/// Keeps users in process memory with the semantics of `PostgresUserRepository`, for tests
+/// and local runs that need no database.
+#[derive(Constructor, Debug, Clone, Default)]
+pub struct InMemoryUserRepository {
+    db: InMemoryDatabase,
+}
+
+fn to_user(tables: &Tables, id: &str, providers: Vec<LoginProviderRecord>) -> Option<User> {
+    let record = tables.users.get(id)?;
+    let mut roles = record.roles.clone();
+    roles.sort_by_key(|r| String::from(r));
+    Some(
+        User::new(
+            UserId::new(id.to_string()),
+            Some(providers.into_iter().map(|p| p.provider).collect()),
+        )
+        .with_roles(roles)
+        .with_status(record.status),
+    )
+}
+
+fn matches(tables: &Tables, id: &str, record: &UserRecord, c: &UserSearchCondition) -> bool {
+    let provider_kind = c.provider_kind.as_ref().is_none_or(|kind| {
+        tables
+            .login_providers
+            .iter()
+            .any(|p| p.user_id == id && &p.provider.kind == kind)
+    });
+    let name_prefix = c.name_prefix.as_ref().is_none_or(|prefix| {
+        tables
+            .profiles
+            .get(id)
+            .is_some_and(|p| p.name.0.starts_with(prefix.as_str()))
+    });
+    provider_kind
+        && name_prefix
+        && c.created_after.is_none_or(|t| record.created_at >= t)
+        && c.created_before.is_none_or(|t| record.created_at < t)
+}
+
+#[async_trait]
+impl Repository<UserId, User> for InMemoryUserRepository {
+    #[tracing::instrument(skip(self))]
+    async fn resolve(&self, id: &UserId) -> Result<Option<User>, ResolveError> {
+        let tables = self.db.lock()?;
+        let providers = tables
+            .login_providers
+            .iter()
+            .filter(|p| p.user_id == id.0)
+            .cloned()
+            .collect();
+        Ok(to_user(&tables, &id.0, providers))
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    /// Same as `resolve`: there is no replica.
+    #[tracing::instrument(skip(self))]
+    async fn resolve_for_update(&self, id: &UserId) -> Result<Option<User>, ResolveError> {
+        self.resolve(id).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn find_by_id_in_provider(
+        &self,
+        id_in_provider: &IdInProvider,
+    ) -> Result<Option<User>, FilterByIdInProviderError> {
+        let tables = self.db.lock()?;
+        let providers = tables
+            .login_providers
+            .iter()
+            .filter(|p| &p.provider.id_in_provider == id_in_provider)
+            .cloned()
+            .collect::<Vec<_>>();
+        let user_id = match providers.first() {
+            Some(p) => p.user_id.clone(),
+            None => return Ok(None),
+        };
+        Ok(to_user(&tables, &user_id, providers))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn search(
+        &self,
+        condition: &UserSearchCondition,
+        pagination: &Pagination,
+    ) -> Result<Page<User>, SearchError> {
+        let tables = self.db.lock()?;
+        let mut found = tables
+            .users
+            .iter()
+            .filter(|(id, record)| matches(&tables, id, record, condition))
+            .collect::<Vec<_>>();
+        found.sort_by_key(|(id, record)| (record.created_at, id.as_str()));
+        let users = found
+            .iter()
+            .skip(pagination.offset.max(0) as usize)
+            .take(pagination.limit.max(0) as usize)
+            .filter_map(|(id, _)| {
+                let providers = tables
+                    .login_providers
+                    .iter()
+                    .filter(|p| &p.user_id == *id)
+                    .cloned()
+                    .collect();
+                to_user(&tables, id, providers)
+            })
+            .collect();
+        Ok(Page::new(users, found.len() as i64))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn store(&self, u: &User) -> Result<(), StoreError> {
+        let roles = u.roles.iter().collect::<HashSet<&Role>>();
+        if roles.len() != u.roles.len() {
+            return Err(anyhow!("duplicate roles for user {}", u.id.0).into());
+        }
+        let mut tables = self.db.lock()?;
+        let created_at = tables
+            .users
+            .get(&u.id.0)
+            .map_or_else(OffsetDateTime::now_utc, |r| r.created_at);
+        tables.users.insert(
+            u.id.0.clone(),
+            UserRecord {
+                status: u.status,
+                roles: u.roles.clone(),
+                created_at,
+            },
+        );
+        for provider in u.providers.iter() {
+            match tables
+                .login_providers
+                .iter_mut()
+                .find(|p| p.provider == *provider)
+            {
+                Some(existing) => existing.user_id = u.id.0.clone(),
+                None => tables.login_providers.push(LoginProviderRecord {
+                    user_id: u.id.0.clone(),
+                    provider: provider.clone(),
+                }),
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,2 +1,16 @@
+#[cfg(test)]
+mod contract;
+pub mod default_repository;
+pub mod in_memory_database;
+pub mod in_memory_device_authorization_repository;
+pub mod in_memory_scoped_token_repository;
+pub mod in_memory_user_profile_repository;
+pub mod in_memory_user_repository;
+pub mod postgres_device_authorization_repository;
+pub mod postgres_scoped_token_repository;
 pub mod postgres_user_profile_repository;
 pub mod postgres_user_repository;
+pub mod sqlite_device_authorization_repository;
+pub mod sqlite_scoped_token_repository;
+pub mod sqlite_user_profile_repository;
+pub mod sqlite_user_repository;
@@ -0,0 +1,140 @@
+use account::model::device_authorization::Scope;
+use account::model::scoped_token::{ScopedToken, ScopedTokenValue};
+use account::model::user::UserId;
+use account::repository::meta::{Repository, ResolveError};
+use account::repository::scoped_token_repository::{ScopedTokenRepository, StoreError};
+use anyhow::Context;
+use async_trait::async_trait;
+use derive_more::Constructor;
+use indoc::indoc;
+use sha2::{Digest, Sha256};
+use sqlx::{query, query_as, PgPool};
+use time::OffsetDateTime;
+
+use crate::db_conn::HaveDBConnection;
+
+/// Scoped tokens are bearer secrets, so only their SHA-256 digest is persisted.
+#[derive(Constructor, Debug, Clone)]
+pub struct PostgresScopedTokenRepository {
+    conn: PgPool,
+}
+
+impl HaveDBConnection for PostgresScopedTokenRepository {
+    fn db_connection(&self) -> &PgPool {
+        &self.conn
+    }
+}
+
+pub(crate) fn hash_token(token: &ScopedTokenValue) -> String {
+    format!("{:x}", Sha256::digest(token.0.as_bytes()))
+}
+
+#[derive(sqlx::FromRow)]
+struct ScopedTokenRow {
+    user_id: String,
+    scope: String,
+    expires_at: i64,
+}
+
+#[async_trait]
+impl Repository<ScopedTokenValue, ScopedToken> for PostgresScopedTokenRepository {
+    #[tracing::instrument(skip(self))]
+    async fn resolve(&self, id: &ScopedTokenValue) -> Result<Option<ScopedToken>, ResolveError> {
+        let row = match query_as::<_, ScopedTokenRow>(indoc! {"
+            SELECT user_id, scope, CAST(EXTRACT(EPOCH FROM expires_at) AS BIGINT) AS expires_at
+            FROM scoped_tokens WHERE token_hash=$1;
+        "})
+        .bind(hash_token(id))
+        .fetch_optional(self.db_connection())
+        .await
+        .context("Failed execute query")?
+        {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        Ok(Some(ScopedToken::new(
+            id.clone(),
+            UserId::new(row.user_id),
+            Scope::new(row.scope),
+            OffsetDateTime::from_unix_timestamp(row.expires_at).context("expires_at")?,
+        )))
+    }
+}
+
+#[async_trait]
+impl ScopedTokenRepository for PostgresScopedTokenRepository {
+    #[tracing::instrument(skip(self))]
+    async fn store(&self, t: &ScopedToken) -> Result<(), StoreError> {
+        query(indoc! {"
+            INSERT INTO scoped_tokens (token_hash, user_id, scope, expires_at, updated_at)
+            VALUES ($1, $2, $3, to_timestamp($4) AT TIME ZONE 'UTC', NOW())
+            ON CONFLICT ON CONSTRAINT scoped_tokens_pkey
+            DO UPDATE SET user_id=$2, scope=$3, expires_at=to_timestamp($4) AT TIME ZONE 'UTC', updated_at=NOW();
+        "})
+        .bind(hash_token(&t.token))
+        .bind(&t.user_id.0)
+        .bind(&t.scope.0)
+        .bind(t.expires_at.unix_timestamp())
+        .execute(self.db_connection())
+        .await
+        .context("Failed store scoped token")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PostgresScopedTokenRepository;
+    use crate::db_conn::{TestDBConnection, TestDBInterface};
+    use account::model::device_authorization::Scope;
+    use account::model::scoped_token::{ScopedToken, ScopedTokenValue};
+    use account::model::user::UserId;
+    use account::repository::meta::Repository;
+    use account::repository::scoped_token_repository::ScopedTokenRepository;
+    use time::OffsetDateTime;
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_scoped_token_repository_store_and_resolve() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let repo = PostgresScopedTokenRepository::new(db_conn.conn.clone());
+        sqlx::query("INSERT INTO users (id, updated_at) VALUES ($1, NOW());")
+            .bind("foo")
+            .execute(&db_conn.conn)
+            .await
+            .unwrap();
+        let token = ScopedToken::issue(
+            "xxxx".to_string(),
+            UserId::new("foo".to_string()),
+            Scope::new("profile".to_string()),
+            OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap(),
+        );
+        repo.store(&token).await.unwrap();
+        let resolved = repo.resolve(&token.token).await.unwrap();
+        let unknown = repo
+            .resolve(&ScopedTokenValue::new("mtk_unknown".to_string()))
+            .await
+            .unwrap();
+        db_conn.flush().await;
+
+        assert_eq!(resolved, Some(token));
+        assert!(unknown.is_none());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_scoped_token_repository_store_is_err_when_user_not_exist() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let repo = PostgresScopedTokenRepository::new(db_conn.conn.clone());
+        let token = ScopedToken::issue(
+            "xxxx".to_string(),
+            UserId::new("unknown".to_string()),
+            Scope::new("profile".to_string()),
+            OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap(),
+        );
+        let result = repo.store(&token).await;
+        db_conn.flush().await;
+
+        assert!(result.is_err());
+    }
+}
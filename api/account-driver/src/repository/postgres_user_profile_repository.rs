@@ -1,7 +1,7 @@
 use crate::db_conn::HaveDBConnection;
+use crate::replica::{self, HaveReplicaConnection};
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
-use derive_more::Constructor;
 use indoc::indoc;
 use semval::prelude::*;
 use sqlx::{query, query_as, PgPool};
@@ -9,14 +9,30 @@ use sqlx::{query, query_as, PgPool};
 use account::model::profile::avatar::Avatar;
 use account::model::profile::display_name::DisplayName;
 use account::model::profile::entity::{Profile, ProfileInvalidity};
+use account::model::profile::locale::Locale;
 use account::model::profile::user_name::UserName;
 use account::model::user_profile::{UserProfile, UserProfileId};
 use account::repository::meta::{Repository, ResolveError};
 use account::repository::user_profile_repository::{StoreError, UserProfileRepository};
 
-#[derive(Constructor, Debug, Clone)]
+/// Reads from `replica`, which is the primary itself unless set with `with_replica`.
+#[derive(Debug, Clone)]
 pub struct PostgresUserProfileRepository {
     conn: PgPool,
+    replica: PgPool,
+}
+
+impl PostgresUserProfileRepository {
+    pub fn new(conn: PgPool) -> Self {
+        Self {
+            replica: conn.clone(),
+            conn,
+        }
+    }
+
+    pub fn with_replica(self, replica: PgPool) -> Self {
+        Self { replica, ..self }
+    }
 }
 
 impl HaveDBConnection for PostgresUserProfileRepository {
@@ -25,6 +41,12 @@ impl HaveDBConnection for PostgresUserProfileRepository {
     }
 }
 
+impl HaveReplicaConnection for PostgresUserProfileRepository {
+    fn replica_connection(&self) -> &PgPool {
+        &self.replica
+    }
+}
+
 #[derive(sqlx::FromRow)]
 struct UserRow {
     pub id: String,
@@ -35,6 +57,7 @@ struct ProfileRow {
     name: String,
     display_name: String,
     avatar_url: String,
+    locale: Option<String>,
 }
 
 impl TryFrom<ProfileRow> for Profile {
@@ -46,6 +69,8 @@ impl TryFrom<ProfileRow> for Profile {
             avatar: Avatar {
                 url: value.avatar_url,
             },
+            // A locale no longer supported leaves the language to the request.
+            locale: value.locale.and_then(|l| Locale::try_from(l).ok()),
         };
         profile.validate()?;
         Ok(profile)
@@ -54,10 +79,11 @@ impl TryFrom<ProfileRow> for Profile {
 
 #[async_trait]
 impl Repository<UserProfileId, UserProfile> for PostgresUserProfileRepository {
+    #[tracing::instrument(skip(self))]
     async fn resolve(&self, id: &UserProfileId) -> Result<Option<UserProfile>, ResolveError> {
         let user_id = match query_as::<_, UserRow>("SELECT * FROM users where id=$1;")
             .bind(&id.0)
-            .fetch_optional(self.db_connection())
+            .fetch_optional(self.read_connection())
             .await
             .context("Failed execute query")?
         {
@@ -68,7 +94,7 @@ impl Repository<UserProfileId, UserProfile> for PostgresUserProfileRepository {
         };
         let profile = query_as::<_, ProfileRow>("SELECT * FROM profiles where user_id=$1;")
             .bind(&id.0)
-            .fetch_one(self.db_connection())
+            .fetch_one(self.read_connection())
             .await
             .context("Failed execute query")?;
 
@@ -80,16 +106,27 @@ impl Repository<UserProfileId, UserProfile> for PostgresUserProfileRepository {
 
 #[async_trait]
 impl UserProfileRepository for PostgresUserProfileRepository {
+    #[tracing::instrument(skip(self))]
+    async fn resolve_for_update(
+        &self,
+        id: &UserProfileId,
+    ) -> Result<Option<UserProfile>, ResolveError> {
+        replica::read_primary(self.resolve(id)).await
+    }
+
+    #[tracing::instrument(skip(self))]
     async fn store(&self, up: &UserProfile) -> Result<(), StoreError> {
+        replica::wrote();
         query(indoc! {"
-            INSERT INTO profiles (user_id, name, display_name, avatar_url, updated_at) VALUES ($1, $2, $3, $4, NOW())
+            INSERT INTO profiles (user_id, name, display_name, avatar_url, locale, updated_at) VALUES ($1, $2, $3, $4, $5, NOW())
             ON CONFLICT ON CONSTRAINT profiles_pkey
-            DO UPDATE SET user_id=$1, name=$2, display_name=$3, avatar_url=$4, updated_at=NOW();
+            DO UPDATE SET user_id=$1, name=$2, display_name=$3, avatar_url=$4, locale=$5, updated_at=NOW();
         "})
             .bind(&up.id.0)
             .bind(&up.profile.name.0)
             .bind(&up.profile.display_name.0)
             .bind(&up.profile.avatar.url)
+            .bind(up.profile.locale.as_ref().map(String::from))
             .execute(self.db_connection())
             .await
             .context("Failed insert profile")?;
@@ -113,7 +150,7 @@ mod tests {
     #[tokio::test]
     #[ignore]
     async fn test_postgres_user_profile_repository_resolve_is_resolved_user_profile() {
-        let db_conn = TestDBConnection::default().await;
+        let db_conn = TestDBConnection::default().await.unwrap();
         let repo = PostgresUserProfileRepository::new(db_conn.conn.clone());
         sqlx::query("INSERT INTO users (id, updated_at) VALUES ($1, NOW());")
             .bind("foo")
@@ -134,6 +171,7 @@ mod tests {
                 UserName("foo".to_string()),
                 DisplayName::new("fooo".to_string()),
                 Avatar::new("https://example.com".to_string()),
+                None,
             ),
         );
         let user_profile = repo
@@ -151,7 +189,7 @@ mod tests {
     #[ignore]
     async fn test_postgres_user_profile_repository_resolve_is_not_resolved_user_profile_when_not_exist(
     ) {
-        let db_conn = TestDBConnection::default().await;
+        let db_conn = TestDBConnection::default().await.unwrap();
         let repo = PostgresUserProfileRepository::new(db_conn.conn.clone());
         let user_profile = repo
             .resolve(&UserProfileId::new("foo".to_string()))
@@ -167,7 +205,7 @@ mod tests {
     #[ignore]
     #[cfg(test)]
     async fn test_postgres_user_profile_repository_store_is_stored_user_profile() {
-        let db_conn = TestDBConnection::default().await;
+        let db_conn = TestDBConnection::default().await.unwrap();
         let repo = PostgresUserProfileRepository::new(db_conn.conn.clone());
         let expected_user_profile = UserProfile::new(
             UserProfileId::new("fooo".to_string()),
@@ -175,10 +213,11 @@ mod tests {
                 UserName("fooo".to_string()),
                 DisplayName("fooo".to_string()),
                 Avatar::new("https://example.com".to_string()),
+                None,
             ),
         );
         sqlx::query("INSERT INTO users (id, updated_at) VALUES ($1, NOW());")
-            .bind(&expected_user_profile.id.to_string())
+            .bind(expected_user_profile.id.to_string())
             .execute(&repo.conn)
             .await
             .unwrap();
@@ -193,4 +232,26 @@ mod tests {
         assert_eq!(user, expected_user_profile);
         db_conn.flush().await;
     }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_postgres_user_profile_repository_store_is_err_when_user_not_exist() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let repo = PostgresUserProfileRepository::new(db_conn.conn.clone());
+        let orphan = UserProfile::new(
+            UserProfileId::new("unknown".to_string()),
+            Profile::new(
+                UserName("unknown".to_string()),
+                DisplayName("unknown".to_string()),
+                Avatar::new("https://example.com".to_string()),
+                None,
+            ),
+        );
+        let result = repo.store(&orphan).await;
+        let resolved = repo.resolve(&orphan.id).await.unwrap();
+        db_conn.flush().await;
+
+        assert!(result.is_err());
+        assert!(resolved.is_none());
+    }
 }
@@ -1,19 +1,38 @@
 use account::model::login_provider::{IdInProvider, LoginProvider, ProviderKind};
-use account::model::user::{User, UserId};
-use account::repository::meta::{Repository, ResolveError};
-use account::repository::user_repository::{FilterByIdInProviderError, StoreError, UserRepository};
+use account::model::role::Role;
+use account::model::user::{User, UserId, UserStatus};
+use account::repository::meta::{Page, Pagination, Repository, ResolveError};
+use account::repository::user_repository::{
+    FilterByIdInProviderError, SearchError, StoreError, UserRepository, UserSearchCondition,
+};
 use anyhow::Context;
 use async_trait::async_trait;
-use derive_more::Constructor;
 use indoc::indoc;
 use sqlx::{query, query_as, PgPool};
+use std::collections::HashMap;
 use tracing::info;
 
 use crate::db_conn::HaveDBConnection;
+use crate::replica::{self, HaveReplicaConnection};
 
-#[derive(Constructor, Debug, Clone)]
+/// Reads from `replica`, which is the primary itself unless set with `with_replica`.
+#[derive(Debug, Clone)]
 pub struct PostgresUserRepository {
     conn: PgPool,
+    replica: PgPool,
+}
+
+impl PostgresUserRepository {
+    pub fn new(conn: PgPool) -> Self {
+        Self {
+            replica: conn.clone(),
+            conn,
+        }
+    }
+
+    pub fn with_replica(self, replica: PgPool) -> Self {
+        Self { replica, ..self }
+    }
 }
 
 impl HaveDBConnection for PostgresUserRepository {
@@ -22,9 +41,40 @@ impl HaveDBConnection for PostgresUserRepository {
     }
 }
 
+impl HaveReplicaConnection for PostgresUserRepository {
+    fn replica_connection(&self) -> &PgPool {
+        &self.replica
+    }
+}
+
 #[derive(sqlx::FromRow)]
 struct UserRow {
     pub id: String,
+    pub status: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct CountRow {
+    pub total: i64,
+}
+
+const SEARCH_USERS_CONDITION: &str = indoc! {"
+    FROM users u
+    LEFT JOIN profiles p ON p.user_id = u.id
+    WHERE ($1::varchar IS NULL OR EXISTS (
+            SELECT 1 FROM login_providers lp WHERE lp.user_id = u.id AND lp.kind = $1
+        ))
+        AND ($2::bigint IS NULL OR u.created_at >= to_timestamp($2) AT TIME ZONE 'UTC')
+        AND ($3::bigint IS NULL OR u.created_at < to_timestamp($3) AT TIME ZONE 'UTC')
+        AND ($4::text IS NULL OR p.name LIKE $4 || '%' ESCAPE '\\')
+"};
+
+/// Escapes `LIKE` wildcards so a name prefix is matched literally.
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
 }
 
 #[derive(sqlx::FromRow, Clone)]
@@ -54,12 +104,37 @@ impl TryFrom<&LoginProviderRow> for LoginProvider {
     }
 }
 
+#[derive(sqlx::FromRow)]
+struct UserRoleRow {
+    pub role: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct UserIdRoleRow {
+    pub user_id: String,
+    pub role: String,
+}
+
+impl PostgresUserRepository {
+    async fn resolve_roles(&self, user_id: &str) -> Result<Vec<Role>, anyhow::Error> {
+        query_as::<_, UserRoleRow>("SELECT role FROM user_roles WHERE user_id=$1 ORDER BY role;")
+            .bind(user_id)
+            .fetch_all(self.read_connection())
+            .await
+            .context("Failed execute query")?
+            .into_iter()
+            .map(|row| Role::try_from(row.role).context("Role"))
+            .collect()
+    }
+}
+
 #[async_trait]
 impl Repository<UserId, User> for PostgresUserRepository {
+    #[tracing::instrument(skip(self))]
     async fn resolve(&self, id: &UserId) -> Result<Option<User>, ResolveError> {
         let user_row = match query_as::<_, UserRow>("SELECT * FROM users where id=$1;")
             .bind(&id.0)
-            .fetch_optional(self.db_connection())
+            .fetch_optional(self.read_connection())
             .await
             .context("Failed execute query")?
         {
@@ -71,20 +146,33 @@ impl Repository<UserId, User> for PostgresUserRepository {
         let provider_rows =
             query_as::<_, LoginProviderRow>("SELECT * FROM login_providers where user_id=$1;")
                 .bind(&id.0)
-                .fetch_all(self.db_connection())
+                .fetch_all(self.read_connection())
                 .await
                 .context("Failed execute query")?;
         let providers = provider_rows
             .iter()
             .map(|row| LoginProvider::try_from(row.clone()))
             .collect::<Result<Vec<LoginProvider>, anyhow::Error>>()?;
+        let roles = self.resolve_roles(&user_row.id).await?;
 
-        Ok(Some(User::new(UserId::new(user_row.id), Some(providers))))
+        let status = UserStatus::try_from(user_row.status).context("UserStatus")?;
+
+        Ok(Some(
+            User::new(UserId::new(user_row.id), Some(providers))
+                .with_roles(roles)
+                .with_status(status),
+        ))
     }
 }
 
 #[async_trait]
 impl UserRepository for PostgresUserRepository {
+    #[tracing::instrument(skip(self))]
+    async fn resolve_for_update(&self, id: &UserId) -> Result<Option<User>, ResolveError> {
+        replica::read_primary(self.resolve(id)).await
+    }
+
+    #[tracing::instrument(skip(self))]
     async fn find_by_id_in_provider(
         &self,
         id_in_provider: &IdInProvider,
@@ -93,16 +181,16 @@ impl UserRepository for PostgresUserRepository {
             "SELECT * FROM login_providers WHERE id_in_provider=$1",
         )
         .bind(&id_in_provider.0)
-        .fetch_all(self.db_connection())
+        .fetch_all(self.read_connection())
         .await
         .context("Failed execute query")?;
-        let first_provider_row = match provider_rows.get(0) {
+        let first_provider_row = match provider_rows.first() {
             Some(r) => r,
             None => return Ok(None),
         };
         let user = match query_as::<_, UserRow>("SELECT * FROM users WHERE id=$1")
             .bind(&first_provider_row.user_id)
-            .fetch_optional(self.db_connection())
+            .fetch_optional(self.read_connection())
             .await
             .context("Failed find user query")?
         {
@@ -111,19 +199,116 @@ impl UserRepository for PostgresUserRepository {
         };
         let providers = provider_rows
             .iter()
-            .map(|row| LoginProvider::try_from(row))
+            .map(LoginProvider::try_from)
             .collect::<Result<Vec<LoginProvider>, anyhow::Error>>()?;
-        Ok(Some(User::new(UserId::new(user.id), Some(providers))))
+        let roles = self.resolve_roles(&user.id).await?;
+        let status = UserStatus::try_from(user.status).context("UserStatus")?;
+        Ok(Some(
+            User::new(UserId::new(user.id), Some(providers))
+                .with_roles(roles)
+                .with_status(status),
+        ))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn search(
+        &self,
+        condition: &UserSearchCondition,
+        pagination: &Pagination,
+    ) -> Result<Page<User>, SearchError> {
+        let provider_kind = condition.provider_kind.as_ref().map(String::from);
+        let created_after = condition.created_after.map(|t| t.unix_timestamp());
+        let created_before = condition.created_before.map(|t| t.unix_timestamp());
+        let name_prefix = condition.name_prefix.as_deref().map(escape_like);
+
+        let count = query_as::<_, CountRow>(
+            format!("SELECT COUNT(*) AS total {};", SEARCH_USERS_CONDITION).as_str(),
+        )
+        .bind(&provider_kind)
+        .bind(created_after)
+        .bind(created_before)
+        .bind(&name_prefix)
+        .fetch_one(self.read_connection())
+        .await
+        .context("Failed count users query")?;
+        let user_rows = query_as::<_, UserRow>(
+            format!(
+                "SELECT u.id, u.status {} ORDER BY u.created_at, u.id LIMIT $5 OFFSET $6;",
+                SEARCH_USERS_CONDITION
+            )
+            .as_str(),
+        )
+        .bind(&provider_kind)
+        .bind(created_after)
+        .bind(created_before)
+        .bind(&name_prefix)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.read_connection())
+        .await
+        .context("Failed search users query")?;
+
+        // Providers and roles of the whole page, rather than resolving each user.
+        let ids = user_rows.iter().map(|u| u.id.clone()).collect::<Vec<_>>();
+        let mut providers = HashMap::<String, Vec<LoginProvider>>::new();
+        for row in query_as::<_, LoginProviderRow>(
+            "SELECT * FROM login_providers WHERE user_id = ANY($1);",
+        )
+        .bind(&ids)
+        .fetch_all(self.read_connection())
+        .await
+        .context("Failed search providers query")?
+        {
+            let provider = LoginProvider::try_from(&row)?;
+            providers.entry(row.user_id).or_default().push(provider);
+        }
+        let mut roles = HashMap::<String, Vec<Role>>::new();
+        for row in query_as::<_, UserIdRoleRow>(
+            "SELECT user_id, role FROM user_roles WHERE user_id = ANY($1) ORDER BY role;",
+        )
+        .bind(&ids)
+        .fetch_all(self.read_connection())
+        .await
+        .context("Failed search roles query")?
+        {
+            let role = Role::try_from(row.role).context("Role")?;
+            roles.entry(row.user_id).or_default().push(role);
+        }
+
+        let users = user_rows
+            .into_iter()
+            .map(|row| {
+                let status = UserStatus::try_from(row.status).context("UserStatus")?;
+                Ok(
+                    User::new(UserId::new(row.id.clone()), providers.remove(&row.id))
+                        .with_roles(roles.remove(&row.id).unwrap_or_default())
+                        .with_status(status),
+                )
+            })
+            .collect::<Result<Vec<User>, anyhow::Error>>()?;
+        Ok(Page::new(users, count.total))
     }
 
     #[tracing::instrument(skip(self))]
     async fn store(&self, u: &User) -> Result<(), StoreError> {
+        // Even a failed commit may have been applied.
+        replica::wrote();
         info!("Start transaction");
         let mut transaction = self
             .db_connection()
             .begin()
             .await
             .context("failed get context")?;
+        query(indoc! {"
+            INSERT INTO users (id, status, updated_at) VALUES ($1, $2, NOW())
+            ON CONFLICT ON CONSTRAINT users_pkey
+            DO UPDATE SET id=$1, status=$2, updated_at=NOW();
+        "})
+        .bind(u.id.0.clone())
+        .bind(String::from(&u.status))
+        .execute(&mut transaction)
+        .await
+        .context("failed user store")?;
         for login_provider in u.providers.iter() {
             query(indoc! {"
                 INSERT INTO login_providers (user_id, kind, id_in_provider, updated_at) VALUES ($1, $2, $3, NOW())
@@ -135,15 +320,19 @@ impl UserRepository for PostgresUserRepository {
                 .bind(&login_provider.id_in_provider.0)
                 .execute(&mut transaction).await.context("failed login_provider store")?;
         }
-        query(indoc! {"
-            INSERT INTO users (id, updated_at) VALUES ($1, NOW())
-            ON CONFLICT ON CONSTRAINT users_pkey
-            DO UPDATE SET id=$1, updated_at=NOW();
-        "})
-        .bind(u.id.0.clone())
-        .execute(&mut transaction)
-        .await
-        .context("failed user store")?;
+        query("DELETE FROM user_roles WHERE user_id=$1;")
+            .bind(&u.id.0)
+            .execute(&mut transaction)
+            .await
+            .context("failed user_roles delete")?;
+        for role in u.roles.iter() {
+            query("INSERT INTO user_roles (user_id, role, updated_at) VALUES ($1, $2, NOW());")
+                .bind(&u.id.0)
+                .bind(String::from(role))
+                .execute(&mut transaction)
+                .await
+                .context("failed user_roles store")?;
+        }
         transaction
             .commit()
             .await
@@ -157,15 +346,17 @@ impl UserRepository for PostgresUserRepository {
 mod tests {
     use super::PostgresUserRepository;
     use crate::db_conn::{TestDBConnection, TestDBInterface};
+    use crate::replica::{read_primary, track_writes};
     use account::model::login_provider::{IdInProvider, LoginProvider, ProviderKind};
-    use account::model::user::{User, UserId};
+    use account::model::role::Role;
+    use account::model::user::{User, UserId, UserStatus};
 
-    use account::repository::meta::Repository;
-    use account::repository::user_repository::UserRepository;
+    use account::repository::meta::{Pagination, Repository};
+    use account::repository::user_repository::{UserRepository, UserSearchCondition};
     #[tokio::test]
     #[ignore]
     async fn postgres_user_repository_resolve_return_to_user() {
-        let db_conn = TestDBConnection::default().await;
+        let db_conn = TestDBConnection::default().await.unwrap();
         let repo = PostgresUserRepository::new(db_conn.conn.clone());
         sqlx::query("INSERT INTO users (id, updated_at) VALUES ($1, NOW());")
             .bind("foo")
@@ -187,7 +378,7 @@ mod tests {
     #[tokio::test]
     #[ignore]
     async fn postgres_user_repository_resolve_return_to_resolve_err() {
-        let db_conn = TestDBConnection::default().await;
+        let db_conn = TestDBConnection::default().await.unwrap();
         let repo = PostgresUserRepository::new(db_conn.conn);
         let result = repo.resolve(&UserId::new("foo".to_string())).await.unwrap();
 
@@ -197,7 +388,7 @@ mod tests {
     #[tokio::test]
     #[ignore]
     async fn postgres_user_repository_find_by_id_in_filter_return_to_user() {
-        let db_conn = TestDBConnection::default().await;
+        let db_conn = TestDBConnection::default().await.unwrap();
         let repo = PostgresUserRepository::new(db_conn.conn.clone());
         let login_providers = vec![LoginProvider::new(
             ProviderKind::Google,
@@ -216,7 +407,7 @@ mod tests {
     #[tokio::test]
     #[ignore]
     async fn postgres_user_repository_find_by_id_in_filter_return_to_none() {
-        let db_conn = TestDBConnection::default().await;
+        let db_conn = TestDBConnection::default().await.unwrap();
         let repo = PostgresUserRepository::new(db_conn.conn.clone());
         let login_providers = vec![LoginProvider::new(
             ProviderKind::Google,
@@ -231,4 +422,159 @@ mod tests {
 
         assert_eq!(find_result.unwrap(), None);
     }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_user_repository_store_replace_roles() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let repo = PostgresUserRepository::new(db_conn.conn.clone());
+        let user = User::new(UserId::new("dummy1".to_string()), None)
+            .with_roles(vec![Role::Admin, Role::Editor]);
+        repo.store(&user).await.unwrap();
+        let user = user.with_roles(vec![Role::Viewer]);
+        repo.store(&user).await.unwrap();
+        let resolved = repo.resolve(&user.id).await;
+        db_conn.flush().await;
+
+        assert_eq!(resolved.unwrap(), Some(user));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_user_repository_store_status() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let repo = PostgresUserRepository::new(db_conn.conn.clone());
+        let user =
+            User::new(UserId::new("dummy1".to_string()), None).with_status(UserStatus::Disabled);
+        repo.store(&user).await.unwrap();
+        let resolved = repo.resolve(&user.id).await;
+        db_conn.flush().await;
+
+        assert_eq!(resolved.unwrap(), Some(user));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_user_repository_search_filter_by_provider_kind() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let repo = PostgresUserRepository::new(db_conn.conn.clone());
+        let google_user = User::new(
+            UserId::new("dummy1".to_string()),
+            Some(vec![LoginProvider::new(
+                ProviderKind::Google,
+                IdInProvider::new("test1".to_string()),
+            )]),
+        );
+        let other_user = User::new(UserId::new("dummy2".to_string()), None);
+        repo.store(&google_user).await.unwrap();
+        repo.store(&other_user).await.unwrap();
+        let condition = UserSearchCondition {
+            provider_kind: Some(ProviderKind::Google),
+            ..Default::default()
+        };
+        let page = repo.search(&condition, &Pagination::default()).await;
+        db_conn.flush().await;
+
+        let page = page.unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items, vec![google_user]);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_user_repository_reject_orphan_login_provider() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let result = sqlx::query(
+            "INSERT INTO login_providers (user_id, kind, id_in_provider, updated_at) VALUES ($1, $2, $3, NOW());",
+        )
+        .bind("unknown")
+        .bind("Google")
+        .bind("test1")
+        .execute(&db_conn.conn)
+        .await;
+        db_conn.flush().await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_user_repository_delete_user_cascade_to_owned_rows() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let repo = PostgresUserRepository::new(db_conn.conn.clone());
+        let user = User::new(
+            UserId::new("dummy1".to_string()),
+            Some(vec![LoginProvider::new(
+                ProviderKind::Google,
+                IdInProvider::new("test1".to_string()),
+            )]),
+        )
+        .with_roles(vec![Role::Viewer]);
+        repo.store(&user).await.unwrap();
+        sqlx::query("DELETE FROM users WHERE id=$1;")
+            .bind(&user.id.0)
+            .execute(&db_conn.conn)
+            .await
+            .unwrap();
+        let found = repo
+            .find_by_id_in_provider(&IdInProvider::new("test1".to_string()))
+            .await
+            .unwrap();
+        let (roles,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM user_roles WHERE user_id=$1;")
+            .bind(&user.id.0)
+            .fetch_one(&db_conn.conn)
+            .await
+            .unwrap();
+        db_conn.flush().await;
+
+        assert!(found.is_none());
+        assert_eq!(roles, 0);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_user_repository_store_on_primary_and_read_own_writes() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let repo =
+            PostgresUserRepository::new(db_conn.conn.clone()).with_replica(db_conn.replica.clone());
+        let user = User::new(UserId::new("dummy1".to_string()), None);
+        let resolved = track_writes(async {
+            repo.store(&user).await.unwrap();
+            repo.resolve(&user.id).await
+        })
+        .await;
+        db_conn.flush().await;
+
+        assert_eq!(resolved.unwrap(), Some(user));
+        assert_eq!(db_conn.replica.size(), 0);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_user_repository_read_from_replica_unless_primary_asked() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let repo =
+            PostgresUserRepository::new(db_conn.conn.clone()).with_replica(db_conn.replica.clone());
+        let id = IdInProvider::new("foo".to_string());
+        let on_primary = read_primary(repo.find_by_id_in_provider(&id)).await;
+        let replica_used_by_primary_read = db_conn.replica.size();
+        let on_replica = repo.find_by_id_in_provider(&id).await;
+
+        assert!(on_primary.unwrap().is_none());
+        assert_eq!(replica_used_by_primary_read, 0);
+        assert!(on_replica.unwrap().is_none());
+        assert_eq!(db_conn.replica.size(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_user_repository_resolve_for_update_on_primary() {
+        let db_conn = TestDBConnection::default().await.unwrap();
+        let repo =
+            PostgresUserRepository::new(db_conn.conn.clone()).with_replica(db_conn.replica.clone());
+        let resolved = track_writes(repo.resolve_for_update(&UserId::new("foo".to_string()))).await;
+
+        assert!(resolved.unwrap().is_none());
+        assert_eq!(db_conn.replica.size(), 0);
+    }
 }
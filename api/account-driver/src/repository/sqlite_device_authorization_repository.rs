@@ -0,0 +1,313 @@
+use account::model::device_authorization::{
+    ClientId, DeviceAuthorization, DeviceAuthorizationStatus, DeviceCode, Scope, UserCode,
+};
+use account::model::user::UserId;
+use account::repository::device_authorization_repository::{
+    DeviceAuthorizationRepository, FindByUserCodeError, StoreError,
+};
+use account::repository::meta::{Repository, ResolveError};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use indoc::indoc;
+use sqlx::{query, query_as, SqlitePool};
+use time::OffsetDateTime;
+
+use super::postgres_device_authorization_repository::hash_device_code;
+use crate::db_conn::HaveSqliteConnection;
+
+#[derive(Constructor, Debug, Clone)]
+pub struct SqliteDeviceAuthorizationRepository {
+    conn: SqlitePool,
+}
+
+impl HaveSqliteConnection for SqliteDeviceAuthorizationRepository {
+    fn sqlite_connection(&self) -> &SqlitePool {
+        &self.conn
+    }
+}
+
+const SELECT_DEVICE_AUTHORIZATION: &str = indoc! {"
+    SELECT user_code, client_id, scope, status, user_id, interval_seconds,
+        expires_at, last_polled_at
+    FROM device_authorizations
+"};
+
+#[derive(sqlx::FromRow)]
+struct DeviceAuthorizationRow {
+    user_code: String,
+    client_id: String,
+    scope: String,
+    status: String,
+    user_id: Option<String>,
+    interval_seconds: i64,
+    expires_at: i64,
+    last_polled_at: Option<i64>,
+}
+
+impl DeviceAuthorizationRow {
+    /// `device_code` is the code the row was resolved by, empty when it was found by user code.
+    fn into_authorization(self, device_code: DeviceCode) -> anyhow::Result<DeviceAuthorization> {
+        let status = match (self.status.as_str(), self.user_id) {
+            ("Pending", _) => DeviceAuthorizationStatus::Pending,
+            ("Approved", Some(user_id)) => {
+                DeviceAuthorizationStatus::Approved(UserId::new(user_id))
+            }
+            ("Denied", _) => DeviceAuthorizationStatus::Denied,
+            ("Consumed", _) => DeviceAuthorizationStatus::Consumed,
+            (status, _) => return Err(anyhow!("Unknown device authorization status: {}", status)),
+        };
+        Ok(DeviceAuthorization {
+            device_code,
+            user_code: UserCode::new(self.user_code),
+            client_id: ClientId::new(self.client_id),
+            scope: Scope::new(self.scope),
+            status,
+            interval: self.interval_seconds,
+            expires_at: OffsetDateTime::from_unix_timestamp(self.expires_at)
+                .context("expires_at")?,
+            last_polled_at: self
+                .last_polled_at
+                .map(OffsetDateTime::from_unix_timestamp)
+                .transpose()
+                .context("last_polled_at")?,
+        })
+    }
+}
+
+fn status_columns(status: &DeviceAuthorizationStatus) -> (&'static str, Option<&str>) {
+    match status {
+        DeviceAuthorizationStatus::Pending => ("Pending", None),
+        DeviceAuthorizationStatus::Approved(user_id) => ("Approved", Some(&user_id.0)),
+        DeviceAuthorizationStatus::Denied => ("Denied", None),
+        DeviceAuthorizationStatus::Consumed => ("Consumed", None),
+    }
+}
+
+#[async_trait]
+impl Repository<DeviceCode, DeviceAuthorization> for SqliteDeviceAuthorizationRepository {
+    #[tracing::instrument(skip(self))]
+    async fn resolve(&self, id: &DeviceCode) -> Result<Option<DeviceAuthorization>, ResolveError> {
+        let row = query_as::<_, DeviceAuthorizationRow>(
+            format!("{} WHERE device_code_hash=?1;", SELECT_DEVICE_AUTHORIZATION).as_str(),
+        )
+        .bind(hash_device_code(id))
+        .fetch_optional(self.sqlite_connection())
+        .await
+        .context("Failed execute query")?;
+        Ok(row.map(|r| r.into_authorization(id.clone())).transpose()?)
+    }
+}
+
+#[async_trait]
+impl DeviceAuthorizationRepository for SqliteDeviceAuthorizationRepository {
+    #[tracing::instrument(skip(self))]
+    async fn store(&self, a: &DeviceAuthorization) -> Result<(), StoreError> {
+        let (status, user_id) = status_columns(&a.status);
+        query(indoc! {"
+            INSERT INTO device_authorizations (device_code_hash, user_code, client_id, scope, status, user_id, interval_seconds, expires_at, last_polled_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, strftime('%s', 'now'))
+            ON CONFLICT (device_code_hash)
+            DO UPDATE SET status=?5, user_id=?6, interval_seconds=?7, last_polled_at=?9, updated_at=strftime('%s', 'now');
+        "})
+            .bind(hash_device_code(&a.device_code))
+            .bind(&a.user_code.0)
+            .bind(&a.client_id.0)
+            .bind(&a.scope.0)
+            .bind(status)
+            .bind(user_id)
+            .bind(a.interval)
+            .bind(a.expires_at.unix_timestamp())
+            .bind(a.last_polled_at.map(|t| t.unix_timestamp()))
+            .execute(self.sqlite_connection())
+            .await
+            .context("Failed store device authorization")?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn store_if(
+        &self,
+        a: &DeviceAuthorization,
+        expected: &DeviceAuthorizationStatus,
+    ) -> Result<bool, StoreError> {
+        let (status, user_id) = status_columns(&a.status);
+        let (expected_status, expected_user_id) = status_columns(expected);
+        let result = query(indoc! {"
+            UPDATE device_authorizations
+            SET status=?2, user_id=?3, interval_seconds=?4, last_polled_at=?5, updated_at=strftime('%s', 'now')
+            WHERE user_code=?1 AND status=?6 AND user_id IS ?7;
+        "})
+            .bind(&a.user_code.0)
+            .bind(status)
+            .bind(user_id)
+            .bind(a.interval)
+            .bind(a.last_polled_at.map(|t| t.unix_timestamp()))
+            .bind(expected_status)
+            .bind(expected_user_id)
+            .execute(self.sqlite_connection())
+            .await
+            .context("Failed store device authorization")?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn find_by_user_code(
+        &self,
+        user_code: &UserCode,
+    ) -> Result<Option<DeviceAuthorization>, FindByUserCodeError> {
+        let row = query_as::<_, DeviceAuthorizationRow>(
+            format!("{} WHERE user_code=?1;", SELECT_DEVICE_AUTHORIZATION).as_str(),
+        )
+        .bind(&user_code.0)
+        .fetch_optional(self.sqlite_connection())
+        .await
+        .context("Failed execute query")?;
+        Ok(row
+            .map(|r| r.into_authorization(DeviceCode::default()))
+            .transpose()?)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_expired(&self, before: OffsetDateTime) -> Result<u64, StoreError> {
+        let done = query("DELETE FROM device_authorizations WHERE expires_at < ?1;")
+            .bind(before.unix_timestamp())
+            .execute(self.sqlite_connection())
+            .await
+            .context("Failed delete expired device authorizations")?;
+        Ok(done.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SqliteDeviceAuthorizationRepository;
+    use crate::db_conn::test_sqlite_pool;
+    use crate::repository::postgres_device_authorization_repository::hash_device_code;
+    use account::model::device_authorization::{
+        ClientId, DeviceAuthorization, DeviceAuthorizationStatus, DeviceCode, Scope, UserCode,
+    };
+    use account::model::user::UserId;
+    use account::repository::device_authorization_repository::DeviceAuthorizationRepository;
+    use account::repository::meta::Repository;
+    use time::OffsetDateTime;
+
+    fn issued() -> DeviceAuthorization {
+        DeviceAuthorization::issue(
+            DeviceCode::new("device".to_string()),
+            UserCode::new("BCDF-GHJK".to_string()),
+            ClientId::new("cli".to_string()),
+            Scope::new("profile".to_string()),
+            OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn sqlite_device_authorization_repository_store_and_resolve() {
+        let repo = SqliteDeviceAuthorizationRepository::new(test_sqlite_pool().await);
+        sqlx::query("INSERT INTO users (id, updated_at) VALUES (?1, 0);")
+            .bind("foo")
+            .execute(&repo.conn)
+            .await
+            .unwrap();
+        let mut authorization = issued();
+        repo.store(&authorization).await.unwrap();
+        authorization
+            .approve(
+                UserId::new("foo".to_string()),
+                authorization.expires_at - time::Duration::seconds(1),
+            )
+            .unwrap();
+        repo.store(&authorization).await.unwrap();
+
+        let resolved = repo.resolve(&authorization.device_code).await.unwrap();
+        let found = repo
+            .find_by_user_code(&authorization.user_code)
+            .await
+            .unwrap();
+
+        assert_eq!(resolved, Some(authorization.clone()));
+        assert_eq!(
+            found,
+            Some(DeviceAuthorization {
+                device_code: DeviceCode::default(),
+                ..authorization
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn sqlite_device_authorization_repository_store_if_only_from_expected_status() {
+        let repo = SqliteDeviceAuthorizationRepository::new(test_sqlite_pool().await);
+        sqlx::query("INSERT INTO users (id, updated_at) VALUES (?1, 0);")
+            .bind("foo")
+            .execute(&repo.conn)
+            .await
+            .unwrap();
+        let mut authorization = issued();
+        repo.store(&authorization).await.unwrap();
+        let now = authorization.expires_at - time::Duration::seconds(1);
+        authorization
+            .approve(UserId::new("foo".to_string()), now)
+            .unwrap();
+        let approved = repo
+            .store_if(&authorization, &DeviceAuthorizationStatus::Pending)
+            .await
+            .unwrap();
+        let expected = authorization.status.clone();
+        authorization.poll(now).unwrap();
+        let consumed = repo.store_if(&authorization, &expected).await.unwrap();
+        let consumed_again = repo.store_if(&authorization, &expected).await.unwrap();
+        let resolved = repo.resolve(&authorization.device_code).await.unwrap();
+
+        assert!(approved);
+        assert!(consumed);
+        assert!(!consumed_again);
+        assert_eq!(resolved, Some(authorization));
+    }
+
+    #[tokio::test]
+    async fn sqlite_device_authorization_repository_resolve_return_to_none() {
+        let repo = SqliteDeviceAuthorizationRepository::new(test_sqlite_pool().await);
+        let resolved = repo
+            .resolve(&DeviceCode::new("unknown".to_string()))
+            .await
+            .unwrap();
+
+        assert!(resolved.is_none());
+    }
+
+    #[tokio::test]
+    async fn sqlite_device_authorization_repository_store_digest_of_device_code() {
+        let repo = SqliteDeviceAuthorizationRepository::new(test_sqlite_pool().await);
+        repo.store(&issued()).await.unwrap();
+
+        let (stored,): (String,) =
+            sqlx::query_as("SELECT device_code_hash FROM device_authorizations;")
+                .fetch_one(&repo.conn)
+                .await
+                .unwrap();
+
+        assert_eq!(
+            stored,
+            hash_device_code(&DeviceCode::new("device".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn sqlite_device_authorization_repository_delete_expired() {
+        let repo = SqliteDeviceAuthorizationRepository::new(test_sqlite_pool().await);
+        let authorization = issued();
+        repo.store(&authorization).await.unwrap();
+
+        let before_expiry = repo.delete_expired(authorization.expires_at).await.unwrap();
+        let after_expiry = repo
+            .delete_expired(authorization.expires_at + time::Duration::seconds(1))
+            .await
+            .unwrap();
+        let resolved = repo.resolve(&authorization.device_code).await.unwrap();
+
+        assert_eq!((before_expiry, after_expiry), (0, 1));
+        assert!(resolved.is_none());
+    }
+}
@@ -0,0 +1,127 @@
+use account::model::device_authorization::Scope;
+use account::model::scoped_token::{ScopedToken, ScopedTokenValue};
+use account::model::user::UserId;
+use account::repository::meta::{Repository, ResolveError};
+use account::repository::scoped_token_repository::{ScopedTokenRepository, StoreError};
+use anyhow::Context;
+use async_trait::async_trait;
+use derive_more::Constructor;
+use indoc::indoc;
+use sqlx::{query, query_as, SqlitePool};
+use time::OffsetDateTime;
+
+use super::postgres_scoped_token_repository::hash_token;
+use crate::db_conn::HaveSqliteConnection;
+
+/// Persists the same SHA-256 digest as the postgres repository, never the token itself.
+#[derive(Constructor, Debug, Clone)]
+pub struct SqliteScopedTokenRepository {
+    conn: SqlitePool,
+}
+
+impl HaveSqliteConnection for SqliteScopedTokenRepository {
+    fn sqlite_connection(&self) -> &SqlitePool {
+        &self.conn
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ScopedTokenRow {
+    user_id: String,
+    scope: String,
+    expires_at: i64,
+}
+
+#[async_trait]
+impl Repository<ScopedTokenValue, ScopedToken> for SqliteScopedTokenRepository {
+    #[tracing::instrument(skip(self))]
+    async fn resolve(&self, id: &ScopedTokenValue) -> Result<Option<ScopedToken>, ResolveError> {
+        let row = match query_as::<_, ScopedTokenRow>(
+            "SELECT user_id, scope, expires_at FROM scoped_tokens WHERE token_hash=?1;",
+        )
+        .bind(hash_token(id))
+        .fetch_optional(self.sqlite_connection())
+        .await
+        .context("Failed execute query")?
+        {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        Ok(Some(ScopedToken::new(
+            id.clone(),
+            UserId::new(row.user_id),
+            Scope::new(row.scope),
+            OffsetDateTime::from_unix_timestamp(row.expires_at).context("expires_at")?,
+        )))
+    }
+}
+
+#[async_trait]
+impl ScopedTokenRepository for SqliteScopedTokenRepository {
+    #[tracing::instrument(skip(self))]
+    async fn store(&self, t: &ScopedToken) -> Result<(), StoreError> {
+        query(indoc! {"
+            INSERT INTO scoped_tokens (token_hash, user_id, scope, expires_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, strftime('%s', 'now'))
+            ON CONFLICT (token_hash)
+            DO UPDATE SET user_id=?2, scope=?3, expires_at=?4, updated_at=strftime('%s', 'now');
+        "})
+        .bind(hash_token(&t.token))
+        .bind(&t.user_id.0)
+        .bind(&t.scope.0)
+        .bind(t.expires_at.unix_timestamp())
+        .execute(self.sqlite_connection())
+        .await
+        .context("Failed store scoped token")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SqliteScopedTokenRepository;
+    use crate::db_conn::test_sqlite_pool;
+    use account::model::device_authorization::Scope;
+    use account::model::scoped_token::{ScopedToken, ScopedTokenValue};
+    use account::model::user::UserId;
+    use account::repository::meta::Repository;
+    use account::repository::scoped_token_repository::ScopedTokenRepository;
+    use time::OffsetDateTime;
+
+    fn token(user_id: &str) -> ScopedToken {
+        ScopedToken::issue(
+            "xxxx".to_string(),
+            UserId::new(user_id.to_string()),
+            Scope::new("profile".to_string()),
+            OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn sqlite_scoped_token_repository_store_and_resolve() {
+        let repo = SqliteScopedTokenRepository::new(test_sqlite_pool().await);
+        sqlx::query("INSERT INTO users (id, updated_at) VALUES (?1, 0);")
+            .bind("foo")
+            .execute(&repo.conn)
+            .await
+            .unwrap();
+        let token = token("foo");
+        repo.store(&token).await.unwrap();
+        let resolved = repo.resolve(&token.token).await.unwrap();
+        let unknown = repo
+            .resolve(&ScopedTokenValue::new("mtk_unknown".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(resolved, Some(token));
+        assert!(unknown.is_none());
+    }
+
+    #[tokio::test]
+    async fn sqlite_scoped_token_repository_store_is_err_when_user_not_exist() {
+        let repo = SqliteScopedTokenRepository::new(test_sqlite_pool().await);
+        let result = repo.store(&token("unknown")).await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,178 @@
+use crate::db_conn::HaveSqliteConnection;
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use indoc::indoc;
+use semval::prelude::*;
+use sqlx::{query, query_as, SqlitePool};
+
+use account::model::profile::avatar::Avatar;
+use account::model::profile::display_name::DisplayName;
+use account::model::profile::entity::{Profile, ProfileInvalidity};
+use account::model::profile::locale::Locale;
+use account::model::profile::user_name::UserName;
+use account::model::user_profile::{UserProfile, UserProfileId};
+use account::repository::meta::{Repository, ResolveError};
+use account::repository::user_profile_repository::{StoreError, UserProfileRepository};
+
+#[derive(Constructor, Debug, Clone)]
+pub struct SqliteUserProfileRepository {
+    conn: SqlitePool,
+}
+
+impl HaveSqliteConnection for SqliteUserProfileRepository {
+    fn sqlite_connection(&self) -> &SqlitePool {
+        &self.conn
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct UserRow {
+    pub id: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct ProfileRow {
+    name: String,
+    display_name: String,
+    avatar_url: String,
+    locale: Option<String>,
+}
+
+impl TryFrom<ProfileRow> for Profile {
+    type Error = ValidationContext<ProfileInvalidity>;
+    fn try_from(value: ProfileRow) -> Result<Self, Self::Error> {
+        let profile = Profile {
+            name: UserName(value.name),
+            display_name: DisplayName(value.display_name),
+            avatar: Avatar {
+                url: value.avatar_url,
+            },
+            // A locale no longer supported leaves the language to the request.
+            locale: value.locale.and_then(|l| Locale::try_from(l).ok()),
+        };
+        profile.validate()?;
+        Ok(profile)
+    }
+}
+
+#[async_trait]
+impl Repository<UserProfileId, UserProfile> for SqliteUserProfileRepository {
+    #[tracing::instrument(skip(self))]
+    async fn resolve(&self, id: &UserProfileId) -> Result<Option<UserProfile>, ResolveError> {
+        let user_id = match query_as::<_, UserRow>("SELECT id FROM users WHERE id=?1;")
+            .bind(&id.0)
+            .fetch_optional(self.sqlite_connection())
+            .await
+            .context("Failed execute query")?
+        {
+            Some(row) => row.id,
+            None => return Ok(None),
+        };
+        let profile = query_as::<_, ProfileRow>(
+            "SELECT name, display_name, avatar_url, locale FROM profiles WHERE user_id=?1;",
+        )
+        .bind(&id.0)
+        .fetch_one(self.sqlite_connection())
+        .await
+        .context("Failed execute query")?;
+
+        let profile = Profile::try_from(profile)
+            .map_err(|_| anyhow!("Cannot convert profile_row to model. id: {}", user_id))?;
+        Ok(Some(UserProfile::new(UserProfileId::new(user_id), profile)))
+    }
+}
+
+#[async_trait]
+impl UserProfileRepository for SqliteUserProfileRepository {
+    /// Same as `resolve`: there is no replica.
+    #[tracing::instrument(skip(self))]
+    async fn resolve_for_update(
+        &self,
+        id: &UserProfileId,
+    ) -> Result<Option<UserProfile>, ResolveError> {
+        self.resolve(id).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn store(&self, up: &UserProfile) -> Result<(), StoreError> {
+        query(indoc! {"
+            INSERT INTO profiles (user_id, name, display_name, avatar_url, locale, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, strftime('%s', 'now'))
+            ON CONFLICT (user_id)
+            DO UPDATE SET name=?2, display_name=?3, avatar_url=?4, locale=?5, updated_at=strftime('%s', 'now');
+        "})
+            .bind(&up.id.0)
+            .bind(&up.profile.name.0)
+            .bind(&up.profile.display_name.0)
+            .bind(&up.profile.avatar.url)
+            .bind(up.profile.locale.as_ref().map(String::from))
+            .execute(self.sqlite_connection())
+            .await
+            .context("Failed insert profile")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SqliteUserProfileRepository;
+    use crate::db_conn::test_sqlite_pool;
+    use account::model::profile::avatar::Avatar;
+    use account::model::profile::display_name::DisplayName;
+    use account::model::profile::entity::Profile;
+    use account::model::profile::user_name::UserName;
+    use account::model::user_profile::{UserProfile, UserProfileId};
+    use account::repository::meta::Repository;
+    use account::repository::user_profile_repository::UserProfileRepository;
+
+    fn user_profile(id: &str) -> UserProfile {
+        UserProfile::new(
+            UserProfileId::new(id.to_string()),
+            Profile::new(
+                UserName(id.to_string()),
+                DisplayName(id.to_string()),
+                Avatar::new("https://example.com".to_string()),
+                None,
+            ),
+        )
+    }
+
+    #[tokio::test]
+    async fn sqlite_user_profile_repository_resolve_return_to_none_when_not_exist() {
+        let repo = SqliteUserProfileRepository::new(test_sqlite_pool().await);
+        let user_profile = repo
+            .resolve(&UserProfileId::new("foo".to_string()))
+            .await
+            .unwrap();
+
+        assert!(user_profile.is_none());
+    }
+
+    #[tokio::test]
+    async fn sqlite_user_profile_repository_store_and_update() {
+        let repo = SqliteUserProfileRepository::new(test_sqlite_pool().await);
+        sqlx::query("INSERT INTO users (id, updated_at) VALUES (?1, 0);")
+            .bind("fooo")
+            .execute(&repo.conn)
+            .await
+            .unwrap();
+        let mut expected = user_profile("fooo");
+        repo.store(&expected).await.unwrap();
+        expected.profile.display_name = DisplayName("renamed".to_string());
+        repo.store(&expected).await.unwrap();
+        let resolved = repo.resolve(&expected.id).await.unwrap();
+
+        assert_eq!(resolved, Some(expected));
+    }
+
+    #[tokio::test]
+    async fn sqlite_user_profile_repository_store_is_err_when_user_not_exist() {
+        let repo = SqliteUserProfileRepository::new(test_sqlite_pool().await);
+        let orphan = user_profile("unknown");
+        let result = repo.store(&orphan).await;
+        let resolved = repo.resolve(&orphan.id).await.unwrap();
+
+        assert!(result.is_err());
+        assert!(resolved.is_none());
+    }
+}
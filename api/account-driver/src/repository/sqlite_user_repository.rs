@@ -0,0 +1,480 @@
+use account::model::login_provider::{IdInProvider, LoginProvider, ProviderKind};
+use account::model::role::Role;
+use account::model::user::{User, UserId, UserStatus};
+use account::repository::meta::{Page, Pagination, Repository, ResolveError};
+use account::repository::user_repository::{
+    FilterByIdInProviderError, SearchError, StoreError, UserRepository, UserSearchCondition,
+};
+use anyhow::Context;
+use async_trait::async_trait;
+use derive_more::Constructor;
+use indoc::indoc;
+use sqlx::{query, query_as, SqlitePool};
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::db_conn::HaveSqliteConnection;
+
+#[derive(Constructor, Debug, Clone)]
+pub struct SqliteUserRepository {
+    conn: SqlitePool,
+}
+
+impl HaveSqliteConnection for SqliteUserRepository {
+    fn sqlite_connection(&self) -> &SqlitePool {
+        &self.conn
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct UserRow {
+    pub id: String,
+    pub status: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct CountRow {
+    pub total: i64,
+}
+
+/// SQLite `LIKE` ignores case, so the name prefix is compared with `substr` instead.
+const SEARCH_USERS_CONDITION: &str = indoc! {"
+    FROM users u
+    LEFT JOIN profiles p ON p.user_id = u.id
+    WHERE (?1 IS NULL OR EXISTS (
+            SELECT 1 FROM login_providers lp WHERE lp.user_id = u.id AND lp.kind = ?1
+        ))
+        AND (?2 IS NULL OR u.created_at >= ?2)
+        AND (?3 IS NULL OR u.created_at < ?3)
+        AND (?4 IS NULL OR substr(p.name, 1, length(?4)) = ?4)
+"};
+
+#[derive(sqlx::FromRow)]
+struct LoginProviderRow {
+    pub kind: String,
+    pub id_in_provider: String,
+    pub user_id: String,
+}
+
+impl TryFrom<&LoginProviderRow> for LoginProvider {
+    type Error = anyhow::Error;
+    fn try_from(x: &LoginProviderRow) -> Result<Self, Self::Error> {
+        Ok(Self::new(
+            ProviderKind::try_from(x.kind.clone()).context("ProviderKind")?,
+            IdInProvider::new(x.id_in_provider.clone()),
+        ))
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct UserRoleRow {
+    pub role: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct UserIdRoleRow {
+    pub user_id: String,
+    pub role: String,
+}
+
+impl SqliteUserRepository {
+    async fn resolve_roles(&self, user_id: &str) -> Result<Vec<Role>, anyhow::Error> {
+        query_as::<_, UserRoleRow>("SELECT role FROM user_roles WHERE user_id=?1 ORDER BY role;")
+            .bind(user_id)
+            .fetch_all(self.sqlite_connection())
+            .await
+            .context("Failed execute query")?
+            .into_iter()
+            .map(|row| Role::try_from(row.role).context("Role"))
+            .collect()
+    }
+
+    async fn to_user(
+        &self,
+        user_row: UserRow,
+        provider_rows: &[LoginProviderRow],
+    ) -> Result<User, anyhow::Error> {
+        let providers = provider_rows
+            .iter()
+            .map(LoginProvider::try_from)
+            .collect::<Result<Vec<LoginProvider>, anyhow::Error>>()?;
+        let roles = self.resolve_roles(&user_row.id).await?;
+        let status = UserStatus::try_from(user_row.status).context("UserStatus")?;
+        Ok(User::new(UserId::new(user_row.id), Some(providers))
+            .with_roles(roles)
+            .with_status(status))
+    }
+}
+
+#[async_trait]
+impl Repository<UserId, User> for SqliteUserRepository {
+    #[tracing::instrument(skip(self))]
+    async fn resolve(&self, id: &UserId) -> Result<Option<User>, ResolveError> {
+        let user_row = match query_as::<_, UserRow>("SELECT id, status FROM users WHERE id=?1;")
+            .bind(&id.0)
+            .fetch_optional(self.sqlite_connection())
+            .await
+            .context("Failed execute query")?
+        {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let provider_rows = query_as::<_, LoginProviderRow>(
+            "SELECT kind, id_in_provider, user_id FROM login_providers WHERE user_id=?1;",
+        )
+        .bind(&id.0)
+        .fetch_all(self.sqlite_connection())
+        .await
+        .context("Failed execute query")?;
+        Ok(Some(self.to_user(user_row, &provider_rows).await?))
+    }
+}
+
+#[async_trait]
+impl UserRepository for SqliteUserRepository {
+    /// Same as `resolve`: there is no replica.
+    #[tracing::instrument(skip(self))]
+    async fn resolve_for_update(&self, id: &UserId) -> Result<Option<User>, ResolveError> {
+        self.resolve(id).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn find_by_id_in_provider(
+        &self,
+        id_in_provider: &IdInProvider,
+    ) -> Result<Option<User>, FilterByIdInProviderError> {
+        let provider_rows = query_as::<_, LoginProviderRow>(
+            "SELECT kind, id_in_provider, user_id FROM login_providers WHERE id_in_provider=?1;",
+        )
+        .bind(&id_in_provider.0)
+        .fetch_all(self.sqlite_connection())
+        .await
+        .context("Failed execute query")?;
+        let first_provider_row = match provider_rows.first() {
+            Some(r) => r,
+            None => return Ok(None),
+        };
+        let user_row = match query_as::<_, UserRow>("SELECT id, status FROM users WHERE id=?1;")
+            .bind(&first_provider_row.user_id)
+            .fetch_optional(self.sqlite_connection())
+            .await
+            .context("Failed find user query")?
+        {
+            Some(u) => u,
+            None => return Ok(None),
+        };
+        Ok(Some(self.to_user(user_row, &provider_rows).await?))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn search(
+        &self,
+        condition: &UserSearchCondition,
+        pagination: &Pagination,
+    ) -> Result<Page<User>, SearchError> {
+        let provider_kind = condition.provider_kind.as_ref().map(String::from);
+        let created_after = condition.created_after.map(|t| t.unix_timestamp());
+        let created_before = condition.created_before.map(|t| t.unix_timestamp());
+
+        let count = query_as::<_, CountRow>(
+            format!("SELECT COUNT(*) AS total {};", SEARCH_USERS_CONDITION).as_str(),
+        )
+        .bind(&provider_kind)
+        .bind(created_after)
+        .bind(created_before)
+        .bind(&condition.name_prefix)
+        .fetch_one(self.sqlite_connection())
+        .await
+        .context("Failed count users query")?;
+        let user_rows = query_as::<_, UserRow>(
+            format!(
+                "SELECT u.id, u.status {} ORDER BY u.created_at, u.id LIMIT ?5 OFFSET ?6;",
+                SEARCH_USERS_CONDITION
+            )
+            .as_str(),
+        )
+        .bind(&provider_kind)
+        .bind(created_after)
+        .bind(created_before)
+        .bind(&condition.name_prefix)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.sqlite_connection())
+        .await
+        .context("Failed search users query")?;
+
+        // Providers and roles of the whole page, rather than resolving each user. A page is
+        // far below the number of parameters SQLite binds.
+        let placeholders = vec!["?"; user_rows.len()].join(", ");
+        let provider_sql = format!(
+            "SELECT * FROM login_providers WHERE user_id IN ({});",
+            placeholders
+        );
+        let role_sql = format!(
+            "SELECT user_id, role FROM user_roles WHERE user_id IN ({}) ORDER BY role;",
+            placeholders
+        );
+        let mut provider_query = query_as::<_, LoginProviderRow>(&provider_sql);
+        let mut role_query = query_as::<_, UserIdRoleRow>(&role_sql);
+        for row in &user_rows {
+            provider_query = provider_query.bind(&row.id);
+            role_query = role_query.bind(&row.id);
+        }
+        let (provider_rows, role_rows) = if user_rows.is_empty() {
+            (vec![], vec![])
+        } else {
+            (
+                provider_query
+                    .fetch_all(self.sqlite_connection())
+                    .await
+                    .context("Failed search providers query")?,
+                role_query
+                    .fetch_all(self.sqlite_connection())
+                    .await
+                    .context("Failed search roles query")?,
+            )
+        };
+        let mut providers = HashMap::<String, Vec<LoginProvider>>::new();
+        for row in provider_rows {
+            let provider = LoginProvider::try_from(&row)?;
+            providers.entry(row.user_id).or_default().push(provider);
+        }
+        let mut roles = HashMap::<String, Vec<Role>>::new();
+        for row in role_rows {
+            let role = Role::try_from(row.role).context("Role")?;
+            roles.entry(row.user_id).or_default().push(role);
+        }
+
+        let users = user_rows
+            .into_iter()
+            .map(|row| {
+                let status = UserStatus::try_from(row.status).context("UserStatus")?;
+                Ok(
+                    User::new(UserId::new(row.id.clone()), providers.remove(&row.id))
+                        .with_roles(roles.remove(&row.id).unwrap_or_default())
+                        .with_status(status),
+                )
+            })
+            .collect::<Result<Vec<User>, anyhow::Error>>()?;
+        Ok(Page::new(users, count.total))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn store(&self, u: &User) -> Result<(), StoreError> {
+        info!("Start transaction");
+        let mut transaction = self
+            .sqlite_connection()
+            .begin()
+            .await
+            .context("failed get context")?;
+        query(indoc! {"
+            INSERT INTO users (id, status, updated_at) VALUES (?1, ?2, strftime('%s', 'now'))
+            ON CONFLICT (id)
+            DO UPDATE SET status=?2, updated_at=strftime('%s', 'now');
+        "})
+        .bind(&u.id.0)
+        .bind(String::from(&u.status))
+        .execute(&mut transaction)
+        .await
+        .context("failed user store")?;
+        for login_provider in u.providers.iter() {
+            query(indoc! {"
+                INSERT INTO login_providers (user_id, kind, id_in_provider, updated_at) VALUES (?1, ?2, ?3, strftime('%s', 'now'))
+                ON CONFLICT (kind, id_in_provider)
+                DO UPDATE SET user_id=?1, updated_at=strftime('%s', 'now');
+            "})
+                .bind(&u.id.0)
+                .bind(String::from(&login_provider.kind))
+                .bind(&login_provider.id_in_provider.0)
+                .execute(&mut transaction).await.context("failed login_provider store")?;
+        }
+        query("DELETE FROM user_roles WHERE user_id=?1;")
+            .bind(&u.id.0)
+            .execute(&mut transaction)
+            .await
+            .context("failed user_roles delete")?;
+        for role in u.roles.iter() {
+            query("INSERT INTO user_roles (user_id, role, updated_at) VALUES (?1, ?2, strftime('%s', 'now'));")
+                .bind(&u.id.0)
+                .bind(String::from(role))
+                .execute(&mut transaction)
+                .await
+                .context("failed user_roles store")?;
+        }
+        transaction
+            .commit()
+            .await
+            .context("failed commit sqlite_user_repository store")?;
+        info!("Commit transaction");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SqliteUserRepository;
+    use crate::db_conn::test_sqlite_pool;
+    use account::model::login_provider::{IdInProvider, LoginProvider, ProviderKind};
+    use account::model::role::Role;
+    use account::model::user::{User, UserId, UserStatus};
+    use account::repository::meta::{Pagination, Repository};
+    use account::repository::user_repository::{UserRepository, UserSearchCondition};
+
+    #[tokio::test]
+    async fn sqlite_user_repository_resolve_return_to_user() {
+        let repo = SqliteUserRepository::new(test_sqlite_pool().await);
+        sqlx::query("INSERT INTO users (id, updated_at) VALUES (?1, 0);")
+            .bind("foo")
+            .execute(&repo.conn)
+            .await
+            .unwrap();
+        let user = repo
+            .resolve(&UserId::new("foo".to_string()))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(user, User::new(UserId("foo".to_string()), None));
+    }
+
+    #[tokio::test]
+    async fn sqlite_user_repository_resolve_return_to_none() {
+        let repo = SqliteUserRepository::new(test_sqlite_pool().await);
+        let result = repo.resolve(&UserId::new("foo".to_string())).await.unwrap();
+
+        assert!(result.is_none());
+    }
+
+    #[tokio::test]
+    async fn sqlite_user_repository_find_by_id_in_provider() {
+        let repo = SqliteUserRepository::new(test_sqlite_pool().await);
+        let user = User::new(
+            UserId::new("dummy1".to_string()),
+            Some(vec![LoginProvider::new(
+                ProviderKind::Google,
+                IdInProvider::new("test1".to_string()),
+            )]),
+        );
+        repo.store(&user).await.unwrap();
+        let found = repo
+            .find_by_id_in_provider(&IdInProvider::new("test1".to_string()))
+            .await
+            .unwrap();
+        let not_found = repo
+            .find_by_id_in_provider(&IdInProvider::new("test2".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(found, Some(user));
+        assert_eq!(not_found, None);
+    }
+
+    #[tokio::test]
+    async fn sqlite_user_repository_store_replace_roles_and_status() {
+        let repo = SqliteUserRepository::new(test_sqlite_pool().await);
+        let user = User::new(UserId::new("dummy1".to_string()), None)
+            .with_roles(vec![Role::Admin, Role::Editor]);
+        repo.store(&user).await.unwrap();
+        let user = user
+            .with_roles(vec![Role::Viewer])
+            .with_status(UserStatus::Disabled);
+        repo.store(&user).await.unwrap();
+        let resolved = repo.resolve(&user.id).await.unwrap();
+
+        assert_eq!(resolved, Some(user));
+    }
+
+    #[tokio::test]
+    async fn sqlite_user_repository_search_filter_by_provider_kind_and_name_prefix() {
+        let repo = SqliteUserRepository::new(test_sqlite_pool().await);
+        let google_user = User::new(
+            UserId::new("dummy1".to_string()),
+            Some(vec![LoginProvider::new(
+                ProviderKind::Google,
+                IdInProvider::new("test1".to_string()),
+            )]),
+        );
+        let other_user = User::new(UserId::new("dummy2".to_string()), None);
+        repo.store(&google_user).await.unwrap();
+        repo.store(&other_user).await.unwrap();
+        for (user_id, name) in [("dummy1", "Alice"), ("dummy2", "alice_2")] {
+            sqlx::query("INSERT INTO profiles (user_id, name, display_name, avatar_url, updated_at) VALUES (?1, ?2, ?2, '', 0);")
+                .bind(user_id)
+                .bind(name)
+                .execute(&repo.conn)
+                .await
+                .unwrap();
+        }
+        let by_kind = repo
+            .search(
+                &UserSearchCondition {
+                    provider_kind: Some(ProviderKind::Google),
+                    ..Default::default()
+                },
+                &Pagination::default(),
+            )
+            .await
+            .unwrap();
+        let by_name = repo
+            .search(
+                &UserSearchCondition {
+                    name_prefix: Some("alice".to_string()),
+                    ..Default::default()
+                },
+                &Pagination::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(by_kind.total, 1);
+        assert_eq!(by_kind.items, vec![google_user]);
+        assert_eq!(by_name.total, 1);
+        assert_eq!(by_name.items, vec![other_user]);
+    }
+
+    #[tokio::test]
+    async fn sqlite_user_repository_reject_orphan_login_provider() {
+        let repo = SqliteUserRepository::new(test_sqlite_pool().await);
+        let result = sqlx::query(
+            "INSERT INTO login_providers (user_id, kind, id_in_provider, updated_at) VALUES (?1, ?2, ?3, 0);",
+        )
+        .bind("unknown")
+        .bind("Google")
+        .bind("test1")
+        .execute(&repo.conn)
+        .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn sqlite_user_repository_delete_user_cascade_to_owned_rows() {
+        let repo = SqliteUserRepository::new(test_sqlite_pool().await);
+        let user = User::new(
+            UserId::new("dummy1".to_string()),
+            Some(vec![LoginProvider::new(
+                ProviderKind::Google,
+                IdInProvider::new("test1".to_string()),
+            )]),
+        )
+        .with_roles(vec![Role::Viewer]);
+        repo.store(&user).await.unwrap();
+        sqlx::query("DELETE FROM users WHERE id=?1;")
+            .bind(&user.id.0)
+            .execute(&repo.conn)
+            .await
+            .unwrap();
+        let found = repo
+            .find_by_id_in_provider(&IdInProvider::new("test1".to_string()))
+            .await
+            .unwrap();
+        let (roles,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM user_roles WHERE user_id=?1;")
+            .bind(&user.id.0)
+            .fetch_one(&repo.conn)
+            .await
+            .unwrap();
+
+        assert!(found.is_none());
+        assert_eq!(roles, 0);
+    }
+}
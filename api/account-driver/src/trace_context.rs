@@ -0,0 +1,16 @@
+//! W3C trace context (`traceparent`, `tracestate`) of the requests this service sends.
+use opentelemetry::global;
+use opentelemetry_http::HeaderInjector;
+use reqwest::header::HeaderMap;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Headers continuing the trace of the current span on the server called. Empty until a
+/// propagator is installed, which happens only when traces are exported.
+pub fn trace_headers() -> HeaderMap {
+    let context = tracing::Span::current().context();
+    let mut headers = HeaderMap::new();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(&mut headers))
+    });
+    headers
+}
@@ -0,0 +1,20 @@
+use account::effect::user_code_generator::UserCodeGenerator;
+use derive_more::Constructor;
+use rand::Rng;
+
+/// Characters recommended by RFC 8628 section 6.1: no vowels and no ambiguous glyphs.
+const USER_CODE_CHARSET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+
+#[derive(Debug, Constructor, Clone)]
+pub struct RandomUserCodeGenerator;
+
+impl UserCodeGenerator for RandomUserCodeGenerator {
+    fn generate(&self) -> String {
+        let mut rng = rand::thread_rng();
+        let mut code: Vec<char> = (0..8)
+            .map(|_| USER_CODE_CHARSET[rng.gen_range(0..USER_CODE_CHARSET.len())] as char)
+            .collect();
+        code.insert(4, '-');
+        code.into_iter().collect()
+    }
+}
@@ -0,0 +1,224 @@
+use account::effect::verified_token_cache::VerifiedTokenCache;
+use account::model::user::{User, UserId};
+use lru::LruCache;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use time::{Duration, OffsetDateTime};
+use tracing::warn;
+
+use crate::metrics;
+
+/// Keys are digests so raw tokens are never held in memory longer than the request.
+fn token_key(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    user: User,
+    expires_at: OffsetDateTime,
+}
+
+struct Entries {
+    tokens: LruCache<String, Entry>,
+    /// Token keys per user id, so a user can be dropped without scanning every entry.
+    by_user: HashMap<String, HashSet<String>>,
+}
+
+impl Entries {
+    fn unindex(&mut self, key: &str, user_id: &UserId) {
+        if let Some(keys) = self.by_user.get_mut(&user_id.0) {
+            keys.remove(key);
+            if keys.is_empty() {
+                self.by_user.remove(&user_id.0);
+            }
+        }
+    }
+}
+
+/// Least recently used tokens are evicted once `capacity` entries are held.
+#[derive(Clone)]
+pub struct LruVerifiedTokenCache {
+    entries: Arc<Mutex<Entries>>,
+    ttl: Duration,
+}
+
+impl LruVerifiedTokenCache {
+    pub fn new(capacity: NonZeroUsize, ttl: Duration) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(Entries {
+                tokens: LruCache::new(capacity),
+                by_user: HashMap::new(),
+            })),
+            ttl,
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries.lock().map(|e| e.tokens.len()).unwrap_or(0)
+    }
+
+    fn miss(&self) -> Option<User> {
+        metrics::record_verified_token_cache(false);
+        None
+    }
+}
+
+impl VerifiedTokenCache for LruVerifiedTokenCache {
+    fn get(&self, token: &str, now: OffsetDateTime) -> Option<User> {
+        let key = token_key(token);
+        let mut entries = match self.entries.lock() {
+            Ok(e) => e,
+            Err(_) => {
+                warn!("verified token cache is poisoned");
+                return self.miss();
+            }
+        };
+        let entry = match entries.tokens.get(&key) {
+            Some(e) => e.clone(),
+            None => return self.miss(),
+        };
+        if entry.expires_at <= now {
+            entries.tokens.pop(&key);
+            entries.unindex(&key, &entry.user.id);
+            return self.miss();
+        }
+        metrics::record_verified_token_cache(true);
+        Some(entry.user)
+    }
+
+    fn put(
+        &self,
+        token: &str,
+        user: &User,
+        token_expires_at: Option<OffsetDateTime>,
+        now: OffsetDateTime,
+    ) {
+        let expires_at = match token_expires_at {
+            Some(exp) => exp.min(now + self.ttl),
+            None => now + self.ttl,
+        };
+        if expires_at <= now {
+            return;
+        }
+        let key = token_key(token);
+        let mut entries = match self.entries.lock() {
+            Ok(e) => e,
+            Err(_) => {
+                warn!("verified token cache is poisoned");
+                return;
+            }
+        };
+        let entry = Entry {
+            user: user.clone(),
+            expires_at,
+        };
+        if let Some((old_key, old)) = entries.tokens.push(key.clone(), entry) {
+            entries.unindex(&old_key, &old.user.id);
+        }
+        entries
+            .by_user
+            .entry(user.id.0.clone())
+            .or_default()
+            .insert(key);
+    }
+
+    fn invalidate_user(&self, user_id: &UserId) {
+        let mut entries = match self.entries.lock() {
+            Ok(e) => e,
+            Err(_) => {
+                warn!("verified token cache is poisoned");
+                return;
+            }
+        };
+        if let Some(keys) = entries.by_user.remove(&user_id.0) {
+            for key in keys {
+                entries.tokens.pop(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LruVerifiedTokenCache;
+    use account::effect::verified_token_cache::VerifiedTokenCache;
+    use account::model::user::{User, UserId};
+    use std::num::NonZeroUsize;
+    use time::{Duration, OffsetDateTime};
+
+    fn cache(capacity: usize) -> LruVerifiedTokenCache {
+        LruVerifiedTokenCache::new(NonZeroUsize::new(capacity).unwrap(), Duration::seconds(60))
+    }
+
+    fn user(id: &str) -> User {
+        User::new(UserId::new(id.to_string()), None)
+    }
+
+    fn now() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap()
+    }
+
+    #[test]
+    fn get_return_to_user_until_ttl_elapses() {
+        let cache = cache(10);
+        cache.put("token", &user("foo"), None, now());
+
+        assert_eq!(
+            cache.get("token", now() + Duration::seconds(59)),
+            Some(user("foo"))
+        );
+        assert_eq!(cache.get("token", now() + Duration::seconds(60)), None);
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn get_return_to_none_after_token_expires() {
+        let cache = cache(10);
+        cache.put(
+            "token",
+            &user("foo"),
+            Some(now() + Duration::seconds(10)),
+            now(),
+        );
+
+        assert_eq!(cache.get("token", now() + Duration::seconds(10)), None);
+    }
+
+    #[test]
+    fn put_skip_already_expired_token() {
+        let cache = cache(10);
+        cache.put("token", &user("foo"), Some(now()), now());
+
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn put_evict_least_recently_used() {
+        let cache = cache(2);
+        cache.put("a", &user("foo"), None, now());
+        cache.put("b", &user("bar"), None, now());
+        cache.get("a", now());
+        cache.put("c", &user("baz"), None, now());
+
+        assert_eq!(cache.get("a", now()), Some(user("foo")));
+        assert_eq!(cache.get("b", now()), None);
+        assert_eq!(cache.get("c", now()), Some(user("baz")));
+    }
+
+    #[test]
+    fn invalidate_user_drop_every_token_of_user() {
+        let cache = cache(10);
+        cache.put("a", &user("foo"), None, now());
+        cache.put("b", &user("foo"), None, now());
+        cache.put("c", &user("bar"), None, now());
+        cache.invalidate_user(&UserId::new("foo".to_string()));
+
+        assert_eq!(cache.get("a", now()), None);
+        assert_eq!(cache.get("b", now()), None);
+        assert_eq!(cache.get("c", now()), Some(user("bar")));
+    }
+}
@@ -1,5 +1,14 @@
+use account::actor::admin::Admin;
+use account::actor::editor::Editor;
 use account::actor::user::User;
-use account::usecase::verify::VerifyUseCase;
+use account::effect::rate_limit_store::{HaveRateLimitStore, IdentityRateLimit, RateLimitDecision};
+use account::model::device_authorization::Scope;
+use account::model::scoped_token::ScopedTokenValue;
+use account::model::user::User as UserModel;
+use account::usecase::verify::{VerifyUseCase, VerifyUseCaseError};
+use account::usecase::verify_scoped_token::{
+    VerifyScopedTokenUseCase, VerifyScopedTokenUseCaseError,
+};
 use anyhow::anyhow;
 use async_trait::async_trait;
 use axum::extract::FromRequest;
@@ -9,18 +18,166 @@ use axum::headers::authorization::Bearer;
 use axum::BoxError;
 use axum::Extension;
 use axum::TypedHeader;
-use derive_more::Deref;
 use http_body::Body;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use time::OffsetDateTime;
+use tracing::Span;
 
 use crate::error::Error;
-use crate::kernel::Kernel;
+use crate::i18n::AuthenticatedUser;
+use crate::request_context::RequestContext;
 
-#[derive(Debug, Clone, Deref)]
-pub struct UserActor(User);
+/// Kernel able to verify both Firebase ID tokens and scoped tokens, and to count the requests
+/// of the verified user, required by every actor extractor.
+pub trait AuthenticateKernel:
+    VerifyUseCase + VerifyScopedTokenUseCase + HaveRateLimitStore + Clone + Send + Sync + 'static
+{
+}
+impl<T> AuthenticateKernel for T where
+    T: VerifyUseCase
+        + VerifyScopedTokenUseCase
+        + HaveRateLimitStore
+        + Clone
+        + Send
+        + Sync
+        + 'static
+{
+}
+
+/// Scope a scoped token must grant to reach a route, added to the route as an extension.
+/// Routes without one are reachable with Firebase ID tokens only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequiredScope(pub &'static str);
+
+/// Implements `Debug`, `Clone` and `Deref` without requiring them of the kernel type `K`,
+/// which only selects the `Extension` the actor is authenticated with.
+macro_rules! actor {
+    ($(#[$attr:meta])* $name:ident($target:ty)) => {
+        $(#[$attr])*
+        pub struct $name<K>($target, Option<Scope>, PhantomData<fn() -> K>);
+
+        impl<K> $name<K> {
+            /// Scope of the scoped token the request was authenticated with, `None` for a
+            /// Firebase ID token, which grants every scope.
+            pub fn scope(&self) -> Option<&Scope> {
+                self.1.as_ref()
+            }
+        }
+
+        impl<K> fmt::Debug for $name<K> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_tuple(stringify!($name))
+                    .field(&self.0)
+                    .field(&self.1)
+                    .finish()
+            }
+        }
+
+        impl<K> Clone for $name<K> {
+            fn clone(&self) -> Self {
+                Self(self.0.clone(), self.1.clone(), PhantomData)
+            }
+        }
+
+        impl<K> Deref for $name<K> {
+            type Target = $target;
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+    };
+}
+
+actor!(UserActor(User));
+
+actor!(
+    /// Authenticated user holding the `Admin` role.
+    AdminActor(Admin)
+);
+
+actor!(
+    /// Authenticated user holding the `Editor` or `Admin` role.
+    EditorActor(Editor)
+);
+
+/// Authenticated user, promoted to `Admin` when the role is held.
+#[derive(Debug, Clone)]
+pub enum Auditor {
+    Admin(Admin),
+    User(User),
+}
+
+actor!(
+    /// Authenticated user as an `Auditor`.
+    AuditorActor(Auditor)
+);
+
+/// Verifies the bearer token, and rejects a scoped token unless it grants the
+/// `RequiredScope` of the route, and a user past the `IdentityRateLimit` of the route.
+async fn authenticate<K, B>(req: &mut RequestParts<B>) -> Result<(UserModel, Option<Scope>), Error>
+where
+    K: AuthenticateKernel,
+    B: Body + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    let authorization = req
+        .extract::<TypedHeader<headers::Authorization<Bearer>>>()
+        .await
+        .map_err(|_| Error::Unauthorized)?;
+    let kernel = req
+        .extract::<Extension<K>>()
+        .await
+        .map_err(|_| Error::InternalServerError(anyhow!("kernel is not provided")))?;
+    let RequestContext(context) = req
+        .extract::<RequestContext>()
+        .await
+        .map_err(|_| Error::InternalServerError(anyhow!("request context is not provided")))?;
+    let token = authorization.token();
+    let (user, scope) = if ScopedTokenValue::is_scoped_token(token) {
+        let verified = VerifyScopedTokenUseCase::execute(&kernel.0, token, &context)
+            .await
+            .map_err(|e| match e {
+                VerifyScopedTokenUseCaseError::UserDisabled(_) => Error::Forbidden,
+                _ => Error::Unauthorized,
+            })?;
+        let required = req.extensions().get::<RequiredScope>();
+        match required {
+            Some(RequiredScope(required)) if verified.scope.grants(required) => {}
+            _ => return Err(Error::InsufficientScope),
+        }
+        (verified.user, Some(verified.scope))
+    } else {
+        let verified = VerifyUseCase::execute(&kernel.0, token, &context, None)
+            .await
+            .map_err(|e| match e {
+                VerifyUseCaseError::UserDisabled(_) => Error::Forbidden,
+                _ => Error::Unauthorized,
+            })?;
+        (verified.user, None)
+    };
+    Span::current().record("user_id", &user.id.0.as_str());
+    if let Some(limit) = req.extensions().get::<IdentityRateLimit>() {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        if let RateLimitDecision::Limited { retry_after } = limit
+            .check(kernel.rate_limit_store(), &user.id.0, now)
+            .await
+        {
+            return Err(Error::TooManyRequests { retry_after });
+        }
+    }
+    if let Some(authenticated) = req.extensions().get::<AuthenticatedUser>() {
+        authenticated.set(user.id.0.clone());
+    }
+    Ok((user, scope))
+}
 
 #[async_trait]
-impl<B> FromRequest<B> for UserActor
+impl<K, B> FromRequest<B> for UserActor<K>
 where
+    K: AuthenticateKernel,
     B: Body + Send,
     B::Data: Send,
     B::Error: Into<BoxError>,
@@ -28,18 +185,61 @@ where
     type Rejection = Error;
 
     async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
-        let authorization = req
-            .extract::<TypedHeader<headers::Authorization<Bearer>>>()
-            .await
-            .map_err(|_| Error::Unauthorized)?;
-        let kernel = req
-            .extract::<Extension<Kernel>>()
-            .await
-            .map_err(|_| Error::InternalServerError(anyhow!("kernel is not provided")))?;
-        let verify_result = kernel
-            .execute(authorization.token())
-            .await
-            .map_err(|_| Error::Unauthorized)?;
-        Ok(UserActor(verify_result.user.into()))
+        let (user, scope) = authenticate::<K, B>(req).await?;
+        Ok(UserActor(user.into(), scope, PhantomData))
+    }
+}
+
+#[async_trait]
+impl<K, B> FromRequest<B> for AdminActor<K>
+where
+    K: AuthenticateKernel,
+    B: Body + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let (user, scope) = authenticate::<K, B>(req).await?;
+        let admin = Admin::try_from(user).map_err(|_| Error::Forbidden)?;
+        Ok(AdminActor(admin, scope, PhantomData))
+    }
+}
+
+#[async_trait]
+impl<K, B> FromRequest<B> for EditorActor<K>
+where
+    K: AuthenticateKernel,
+    B: Body + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let (user, scope) = authenticate::<K, B>(req).await?;
+        let editor = Editor::try_from(user).map_err(|_| Error::Forbidden)?;
+        Ok(EditorActor(editor, scope, PhantomData))
+    }
+}
+
+#[async_trait]
+impl<K, B> FromRequest<B> for AuditorActor<K>
+where
+    K: AuthenticateKernel,
+    B: Body + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let (user, scope) = authenticate::<K, B>(req).await?;
+        let auditor = match Admin::try_from(user) {
+            Ok(admin) => Auditor::Admin(admin),
+            Err(user) => Auditor::User(user.into()),
+        };
+        Ok(AuditorActor(auditor, scope, PhantomData))
     }
 }
@@ -0,0 +1,52 @@
+use account_http::config::{ConfigError, MockConfig};
+use account_http::listener::Listener;
+use account_http::mock::MockKernel;
+use account_http::{router, shutdown, telemetry};
+use std::process::exit;
+use tracing::{error, info};
+
+#[tokio::main]
+async fn main() {
+    let config = match MockConfig::load() {
+        Ok(c) => c,
+        Err(ConfigError::HelpRequested(usage)) => {
+            print!("{}", usage);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(2);
+        }
+    };
+    if let Err(e) = telemetry::init(&config.log, &config.trace) {
+        eprintln!("failed to export traces: {}", e);
+        exit(1);
+    }
+    let kernel = MockKernel::new(config.device_verification_uri);
+    if let Err(e) = kernel.seed().await {
+        error!("failed to seed fixtures: {:#}", e);
+        exit(1);
+    }
+    info!("seeded fixtures");
+    let app = router(kernel, config.rate_limit, &config.server);
+    let listener = match Listener::bind(&config.server).await {
+        Ok(l) => l,
+        Err(e) => {
+            error!("failed to listen: {:#}", e);
+            exit(1);
+        }
+    };
+    info!("listening on {}", listener.describe());
+    let served = shutdown::serve(
+        listener,
+        app,
+        shutdown::signal(),
+        config.server.shutdown_timeout,
+    )
+    .await;
+    telemetry::shutdown();
+    if let Err(e) = served {
+        error!("server failed: {:#}", e);
+        exit(1);
+    }
+}
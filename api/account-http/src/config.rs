@@ -0,0 +1,1407 @@
+use crate::middleware::rate_limit::{RateLimitConfig, RouteRateLimit};
+use account::effect::rate_limit_store::RateLimitRule;
+use account_driver::db_conn::{ConnectRetry, DBBackend, DBConfig, DBTimeouts};
+use axum::http::HeaderValue;
+use ipnet::IpNet;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Names the file read before environment variables and flags.
+const CONFIG_FILE_ENV: &str = "ACCOUNT_CONFIG_FILE";
+
+struct Key {
+    name: &'static str,
+    /// Checked in order, the first one set wins.
+    env: &'static [&'static str],
+    default: Option<&'static str>,
+    help: &'static str,
+}
+
+/// Every setting, addressed as `section.name` in TOML, `--section-name` on the command line
+/// and by the listed environment variables.
+const KEYS: &[Key] = &[
+    Key {
+        name: "server.bind_address",
+        env: &["ACCOUNT_BIND_ADDRESS"],
+        default: Some("127.0.0.1:3000"),
+        help: "address the HTTP server listens on",
+    },
+    Key {
+        name: "server.cors_origins",
+        env: &["ACCOUNT_CORS_ORIGINS"],
+        default: Some("http://localhost:3003"),
+        help: "comma separated origins allowed by CORS",
+    },
+    Key {
+        name: "server.shutdown_timeout_seconds",
+        env: &["ACCOUNT_SHUTDOWN_TIMEOUT_SECONDS"],
+        default: Some("30"),
+        help: "wait for requests in flight on SIGTERM or SIGINT",
+    },
+    Key {
+        name: "server.tls_cert_path",
+        env: &["ACCOUNT_TLS_CERT_PATH"],
+        default: Some(""),
+        help: "PEM certificate chain, serves HTTPS with tls_key_path when set",
+    },
+    Key {
+        name: "server.tls_key_path",
+        env: &["ACCOUNT_TLS_KEY_PATH"],
+        default: Some(""),
+        help: "PEM private key of tls_cert_path",
+    },
+    Key {
+        name: "server.unix_socket_path",
+        env: &["ACCOUNT_UNIX_SOCKET_PATH"],
+        default: Some(""),
+        help: "Unix domain socket listened on instead of bind_address when set",
+    },
+    Key {
+        name: "server.trusted_proxies",
+        env: &["ACCOUNT_TRUSTED_PROXIES"],
+        default: Some(""),
+        help: "comma separated proxy addresses or CIDRs whose X-Forwarded-For is believed",
+    },
+    Key {
+        name: "database.backend",
+        env: &["ACCOUNT_DB_BACKEND"],
+        default: Some("postgres"),
+        help: "postgres, or sqlite for a single node",
+    },
+    Key {
+        name: "database.url",
+        env: &["ACCOUNT_DATABASE_URL", "DATABASE_URL"],
+        default: None,
+        help: "postgres:// or sqlite:// connection url",
+    },
+    Key {
+        name: "database.replica_url",
+        env: &["ACCOUNT_DATABASE_REPLICA_URL"],
+        default: Some(""),
+        help: "postgres:// url of a read replica user and profile reads go to",
+    },
+    Key {
+        name: "database.max_connections",
+        env: &["ACCOUNT_DB_MAX_CONNECTIONS"],
+        default: Some("5"),
+        help: "upper bound of the connection pool",
+    },
+    Key {
+        name: "database.min_connections",
+        env: &["ACCOUNT_DB_MIN_CONNECTIONS"],
+        default: Some("0"),
+        help: "connections kept open while idle",
+    },
+    Key {
+        name: "database.acquire_timeout_seconds",
+        env: &["ACCOUNT_DB_ACQUIRE_TIMEOUT_SECONDS"],
+        default: Some("30"),
+        help: "wait for a pooled connection before failing",
+    },
+    Key {
+        name: "database.idle_timeout_seconds",
+        env: &["ACCOUNT_DB_IDLE_TIMEOUT_SECONDS"],
+        default: Some("600"),
+        help: "close connections idle for longer, 0 keeps them",
+    },
+    Key {
+        name: "database.max_lifetime_seconds",
+        env: &["ACCOUNT_DB_MAX_LIFETIME_SECONDS"],
+        default: Some("1800"),
+        help: "close connections open for longer, 0 keeps them",
+    },
+    Key {
+        name: "database.statement_timeout_seconds",
+        env: &["ACCOUNT_DB_STATEMENT_TIMEOUT_SECONDS"],
+        default: Some("0"),
+        help: "cancel statements running for longer, 0 never, postgres only",
+    },
+    Key {
+        name: "database.connect_retries",
+        env: &["ACCOUNT_DB_CONNECT_RETRIES"],
+        default: Some("5"),
+        help: "connect again while the database is unreachable",
+    },
+    Key {
+        name: "database.connect_backoff_seconds",
+        env: &["ACCOUNT_DB_CONNECT_BACKOFF_SECONDS"],
+        default: Some("1"),
+        help: "wait before the first retry, doubled before each next one",
+    },
+    Key {
+        name: "database.connect_max_backoff_seconds",
+        env: &["ACCOUNT_DB_CONNECT_MAX_BACKOFF_SECONDS"],
+        default: Some("30"),
+        help: "longest wait between retries",
+    },
+    Key {
+        name: "database.migrate_on_start",
+        env: &["ACCOUNT_DB_MIGRATE_ON_START"],
+        default: Some("true"),
+        help: "apply pending migrations before serving",
+    },
+    Key {
+        name: "log.format",
+        env: &["ACCOUNT_LOG_FORMAT"],
+        default: Some("text"),
+        help: "text or json",
+    },
+    Key {
+        name: "log.filter",
+        env: &["RUST_LOG"],
+        default: Some("tower_http=debug"),
+        help: "tracing env filter directives",
+    },
+    Key {
+        name: "trace.otlp_endpoint",
+        env: &["ACCOUNT_OTLP_ENDPOINT", "OTEL_EXPORTER_OTLP_ENDPOINT"],
+        default: Some(""),
+        help: "OTLP/HTTP collector spans are exported to, none when empty",
+    },
+    Key {
+        name: "trace.service_name",
+        env: &["OTEL_SERVICE_NAME"],
+        default: Some("account-http"),
+        help: "service.name of the exported spans",
+    },
+    Key {
+        name: "auth.firebase_project_id",
+        env: &["ACCOUNT_FIREBASE_PROJECT_ID"],
+        default: None,
+        help: "audience expected in Firebase ID tokens",
+    },
+    Key {
+        name: "auth.device_verification_uri",
+        env: &["ACCOUNT_DEVICE_VERIFICATION_URI"],
+        default: Some("http://localhost:3003/device"),
+        help: "page where users enter device codes",
+    },
+    Key {
+        name: "auth.token_cache_capacity",
+        env: &["ACCOUNT_VERIFIED_TOKEN_CACHE_CAPACITY"],
+        default: Some("10000"),
+        help: "verified tokens kept in memory",
+    },
+    Key {
+        name: "auth.token_cache_ttl_seconds",
+        env: &["ACCOUNT_VERIFIED_TOKEN_CACHE_TTL_SECONDS"],
+        default: Some("60"),
+        help: "longest time a verified token is trusted without checking again",
+    },
+    Key {
+        name: "rate_limit.store",
+        env: &["ACCOUNT_RATE_LIMIT_STORE"],
+        default: Some("memory"),
+        help: "memory or postgres",
+    },
+    Key {
+        name: "rate_limit.sign_up_per_ip",
+        env: &["ACCOUNT_RATE_LIMIT_SIGN_UP_PER_IP"],
+        default: Some("10/60"),
+        help: "<limit>/<window seconds>",
+    },
+    Key {
+        name: "rate_limit.sign_up_per_identity",
+        env: &["ACCOUNT_RATE_LIMIT_SIGN_UP_PER_IDENTITY"],
+        default: Some("5/60"),
+        help: "<limit>/<window seconds> per verified Firebase user",
+    },
+    Key {
+        name: "rate_limit.verify_per_ip",
+        env: &["ACCOUNT_RATE_LIMIT_VERIFY_PER_IP"],
+        default: Some("120/60"),
+        help: "<limit>/<window seconds>",
+    },
+    Key {
+        name: "rate_limit.verify_per_identity",
+        env: &["ACCOUNT_RATE_LIMIT_VERIFY_PER_IDENTITY"],
+        default: Some("60/60"),
+        help: "<limit>/<window seconds> per verified Firebase user",
+    },
+    Key {
+        name: "rate_limit.device_code_per_ip",
+        env: &["ACCOUNT_RATE_LIMIT_DEVICE_CODE_PER_IP"],
+        default: Some("10/60"),
+        help: "<limit>/<window seconds>",
+    },
+    Key {
+        name: "rate_limit.device_token_per_ip",
+        env: &["ACCOUNT_RATE_LIMIT_DEVICE_TOKEN_PER_IP"],
+        default: Some("60/60"),
+        help: "<limit>/<window seconds>",
+    },
+];
+
+/// Keys read by `account-mock`, which needs neither a database nor a Firebase project.
+const MOCK_KEYS: &[&str] = &[
+    "server.bind_address",
+    "server.cors_origins",
+    "server.shutdown_timeout_seconds",
+    "server.tls_cert_path",
+    "server.tls_key_path",
+    "server.unix_socket_path",
+    "server.trusted_proxies",
+    "log.format",
+    "log.filter",
+    "trace.otlp_endpoint",
+    "trace.service_name",
+    "auth.device_verification_uri",
+    "rate_limit.sign_up_per_ip",
+    "rate_limit.sign_up_per_identity",
+    "rate_limit.verify_per_ip",
+    "rate_limit.verify_per_identity",
+    "rate_limit.device_code_per_ip",
+    "rate_limit.device_token_per_ip",
+];
+
+fn any_key(_: &str) -> bool {
+    true
+}
+
+fn is_mock_key(name: &str) -> bool {
+    MOCK_KEYS.contains(&name)
+}
+
+fn flag_name(key: &str) -> String {
+    format!("--{}", key.replace(['.', '_'], "-"))
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("invalid configuration:\n{}", .0.iter().map(|p| format!("  - {}", p)).collect::<Vec<_>>().join("\n"))]
+    Invalid(Vec<String>),
+    /// `--help` was passed. Holds the usage text.
+    #[error("{0}")]
+    HelpRequested(String),
+}
+
+/// Where a value was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    Default,
+    File(PathBuf),
+    Env(&'static str),
+    Flag,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File(path) => write!(f, "file {}", path.display()),
+            Source::Env(name) => write!(f, "env {}", name),
+            Source::Flag => write!(f, "command line"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err("expected text or json".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitStoreKind {
+    Memory,
+    Postgres,
+}
+
+impl FromStr for RateLimitStoreKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "memory" => Ok(RateLimitStoreKind::Memory),
+            "postgres" => Ok(RateLimitStoreKind::Postgres),
+            _ => Err("expected memory or postgres".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerConfig {
+    pub bind_address: SocketAddr,
+    pub cors_origins: Vec<HeaderValue>,
+    /// Requests still in flight this long after a shutdown signal are dropped.
+    pub shutdown_timeout: Duration,
+    /// Serves HTTPS instead of plain HTTP.
+    pub tls: Option<TlsConfig>,
+    /// Listened on instead of `bind_address`.
+    pub unix_socket: Option<PathBuf>,
+    /// Peers whose `X-Forwarded-For` and `X-Real-IP` tell the client address.
+    pub trusted_proxies: Vec<IpNet>,
+}
+
+/// PEM files of the certificate chain and its private key, read again when they change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    pub filter: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceConfig {
+    /// Base url of an OTLP/HTTP collector, e.g. `http://localhost:4318`. Spans are exported
+    /// to `<otlp_endpoint>/v1/traces`, or not at all when unset.
+    pub otlp_endpoint: Option<String>,
+    pub service_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthConfig {
+    pub firebase_project_id: String,
+    pub device_verification_uri: String,
+    pub token_cache_capacity: NonZeroUsize,
+    pub token_cache_ttl: time::Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitSettings {
+    pub store: RateLimitStoreKind,
+    pub routes: RateLimitConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppConfig {
+    pub server: ServerConfig,
+    pub database: DBConfig,
+    pub migrate_on_start: bool,
+    pub log: LogConfig,
+    pub trace: TraceConfig,
+    pub auth: AuthConfig,
+    pub rate_limit: RateLimitSettings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrateAction {
+    Up,
+    Status,
+    /// Records a schema psqldef created as migrated, without applying anything else.
+    Baseline,
+    /// Reverts migrations newer than `to`, or the latest one.
+    Down {
+        to: Option<i64>,
+    },
+}
+
+/// Settings of `account-mock`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockConfig {
+    pub server: ServerConfig,
+    pub log: LogConfig,
+    pub trace: TraceConfig,
+    pub device_verification_uri: String,
+    pub rate_limit: RateLimitConfig,
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Serve(Box<AppConfig>),
+    /// Only the database settings are required.
+    Migrate(MigrateAction, DBConfig),
+}
+
+impl Command {
+    /// Merges defaults, the TOML file, environment variables and command line flags, later
+    /// layers overriding earlier ones.
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_from(std::env::args().skip(1), |name| std::env::var(name).ok())
+    }
+
+    fn load_from<I, E>(args: I, env: E) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = String>,
+        E: Fn(&str) -> Option<String>,
+    {
+        let (layers, migrate) = Layers::load(args, env, any_key, usage)?;
+        match migrate {
+            Some(action) => layers
+                .resolve_database()
+                .map(|database| Command::Migrate(action, database)),
+            None => layers
+                .resolve()
+                .map(|config| Command::Serve(Box::new(config))),
+        }
+    }
+}
+
+impl MockConfig {
+    /// Loads the subset of settings `account-mock` reads, layered as `Command::load` does.
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_from(std::env::args().skip(1), |name| std::env::var(name).ok())
+    }
+
+    fn load_from<I, E>(args: I, env: E) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = String>,
+        E: Fn(&str) -> Option<String>,
+    {
+        let (mut layers, migrate) = Layers::load(args, env, is_mock_key, mock_usage)?;
+        if migrate.is_some() {
+            layers
+                .problems
+                .push("account-mock has no migrate subcommand".to_string());
+        }
+        layers.resolve_mock()
+    }
+}
+
+fn usage() -> String {
+    usage_of(
+        "Usage: account-http [--config <file>] [--<key> <value>]...\n\
+         \x20      account-http migrate [up | status | baseline | down [--to <version>]] [--<key> <value>]...\n\n",
+        any_key,
+    )
+}
+
+fn mock_usage() -> String {
+    usage_of(
+        "Usage: account-mock [--config <file>] [--<key> <value>]...\n\n\
+         Serves the account API over in-memory fixtures, accepting fake:<uid> as ID token.\n\n",
+        is_mock_key,
+    )
+}
+
+fn usage_of(header: &str, accepts: fn(&str) -> bool) -> String {
+    let mut usage = format!(
+        "{}Settings are read from defaults, the TOML file (--config or ACCOUNT_CONFIG_FILE),\n\
+         environment variables and flags, later sources overriding earlier ones.\n\n",
+        header
+    );
+    for key in KEYS.iter().filter(|k| accepts(k.name)) {
+        usage.push_str(&format!(
+            "  {:<36} {} ({})\n",
+            flag_name(key.name),
+            key.help,
+            key.env.join(", ")
+        ));
+    }
+    usage
+}
+
+#[derive(Default)]
+struct Args {
+    migrate: Option<MigrateAction>,
+    config_file: Option<PathBuf>,
+    flags: Vec<(&'static str, String)>,
+}
+
+fn parse_args<I>(
+    args: I,
+    problems: &mut Vec<String>,
+    accepts: fn(&str) -> bool,
+    usage: fn() -> String,
+) -> Result<Args, ConfigError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut parsed = Args::default();
+    let mut positional = 0;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            return Err(ConfigError::HelpRequested(usage()));
+        }
+        if !arg.starts_with('-') {
+            match (positional, parsed.migrate, arg.as_str()) {
+                (0, _, "migrate") => parsed.migrate = Some(MigrateAction::Up),
+                (1, Some(_), "up") => parsed.migrate = Some(MigrateAction::Up),
+                (1, Some(_), "status") => parsed.migrate = Some(MigrateAction::Status),
+                (1, Some(_), "baseline") => parsed.migrate = Some(MigrateAction::Baseline),
+                (1, Some(_), "down") => parsed.migrate = Some(MigrateAction::Down { to: None }),
+                _ => problems.push(format!("unexpected argument {}", arg)),
+            }
+            positional += 1;
+            continue;
+        }
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        let value = match inline_value.or_else(|| args.next()) {
+            Some(v) => v,
+            None => {
+                problems.push(format!("flag {} needs a value", flag));
+                continue;
+            }
+        };
+        if flag == "--config" {
+            parsed.config_file = Some(PathBuf::from(value));
+            continue;
+        }
+        if flag == "--to" {
+            match (parsed.migrate, value.parse::<i64>()) {
+                (Some(MigrateAction::Down { .. }), Ok(to)) => {
+                    parsed.migrate = Some(MigrateAction::Down { to: Some(to) })
+                }
+                (Some(MigrateAction::Down { .. }), Err(_)) => {
+                    problems.push(format!("--to {:?} is not a version", value))
+                }
+                _ => problems.push("--to is only valid with migrate down".to_string()),
+            }
+            continue;
+        }
+        match KEYS
+            .iter()
+            .find(|k| accepts(k.name) && flag_name(k.name) == flag)
+        {
+            Some(k) => parsed.flags.push((k.name, value)),
+            None => problems.push(format!("unknown flag {}", flag)),
+        }
+    }
+    Ok(parsed)
+}
+
+#[derive(Default)]
+struct Layers {
+    values: BTreeMap<&'static str, (String, Source)>,
+    problems: Vec<String>,
+}
+
+impl Layers {
+    /// Layers the file, environment variables and flags over the defaults of the keys
+    /// `accepts` admits.
+    fn load<I, E>(
+        args: I,
+        env: E,
+        accepts: fn(&str) -> bool,
+        usage: fn() -> String,
+    ) -> Result<(Self, Option<MigrateAction>), ConfigError>
+    where
+        I: IntoIterator<Item = String>,
+        E: Fn(&str) -> Option<String>,
+    {
+        let mut layers = Layers::default();
+        let Args {
+            migrate,
+            config_file,
+            flags,
+        } = parse_args(args, &mut layers.problems, accepts, usage)?;
+        if let Some(path) = config_file.or_else(|| env(CONFIG_FILE_ENV).map(PathBuf::from)) {
+            layers.read_file(path, accepts);
+        }
+        for key in KEYS.iter().filter(|k| accepts(k.name)) {
+            if let Some((name, value)) = key
+                .env
+                .iter()
+                .find_map(|name| env(name).map(|value| (*name, value)))
+            {
+                layers.set(key.name, value, Source::Env(name));
+            }
+        }
+        for (key, value) in flags {
+            layers.set(key, value, Source::Flag);
+        }
+        Ok((layers, migrate))
+    }
+
+    fn set(&mut self, key: &'static str, value: String, source: Source) {
+        self.values.insert(key, (value, source));
+    }
+
+    fn read_file(&mut self, path: PathBuf, accepts: fn(&str) -> bool) {
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) => {
+                self.problems
+                    .push(format!("cannot read {}: {}", path.display(), e));
+                return;
+            }
+        };
+        let table = match contents.parse::<toml::Value>() {
+            Ok(toml::Value::Table(t)) => t,
+            Ok(_) => {
+                self.problems
+                    .push(format!("{} is not a TOML table", path.display()));
+                return;
+            }
+            Err(e) => {
+                self.problems.push(format!("{}: {}", path.display(), e));
+                return;
+            }
+        };
+        for (section, values) in table {
+            let values = match values {
+                toml::Value::Table(t) => t,
+                _ => {
+                    self.problems
+                        .push(format!("{}: [{}] is not a table", path.display(), section));
+                    continue;
+                }
+            };
+            for (name, value) in values {
+                let dotted = format!("{}.{}", section, name);
+                let key = match KEYS.iter().find(|k| accepts(k.name) && k.name == dotted) {
+                    Some(k) => k.name,
+                    None => {
+                        self.problems
+                            .push(format!("{}: unknown key {}", path.display(), dotted));
+                        continue;
+                    }
+                };
+                let value = match value {
+                    toml::Value::String(s) => s,
+                    toml::Value::Integer(i) => i.to_string(),
+                    toml::Value::Boolean(b) => b.to_string(),
+                    toml::Value::Array(items) => items
+                        .iter()
+                        .map(|i| match i {
+                            toml::Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(","),
+                    other => {
+                        self.problems.push(format!(
+                            "{}: {} has unsupported value {}",
+                            path.display(),
+                            dotted,
+                            other
+                        ));
+                        continue;
+                    }
+                };
+                self.set(key, value, Source::File(path.clone()));
+            }
+        }
+    }
+
+    /// Parses `key` with `parse`, recording a problem that names the key and its source when
+    /// the value is missing or invalid.
+    fn get<T>(
+        &mut self,
+        key: &'static str,
+        parse: impl Fn(&str) -> Result<T, String>,
+    ) -> Option<T> {
+        let spec = KEYS
+            .iter()
+            .find(|k| k.name == key)
+            .expect("every resolved key is listed in KEYS");
+        let (value, source) = match self.values.get(key) {
+            Some((value, source)) => (value.clone(), source.clone()),
+            None => match spec.default {
+                Some(default) => (default.to_string(), Source::Default),
+                None => {
+                    self.problems.push(format!(
+                        "{} is required, set {} or {}",
+                        key,
+                        flag_name(key),
+                        spec.env.join(" or ")
+                    ));
+                    return None;
+                }
+            },
+        };
+        match parse(value.trim()) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                self.problems
+                    .push(format!("{}={:?} from {}: {}", key, value, source, e));
+                None
+            }
+        }
+    }
+
+    fn parsed<T>(&mut self, key: &'static str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.get(key, |v| v.parse::<T>().map_err(|e| e.to_string()))
+    }
+
+    fn database(&mut self) -> Option<DBConfig> {
+        let backend = self.parsed::<DBBackend>("database.backend");
+        let database_url = self.get("database.url", non_empty);
+        let replica_url = self.get("database.replica_url", |v| {
+            Ok((!v.is_empty()).then(|| v.to_string()))
+        });
+        let max_connections = self.get("database.max_connections", |v| {
+            match v.parse::<u32>().map_err(|e| e.to_string())? {
+                0 => Err("must be at least 1".to_string()),
+                n => Ok(n),
+            }
+        });
+        let min_connections = self.parsed::<u32>("database.min_connections");
+        let acquire_timeout = self.parsed::<u64>("database.acquire_timeout_seconds");
+        let idle_timeout = self.parsed::<u64>("database.idle_timeout_seconds");
+        let max_lifetime = self.parsed::<u64>("database.max_lifetime_seconds");
+        let statement_timeout = self.parsed::<u64>("database.statement_timeout_seconds");
+        let retries = self.parsed::<u32>("database.connect_retries");
+        let backoff = self.parsed::<u64>("database.connect_backoff_seconds");
+        let max_backoff = self.parsed::<u64>("database.connect_max_backoff_seconds");
+        if let (Some(min), Some(max)) = (min_connections, max_connections) {
+            if min > max {
+                self.problems.push(format!(
+                    "database.min_connections={} exceeds database.max_connections={}",
+                    min, max
+                ));
+            }
+        }
+        if let (Some(backend), Some(url)) = (backend, &database_url) {
+            if !backend.accepts(url) {
+                self.problems.push(format!(
+                    "database.url is not a {} url, as database.backend={} requires",
+                    backend, backend
+                ));
+            }
+        }
+        if let (Some(backend), Some(Some(url))) = (backend, &replica_url) {
+            if backend != DBBackend::Postgres {
+                self.problems
+                    .push("database.replica_url requires database.backend=postgres".to_string());
+            } else if !backend.accepts(url) {
+                self.problems
+                    .push("database.replica_url is not a postgres url".to_string());
+            }
+        }
+        if let (Some(DBBackend::Sqlite), Some(1..)) = (backend, statement_timeout) {
+            self.problems.push(
+                "database.statement_timeout_seconds requires database.backend=postgres".to_string(),
+            );
+        }
+        let seconds = |s: u64| (s > 0).then(|| Duration::from_secs(s));
+        Some(DBConfig::new(
+            backend?,
+            database_url?,
+            replica_url?,
+            max_connections?,
+            min_connections?,
+            DBTimeouts::new(
+                Duration::from_secs(acquire_timeout?),
+                seconds(idle_timeout?),
+                seconds(max_lifetime?),
+                seconds(statement_timeout?),
+            ),
+            ConnectRetry::new(
+                retries?,
+                Duration::from_secs(backoff?),
+                Duration::from_secs(max_backoff?),
+            ),
+        ))
+    }
+
+    fn server(&mut self) -> Option<ServerConfig> {
+        let bind_address = self.parsed::<SocketAddr>("server.bind_address");
+        let cors_origins = self.get("server.cors_origins", parse_origins);
+        let shutdown_timeout = self.parsed::<u64>("server.shutdown_timeout_seconds");
+        let cert_path = self.get("server.tls_cert_path", optional_path);
+        let key_path = self.get("server.tls_key_path", optional_path);
+        let unix_socket = self.get("server.unix_socket_path", optional_path);
+        let trusted_proxies = self.get("server.trusted_proxies", parse_proxies);
+        let tls = match (cert_path?, key_path?) {
+            (Some(cert_path), Some(key_path)) => Some(TlsConfig {
+                cert_path,
+                key_path,
+            }),
+            (None, None) => None,
+            _ => {
+                self.problems.push(
+                    "server.tls_cert_path and server.tls_key_path must be set together".to_string(),
+                );
+                return None;
+            }
+        };
+        if tls.is_some() && matches!(unix_socket, Some(Some(_))) {
+            self.problems.push(
+                "server.unix_socket_path cannot be combined with server.tls_cert_path".to_string(),
+            );
+            return None;
+        }
+        Some(ServerConfig {
+            bind_address: bind_address?,
+            cors_origins: cors_origins?,
+            shutdown_timeout: Duration::from_secs(shutdown_timeout?),
+            tls,
+            unix_socket: unix_socket?,
+            trusted_proxies: trusted_proxies?,
+        })
+    }
+
+    fn trace(&mut self) -> Option<TraceConfig> {
+        let otlp_endpoint = self.get("trace.otlp_endpoint", |v| {
+            Ok((!v.is_empty()).then(|| v.trim_end_matches('/').to_string()))
+        });
+        let service_name = self.get("trace.service_name", non_empty);
+        Some(TraceConfig {
+            otlp_endpoint: otlp_endpoint?,
+            service_name: service_name?,
+        })
+    }
+
+    fn resolve_database(mut self) -> Result<DBConfig, ConfigError> {
+        match self.database() {
+            Some(database) if self.problems.is_empty() => Ok(database),
+            _ => Err(ConfigError::Invalid(self.problems)),
+        }
+    }
+
+    fn resolve(mut self) -> Result<AppConfig, ConfigError> {
+        let database = self.database();
+        let server = self.server();
+        let migrate_on_start = self.parsed::<bool>("database.migrate_on_start");
+        let log_format = self.parsed::<LogFormat>("log.format");
+        let log_filter = self.get("log.filter", |v| Ok(v.to_string()));
+        let trace = self.trace();
+        let firebase_project_id = self.get("auth.firebase_project_id", non_empty);
+        let device_verification_uri = self.get("auth.device_verification_uri", non_empty);
+        let token_cache_capacity = self.parsed::<NonZeroUsize>("auth.token_cache_capacity");
+        let token_cache_ttl = self.parsed::<u32>("auth.token_cache_ttl_seconds");
+        let rate_limit_store = self.parsed::<RateLimitStoreKind>("rate_limit.store");
+        let sign_up_per_ip = self.get("rate_limit.sign_up_per_ip", parse_rule);
+        let sign_up_per_identity = self.get("rate_limit.sign_up_per_identity", parse_rule);
+        let verify_per_ip = self.get("rate_limit.verify_per_ip", parse_rule);
+        let verify_per_identity = self.get("rate_limit.verify_per_identity", parse_rule);
+        let device_code_per_ip = self.get("rate_limit.device_code_per_ip", parse_rule);
+        let device_token_per_ip = self.get("rate_limit.device_token_per_ip", parse_rule);
+        if let (Some(database), Some(RateLimitStoreKind::Postgres)) = (&database, rate_limit_store)
+        {
+            if database.backend != DBBackend::Postgres {
+                self.problems.push(
+                    "rate_limit.store=postgres requires database.backend=postgres".to_string(),
+                );
+            }
+        }
+
+        let config = (|| {
+            Some(AppConfig {
+                server: server?,
+                database: database?,
+                migrate_on_start: migrate_on_start?,
+                log: LogConfig {
+                    format: log_format?,
+                    filter: log_filter?,
+                },
+                trace: trace?,
+                auth: AuthConfig {
+                    firebase_project_id: firebase_project_id?,
+                    device_verification_uri: device_verification_uri?,
+                    token_cache_capacity: token_cache_capacity?,
+                    token_cache_ttl: time::Duration::seconds(token_cache_ttl?.into()),
+                },
+                rate_limit: RateLimitSettings {
+                    store: rate_limit_store?,
+                    routes: RateLimitConfig::new(
+                        RouteRateLimit::new(sign_up_per_ip?, Some(sign_up_per_identity?)),
+                        RouteRateLimit::new(verify_per_ip?, Some(verify_per_identity?)),
+                        RouteRateLimit::new(device_code_per_ip?, None),
+                        RouteRateLimit::new(device_token_per_ip?, None),
+                    ),
+                },
+            })
+        })();
+        match config {
+            Some(config) if self.problems.is_empty() => Ok(config),
+            _ => Err(ConfigError::Invalid(self.problems)),
+        }
+    }
+
+    fn resolve_mock(mut self) -> Result<MockConfig, ConfigError> {
+        let server = self.server();
+        let log_format = self.parsed::<LogFormat>("log.format");
+        let log_filter = self.get("log.filter", |v| Ok(v.to_string()));
+        let trace = self.trace();
+        let device_verification_uri = self.get("auth.device_verification_uri", non_empty);
+        let sign_up_per_ip = self.get("rate_limit.sign_up_per_ip", parse_rule);
+        let sign_up_per_identity = self.get("rate_limit.sign_up_per_identity", parse_rule);
+        let verify_per_ip = self.get("rate_limit.verify_per_ip", parse_rule);
+        let verify_per_identity = self.get("rate_limit.verify_per_identity", parse_rule);
+        let device_code_per_ip = self.get("rate_limit.device_code_per_ip", parse_rule);
+        let device_token_per_ip = self.get("rate_limit.device_token_per_ip", parse_rule);
+
+        let config = (|| {
+            Some(MockConfig {
+                server: server?,
+                log: LogConfig {
+                    format: log_format?,
+                    filter: log_filter?,
+                },
+                trace: trace?,
+                device_verification_uri: device_verification_uri?,
+                rate_limit: RateLimitConfig::new(
+                    RouteRateLimit::new(sign_up_per_ip?, Some(sign_up_per_identity?)),
+                    RouteRateLimit::new(verify_per_ip?, Some(verify_per_identity?)),
+                    RouteRateLimit::new(device_code_per_ip?, None),
+                    RouteRateLimit::new(device_token_per_ip?, None),
+                ),
+            })
+        })();
+        match config {
+            Some(config) if self.problems.is_empty() => Ok(config),
+            _ => Err(ConfigError::Invalid(self.problems)),
+        }
+    }
+}
+
+fn non_empty(v: &str) -> Result<String, String> {
+    if v.is_empty() {
+        return Err("must not be empty".to_string());
+    }
+    Ok(v.to_string())
+}
+
+fn optional_path(v: &str) -> Result<Option<PathBuf>, String> {
+    Ok((!v.is_empty()).then(|| PathBuf::from(v)))
+}
+
+fn parse_origins(v: &str) -> Result<Vec<HeaderValue>, String> {
+    v.split(',')
+        .map(str::trim)
+        .filter(|o| !o.is_empty())
+        .map(|o| HeaderValue::from_str(o).map_err(|_| format!("{:?} is not a valid origin", o)))
+        .collect()
+}
+
+/// Reads addresses, taken as a single host, and CIDRs, e.g. `10.0.0.0/8,192.0.2.1`.
+fn parse_proxies(v: &str) -> Result<Vec<IpNet>, String> {
+    v.split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            p.parse::<IpNet>()
+                .or_else(|_| p.parse::<IpAddr>().map(IpNet::from))
+                .map_err(|_| format!("{:?} is not an address or CIDR", p))
+        })
+        .collect()
+}
+
+/// Reads a rule written as `<limit>/<window seconds>`, e.g. `10/60`.
+fn parse_rule(v: &str) -> Result<RateLimitRule, String> {
+    let (limit, window) = v
+        .split_once('/')
+        .ok_or_else(|| "expected <limit>/<window seconds>".to_string())?;
+    let limit = limit
+        .parse::<i64>()
+        .map_err(|_| "limit is not numeric".to_string())?;
+    let window = window
+        .parse::<i64>()
+        .map_err(|_| "window is not numeric".to_string())?;
+    if limit < 1 || window < 1 {
+        return Err("limit and window must be positive".to_string());
+    }
+    Ok(RateLimitRule::new(limit, window))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AppConfig, Command, ConfigError, LogFormat, MigrateAction, MockConfig, RateLimitStoreKind,
+        TlsConfig,
+    };
+    use account::effect::rate_limit_store::RateLimitRule;
+    use account_driver::db_conn::{ConnectRetry, DBBackend, DBTimeouts};
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn command(args: &[&str], env: &[(&str, &str)]) -> Result<Command, ConfigError> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Command::load_from(args.iter().map(|a| a.to_string()), |name| {
+            env.get(name).cloned()
+        })
+    }
+
+    fn load(args: &[&str], env: &[(&str, &str)]) -> Result<AppConfig, ConfigError> {
+        command(args, env).map(|c| match c {
+            Command::Serve(config) => *config,
+            other => panic!("expected serve, got {:?}", other),
+        })
+    }
+
+    fn load_mock(args: &[&str], env: &[(&str, &str)]) -> Result<MockConfig, ConfigError> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        MockConfig::load_from(args.iter().map(|a| a.to_string()), |name| {
+            env.get(name).cloned()
+        })
+    }
+
+    fn problems<T: std::fmt::Debug>(result: Result<T, ConfigError>) -> Vec<String> {
+        match result {
+            Err(ConfigError::Invalid(problems)) => problems,
+            other => panic!("expected invalid configuration, got {:?}", other),
+        }
+    }
+
+    const REQUIRED: &[(&str, &str)] = &[
+        ("ACCOUNT_DATABASE_URL", "postgres://localhost/account"),
+        ("ACCOUNT_FIREBASE_PROJECT_ID", "project"),
+    ];
+
+    #[test]
+    fn load_use_defaults_for_optional_settings() {
+        let config = load(&[], REQUIRED).unwrap();
+
+        assert_eq!(config.server.bind_address.to_string(), "127.0.0.1:3000");
+        assert_eq!(config.server.cors_origins, vec!["http://localhost:3003"]);
+        assert_eq!(config.server.shutdown_timeout, Duration::from_secs(30));
+        assert_eq!(config.database.url, "postgres://localhost/account");
+        assert_eq!(config.database.replica_url, None);
+        assert_eq!(config.database.max_connections, 5);
+        assert_eq!(
+            config.database.timeouts,
+            DBTimeouts::new(
+                Duration::from_secs(30),
+                Some(Duration::from_secs(600)),
+                Some(Duration::from_secs(1800)),
+                None
+            )
+        );
+        assert_eq!(
+            config.database.retry,
+            ConnectRetry::new(5, Duration::from_secs(1), Duration::from_secs(30))
+        );
+        assert_eq!(config.log.format, LogFormat::Text);
+        assert_eq!(config.trace.otlp_endpoint, None);
+        assert_eq!(config.rate_limit.store, RateLimitStoreKind::Memory);
+        assert_eq!(
+            config.rate_limit.routes.verify.per_ip,
+            RateLimitRule::new(120, 60)
+        );
+    }
+
+    #[test]
+    fn load_read_otlp_endpoint_from_opentelemetry_env() {
+        let mut env = REQUIRED.to_vec();
+        env.push(("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318/"));
+        env.push(("OTEL_SERVICE_NAME", "account"));
+        let config = load(&[], &env).unwrap();
+
+        assert_eq!(
+            config.trace.otlp_endpoint.as_deref(),
+            Some("http://localhost:4318")
+        );
+        assert_eq!(config.trace.service_name, "account");
+    }
+
+    #[test]
+    fn load_report_every_missing_required_setting() {
+        let problems = problems(load(&[], &[]));
+
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].starts_with("database.url is required"));
+        assert!(problems[1].starts_with("auth.firebase_project_id is required"));
+    }
+
+    #[test]
+    fn load_select_sqlite_backend() {
+        let config = load(
+            &["--database-backend", "sqlite"],
+            &[
+                ("ACCOUNT_DATABASE_URL", "sqlite://account.db"),
+                ("ACCOUNT_FIREBASE_PROJECT_ID", "project"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.database.backend, DBBackend::Sqlite);
+        assert_eq!(config.database.url, "sqlite://account.db");
+    }
+
+    #[test]
+    fn load_reject_url_and_store_not_matching_backend() {
+        let problems = problems(load(
+            &["--database-backend=sqlite", "--rate-limit-store=postgres"],
+            REQUIRED,
+        ));
+
+        assert_eq!(
+            problems,
+            vec![
+                "database.url is not a sqlite url, as database.backend=sqlite requires",
+                "rate_limit.store=postgres requires database.backend=postgres",
+            ]
+        );
+    }
+
+    #[test]
+    fn load_read_tls_and_unix_socket_paths() {
+        let mut env = REQUIRED.to_vec();
+        env.push(("ACCOUNT_TLS_CERT_PATH", "/etc/account/cert.pem"));
+        env.push(("ACCOUNT_TLS_KEY_PATH", "/etc/account/key.pem"));
+        let tls = load(&[], &env).unwrap();
+        let unix = load(
+            &["--server-unix-socket-path", "/run/account.sock"],
+            REQUIRED,
+        )
+        .unwrap();
+
+        assert_eq!(
+            tls.server.tls,
+            Some(TlsConfig {
+                cert_path: "/etc/account/cert.pem".into(),
+                key_path: "/etc/account/key.pem".into(),
+            })
+        );
+        assert_eq!(tls.server.unix_socket, None);
+        assert_eq!(unix.server.tls, None);
+        assert_eq!(unix.server.unix_socket, Some("/run/account.sock".into()));
+    }
+
+    #[test]
+    fn load_read_trusted_proxies() {
+        let mut env = REQUIRED.to_vec();
+        env.push(("ACCOUNT_TRUSTED_PROXIES", "10.0.0.0/8, 192.0.2.1,::1"));
+        let config = load(&[], &env).unwrap();
+        let invalid = problems(load(&["--server-trusted-proxies", "proxy.local"], REQUIRED));
+
+        assert_eq!(
+            config.server.trusted_proxies,
+            vec![
+                "10.0.0.0/8".parse().unwrap(),
+                "192.0.2.1/32".parse().unwrap(),
+                "::1/128".parse().unwrap()
+            ]
+        );
+        assert_eq!(invalid.len(), 1);
+        assert!(invalid[0].starts_with("server.trusted_proxies"));
+        assert!(load(&[], REQUIRED)
+            .unwrap()
+            .server
+            .trusted_proxies
+            .is_empty());
+    }
+
+    #[test]
+    fn load_reject_incomplete_tls_and_tls_over_unix_socket() {
+        let cert_only = problems(load(&["--server-tls-cert-path=cert.pem"], REQUIRED));
+        let over_unix = problems(load(
+            &[
+                "--server-tls-cert-path=cert.pem",
+                "--server-tls-key-path=key.pem",
+                "--server-unix-socket-path=account.sock",
+            ],
+            REQUIRED,
+        ));
+
+        assert_eq!(
+            cert_only,
+            vec!["server.tls_cert_path and server.tls_key_path must be set together"]
+        );
+        assert_eq!(
+            over_unix,
+            vec!["server.unix_socket_path cannot be combined with server.tls_cert_path"]
+        );
+    }
+
+    #[test]
+    fn load_reject_replica_url_of_other_backend() {
+        let problems = problems(load(
+            &["--database-replica-url=sqlite://replica.db"],
+            REQUIRED,
+        ));
+
+        assert_eq!(problems, vec!["database.replica_url is not a postgres url"]);
+    }
+
+    #[test]
+    fn load_reject_replica_url_on_sqlite() {
+        let problems = problems(load(
+            &["--database-replica-url=postgres://replica/account"],
+            &[
+                ("ACCOUNT_DB_BACKEND", "sqlite"),
+                ("ACCOUNT_DATABASE_URL", "sqlite://account.db"),
+                ("ACCOUNT_FIREBASE_PROJECT_ID", "project"),
+            ],
+        ));
+
+        assert_eq!(
+            problems,
+            vec!["database.replica_url requires database.backend=postgres"]
+        );
+    }
+
+    #[test]
+    fn load_reject_statement_timeout_on_sqlite() {
+        let problems = problems(load(
+            &["--database-statement-timeout-seconds=5"],
+            &[
+                ("ACCOUNT_DB_BACKEND", "sqlite"),
+                ("ACCOUNT_DATABASE_URL", "sqlite://account.db"),
+                ("ACCOUNT_FIREBASE_PROJECT_ID", "project"),
+            ],
+        ));
+
+        assert_eq!(
+            problems,
+            vec!["database.statement_timeout_seconds requires database.backend=postgres"]
+        );
+    }
+
+    #[test]
+    fn load_prefer_flags_over_env_over_file() {
+        let path =
+            std::env::temp_dir().join(format!("account-http-config-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "[server]\nbind_address = \"0.0.0.0:1\"\ncors_origins = [\"https://a.example\", \"https://b.example\"]\n\n[database]\nmax_connections = 7\nmin_connections = 2\n",
+        )
+        .unwrap();
+        let mut env = REQUIRED.to_vec();
+        env.push(("ACCOUNT_BIND_ADDRESS", "0.0.0.0:2"));
+        env.push(("ACCOUNT_DB_MAX_CONNECTIONS", "8"));
+        let config = load(
+            &[
+                "--config",
+                path.to_str().unwrap(),
+                "--database-max-connections=9",
+                "--log-format",
+                "json",
+            ],
+            &env,
+        );
+        std::fs::remove_file(&path).unwrap();
+        let config = config.unwrap();
+
+        assert_eq!(config.server.bind_address.to_string(), "0.0.0.0:2");
+        assert_eq!(
+            config.server.cors_origins,
+            vec!["https://a.example", "https://b.example"]
+        );
+        assert_eq!(config.database.max_connections, 9);
+        assert_eq!(config.database.min_connections, 2);
+        assert_eq!(config.log.format, LogFormat::Json);
+    }
+
+    #[test]
+    fn load_report_invalid_values_with_source() {
+        let mut env = REQUIRED.to_vec();
+        env.push(("ACCOUNT_DB_MAX_CONNECTIONS", "many"));
+        env.push(("ACCOUNT_RATE_LIMIT_VERIFY_PER_IP", "10"));
+        let problems = problems(load(&["--log-format", "yaml", "--unknown", "x"], &env));
+
+        assert_eq!(problems.len(), 4);
+        assert_eq!(problems[0], "unknown flag --unknown");
+        assert!(problems[1].contains("env ACCOUNT_DB_MAX_CONNECTIONS"));
+        assert!(problems[2].starts_with("log.format=\"yaml\" from command line"));
+        assert!(problems[3].starts_with("rate_limit.verify_per_ip"));
+    }
+
+    #[test]
+    fn load_report_missing_config_file() {
+        let problems = problems(load(&["--config", "/nonexistent/account.toml"], REQUIRED));
+
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("cannot read /nonexistent/account.toml"));
+    }
+
+    #[test]
+    fn load_migrate_require_only_database_settings() {
+        let result = command(
+            &["migrate", "down", "--to", "20220801000000"],
+            &[("DATABASE_URL", "postgres://localhost/account")],
+        );
+
+        match result {
+            Ok(Command::Migrate(action, database)) => {
+                assert_eq!(
+                    action,
+                    MigrateAction::Down {
+                        to: Some(20220801000000)
+                    }
+                );
+                assert_eq!(database.url, "postgres://localhost/account");
+            }
+            other => panic!("expected migrate, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn load_migrate_baseline() {
+        let result = command(
+            &["migrate", "baseline"],
+            &[("DATABASE_URL", "postgres://localhost/account")],
+        );
+
+        assert!(matches!(
+            result,
+            Ok(Command::Migrate(MigrateAction::Baseline, _))
+        ));
+    }
+
+    #[test]
+    fn load_report_misplaced_migrate_arguments() {
+        let problems = problems(command(&["status", "--to", "1"], REQUIRED));
+
+        assert_eq!(
+            problems,
+            vec![
+                "unexpected argument status".to_string(),
+                "--to is only valid with migrate down".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn load_return_usage_when_help_requested() {
+        assert!(matches!(
+            load(&["--help"], &[]),
+            Err(ConfigError::HelpRequested(_))
+        ));
+    }
+
+    #[test]
+    fn load_mock_need_neither_database_nor_firebase() {
+        let config = load_mock(&["--server-bind-address", "0.0.0.0:3000"], &[]).unwrap();
+
+        assert_eq!(config.server.bind_address.to_string(), "0.0.0.0:3000");
+        assert_eq!(config.server.cors_origins, vec!["http://localhost:3003"]);
+        assert_eq!(
+            config.device_verification_uri,
+            "http://localhost:3003/device"
+        );
+        assert_eq!(config.rate_limit.verify.per_ip, RateLimitRule::new(120, 60));
+    }
+
+    #[test]
+    fn load_mock_reject_settings_it_does_not_read() {
+        assert_eq!(
+            problems(load_mock(
+                &["migrate", "--database-url", "postgres://localhost/account"],
+                &[("ACCOUNT_DATABASE_URL", "postgres://localhost/account")]
+            )),
+            vec![
+                "unknown flag --database-url".to_string(),
+                "account-mock has no migrate subcommand".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn load_mock_return_mock_usage_when_help_requested() {
+        match load_mock(&["--help"], &[]) {
+            Err(ConfigError::HelpRequested(usage)) => {
+                assert!(usage.starts_with("Usage: account-mock"));
+                assert!(!usage.contains("--database-url"));
+            }
+            other => panic!("expected usage, got {:?}", other),
+        }
+    }
+}
@@ -1,13 +1,28 @@
-use axum::http::StatusCode;
+use account::model::profile::avatar::AvatarInvalidity;
+use account::model::profile::entity::ProfileInvalidity;
+use account::model::profile::locale::Locale;
+use account::model::profile::user_name::{UserName, UserNameInvalidity};
+use axum::http::header::{CONTENT_TYPE, RETRY_AFTER};
+use axum::http::{HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use derive_more::Constructor;
 use serde::Serialize;
-use serde_json::json;
 use strum_macros::{Display, EnumString};
 use tracing::error;
+use ts_rs::TS;
+use utoipa::ToSchema;
 
-#[derive(EnumString, Debug, Display, Serialize, Clone)]
+use crate::i18n::{Args, Catalogue};
+
+mod usecase;
+
+/// Media type of every error response (RFC 7807).
+pub const PROBLEM_JSON: &str = "application/problem+json";
+/// Prefix of the `type` of the problems this service defines.
+pub const PROBLEM_TYPE_PREFIX: &str = "urn:matsunoki:problem:";
+
+#[derive(EnumString, Debug, Display, Serialize, Clone, ToSchema, TS)]
 pub enum BadRequestKind {
     #[strum(serialize = "token_expired")]
     TokenExpired,
@@ -19,24 +34,240 @@ pub enum BadRequestKind {
     UserNotFound,
     #[strum(serialize = "profile_validation_error")]
     ProfileValidationError,
+    #[strum(serialize = "device_code_not_found")]
+    DeviceCodeNotFound,
+    #[strum(serialize = "device_code_expired")]
+    DeviceCodeExpired,
+    #[strum(serialize = "device_code_already_processed")]
+    DeviceCodeAlreadyProcessed,
+    #[strum(serialize = "role_grant_error")]
+    RoleGrantError,
+    #[strum(serialize = "user_manage_error")]
+    UserManageError,
+    #[strum(serialize = "user_disabled")]
+    UserDisabled,
 }
 
-#[derive(Debug, Clone, Serialize, Constructor)]
+#[derive(Debug, Clone, Constructor)]
 pub struct BadRequestPayload {
     kind: BadRequestKind,
     key: String,
 }
 
+/// Error codes of the OAuth 2.0 token endpoint (RFC 6749 section 5.2, RFC 8628 section 3.5).
+#[derive(EnumString, Debug, Display, Serialize, Clone, ToSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuthErrorKind {
+    #[strum(serialize = "authorization_pending")]
+    AuthorizationPending,
+    #[strum(serialize = "slow_down")]
+    SlowDown,
+    #[strum(serialize = "access_denied")]
+    AccessDenied,
+    #[strum(serialize = "expired_token")]
+    ExpiredToken,
+    #[strum(serialize = "invalid_grant")]
+    InvalidGrant,
+    #[strum(serialize = "unsupported_grant_type")]
+    UnsupportedGrantType,
+}
+
+#[derive(Debug, Clone, Constructor)]
+pub struct OAuthErrorPayload {
+    error: OAuthErrorKind,
+    error_description: String,
+}
+
+/// A request parameter a `profile_validation_error` rejected.
+#[derive(Debug, Clone, Serialize, ToSchema, TS)]
+pub struct InvalidParam {
+    /// Name of the parameter in the request body.
+    #[schema(example = "user_name")]
+    pub name: String,
+    /// The check that failed, stable across languages.
+    #[schema(example = "min_length")]
+    pub code: String,
+    /// Why the value is rejected, in the language of the response.
+    pub reason: String,
+    #[serde(skip)]
+    #[ts(skip)]
+    args: Args,
+}
+
+impl InvalidParam {
+    fn of(invalidity: &ProfileInvalidity) -> Self {
+        let (name, code, args) = match invalidity {
+            ProfileInvalidity::UserName(UserNameInvalidity::MinLength) => (
+                "user_name",
+                "min_length",
+                vec![("min", UserName::MIN_LENGTH.to_string())],
+            ),
+            ProfileInvalidity::UserName(UserNameInvalidity::MaxLength) => (
+                "user_name",
+                "max_length",
+                vec![("max", UserName::MAX_LENGTH.to_string())],
+            ),
+            ProfileInvalidity::UserName(UserNameInvalidity::Format) => {
+                ("user_name", "format", vec![])
+            }
+            ProfileInvalidity::Avatar(AvatarInvalidity::NotUrl) => {
+                ("avatar_url", "not_url", vec![])
+            }
+        };
+        Self {
+            name: name.to_string(),
+            code: code.to_string(),
+            reason: String::new(),
+            args,
+        }
+    }
+}
+
+/// Problem details (RFC 7807), the body of every error response.
+///
+/// `title`, `detail` and the `reason` of `invalid_params` are written in the language
+/// `middleware::problem` picked for the request (see `i18n`), which also fills in `instance` and
+/// `request_id`. The members after them are extensions set by some problems only: `kind` and
+/// `key` by bad requests, `error` and `error_description` by the token endpoint, as RFC 6749
+/// expects, `retry_after` by rate limited requests and `invalid_params` by profile validation.
+#[derive(Debug, Clone, Serialize, ToSchema, TS)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    #[schema(example = "urn:matsunoki:problem:user_not_found")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub instance: Option<String>,
+    /// Also sent as `X-Request-Id`; quote it when reporting an internal server error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub kind: Option<BadRequestKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<OAuthErrorKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error_description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub retry_after: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub invalid_params: Option<Vec<InvalidParam>>,
+    /// Parameters of the catalogue `detail`.
+    #[serde(skip)]
+    #[ts(skip)]
+    args: Args,
+}
+
+impl Problem {
+    /// A problem with no extension members.
+    pub fn new(problem_type: String, title: &str, status: StatusCode) -> Self {
+        Self {
+            problem_type,
+            title: title.to_string(),
+            status: status.as_u16(),
+            detail: None,
+            instance: None,
+            request_id: None,
+            kind: None,
+            key: None,
+            error: None,
+            error_description: None,
+            retry_after: None,
+            invalid_params: None,
+            args: vec![],
+        }
+    }
+
+    /// The problem `urn:matsunoki:problem:<code>`, titled and detailed by the catalogue.
+    fn of(code: &str, status: StatusCode) -> Self {
+        Self::new(format!("{}{}", PROBLEM_TYPE_PREFIX, code), "", status)
+    }
+
+    /// Rewrites the messages in `locale`. Messages the catalogue does not know are kept, such
+    /// as the `detail` of the `about:blank` problems made of other error responses.
+    pub fn localize(self, locale: Locale) -> Self {
+        let catalogue = Catalogue::of(locale);
+        let (title, detail) = match self.problem_type.strip_prefix(PROBLEM_TYPE_PREFIX) {
+            Some(code) => (
+                catalogue.problem_title(code),
+                catalogue.problem_detail(code, &self.args),
+            ),
+            None => (
+                StatusCode::from_u16(self.status)
+                    .ok()
+                    .and_then(|status| catalogue.status_title(status)),
+                None,
+            ),
+        };
+        let invalid_params = self.invalid_params.map(|params| {
+            params
+                .into_iter()
+                .map(|param| InvalidParam {
+                    reason: catalogue
+                        .invalidity(&param.name, &param.code, &param.args)
+                        .unwrap_or(param.reason),
+                    ..param
+                })
+                .collect()
+        });
+        Self {
+            title: title.map(str::to_string).unwrap_or(self.title),
+            detail: detail.or(self.detail),
+            invalid_params,
+            ..self
+        }
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut res = (
+            status,
+            [(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON))],
+            Json(&self),
+        )
+            .into_response();
+        if let Some(retry_after) = self.retry_after {
+            res.headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        res.extensions_mut().insert(self);
+        res
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("bad request")]
     BadRequest(BadRequestPayload),
+    #[error("oauth error")]
+    OAuth(OAuthErrorPayload),
     #[error("authentication required")]
     Unauthorized,
     #[error("user may not perform that action")]
     Forbidden,
+    #[error("token scope does not cover that action")]
+    InsufficientScope,
     #[error("request path not found")]
     NotFound,
+    #[error("invalid profile: {0:?}")]
+    InvalidProfile(Vec<ProfileInvalidity>),
+    #[error("too many requests")]
+    TooManyRequests { retry_after: i64 },
     #[error("internal server error")]
     InternalServerErrorEmpty,
     #[error("internal server error")]
@@ -47,35 +278,67 @@ impl Error {
     fn status_code(&self) -> StatusCode {
         match self {
             Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidProfile(_) => StatusCode::BAD_REQUEST,
+            Self::OAuth(_) => StatusCode::BAD_REQUEST,
             Self::Unauthorized => StatusCode::UNAUTHORIZED,
             Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::InsufficientScope => StatusCode::FORBIDDEN,
             Self::NotFound => StatusCode::NOT_FOUND,
+            Self::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
             Self::InternalServerErrorEmpty => StatusCode::INTERNAL_SERVER_ERROR,
             Self::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    /// The problem reported to the client, in `Locale::default()`. Internal errors keep their
+    /// cause to the logs.
+    pub fn problem(&self) -> Problem {
+        let status = self.status_code();
+        let problem = match self {
+            Self::BadRequest(payload) => Problem {
+                kind: Some(payload.kind.clone()),
+                key: Some(payload.key.clone()),
+                ..Problem::of(&payload.kind.to_string(), status)
+            },
+            Self::InvalidProfile(invalidities) => {
+                let kind = BadRequestKind::ProfileValidationError;
+                Problem {
+                    kind: Some(kind.clone()),
+                    key: Some(format!("{:?}", invalidities)),
+                    invalid_params: Some(invalidities.iter().map(InvalidParam::of).collect()),
+                    ..Problem::of(&kind.to_string(), status)
+                }
+            }
+            Self::OAuth(payload) => Problem {
+                error: Some(payload.error.clone()),
+                error_description: Some(payload.error_description.clone()),
+                ..Problem::of(&payload.error.to_string(), status)
+            },
+            Self::Unauthorized => Problem::of("unauthorized", status),
+            Self::Forbidden => Problem::of("forbidden", status),
+            Self::InsufficientScope => Problem::of("insufficient_scope", status),
+            Self::NotFound => Problem::of("not_found", status),
+            Self::TooManyRequests { retry_after } => Problem {
+                retry_after: Some(*retry_after),
+                args: vec![("retry_after", retry_after.to_string())],
+                ..Problem::of("too_many_requests", status)
+            },
+            Self::InternalServerErrorEmpty | Self::InternalServerError(_) => {
+                Problem::of("internal_server_error", status)
+            }
+        };
+        problem.localize(Locale::default())
+    }
 }
 
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
         match self {
-            Self::Unauthorized => {
-                return (self.status_code(), Json(json!({"message": "unauthorized"})))
-                    .into_response()
-            }
-            Self::InternalServerError(ref e) => {
-                error!("Generic error: {:?}", e);
-                (
-                    self.status_code(),
-                    Json(json!({"message": "internal server error"})),
-                )
-                    .into_response()
-            }
-            Self::BadRequest(ref payload) => {
-                error!("Bad request: {:?}", payload);
-                (self.status_code(), Json(payload)).into_response()
-            }
-            _ => (self.status_code(), Json(json!({"message": "other"}))).into_response(),
+            Self::InternalServerError(ref e) => error!("Generic error: {:?}", e),
+            Self::BadRequest(ref payload) => error!("Bad request: {:?}", payload),
+            Self::InvalidProfile(ref invalidities) => error!("Bad request: {:?}", invalidities),
+            _ => {}
         }
+        self.problem().into_response()
     }
 }
@@ -0,0 +1,199 @@
+//! How each use case failure is reported, so that handlers only need `map_err(Error::from)`.
+use account::model::device_authorization::{ApproveError, PollError};
+use account::usecase::approve_device_code::ApproveDeviceCodeUseCaseError;
+use account::usecase::exchange_device_code::ExchangeDeviceCodeUseCaseError;
+use account::usecase::issue_device_code::IssueDeviceCodeUseCaseError;
+use account::usecase::list_audit_events::ListAuditEventsUseCaseError;
+use account::usecase::reset_user_name::ResetUserNameUseCaseError;
+use account::usecase::resolve_profile::ResolveProfileUseCaseError;
+use account::usecase::resolve_user::ResolveUserUseCaseError;
+use account::usecase::search_users::SearchUsersUseCaseError;
+use account::usecase::sign_up::SignUpUseCaseError;
+use account::usecase::update_profile::UpdateProfileUseCaseError;
+use account::usecase::update_role::UpdateRoleUseCaseError;
+use account::usecase::update_user_status::UpdateUserStatusUseCaseError;
+use account::usecase::verify::VerifyUseCaseError;
+use anyhow::anyhow;
+
+use super::{BadRequestKind, BadRequestPayload, Error, OAuthErrorKind, OAuthErrorPayload};
+
+fn bad_request(kind: BadRequestKind, key: String) -> Error {
+    Error::BadRequest(BadRequestPayload::new(kind, key))
+}
+
+impl From<SignUpUseCaseError> for Error {
+    fn from(e: SignUpUseCaseError) -> Self {
+        match e {
+            SignUpUseCaseError::AlreadyExist(e) => bad_request(BadRequestKind::AlreadyExist, e),
+            SignUpUseCaseError::VerifyFailed(e) => {
+                bad_request(BadRequestKind::VerifyFailed, e.to_string())
+            }
+            SignUpUseCaseError::ResolveError(e) => Error::InternalServerError(anyhow!(e)),
+            SignUpUseCaseError::StoreError(e) => Error::InternalServerError(anyhow!(e)),
+            SignUpUseCaseError::Unexpected(e) => Error::InternalServerError(anyhow!(e)),
+            SignUpUseCaseError::RateLimited { retry_after } => {
+                Error::TooManyRequests { retry_after }
+            }
+        }
+    }
+}
+
+impl From<VerifyUseCaseError> for Error {
+    fn from(e: VerifyUseCaseError) -> Self {
+        match e {
+            VerifyUseCaseError::UserNotFound(e) => bad_request(BadRequestKind::UserNotFound, e),
+            VerifyUseCaseError::UserDisabled(e) => bad_request(BadRequestKind::UserDisabled, e),
+            VerifyUseCaseError::VerifyFailed(e) => {
+                bad_request(BadRequestKind::VerifyFailed, e.to_string())
+            }
+            VerifyUseCaseError::FilterError(e) => Error::InternalServerError(anyhow!(e)),
+            VerifyUseCaseError::RateLimited { retry_after } => {
+                Error::TooManyRequests { retry_after }
+            }
+        }
+    }
+}
+
+impl From<ResolveProfileUseCaseError> for Error {
+    fn from(e: ResolveProfileUseCaseError) -> Self {
+        match e {
+            ResolveProfileUseCaseError::ResolveError(e) => Error::InternalServerError(anyhow!(e)),
+            ResolveProfileUseCaseError::NotFound => Error::NotFound,
+        }
+    }
+}
+
+impl From<UpdateProfileUseCaseError> for Error {
+    fn from(e: UpdateProfileUseCaseError) -> Self {
+        match e {
+            UpdateProfileUseCaseError::ProfileValidationError(e) => {
+                let mut invalidities = Vec::new();
+                for invalidity in e.into_iter() {
+                    if !invalidities.contains(&invalidity) {
+                        invalidities.push(invalidity);
+                    }
+                }
+                Error::InvalidProfile(invalidities)
+            }
+            UpdateProfileUseCaseError::StoreError(e) => Error::InternalServerError(anyhow!(e)),
+        }
+    }
+}
+
+impl From<IssueDeviceCodeUseCaseError> for Error {
+    fn from(e: IssueDeviceCodeUseCaseError) -> Self {
+        match e {
+            IssueDeviceCodeUseCaseError::StoreError(e) => Error::InternalServerError(anyhow!(e)),
+        }
+    }
+}
+
+impl From<ApproveDeviceCodeUseCaseError> for Error {
+    fn from(e: ApproveDeviceCodeUseCaseError) -> Self {
+        match e {
+            ApproveDeviceCodeUseCaseError::NotFound(e) => {
+                bad_request(BadRequestKind::DeviceCodeNotFound, e)
+            }
+            ApproveDeviceCodeUseCaseError::ApproveError(e) => {
+                let kind = match e {
+                    ApproveError::Expired => BadRequestKind::DeviceCodeExpired,
+                    ApproveError::AlreadyProcessed => BadRequestKind::DeviceCodeAlreadyProcessed,
+                };
+                bad_request(kind, e.to_string())
+            }
+            ApproveDeviceCodeUseCaseError::FindError(e) => Error::InternalServerError(anyhow!(e)),
+            ApproveDeviceCodeUseCaseError::StoreError(e) => Error::InternalServerError(anyhow!(e)),
+        }
+    }
+}
+
+impl From<ExchangeDeviceCodeUseCaseError> for Error {
+    fn from(e: ExchangeDeviceCodeUseCaseError) -> Self {
+        let kind = match e {
+            ExchangeDeviceCodeUseCaseError::Poll(ref p) => match p {
+                PollError::AuthorizationPending => OAuthErrorKind::AuthorizationPending,
+                PollError::SlowDown(_) => OAuthErrorKind::SlowDown,
+                PollError::AccessDenied => OAuthErrorKind::AccessDenied,
+                PollError::Expired => OAuthErrorKind::ExpiredToken,
+                PollError::AlreadyConsumed => OAuthErrorKind::InvalidGrant,
+            },
+            ExchangeDeviceCodeUseCaseError::InvalidGrant => OAuthErrorKind::InvalidGrant,
+            ExchangeDeviceCodeUseCaseError::UnsupportedGrantType(_) => {
+                OAuthErrorKind::UnsupportedGrantType
+            }
+            ExchangeDeviceCodeUseCaseError::ResolveError(e) => {
+                return Error::InternalServerError(anyhow!(e))
+            }
+            ExchangeDeviceCodeUseCaseError::DeviceAuthorizationStoreError(e) => {
+                return Error::InternalServerError(anyhow!(e))
+            }
+            ExchangeDeviceCodeUseCaseError::ScopedTokenStoreError(e) => {
+                return Error::InternalServerError(anyhow!(e))
+            }
+        };
+        Error::OAuth(OAuthErrorPayload::new(kind, e.to_string()))
+    }
+}
+
+impl From<ListAuditEventsUseCaseError> for Error {
+    fn from(e: ListAuditEventsUseCaseError) -> Self {
+        match e {
+            ListAuditEventsUseCaseError::AuditLogError(e) => Error::InternalServerError(anyhow!(e)),
+        }
+    }
+}
+
+impl From<ResolveUserUseCaseError> for Error {
+    fn from(e: ResolveUserUseCaseError) -> Self {
+        match e {
+            ResolveUserUseCaseError::UserNotFound(_) => Error::NotFound,
+            ResolveUserUseCaseError::ResolveError(e) => Error::InternalServerError(anyhow!(e)),
+        }
+    }
+}
+
+impl From<UpdateRoleUseCaseError> for Error {
+    fn from(e: UpdateRoleUseCaseError) -> Self {
+        match e {
+            UpdateRoleUseCaseError::UserNotFound(e) => bad_request(BadRequestKind::UserNotFound, e),
+            UpdateRoleUseCaseError::RoleGrantError(e) => {
+                bad_request(BadRequestKind::RoleGrantError, e.to_string())
+            }
+            UpdateRoleUseCaseError::ResolveError(e) => Error::InternalServerError(anyhow!(e)),
+            UpdateRoleUseCaseError::StoreError(e) => Error::InternalServerError(anyhow!(e)),
+        }
+    }
+}
+
+impl From<SearchUsersUseCaseError> for Error {
+    fn from(e: SearchUsersUseCaseError) -> Self {
+        match e {
+            SearchUsersUseCaseError::SearchError(e) => Error::InternalServerError(anyhow!(e)),
+        }
+    }
+}
+
+impl From<UpdateUserStatusUseCaseError> for Error {
+    fn from(e: UpdateUserStatusUseCaseError) -> Self {
+        match e {
+            UpdateUserStatusUseCaseError::UserNotFound(e) => {
+                bad_request(BadRequestKind::UserNotFound, e)
+            }
+            UpdateUserStatusUseCaseError::UserManageError(e) => {
+                bad_request(BadRequestKind::UserManageError, e.to_string())
+            }
+            UpdateUserStatusUseCaseError::ResolveError(e) => Error::InternalServerError(anyhow!(e)),
+            UpdateUserStatusUseCaseError::StoreError(e) => Error::InternalServerError(anyhow!(e)),
+        }
+    }
+}
+
+impl From<ResetUserNameUseCaseError> for Error {
+    fn from(e: ResetUserNameUseCaseError) -> Self {
+        match e {
+            ResetUserNameUseCaseError::ProfileNotFound(_) => Error::NotFound,
+            ResetUserNameUseCaseError::ResolveError(e) => Error::InternalServerError(anyhow!(e)),
+            ResetUserNameUseCaseError::StoreError(e) => Error::InternalServerError(anyhow!(e)),
+        }
+    }
+}
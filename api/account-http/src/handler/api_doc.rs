@@ -0,0 +1,47 @@
+use axum::http::header::CONTENT_SECURITY_POLICY;
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse};
+use axum::Json;
+use utoipa::OpenApi;
+
+use crate::openapi::ApiDoc;
+
+/// Swagger UI over `/openapi.json`, loaded from the jsDelivr CDN at a pinned version. Bump
+/// the version in `DOCS_PAGE` and `DOCS_POLICY` together.
+pub(crate) const DOCS_PAGE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>account API</title>
+  <link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/swagger-ui-dist@5.17.14/swagger-ui.css" crossorigin="anonymous" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://cdn.jsdelivr.net/npm/swagger-ui-dist@5.17.14/swagger-ui-bundle.js" crossorigin="anonymous"></script>
+  <script>
+    window.ui = SwaggerUIBundle({ url: "openapi.json", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>
+"##;
+
+/// Lets the docs page load nothing but the pinned Swagger UI files and its own inline
+/// script, named by its SHA-256, and fetch only from this origin.
+pub(crate) const DOCS_POLICY: &str = "default-src 'none'; \
+    script-src https://cdn.jsdelivr.net/npm/swagger-ui-dist@5.17.14/swagger-ui-bundle.js 'sha256-l6JPv6mU7Ud3CcYzxaENdwY/2iglfDHOrm50yxX1vVY='; \
+    style-src https://cdn.jsdelivr.net/npm/swagger-ui-dist@5.17.14/swagger-ui.css 'unsafe-inline'; \
+    img-src 'self' data:; connect-src 'self'";
+
+#[tracing::instrument]
+pub async fn openapi_handler() -> impl IntoResponse {
+    (StatusCode::OK, Json(ApiDoc::openapi()))
+}
+
+#[tracing::instrument]
+pub async fn docs_handler() -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        [(CONTENT_SECURITY_POLICY, DOCS_POLICY)],
+        Html(DOCS_PAGE),
+    )
+}
@@ -0,0 +1,44 @@
+use account::usecase::approve_device_code::{
+    ApproveDeviceCodeUseCase, ApproveDeviceCodeUseCaseParams,
+};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use axum::Json;
+
+use crate::actor::{AuthenticateKernel, UserActor};
+use crate::error::Error;
+
+/// Approves or denies the device that shows `user_code`, on behalf of the signed in user.
+#[utoipa::path(
+    post,
+    path = "/device/approve",
+    tag = "device",
+    operation_id = "approve_device_code",
+    security(("bearer" = [])),
+    request_body = ApproveDeviceCodeUseCaseParams,
+    responses(
+        (status = 204, description = "Decision recorded"),
+        (
+            status = 400,
+            description = "`DeviceCodeNotFound`, `DeviceCodeExpired` or `DeviceCodeAlreadyProcessed`",
+            body = Problem
+        ),
+        (status = 401, description = "Missing or invalid token", body = Problem),
+        (status = 403, description = "Scoped tokens are not accepted", body = Problem),
+    )
+)]
+#[tracing::instrument(skip(kernel))]
+pub async fn approve_device_code_handler<K>(
+    user_actor: UserActor<K>,
+    kernel: Extension<K>,
+    Json(params): Json<ApproveDeviceCodeUseCaseParams>,
+) -> Result<Response, Error>
+where
+    K: ApproveDeviceCodeUseCase + AuthenticateKernel,
+{
+    match ApproveDeviceCodeUseCase::execute(&kernel.0, &user_actor, params).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT.into_response()),
+        Err(e) => Err(Error::from(e)),
+    }
+}
@@ -0,0 +1,60 @@
+use account::usecase::exchange_device_code::{
+    ExchangeDeviceCodeUseCase, ExchangeDeviceCodeUseCaseParams, ExchangeDeviceCodeUseCaseResult,
+};
+use axum::http::header::CACHE_CONTROL;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use axum::{Form, Json};
+use serde::Serialize;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+use crate::error::Error;
+
+#[derive(Serialize, ToSchema, TS)]
+pub struct ExchangeDeviceCodeResponse {
+    #[serde(flatten)]
+    result: ExchangeDeviceCodeUseCaseResult,
+    #[schema(example = "Bearer")]
+    #[ts(type = "\"Bearer\"")]
+    token_type: &'static str,
+}
+
+/// Polls for the scoped token of an approved device code (RFC 8628 section 3.4).
+#[utoipa::path(
+    post,
+    path = "/device/token",
+    tag = "device",
+    operation_id = "exchange_device_code",
+    request_body(
+        content = ExchangeDeviceCodeUseCaseParams,
+        content_type = "application/x-www-form-urlencoded"
+    ),
+    responses(
+        (status = 200, description = "Scoped token issued", body = ExchangeDeviceCodeResponse),
+        (status = 400, description = "OAuth error (RFC 8628 section 3.5)", body = Problem),
+        (status = 429, description = "Rate limited, see `Retry-After`", body = Problem),
+    )
+)]
+#[tracing::instrument(skip(kernel, params))]
+pub async fn exchange_device_code_handler<K>(
+    kernel: Extension<K>,
+    Form(params): Form<ExchangeDeviceCodeUseCaseParams>,
+) -> Result<Response, Error>
+where
+    K: ExchangeDeviceCodeUseCase + Clone + Send + Sync + 'static,
+{
+    match kernel.execute(params).await {
+        Ok(result) => Ok((
+            StatusCode::OK,
+            [(CACHE_CONTROL, "no-store")],
+            Json(ExchangeDeviceCodeResponse {
+                result,
+                token_type: "Bearer",
+            }),
+        )
+            .into_response()),
+        Err(e) => Err(Error::from(e)),
+    }
+}
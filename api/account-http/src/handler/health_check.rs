@@ -1,9 +1,15 @@
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
-use axum::Json;
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
 use serde::Serialize;
+use std::collections::BTreeMap;
+use ts_rs::TS;
+use utoipa::ToSchema;
 
-#[derive(Serialize)]
+use crate::readiness::{Check, CheckStatus, ReadinessKernel};
+use crate::shutdown::Draining;
+
+#[derive(Serialize, ToSchema, TS)]
 pub struct HealthCheckResponse {
     pub version: String,
     pub sha: String,
@@ -13,15 +19,98 @@ pub struct HealthCheckResponse {
 impl Default for HealthCheckResponse {
     fn default() -> Self {
         Self {
-            version: env!("VERGEN_BUILD_TIMESTAMP").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
             sha: env!("VERGEN_GIT_SHA").to_string(),
             build_timestamp: env!("VERGEN_BUILD_TIMESTAMP").to_string(),
         }
     }
 }
 
+/// Reports the build of the running server.
+#[utoipa::path(
+    get,
+    path = "/hc",
+    tag = "health",
+    operation_id = "health_check",
+    responses(
+        (status = 200, description = "Build information", body = HealthCheckResponse),
+    )
+)]
 #[tracing::instrument]
 pub async fn health_check_handler() -> impl IntoResponse {
     let response = HealthCheckResponse::default();
     (StatusCode::OK, Json(response))
 }
+
+#[derive(Serialize, ToSchema, TS)]
+pub struct LivenessResponse {
+    pub status: CheckStatus,
+}
+
+/// Answers as long as the process serves requests, whatever its dependencies.
+#[utoipa::path(
+    get,
+    path = "/livez",
+    tag = "health",
+    operation_id = "livez",
+    responses(
+        (status = 200, description = "The process is alive", body = LivenessResponse),
+    )
+)]
+pub async fn livez_handler() -> impl IntoResponse {
+    let response = LivenessResponse {
+        status: CheckStatus::Ok,
+    };
+    (StatusCode::OK, Json(response))
+}
+
+#[derive(Serialize, ToSchema, TS)]
+pub struct ReadinessResponse {
+    /// `fail` when any check failed.
+    pub status: CheckStatus,
+    pub checks: BTreeMap<String, Check>,
+}
+
+impl ReadinessResponse {
+    pub fn new(checks: BTreeMap<String, Check>) -> Self {
+        let status = match checks.values().all(|c| c.status == CheckStatus::Ok) {
+            true => CheckStatus::Ok,
+            false => CheckStatus::Fail,
+        };
+        ReadinessResponse { status, checks }
+    }
+}
+
+impl IntoResponse for ReadinessResponse {
+    fn into_response(self) -> Response {
+        let status = match self.status {
+            CheckStatus::Ok => StatusCode::OK,
+            CheckStatus::Fail => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        (status, Json(self)).into_response()
+    }
+}
+
+/// Checks the database, its migrations and the Firebase signing keys the server depends on.
+/// Fails from the moment a shutdown signal is received.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    operation_id = "readyz",
+    responses(
+        (status = 200, description = "Every check passed", body = ReadinessResponse),
+        (status = 503, description = "A check failed, see its `detail`", body = ReadinessResponse),
+    )
+)]
+#[tracing::instrument(skip(kernel))]
+pub async fn readyz_handler<K: ReadinessKernel>(
+    kernel: Extension<K>,
+    draining: Option<Extension<Draining>>,
+) -> ReadinessResponse {
+    let mut checks = kernel.check_readiness().await;
+    if matches!(draining, Some(Extension(d)) if d.is_draining()) {
+        checks.insert("shutdown".to_string(), Check::fail("shutting down"));
+    }
+    ReadinessResponse::new(checks)
+}
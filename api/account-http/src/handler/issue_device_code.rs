@@ -0,0 +1,63 @@
+use account::effect::config::{Config, HaveConfig};
+use account::usecase::issue_device_code::{
+    IssueDeviceCodeUseCase, IssueDeviceCodeUseCaseParams, IssueDeviceCodeUseCaseResult,
+};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use axum::{Form, Json};
+use serde::Serialize;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+use crate::error::Error;
+
+#[derive(Serialize, ToSchema, TS)]
+pub struct IssueDeviceCodeResponse {
+    #[serde(flatten)]
+    result: IssueDeviceCodeUseCaseResult,
+    verification_uri: String,
+    verification_uri_complete: String,
+}
+
+/// Starts the device authorization grant (RFC 8628 section 3.1).
+#[utoipa::path(
+    post,
+    path = "/device/code",
+    tag = "device",
+    operation_id = "issue_device_code",
+    request_body(
+        content = IssueDeviceCodeUseCaseParams,
+        content_type = "application/x-www-form-urlencoded"
+    ),
+    responses(
+        (status = 200, description = "Codes issued", body = IssueDeviceCodeResponse),
+        (status = 429, description = "Rate limited, see `Retry-After`", body = Problem),
+    )
+)]
+#[tracing::instrument(skip(kernel))]
+pub async fn issue_device_code_handler<K>(
+    kernel: Extension<K>,
+    Form(params): Form<IssueDeviceCodeUseCaseParams>,
+) -> Result<Response, Error>
+where
+    K: IssueDeviceCodeUseCase + HaveConfig + Clone + Send + Sync + 'static,
+{
+    match kernel.execute(params).await {
+        Ok(result) => {
+            let verification_uri = kernel.config().device_verification_uri().to_string();
+            let verification_uri_complete =
+                format!("{}?user_code={}", verification_uri, result.user_code);
+            Ok((
+                StatusCode::OK,
+                Json(IssueDeviceCodeResponse {
+                    result,
+                    verification_uri,
+                    verification_uri_complete,
+                }),
+            )
+                .into_response())
+        }
+        Err(e) => Err(Error::from(e)),
+    }
+}
@@ -0,0 +1,72 @@
+use account::effect::audit_log::AuditEventCondition;
+use account::model::audit_event::AuditAction;
+use account::model::user::UserId;
+use account::repository::meta::Pagination;
+use account::usecase::list_audit_events::{ListAuditEventsUseCase, ListAuditEventsUseCaseResult};
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use axum::Json;
+use derive_more::Constructor;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::actor::{Auditor, AuditorActor, AuthenticateKernel};
+use crate::error::Error;
+
+#[derive(Serialize, Constructor, ToSchema)]
+pub struct ListAuditEventsResponse(ListAuditEventsUseCaseResult);
+
+/// `actor_id` is honoured for admins only; other users always see their own events.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListAuditEventsQuery {
+    actor_id: Option<String>,
+    action: Option<AuditAction>,
+    offset: Option<i64>,
+    limit: Option<i64>,
+}
+
+/// Lists audit events, newest first.
+#[utoipa::path(
+    get,
+    path = "/audit",
+    tag = "audit",
+    operation_id = "list_audit_events",
+    security(("bearer" = [])),
+    params(ListAuditEventsQuery),
+    responses(
+        (status = 200, description = "A page of events", body = ListAuditEventsResponse),
+        (status = 401, description = "Missing or invalid token", body = Problem),
+        (status = 403, description = "Scoped tokens are not accepted", body = Problem),
+    )
+)]
+#[tracing::instrument(skip(kernel))]
+pub async fn list_audit_events_handler<K>(
+    auditor_actor: AuditorActor<K>,
+    kernel: Extension<K>,
+    Query(query): Query<ListAuditEventsQuery>,
+) -> Result<Response, Error>
+where
+    K: ListAuditEventsUseCase + AuthenticateKernel,
+{
+    let default = Pagination::default();
+    let pagination = Pagination::new(
+        query.offset.unwrap_or(default.offset),
+        query.limit.unwrap_or(default.limit),
+    );
+    let condition = AuditEventCondition::new(query.actor_id.map(UserId::new), query.action);
+    let result = match &*auditor_actor {
+        Auditor::Admin(admin) => {
+            ListAuditEventsUseCase::execute(&kernel.0, admin, condition, pagination).await
+        }
+        Auditor::User(user) => {
+            ListAuditEventsUseCase::execute(&kernel.0, user, condition, pagination).await
+        }
+    };
+    match result {
+        Ok(result) => Ok((StatusCode::OK, Json(ListAuditEventsResponse(result))).into_response()),
+        Err(e) => Err(Error::from(e)),
+    }
+}
@@ -0,0 +1,11 @@
+use axum::http::header::CONTENT_TYPE;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use prometheus::TEXT_FORMAT;
+
+use crate::metrics::render;
+
+/// Serves the metrics of the server and its drivers to Prometheus.
+pub async fn metrics_handler() -> impl IntoResponse {
+    (StatusCode::OK, [(CONTENT_TYPE, TEXT_FORMAT)], render())
+}
@@ -0,0 +1,54 @@
+use account::usecase::reset_user_name::{ResetUserNameUseCase, ResetUserNameUseCaseResult};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use axum::Json;
+use derive_more::Constructor;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+use crate::actor::{AuthenticateKernel, EditorActor};
+use crate::error::Error;
+use crate::request_context::RequestContext;
+
+#[derive(Serialize, Constructor, ToSchema)]
+pub struct ResetUserNameResponse(ResetUserNameUseCaseResult);
+
+#[derive(Debug, Deserialize, ToSchema, TS)]
+pub struct ResetUserNameParams {
+    user_id: String,
+}
+
+/// Replaces the user name of a user with a placeholder. Editors and admins only.
+#[utoipa::path(
+    post,
+    path = "/admin/reset_user_name",
+    tag = "admin",
+    operation_id = "reset_user_name",
+    security(("bearer" = [])),
+    request_body = ResetUserNameParams,
+    responses(
+        (status = 200, description = "The updated profile", body = ResetUserNameResponse),
+        (status = 401, description = "Missing or invalid token", body = Problem),
+        (status = 403, description = "Not an editor or admin, or a scoped token", body = Problem),
+        (status = 404, description = "The user has no profile", body = Problem),
+    )
+)]
+#[tracing::instrument(skip(kernel))]
+pub async fn reset_user_name_handler<K>(
+    editor_actor: EditorActor<K>,
+    kernel: Extension<K>,
+    request_context: RequestContext,
+    Json(params): Json<ResetUserNameParams>,
+) -> Result<Response, Error>
+where
+    K: ResetUserNameUseCase + AuthenticateKernel,
+{
+    match ResetUserNameUseCase::execute(&kernel.0, &*editor_actor, params.user_id, &request_context)
+        .await
+    {
+        Ok(result) => Ok((StatusCode::OK, Json(ResetUserNameResponse(result))).into_response()),
+        Err(e) => Err(Error::from(e)),
+    }
+}
@@ -1,31 +1,42 @@
-use account::usecase::resolve_profile::{
-    ResolveProfileUseCase, ResolveProfileUseCaseError, ResolveProfileUseCaseResult,
-};
-use anyhow::anyhow;
+use account::usecase::resolve_profile::{ResolveProfileUseCase, ResolveProfileUseCaseResult};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::Extension;
 use axum::Json;
 use derive_more::Constructor;
 use serde::Serialize;
+use utoipa::ToSchema;
 
-use crate::actor::UserActor;
+use crate::actor::{AuthenticateKernel, UserActor};
 use crate::error::Error;
-use crate::kernel::Kernel;
 
-#[derive(Serialize, Constructor)]
+#[derive(Serialize, Constructor, ToSchema)]
 pub struct ResolveProfileResponse(ResolveProfileUseCaseResult);
 
+/// Returns the profile of the signed in user.
+#[utoipa::path(
+    get,
+    path = "/resolve_profile",
+    tag = "profile",
+    operation_id = "resolve_profile",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Profile of the user", body = ResolveProfileResponse),
+        (status = 401, description = "Missing or invalid token", body = Problem),
+        (status = 403, description = "Scoped token without the `profile` scope", body = Problem),
+        (status = 404, description = "No profile stored yet", body = Problem),
+    )
+)]
 #[tracing::instrument(skip(kernel))]
-pub async fn resolve_profile_handler(
-    user_actor: UserActor,
-    kernel: Extension<Kernel>,
-) -> Result<Response, Error> {
-    match kernel.execute(&user_actor).await {
+pub async fn resolve_profile_handler<K>(
+    user_actor: UserActor<K>,
+    kernel: Extension<K>,
+) -> Result<Response, Error>
+where
+    K: ResolveProfileUseCase + AuthenticateKernel,
+{
+    match ResolveProfileUseCase::execute(&kernel.0, &user_actor).await {
         Ok(result) => Ok((StatusCode::OK, Json(ResolveProfileResponse(result))).into_response()),
-        Err(e) => Err(match e {
-            ResolveProfileUseCaseError::ResolveError(e) => Error::InternalServerError(anyhow!(e)),
-            ResolveProfileUseCaseError::NotFound => Error::NotFound,
-        }),
+        Err(e) => Err(Error::from(e)),
     }
 }
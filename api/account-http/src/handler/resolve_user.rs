@@ -0,0 +1,51 @@
+use account::usecase::resolve_user::{ResolveUserUseCase, ResolveUserUseCaseResult};
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use axum::Json;
+use derive_more::Constructor;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::actor::{AdminActor, AuthenticateKernel};
+use crate::error::Error;
+
+#[derive(Serialize, Constructor, ToSchema)]
+pub struct ResolveUserResponse(ResolveUserUseCaseResult);
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ResolveUserQuery {
+    user_id: String,
+}
+
+/// Returns any user with their profile. Admins only.
+#[utoipa::path(
+    get,
+    path = "/admin/resolve_user",
+    tag = "admin",
+    operation_id = "resolve_user",
+    security(("bearer" = [])),
+    params(ResolveUserQuery),
+    responses(
+        (status = 200, description = "The user", body = ResolveUserResponse),
+        (status = 401, description = "Missing or invalid token", body = Problem),
+        (status = 403, description = "Not an admin, or a scoped token", body = Problem),
+        (status = 404, description = "No such user", body = Problem),
+    )
+)]
+#[tracing::instrument(skip(kernel))]
+pub async fn resolve_user_handler<K>(
+    admin_actor: AdminActor<K>,
+    kernel: Extension<K>,
+    Query(query): Query<ResolveUserQuery>,
+) -> Result<Response, Error>
+where
+    K: ResolveUserUseCase + AuthenticateKernel,
+{
+    match ResolveUserUseCase::execute(&kernel.0, &*admin_actor, query.user_id).await {
+        Ok(result) => Ok((StatusCode::OK, Json(ResolveUserResponse(result))).into_response()),
+        Err(e) => Err(Error::from(e)),
+    }
+}
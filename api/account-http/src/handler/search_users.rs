@@ -0,0 +1,78 @@
+use account::model::login_provider::ProviderKind;
+use account::repository::meta::Pagination;
+use account::repository::user_repository::UserSearchCondition;
+use account::usecase::search_users::{SearchUsersUseCase, SearchUsersUseCaseResult};
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use axum::Json;
+use derive_more::Constructor;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::actor::{AdminActor, AuthenticateKernel};
+use crate::error::Error;
+
+#[derive(Serialize, Constructor, ToSchema)]
+pub struct SearchUsersResponse(SearchUsersUseCaseResult);
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchUsersQuery {
+    provider_kind: Option<ProviderKind>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    created_after: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    created_before: Option<OffsetDateTime>,
+    name_prefix: Option<String>,
+    offset: Option<i64>,
+    limit: Option<i64>,
+}
+
+impl From<&SearchUsersQuery> for Pagination {
+    fn from(q: &SearchUsersQuery) -> Self {
+        let default = Pagination::default();
+        Pagination::new(
+            q.offset.unwrap_or(default.offset),
+            q.limit.unwrap_or(default.limit),
+        )
+    }
+}
+
+/// Searches users, oldest first. Admins only.
+#[utoipa::path(
+    get,
+    path = "/admin/search_users",
+    tag = "admin",
+    operation_id = "search_users",
+    security(("bearer" = [])),
+    params(SearchUsersQuery),
+    responses(
+        (status = 200, description = "A page of users", body = SearchUsersResponse),
+        (status = 401, description = "Missing or invalid token", body = Problem),
+        (status = 403, description = "Not an admin, or a scoped token", body = Problem),
+    )
+)]
+#[tracing::instrument(skip(kernel))]
+pub async fn search_users_handler<K>(
+    admin_actor: AdminActor<K>,
+    kernel: Extension<K>,
+    Query(query): Query<SearchUsersQuery>,
+) -> Result<Response, Error>
+where
+    K: SearchUsersUseCase + AuthenticateKernel,
+{
+    let pagination = Pagination::from(&query);
+    let condition = UserSearchCondition::new(
+        query.provider_kind,
+        query.created_after,
+        query.created_before,
+        query.name_prefix,
+    );
+    match SearchUsersUseCase::execute(&kernel.0, &*admin_actor, condition, pagination).await {
+        Ok(result) => Ok((StatusCode::OK, Json(SearchUsersResponse(result))).into_response()),
+        Err(e) => Err(Error::from(e)),
+    }
+}
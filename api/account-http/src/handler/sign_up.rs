@@ -1,5 +1,5 @@
-use account::usecase::sign_up::{SignUpUseCase, SignUpUseCaseError, SignUpUseCaseResult};
-use anyhow::anyhow;
+use account::effect::rate_limit_store::IdentityRateLimit;
+use account::usecase::sign_up::{SignUpUseCase, SignUpUseCaseResult};
 use axum::extract::TypedHeader;
 use axum::headers;
 use axum::headers::authorization::Bearer;
@@ -9,32 +9,48 @@ use axum::Extension;
 use axum::Json;
 use derive_more::Constructor;
 use serde::Serialize;
+use utoipa::ToSchema;
 
 use crate::error::Error;
-use crate::error::{BadRequestKind, BadRequestPayload};
-use crate::kernel::Kernel;
+use crate::metrics::record_sign_up;
+use crate::request_context::RequestContext;
 
-#[derive(Serialize, Constructor)]
+#[derive(Serialize, Constructor, ToSchema)]
 pub struct SignUpResponse(SignUpUseCaseResult);
 
+/// Creates the account of the Firebase user the bearer ID token belongs to.
+#[utoipa::path(
+    post,
+    path = "/sign_up",
+    tag = "auth",
+    operation_id = "sign_up",
+    security(("bearer" = [])),
+    responses(
+        (status = 201, description = "Account created", body = SignUpResponse),
+        (status = 400, description = "`AlreadyExist` or `VerifyFailed`", body = Problem),
+        (status = 429, description = "Rate limited, see `Retry-After`", body = Problem),
+    )
+)]
 #[tracing::instrument(skip(kernel, authorization))]
-pub async fn sign_up_handler(
-    kernel: Extension<Kernel>,
+pub async fn sign_up_handler<K>(
+    kernel: Extension<K>,
+    request_context: RequestContext,
+    identity_limit: Option<Extension<IdentityRateLimit>>,
     TypedHeader(authorization): TypedHeader<headers::Authorization<Bearer>>,
-) -> Result<Response, Error> {
-    match kernel.execute(authorization.token().to_string()).await {
+) -> Result<Response, Error>
+where
+    K: SignUpUseCase + Clone + Send + Sync + 'static,
+{
+    let result = kernel
+        .execute(
+            authorization.token().to_string(),
+            &request_context,
+            identity_limit.map(|Extension(limit)| limit),
+        )
+        .await;
+    record_sign_up(&result);
+    match result {
         Ok(result) => Ok((StatusCode::CREATED, Json(SignUpResponse::new(result))).into_response()),
-        Err(e) => Err(match e {
-            SignUpUseCaseError::AlreadyExist(e) => {
-                Error::BadRequest(BadRequestPayload::new(BadRequestKind::AlreadyExist, e))
-            }
-            SignUpUseCaseError::VerifyFailed(e) => Error::BadRequest(BadRequestPayload::new(
-                BadRequestKind::VerifyFailed,
-                e.to_string(),
-            )),
-            SignUpUseCaseError::ResolveError(e) => Error::InternalServerError(anyhow!(e)),
-            SignUpUseCaseError::StoreError(e) => Error::InternalServerError(anyhow!(e)),
-            SignUpUseCaseError::Unexpected(e) => Error::InternalServerError(anyhow!(e)),
-        }),
+        Err(e) => Err(Error::from(e)),
     }
 }
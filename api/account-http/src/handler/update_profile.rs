@@ -1,39 +1,51 @@
 use account::usecase::update_profile::{
-    UpdateProfileUseCase, UpdateProfileUseCaseError, UpdateProfileUseCaseParams,
-    UpdateProfileUseCaseResult,
+    UpdateProfileUseCase, UpdateProfileUseCaseParams, UpdateProfileUseCaseResult,
 };
-use anyhow::anyhow;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::Extension;
 use axum::Json;
 use derive_more::Constructor;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-use crate::actor::UserActor;
+use crate::actor::{AuthenticateKernel, UserActor};
 use crate::error::Error;
-use crate::error::{BadRequestKind, BadRequestPayload};
-use crate::kernel::Kernel;
+use crate::request_context::RequestContext;
 
-#[derive(Serialize, Constructor)]
+#[derive(Serialize, Constructor, ToSchema)]
 pub struct UpdateProfileResponse(UpdateProfileUseCaseResult);
 
 #[derive(Serialize, Deserialize, Constructor)]
 pub struct UpdateProfileParams(UpdateProfileUseCaseParams);
 
+/// Replaces the profile of the signed in user.
+#[utoipa::path(
+    post,
+    path = "/update_profile",
+    tag = "profile",
+    operation_id = "update_profile",
+    security(("bearer" = [])),
+    request_body = UpdateProfileUseCaseParams,
+    responses(
+        (status = 200, description = "Profile stored", body = UpdateProfileResponse),
+        (status = 400, description = "`ProfileValidationError`", body = Problem),
+        (status = 401, description = "Missing or invalid token", body = Problem),
+        (status = 403, description = "Scoped token without the `profile` scope", body = Problem),
+    )
+)]
 #[tracing::instrument(skip(kernel))]
-pub async fn update_profile_handler(
-    user_actor: UserActor,
-    kernel: Extension<Kernel>,
+pub async fn update_profile_handler<K>(
+    user_actor: UserActor<K>,
+    kernel: Extension<K>,
+    request_context: RequestContext,
     Json(params): Json<UpdateProfileUseCaseParams>,
-) -> Result<Response, Error> {
-    match kernel.execute(&user_actor, params).await {
+) -> Result<Response, Error>
+where
+    K: UpdateProfileUseCase + AuthenticateKernel,
+{
+    match UpdateProfileUseCase::execute(&kernel.0, &user_actor, params, &request_context).await {
         Ok(result) => Ok((StatusCode::OK, Json(UpdateProfileResponse(result))).into_response()),
-        Err(e) => Err(match e {
-            UpdateProfileUseCaseError::ProfileValidationError(e) => Error::BadRequest(
-                BadRequestPayload::new(BadRequestKind::ProfileValidationError, format!("{:?}", e)),
-            ),
-            UpdateProfileUseCaseError::StoreError(e) => Error::InternalServerError(anyhow!(e)),
-        }),
+        Err(e) => Err(Error::from(e)),
     }
 }
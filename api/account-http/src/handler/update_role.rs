@@ -0,0 +1,48 @@
+use account::usecase::update_role::{
+    UpdateRoleUseCase, UpdateRoleUseCaseParams, UpdateRoleUseCaseResult,
+};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use axum::Json;
+use derive_more::Constructor;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::actor::{AdminActor, AuthenticateKernel};
+use crate::error::Error;
+use crate::request_context::RequestContext;
+
+#[derive(Serialize, Constructor, ToSchema)]
+pub struct UpdateRoleResponse(UpdateRoleUseCaseResult);
+
+/// Grants or revokes a role. Admins only.
+#[utoipa::path(
+    post,
+    path = "/admin/update_role",
+    tag = "admin",
+    operation_id = "update_role",
+    security(("bearer" = [])),
+    request_body = UpdateRoleUseCaseParams,
+    responses(
+        (status = 200, description = "The updated user", body = UpdateRoleResponse),
+        (status = 400, description = "`UserNotFound` or `RoleGrantError`", body = Problem),
+        (status = 401, description = "Missing or invalid token", body = Problem),
+        (status = 403, description = "Not an admin, or a scoped token", body = Problem),
+    )
+)]
+#[tracing::instrument(skip(kernel))]
+pub async fn update_role_handler<K>(
+    admin_actor: AdminActor<K>,
+    kernel: Extension<K>,
+    request_context: RequestContext,
+    Json(params): Json<UpdateRoleUseCaseParams>,
+) -> Result<Response, Error>
+where
+    K: UpdateRoleUseCase + AuthenticateKernel,
+{
+    match UpdateRoleUseCase::execute(&kernel.0, &*admin_actor, params, &request_context).await {
+        Ok(result) => Ok((StatusCode::OK, Json(UpdateRoleResponse(result))).into_response()),
+        Err(e) => Err(Error::from(e)),
+    }
+}
@@ -0,0 +1,49 @@
+use account::usecase::update_user_status::{
+    UpdateUserStatusUseCase, UpdateUserStatusUseCaseParams, UpdateUserStatusUseCaseResult,
+};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use axum::Json;
+use derive_more::Constructor;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::actor::{AdminActor, AuthenticateKernel};
+use crate::error::Error;
+use crate::request_context::RequestContext;
+
+#[derive(Serialize, Constructor, ToSchema)]
+pub struct UpdateUserStatusResponse(UpdateUserStatusUseCaseResult);
+
+/// Disables or enables a user. Admins only.
+#[utoipa::path(
+    post,
+    path = "/admin/update_user_status",
+    tag = "admin",
+    operation_id = "update_user_status",
+    security(("bearer" = [])),
+    request_body = UpdateUserStatusUseCaseParams,
+    responses(
+        (status = 200, description = "The updated user", body = UpdateUserStatusResponse),
+        (status = 400, description = "`UserNotFound` or `UserManageError`", body = Problem),
+        (status = 401, description = "Missing or invalid token", body = Problem),
+        (status = 403, description = "Not an admin, or a scoped token", body = Problem),
+    )
+)]
+#[tracing::instrument(skip(kernel))]
+pub async fn update_user_status_handler<K>(
+    admin_actor: AdminActor<K>,
+    kernel: Extension<K>,
+    request_context: RequestContext,
+    Json(params): Json<UpdateUserStatusUseCaseParams>,
+) -> Result<Response, Error>
+where
+    K: UpdateUserStatusUseCase + AuthenticateKernel,
+{
+    match UpdateUserStatusUseCase::execute(&kernel.0, &*admin_actor, params, &request_context).await
+    {
+        Ok(result) => Ok((StatusCode::OK, Json(UpdateUserStatusResponse(result))).into_response()),
+        Err(e) => Err(Error::from(e)),
+    }
+}
@@ -1,5 +1,5 @@
-use account::usecase::verify::{VerifyUseCase, VerifyUseCaseError, VerifyUseCaseResult};
-use anyhow::anyhow;
+use account::effect::rate_limit_store::IdentityRateLimit;
+use account::usecase::verify::{VerifyUseCase, VerifyUseCaseResult};
 use axum::extract::TypedHeader;
 use axum::headers;
 use axum::headers::authorization::Bearer;
@@ -9,30 +9,46 @@ use axum::Extension;
 use axum::Json;
 use derive_more::Constructor;
 use serde::Serialize;
+use utoipa::ToSchema;
 
 use crate::error::Error;
-use crate::error::{BadRequestKind, BadRequestPayload};
-use crate::kernel::Kernel;
+use crate::request_context::RequestContext;
 
-#[derive(Serialize, Constructor)]
+#[derive(Serialize, Constructor, ToSchema)]
 pub struct VerifyResponse(VerifyUseCaseResult);
 
+/// Resolves the account the bearer ID token belongs to.
+#[utoipa::path(
+    post,
+    path = "/verify",
+    tag = "auth",
+    operation_id = "verify",
+    security(("bearer" = [])),
+    responses(
+        (status = 201, description = "Token verified", body = VerifyResponse),
+        (status = 400, description = "`UserNotFound`, `UserDisabled` or `VerifyFailed`", body = Problem),
+        (status = 429, description = "Rate limited, see `Retry-After`", body = Problem),
+    )
+)]
 #[tracing::instrument(skip(kernel, authorization))]
-pub async fn verify_handler(
-    kernel: Extension<Kernel>,
+pub async fn verify_handler<K>(
+    kernel: Extension<K>,
+    request_context: RequestContext,
+    identity_limit: Option<Extension<IdentityRateLimit>>,
     TypedHeader(authorization): TypedHeader<headers::Authorization<Bearer>>,
-) -> Result<Response, Error> {
-    match kernel.execute(&authorization.token()).await {
+) -> Result<Response, Error>
+where
+    K: VerifyUseCase + Clone + Send + Sync + 'static,
+{
+    match kernel
+        .execute(
+            authorization.token(),
+            &request_context,
+            identity_limit.map(|Extension(limit)| limit),
+        )
+        .await
+    {
         Ok(result) => Ok((StatusCode::CREATED, Json(VerifyResponse::new(result))).into_response()),
-        Err(e) => Err(match e {
-            VerifyUseCaseError::UserNotFound(e) => {
-                Error::BadRequest(BadRequestPayload::new(BadRequestKind::UserNotFound, e))
-            }
-            VerifyUseCaseError::VerifyFailed(e) => Error::BadRequest(BadRequestPayload::new(
-                BadRequestKind::VerifyFailed,
-                e.to_string(),
-            )),
-            VerifyUseCaseError::FilterError(e) => Error::InternalServerError(anyhow!(e)),
-        }),
+        Err(e) => Err(Error::from(e)),
     }
 }
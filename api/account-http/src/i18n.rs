@@ -0,0 +1,176 @@
+//! Message catalogues of the problems this service reports, one per `Locale`, read from
+//! `locales/<locale>.toml` at the crate root.
+//!
+//! `middleware::problem` renders each problem in the locale the user stored in their profile,
+//! else in the first supported language of `Accept-Language`, else in `Locale::default()`.
+use account::model::profile::locale::Locale;
+use axum::http::StatusCode;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Parameters of a message, substituted for `{name}` in its template.
+pub type Args = Vec<(&'static str, String)>;
+
+#[derive(Debug, Deserialize)]
+struct ProblemText {
+    title: String,
+    detail: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Catalogue {
+    problem: HashMap<String, ProblemText>,
+    status: HashMap<String, String>,
+    validation: HashMap<String, HashMap<String, String>>,
+}
+
+fn fill(template: &str, args: &[(&'static str, String)]) -> String {
+    args.iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+impl Catalogue {
+    pub fn of(locale: Locale) -> &'static Catalogue {
+        static JA: OnceLock<Catalogue> = OnceLock::new();
+        static EN: OnceLock<Catalogue> = OnceLock::new();
+        match locale {
+            Locale::Ja => JA.get_or_init(|| parse(include_str!("../locales/ja.toml"))),
+            Locale::En => EN.get_or_init(|| parse(include_str!("../locales/en.toml"))),
+        }
+    }
+
+    /// Title of the problem type `urn:matsunoki:problem:<code>`.
+    pub fn problem_title(&self, code: &str) -> Option<&str> {
+        self.problem.get(code).map(|p| p.title.as_str())
+    }
+
+    pub fn problem_detail(&self, code: &str, args: &[(&'static str, String)]) -> Option<String> {
+        self.problem
+            .get(code)
+            .and_then(|p| p.detail.as_deref())
+            .map(|detail| fill(detail, args))
+    }
+
+    /// Title of the `about:blank` problem of `status`.
+    pub fn status_title(&self, status: StatusCode) -> Option<&str> {
+        self.status.get(status.as_str()).map(String::as_str)
+    }
+
+    /// Why the parameter `field` is invalid, for the validation failure `code`.
+    pub fn invalidity(
+        &self,
+        field: &str,
+        code: &str,
+        args: &[(&'static str, String)],
+    ) -> Option<String> {
+        self.validation
+            .get(field)
+            .and_then(|codes| codes.get(code))
+            .map(|reason| fill(reason, args))
+    }
+}
+
+fn parse(source: &str) -> Catalogue {
+    toml::from_str(source).expect("message catalogue is malformed")
+}
+
+/// The supported language the client prefers most in `Accept-Language` (RFC 9110 section 12.5.4).
+pub fn negotiate(accept_language: &str) -> Option<Locale> {
+    let mut ranges = accept_language
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            Some((tag, quality))
+        })
+        .filter(|(_, quality)| *quality > 0.0)
+        .collect::<Vec<_>>();
+    // Stable, so equally preferred languages keep the client's order.
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranges
+        .into_iter()
+        .find_map(|(tag, _)| Locale::from_language_tag(tag))
+}
+
+/// Id of the user the request authenticated as, whose stored locale `middleware::problem` looks
+/// up when the request fails. The middleware inserts an empty one into the request extensions
+/// and the actor extractors fill it in.
+#[derive(Debug, Clone, Default)]
+pub struct AuthenticatedUser(Arc<Mutex<Option<String>>>);
+
+impl AuthenticatedUser {
+    pub fn set(&self, user_id: String) {
+        *self.0.lock().unwrap() = Some(user_id);
+    }
+
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{negotiate, Catalogue};
+    use account::model::profile::locale::Locale;
+    use std::collections::BTreeSet;
+
+    fn keys(catalogue: &Catalogue) -> BTreeSet<String> {
+        let problems = catalogue.problem.iter().flat_map(|(code, text)| {
+            let detail = text
+                .detail
+                .as_ref()
+                .map(|_| format!("problem.{}.detail", code));
+            [Some(format!("problem.{}.title", code)), detail]
+        });
+        let statuses = catalogue
+            .status
+            .keys()
+            .map(|status| Some(format!("status.{}", status)));
+        let validations = catalogue.validation.iter().flat_map(|(field, codes)| {
+            codes
+                .keys()
+                .map(move |code| Some(format!("validation.{}.{}", field, code)))
+        });
+        problems
+            .chain(statuses)
+            .chain(validations)
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn catalogues_define_the_same_keys() {
+        assert_eq!(
+            keys(Catalogue::of(Locale::Ja)),
+            keys(Catalogue::of(Locale::En))
+        );
+    }
+
+    #[test]
+    fn invalidity_fill_parameters() {
+        let reason = Catalogue::of(Locale::En).invalidity(
+            "user_name",
+            "min_length",
+            &[("min", "2".to_string())],
+        );
+        assert_eq!(
+            reason.as_deref(),
+            Some("User name must be at least 2 characters.")
+        );
+    }
+
+    #[test]
+    fn negotiate_pick_most_preferred_supported_language() {
+        assert_eq!(negotiate("en-US,en;q=0.9,ja;q=0.8"), Some(Locale::En));
+        assert_eq!(negotiate("fr-FR, ja;q=0.5, en;q=0.7"), Some(Locale::En));
+        assert_eq!(negotiate("en;q=0, ja-JP"), Some(Locale::Ja));
+        assert_eq!(negotiate("fr, *;q=0.1"), None);
+        assert_eq!(negotiate(""), None);
+    }
+}
@@ -1,57 +1,104 @@
 use account::adapter::firebase_auth::HaveFirebaseAuthDriver;
+use account::effect::audit_log::HaveAuditLog;
+use account::effect::clock::{DefaultClock, HaveClock};
+use account::effect::config::HaveConfig;
 use account::effect::id_generator::HaveIdGenerator;
+use account::effect::rate_limit_store::HaveRateLimitStore;
+use account::effect::user_code_generator::HaveUserCodeGenerator;
+use account::effect::verified_token_cache::HaveVerifiedTokenCache;
+use account::repository::device_authorization_repository::HaveDeviceAuthorizationRepository;
+use account::repository::scoped_token_repository::HaveScopedTokenRepository;
 use account::repository::user_profile_repository::HaveUserProfileRepository;
 use account::repository::user_repository::HaveUserRepository;
 use account_driver::adapter::firebase_auth_adapter::DefaultFirebaseAuthAdapter;
+use account_driver::audit_log::{DefaultAuditLog, PostgresAuditLog, SqliteAuditLog};
 use account_driver::config::DefaultConfig;
-use account_driver::db_conn::build_conn;
+use account_driver::db_conn::{build_conn, build_replica, DBBackend, DBPool};
 use account_driver::id_generator::UUIDGenerator;
+use account_driver::metrics::PoolMetrics;
+use account_driver::migration;
+use account_driver::rate_limit_store::{
+    DefaultRateLimitStore, InMemoryRateLimitStore, PostgresRateLimitStore,
+};
+use account_driver::repository::default_repository::{
+    DefaultDeviceAuthorizationRepository, DefaultScopedTokenRepository,
+    DefaultUserProfileRepository, DefaultUserRepository,
+};
+use account_driver::repository::postgres_device_authorization_repository::PostgresDeviceAuthorizationRepository;
+use account_driver::repository::postgres_scoped_token_repository::PostgresScopedTokenRepository;
 use account_driver::repository::postgres_user_profile_repository::PostgresUserProfileRepository;
 use account_driver::repository::postgres_user_repository::PostgresUserRepository;
+use account_driver::repository::sqlite_device_authorization_repository::SqliteDeviceAuthorizationRepository;
+use account_driver::repository::sqlite_scoped_token_repository::SqliteScopedTokenRepository;
+use account_driver::repository::sqlite_user_profile_repository::SqliteUserProfileRepository;
+use account_driver::repository::sqlite_user_repository::SqliteUserRepository;
+use account_driver::user_code_generator::RandomUserCodeGenerator;
+use account_driver::verified_token_cache::LruVerifiedTokenCache;
 
-use derive_more::Deref;
-use std::collections::HashMap;
-use std::env::var;
+use crate::config::{AppConfig, RateLimitStoreKind};
+use crate::readiness::{check_database, check_jwks, check_migrations, Check, ReadinessKernel};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-#[derive(Debug, Deref)]
-pub struct HttpControllerConfig(DefaultConfig);
-
-impl Default for HttpControllerConfig {
-    fn default() -> Self {
-        Self(DefaultConfig {
-            firebase_project_id: var("ACCOUNT_FIREBASE_PROJECT_ID")
-                .expect("env ACCOUNT_FIREBASE_PROJECT_ID is not defined"),
-            max_connections: var("ACCOUNT_DB_MAX_CONNECTIONS")
-                .expect("env ACCOUNT_DB_MAX_CONNECTIONS is not defined")
-                .parse::<u32>()
-                .expect("env ACCOUNT_DB_MAX_CONNECTIONS is not numeric"),
-        })
-    }
-}
+/// How often expired rate limit buckets are deleted from Postgres.
+const RATE_LIMIT_SWEEP_INTERVAL: Duration = Duration::from_secs(10 * 60);
 
 #[derive(Clone)]
 pub struct Kernel {
-    user_repo: PostgresUserRepository,
-    user_profile_repo: PostgresUserProfileRepository,
+    config: DefaultConfig,
+    pool: DBPool,
+    replica: Option<DBPool>,
+    user_repo: DefaultUserRepository,
+    user_profile_repo: DefaultUserProfileRepository,
+    device_authorization_repo: DefaultDeviceAuthorizationRepository,
+    scoped_token_repo: DefaultScopedTokenRepository,
     firebase_auth_adapter: DefaultFirebaseAuthAdapter,
     id_generator: UUIDGenerator,
+    user_code_generator: RandomUserCodeGenerator,
+    clock: DefaultClock,
+    audit_log: DefaultAuditLog,
+    rate_limit_store: DefaultRateLimitStore,
+    verified_token_cache: LruVerifiedTokenCache,
+}
+
+impl HaveConfig for Kernel {
+    type Config = DefaultConfig;
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
 }
 
 impl HaveUserProfileRepository for Kernel {
-    type UserProfileRepository = PostgresUserProfileRepository;
+    type UserProfileRepository = DefaultUserProfileRepository;
     fn user_profile_repository(&self) -> &Self::UserProfileRepository {
         &self.user_profile_repo
     }
 }
 
 impl HaveUserRepository for Kernel {
-    type UserRepository = PostgresUserRepository;
+    type UserRepository = DefaultUserRepository;
     fn user_repository(&self) -> &Self::UserRepository {
         &self.user_repo
     }
 }
 
+impl HaveDeviceAuthorizationRepository for Kernel {
+    type DeviceAuthorizationRepository = DefaultDeviceAuthorizationRepository;
+    fn device_authorization_repository(&self) -> &Self::DeviceAuthorizationRepository {
+        &self.device_authorization_repo
+    }
+}
+
+impl HaveScopedTokenRepository for Kernel {
+    type ScopedTokenRepository = DefaultScopedTokenRepository;
+    fn scoped_token_repository(&self) -> &Self::ScopedTokenRepository {
+        &self.scoped_token_repo
+    }
+}
+
 impl HaveFirebaseAuthDriver for Kernel {
     type FirebaseAuthDriver = DefaultFirebaseAuthAdapter;
     fn firebase_auth(&self) -> &Self::FirebaseAuthDriver {
@@ -66,15 +113,175 @@ impl HaveIdGenerator for Kernel {
     }
 }
 
-pub async fn init() -> Kernel {
-    let config = HttpControllerConfig::default();
-    let pool = build_conn(&config.0).await;
+impl HaveUserCodeGenerator for Kernel {
+    type UserCodeGenerator = RandomUserCodeGenerator;
+    fn user_code_generator(&self) -> &Self::UserCodeGenerator {
+        &self.user_code_generator
+    }
+}
+
+impl HaveClock for Kernel {
+    type Clock = DefaultClock;
+    fn clock(&self) -> &Self::Clock {
+        &self.clock
+    }
+}
+
+impl HaveAuditLog for Kernel {
+    type AuditLog = DefaultAuditLog;
+    fn audit_log(&self) -> &Self::AuditLog {
+        &self.audit_log
+    }
+}
+
+impl HaveVerifiedTokenCache for Kernel {
+    type VerifiedTokenCache = LruVerifiedTokenCache;
+    fn verified_token_cache(&self) -> &Self::VerifiedTokenCache {
+        &self.verified_token_cache
+    }
+}
+
+impl HaveRateLimitStore for Kernel {
+    type RateLimitStore = DefaultRateLimitStore;
+    fn rate_limit_store(&self) -> &Self::RateLimitStore {
+        &self.rate_limit_store
+    }
+}
+
+impl Kernel {
+    /// Closes the database pools once the connections in use are released.
+    pub async fn close(&self) {
+        self.pool.close().await;
+        if let Some(replica) = &self.replica {
+            replica.close().await;
+        }
+    }
+}
+
+#[async_trait]
+impl ReadinessKernel for Kernel {
+    async fn check_readiness(&self) -> BTreeMap<String, Check> {
+        let replica = async {
+            match &self.replica {
+                Some(replica) => Some(check_database(replica).await),
+                None => None,
+            }
+        };
+        let (database, migrations, jwks, replica) = tokio::join!(
+            check_database(&self.pool),
+            check_migrations(&self.pool),
+            check_jwks(&self.firebase_auth_adapter),
+            replica,
+        );
+        let mut checks = BTreeMap::from([
+            ("database".to_string(), database),
+            ("migrations".to_string(), migrations),
+            ("jwks".to_string(), jwks),
+        ]);
+        if let Some(replica) = replica {
+            checks.insert("replica".to_string(), replica);
+        }
+        checks
+    }
+}
+
+pub async fn init(config: &AppConfig) -> anyhow::Result<Kernel> {
+    if config.database.backend == DBBackend::Sqlite && config.database.replica_url.is_some() {
+        return Err(anyhow!("Read replica requires the postgres backend"));
+    }
+    let pool = build_conn(&config.database)
+        .await
+        .context("Failed connect database")?;
+    let replica = build_replica(&config.database)
+        .await
+        .context("Failed connect read replica")?;
+    if config.migrate_on_start {
+        migration::run(&pool)
+            .await
+            .context("Failed migrate database")?;
+    }
+    prometheus::register(Box::new(PoolMetrics::new(
+        pool.clone(),
+        config.database.max_connections,
+    )))
+    .context("Failed register pool metrics")?;
+    let driver_config = DefaultConfig::new(
+        config.auth.firebase_project_id.clone(),
+        config.auth.device_verification_uri.clone(),
+    );
     let jwks_cache = Arc::new(Mutex::new(HashMap::new()));
+    let rate_limit_store = match (config.rate_limit.store, &pool) {
+        (RateLimitStoreKind::Postgres, DBPool::Postgres(pool)) => {
+            let store = PostgresRateLimitStore::new(pool.clone());
+            store.sweep_every(RATE_LIMIT_SWEEP_INTERVAL);
+            DefaultRateLimitStore::Postgres(store)
+        }
+        (RateLimitStoreKind::Postgres, DBPool::Sqlite(_)) => {
+            return Err(anyhow!(
+                "Postgres rate limit store requires the postgres backend"
+            ));
+        }
+        (RateLimitStoreKind::Memory, _) => {
+            DefaultRateLimitStore::InMemory(InMemoryRateLimitStore::default())
+        }
+    };
+    // User and profile reads go to the replica. Device codes, tokens and audit events are read
+    // right after being written by another request, so they stay on the primary.
+    let (user_repo, user_profile_repo, device_authorization_repo, scoped_token_repo, audit_log) =
+        match pool.clone() {
+            DBPool::Postgres(pool) => {
+                let replica = match &replica {
+                    Some(DBPool::Postgres(replica)) => replica.clone(),
+                    _ => pool.clone(),
+                };
+                (
+                    DefaultUserRepository::Postgres(
+                        PostgresUserRepository::new(pool.clone()).with_replica(replica.clone()),
+                    ),
+                    DefaultUserProfileRepository::Postgres(
+                        PostgresUserProfileRepository::new(pool.clone()).with_replica(replica),
+                    ),
+                    DefaultDeviceAuthorizationRepository::Postgres(
+                        PostgresDeviceAuthorizationRepository::new(pool.clone()),
+                    ),
+                    DefaultScopedTokenRepository::Postgres(PostgresScopedTokenRepository::new(
+                        pool.clone(),
+                    )),
+                    DefaultAuditLog::Postgres(PostgresAuditLog::new(pool)),
+                )
+            }
+            DBPool::Sqlite(pool) => (
+                DefaultUserRepository::Sqlite(SqliteUserRepository::new(pool.clone())),
+                DefaultUserProfileRepository::Sqlite(SqliteUserProfileRepository::new(
+                    pool.clone(),
+                )),
+                DefaultDeviceAuthorizationRepository::Sqlite(
+                    SqliteDeviceAuthorizationRepository::new(pool.clone()),
+                ),
+                DefaultScopedTokenRepository::Sqlite(SqliteScopedTokenRepository::new(
+                    pool.clone(),
+                )),
+                DefaultAuditLog::Sqlite(SqliteAuditLog::new(pool)),
+            ),
+        };
 
-    Kernel {
-        user_repo: PostgresUserRepository::new(pool.clone()),
-        user_profile_repo: PostgresUserProfileRepository::new(pool.clone()),
-        firebase_auth_adapter: DefaultFirebaseAuthAdapter::new(config.0.clone(), jwks_cache),
+    Ok(Kernel {
+        config: driver_config.clone(),
+        pool,
+        replica,
+        user_repo,
+        user_profile_repo,
+        device_authorization_repo,
+        scoped_token_repo,
+        firebase_auth_adapter: DefaultFirebaseAuthAdapter::new(driver_config, jwks_cache),
         id_generator: UUIDGenerator::new(),
-    }
+        user_code_generator: RandomUserCodeGenerator::new(),
+        clock: DefaultClock::new(),
+        audit_log,
+        rate_limit_store,
+        verified_token_cache: LruVerifiedTokenCache::new(
+            config.auth.token_cache_capacity,
+            config.auth.token_cache_ttl,
+        ),
+    })
 }
@@ -1,36 +1,199 @@
-use axum::http::{HeaderValue, Method};
+use account::effect::config::HaveConfig;
+use account::effect::rate_limit_store::HaveRateLimitStore;
+use account::model::scoped_token::PROFILE_SCOPE;
+use account::usecase::approve_device_code::ApproveDeviceCodeUseCase;
+use account::usecase::exchange_device_code::ExchangeDeviceCodeUseCase;
+use account::usecase::issue_device_code::IssueDeviceCodeUseCase;
+use account::usecase::list_audit_events::ListAuditEventsUseCase;
+use account::usecase::reset_user_name::ResetUserNameUseCase;
+use account::usecase::resolve_profile::ResolveProfileUseCase;
+use account::usecase::resolve_user::ResolveUserUseCase;
+use account::usecase::search_users::SearchUsersUseCase;
+use account::usecase::sign_up::SignUpUseCase;
+use account::usecase::update_profile::UpdateProfileUseCase;
+use account::usecase::update_role::UpdateRoleUseCase;
+use account::usecase::update_user_status::UpdateUserStatusUseCase;
+use actor::{AuthenticateKernel, RequiredScope};
+use axum::http::header::HeaderName;
+use axum::http::Method;
 use axum::routing::{get, post};
 use axum::Router;
-use kernel::Kernel;
+use config::ServerConfig;
+use middleware::metrics::MetricsLayer;
+use middleware::problem::ProblemLayer;
+use middleware::rate_limit::{RateLimitConfig, RateLimitLayer};
+use middleware::read_your_writes::{ReadYourWritesLayer, READ_PRIMARY_UNTIL};
+use middleware::trace::request_span;
+use readiness::ReadinessKernel;
+use request_context::TrustedProxies;
 use tower_http::add_extension::AddExtensionLayer;
 use tower_http::cors::{Any, CorsLayer};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::trace::TraceLayer;
 
 pub mod actor;
+pub mod config;
 pub mod error;
 pub mod handler;
+pub mod i18n;
 pub mod kernel;
+pub mod listener;
+pub mod metrics;
 pub mod middleware;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
+pub mod openapi;
+pub mod readiness;
+pub mod request_context;
+pub mod shutdown;
+pub mod telemetry;
+#[cfg(test)]
+mod tests;
 
-pub fn router(kernel: Kernel) -> Router {
+/// Every use case served by `router`. `kernel::Kernel` provides them over the configured
+/// database; any other type with the same `Have*` effects serves the same routes.
+pub trait AppKernel:
+    SignUpUseCase
+    + ResolveProfileUseCase
+    + UpdateProfileUseCase
+    + IssueDeviceCodeUseCase
+    + ApproveDeviceCodeUseCase
+    + ExchangeDeviceCodeUseCase
+    + ListAuditEventsUseCase
+    + ResolveUserUseCase
+    + UpdateRoleUseCase
+    + SearchUsersUseCase
+    + UpdateUserStatusUseCase
+    + ResetUserNameUseCase
+    + HaveConfig
+    + HaveRateLimitStore
+    + AuthenticateKernel
+    + ReadinessKernel
+{
+}
+impl<T> AppKernel for T where
+    T: SignUpUseCase
+        + ResolveProfileUseCase
+        + UpdateProfileUseCase
+        + IssueDeviceCodeUseCase
+        + ApproveDeviceCodeUseCase
+        + ExchangeDeviceCodeUseCase
+        + ListAuditEventsUseCase
+        + ResolveUserUseCase
+        + UpdateRoleUseCase
+        + SearchUsersUseCase
+        + UpdateUserStatusUseCase
+        + ResetUserNameUseCase
+        + HaveConfig
+        + HaveRateLimitStore
+        + AuthenticateKernel
+        + ReadinessKernel
+{
+}
+
+pub fn router<K: AppKernel>(
+    kernel: K,
+    rate_limit: RateLimitConfig,
+    server: &ServerConfig,
+) -> Router {
+    let rate_limit_store = kernel.rate_limit_store().clone();
+    let problem = ProblemLayer::new(kernel.clone());
     Router::new()
         .route("/hc", get(handler::health_check::health_check_handler))
-        .route("/sign_up", post(handler::sign_up::sign_up_handler))
-        .route("/verify", post(handler::verify::verify_handler))
+        .route("/livez", get(handler::health_check::livez_handler))
+        .route("/openapi.json", get(handler::api_doc::openapi_handler))
+        .route("/docs", get(handler::api_doc::docs_handler))
+        .route("/metrics", get(handler::metrics::metrics_handler))
+        .route(
+            "/sign_up",
+            post(handler::sign_up::sign_up_handler::<K>).layer(RateLimitLayer::new(
+                rate_limit_store.clone(),
+                "sign_up",
+                rate_limit.sign_up,
+            )),
+        )
+        .route(
+            "/verify",
+            post(handler::verify::verify_handler::<K>).layer(RateLimitLayer::new(
+                rate_limit_store.clone(),
+                "verify",
+                rate_limit.verify,
+            )),
+        )
         .route(
             "/resolve_profile",
-            get(handler::resolve_profile::resolve_profile_handler),
+            get(handler::resolve_profile::resolve_profile_handler::<K>)
+                .layer(AddExtensionLayer::new(RequiredScope(PROFILE_SCOPE))),
         )
         .route(
             "/update_profile",
-            post(handler::update_profile::update_profile_handler),
+            post(handler::update_profile::update_profile_handler::<K>)
+                .layer(AddExtensionLayer::new(RequiredScope(PROFILE_SCOPE))),
+        )
+        .route(
+            "/device/code",
+            post(handler::issue_device_code::issue_device_code_handler::<K>).layer(
+                RateLimitLayer::new(
+                    rate_limit_store.clone(),
+                    "device_code",
+                    rate_limit.device_code,
+                ),
+            ),
+        )
+        .route(
+            "/device/approve",
+            post(handler::approve_device_code::approve_device_code_handler::<K>),
+        )
+        .route(
+            "/device/token",
+            post(handler::exchange_device_code::exchange_device_code_handler::<K>).layer(
+                RateLimitLayer::new(rate_limit_store, "device_token", rate_limit.device_token),
+            ),
+        )
+        .route(
+            "/audit",
+            get(handler::list_audit_events::list_audit_events_handler::<K>),
+        )
+        .route(
+            "/admin/resolve_user",
+            get(handler::resolve_user::resolve_user_handler::<K>),
+        )
+        .route(
+            "/admin/update_role",
+            post(handler::update_role::update_role_handler::<K>),
+        )
+        .route(
+            "/admin/search_users",
+            get(handler::search_users::search_users_handler::<K>),
+        )
+        .route(
+            "/admin/update_user_status",
+            post(handler::update_user_status::update_user_status_handler::<K>),
+        )
+        .route(
+            "/admin/reset_user_name",
+            post(handler::reset_user_name::reset_user_name_handler::<K>),
+        )
+        .layer(AddExtensionLayer::new(kernel.clone()))
+        .layer(ReadYourWritesLayer)
+        .layer(problem)
+        // Added past `problem`, so that a failing probe still answers with its checks.
+        .route(
+            "/readyz",
+            get(handler::health_check::readyz_handler::<K>).layer(AddExtensionLayer::new(kernel)),
         )
-        .layer(AddExtensionLayer::new(kernel))
-        .layer(TraceLayer::new_for_http())
+        .layer(MetricsLayer)
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .layer(AddExtensionLayer::new(TrustedProxies::new(
+            server.trusted_proxies.clone(),
+        )))
         .layer(
             CorsLayer::new()
-                .allow_origin("http://localhost:3003".parse::<HeaderValue>().unwrap())
+                .allow_origin(server.cors_origins.clone())
                 .allow_methods([Method::GET, Method::POST])
-                .allow_headers(Any),
+                .allow_headers(Any)
+                .expose_headers([HeaderName::from_static("x-request-id"), READ_PRIMARY_UNTIL]),
         )
 }
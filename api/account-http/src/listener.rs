@@ -0,0 +1,331 @@
+//! Where the server accepts connections: plain TCP, TLS over TCP, or a Unix domain socket.
+//! Connections are accepted on a task of their own, so that a slow TLS handshake never holds
+//! up the next client.
+use crate::config::{ServerConfig, TlsConfig};
+use anyhow::Context;
+use axum::extract::connect_info::Connected;
+use std::convert::Infallible;
+use std::fs::File;
+use std::io::{self, BufReader, IoSlice};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context as TaskContext, Poll};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::rustls::{self, NoClientAuth};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, info, warn};
+
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+
+/// How often the certificate and key files are checked for a change.
+const RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Longest a client may take to complete the TLS handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause after a failed accept, e.g. when file descriptors run out, before trying again.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+pub enum Listener {
+    Tcp(TcpListener),
+    Tls(TcpListener, Certificates),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Listener {
+    /// Listens on `server.unix_socket` when set, otherwise on `server.bind_address`, over TLS
+    /// when `server.tls` is set. The certificate is reloaded whenever its files change.
+    pub async fn bind(server: &ServerConfig) -> anyhow::Result<Self> {
+        #[cfg(unix)]
+        if let Some(path) = &server.unix_socket {
+            remove_stale_socket(path)?;
+            let listener = UnixListener::bind(path)
+                .with_context(|| format!("Failed listen on {}", path.display()))?;
+            return Ok(Listener::Unix(listener));
+        }
+        let listener = TcpListener::bind(server.bind_address)
+            .await
+            .with_context(|| format!("Failed listen on {}", server.bind_address))?;
+        match &server.tls {
+            Some(tls) => {
+                let certificates = Certificates::load(tls)?;
+                certificates.watch(RELOAD_INTERVAL);
+                Ok(Listener::Tls(listener, certificates))
+            }
+            None => Ok(Listener::Tcp(listener)),
+        }
+    }
+
+    /// Where clients connect, for logs.
+    pub fn describe(&self) -> String {
+        let local = |listener: &TcpListener| {
+            listener
+                .local_addr()
+                .map(|addr| addr.to_string())
+                .unwrap_or_default()
+        };
+        match self {
+            Listener::Tcp(listener) => format!("http://{}", local(listener)),
+            Listener::Tls(listener, _) => format!("https://{}", local(listener)),
+            #[cfg(unix)]
+            Listener::Unix(listener) => listener
+                .local_addr()
+                .ok()
+                .and_then(|addr| addr.as_pathname().map(|p| format!("unix:{}", p.display())))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Accepts connections until the returned `Incoming` is dropped, which closes the listener.
+    pub fn incoming(self) -> Incoming {
+        let (sender, receiver) = mpsc::channel(32);
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = self.run(&sender) => {}
+                _ = sender.closed() => {}
+            }
+        });
+        Incoming(receiver)
+    }
+
+    async fn run(&self, connections: &mpsc::Sender<Connection>) {
+        loop {
+            if let Err(e) = self.accept(connections).await {
+                warn!("failed to accept a connection: {}", e);
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+            }
+        }
+    }
+
+    async fn accept(&self, connections: &mpsc::Sender<Connection>) -> io::Result<()> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, peer) = listener.accept().await?;
+                let _ = connections
+                    .send(Connection::new(Io::Tcp(stream), Some(peer)))
+                    .await;
+            }
+            Listener::Tls(listener, certificates) => {
+                let (stream, peer) = listener.accept().await?;
+                let acceptor = certificates.acceptor();
+                let connections = connections.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            let io = Io::Tls(Box::new(stream));
+                            let _ = connections.send(Connection::new(io, Some(peer))).await;
+                        }
+                        Ok(Err(e)) => debug!("TLS handshake with {} failed: {}", peer, e),
+                        Err(_) => debug!("TLS handshake with {} timed out", peer),
+                    }
+                });
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener) => {
+                let (stream, _) = listener.accept().await?;
+                let _ = connections
+                    .send(Connection::new(Io::Unix(stream), None))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A socket file left behind by a previous run would make `bind` fail.
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> anyhow::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)
+            .with_context(|| format!("Failed remove stale socket {}", path.display())),
+        _ => Ok(()),
+    }
+}
+
+/// Connections accepted by a `Listener`, served by hyper.
+pub struct Incoming(mpsc::Receiver<Connection>);
+
+impl hyper::server::accept::Accept for Incoming {
+    type Conn = Connection;
+    type Error = Infallible;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        self.get_mut().0.poll_recv(cx).map(|c| c.map(Ok))
+    }
+}
+
+enum Io {
+    Tcp(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+pub struct Connection {
+    io: Io,
+    peer: Option<SocketAddr>,
+}
+
+impl Connection {
+    fn new(io: Io, peer: Option<SocketAddr>) -> Self {
+        Connection { io, peer }
+    }
+}
+
+/// Address of the client, unknown over a Unix domain socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerAddr(pub Option<SocketAddr>);
+
+impl Connected<&Connection> for PeerAddr {
+    fn connect_info(connection: &Connection) -> Self {
+        PeerAddr(connection.peer)
+    }
+}
+
+macro_rules! delegate {
+    ($self:ident, $io:ident => $call:expr) => {
+        match &mut $self.get_mut().io {
+            Io::Tcp($io) => $call,
+            Io::Tls($io) => $call,
+            #[cfg(unix)]
+            Io::Unix($io) => $call,
+        }
+    };
+}
+
+impl AsyncRead for Connection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        delegate!(self, io => Pin::new(io).poll_read(cx, buf))
+    }
+}
+
+impl AsyncWrite for Connection {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        delegate!(self, io => Pin::new(io).poll_write(cx, buf))
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        delegate!(self, io => Pin::new(io).poll_write_vectored(cx, bufs))
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match &self.io {
+            Io::Tcp(io) => io.is_write_vectored(),
+            Io::Tls(io) => io.is_write_vectored(),
+            #[cfg(unix)]
+            Io::Unix(io) => io.is_write_vectored(),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        delegate!(self, io => Pin::new(io).poll_flush(cx))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        delegate!(self, io => Pin::new(io).poll_shutdown(cx))
+    }
+}
+
+/// Certificate chain and key served over TLS. A handshake uses the pair loaded last, so a
+/// renewed certificate is picked up without dropping a connection.
+#[derive(Clone)]
+pub struct Certificates {
+    tls: TlsConfig,
+    current: Arc<RwLock<Arc<rustls::ServerConfig>>>,
+}
+
+impl Certificates {
+    pub fn load(tls: &TlsConfig) -> anyhow::Result<Self> {
+        Ok(Certificates {
+            tls: tls.clone(),
+            current: Arc::new(RwLock::new(Arc::new(server_config(tls)?))),
+        })
+    }
+
+    fn acceptor(&self) -> TlsAcceptor {
+        TlsAcceptor::from(self.current.read().unwrap().clone())
+    }
+
+    /// Checks the files every `interval` and loads them again once either changed. A pair
+    /// that fails to load is logged and the one in use is kept.
+    pub fn watch(&self, interval: Duration) {
+        let certificates = self.clone();
+        tokio::spawn(async move {
+            let mut loaded = modified(&certificates.tls);
+            loop {
+                tokio::time::sleep(interval).await;
+                let seen = modified(&certificates.tls);
+                if seen == loaded {
+                    continue;
+                }
+                loaded = seen;
+                match server_config(&certificates.tls) {
+                    Ok(config) => {
+                        *certificates.current.write().unwrap() = Arc::new(config);
+                        info!("reloaded TLS certificate");
+                    }
+                    Err(e) => warn!("kept the TLS certificate in use: {:#}", e),
+                }
+            }
+        });
+    }
+}
+
+fn modified(tls: &TlsConfig) -> Option<(SystemTime, SystemTime)> {
+    let modified = |path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    Some((modified(&tls.cert_path)?, modified(&tls.key_path)?))
+}
+
+/// Reads a PEM certificate chain and a PKCS#8 or PKCS#1 (RSA) private key.
+fn server_config(tls: &TlsConfig) -> anyhow::Result<rustls::ServerConfig> {
+    let open = |path: &std::path::Path| {
+        File::open(path)
+            .map(BufReader::new)
+            .with_context(|| format!("Failed open {}", path.display()))
+    };
+    let certs = rustls_pemfile::certs(&mut open(&tls.cert_path)?)
+        .with_context(|| format!("Failed read {}", tls.cert_path.display()))?;
+    if certs.is_empty() {
+        anyhow::bail!("No certificate in {}", tls.cert_path.display());
+    }
+    let key = rustls_pemfile::read_all(&mut open(&tls.key_path)?)
+        .with_context(|| format!("Failed read {}", tls.key_path.display()))?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key) | rustls_pemfile::Item::RSAKey(key) => Some(key),
+            _ => None,
+        })
+        .with_context(|| format!("No private key in {}", tls.key_path.display()))?;
+    let mut config = rustls::ServerConfig::new(NoClientAuth::new());
+    config
+        .set_single_cert(
+            certs.into_iter().map(rustls::Certificate).collect(),
+            rustls::PrivateKey(key),
+        )
+        .context("Certificate does not match the private key")?;
+    Ok(config)
+}
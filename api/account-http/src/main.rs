@@ -1,26 +1,115 @@
-use account_http::kernel::{init, Kernel};
-use account_http::router;
-use std::net::SocketAddr;
-use tracing::info;
-use tracing_subscriber;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use account_driver::db_conn::{build_conn, DBConfig};
+use account_driver::migration::{self, MigrationState};
+use account_http::config::{AppConfig, Command, ConfigError, MigrateAction};
+use account_http::kernel::init;
+use account_http::listener::Listener;
+use account_http::{router, shutdown, telemetry};
+use anyhow::Context;
+use std::process::exit;
+use tracing::{error, info};
 
 #[tokio::main]
 #[tracing::instrument]
 async fn main() {
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "tower_http=debug".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-    let kernel = init().await;
-    info!("init kernel");
-    let app = router(kernel);
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
-    info!("listening on {}", addr);
-    axum::Server::bind(&addr)
-        .serve(app.into_make_service())
+    let command = match Command::load() {
+        Ok(c) => c,
+        Err(ConfigError::HelpRequested(usage)) => {
+            print!("{}", usage);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(2);
+        }
+    };
+    match command {
+        Command::Migrate(action, database) => {
+            tracing_subscriber::fmt()
+                .with_env_filter("info,sqlx=warn")
+                .init();
+            if let Err(e) = migrate(action, &database).await {
+                error!("failed to migrate: {:#}", e);
+                exit(1);
+            }
+        }
+        Command::Serve(config) => serve(*config).await,
+    }
+}
+
+async fn migrate(action: MigrateAction, database: &DBConfig) -> anyhow::Result<()> {
+    let pool = build_conn(database)
         .await
-        .unwrap();
+        .context("Failed connect database")?;
+    match action {
+        MigrateAction::Up => {
+            migration::run(&pool).await?;
+            info!("database is up to date");
+        }
+        MigrateAction::Status => {
+            for status in migration::status(&pool).await? {
+                let state = match status.state {
+                    MigrationState::Pending => "pending",
+                    MigrationState::Applied => "applied",
+                    MigrationState::Modified => "modified",
+                };
+                println!(
+                    "{:>14}  {:<8}  {}",
+                    status.version, state, status.description
+                );
+            }
+        }
+        MigrateAction::Baseline => {
+            if migration::baseline(&pool).await? {
+                info!(
+                    "recorded version {} as applied",
+                    migration::LEGACY_SCHEMA_VERSION
+                );
+            } else {
+                info!("database needs no baseline");
+            }
+        }
+        MigrateAction::Down { to } => {
+            let version = migration::rollback(&pool, to).await?;
+            info!("database rolled back to version {}", version);
+        }
+    }
+    Ok(())
+}
+
+async fn serve(config: AppConfig) {
+    if let Err(e) = telemetry::init(&config.log, &config.trace) {
+        eprintln!("failed to export traces: {}", e);
+        exit(1);
+    }
+    let kernel = match init(&config).await {
+        Ok(k) => k,
+        Err(e) => {
+            error!("failed to init kernel: {:#}", e);
+            exit(1);
+        }
+    };
+    info!("init kernel");
+    let app = router(kernel.clone(), config.rate_limit.routes, &config.server);
+    let listener = match Listener::bind(&config.server).await {
+        Ok(l) => l,
+        Err(e) => {
+            error!("failed to listen: {:#}", e);
+            exit(1);
+        }
+    };
+    info!("listening on {}", listener.describe());
+    let served = shutdown::serve(
+        listener,
+        app,
+        shutdown::signal(),
+        config.server.shutdown_timeout,
+    )
+    .await;
+    kernel.close().await;
+    info!("closed database pool");
+    telemetry::shutdown();
+    if let Err(e) = served {
+        error!("server failed: {:#}", e);
+        exit(1);
+    }
 }
@@ -0,0 +1,60 @@
+//! Prometheus metrics of the server. They share the default registry with those of
+//! `account_driver::metrics`, and are rendered together at `/metrics`.
+use account::usecase::sign_up::{SignUpUseCaseError, SignUpUseCaseResult};
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, Encoder, HistogramVec, IntCounterVec,
+    TextEncoder,
+};
+use std::sync::OnceLock;
+
+pub struct Metrics {
+    pub requests: IntCounterVec,
+    pub request_seconds: HistogramVec,
+    pub sign_ups: IntCounterVec,
+}
+
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics {
+        requests: register_int_counter_vec!(
+            "http_requests_total",
+            "Requests served, by the route they matched and their status.",
+            &["method", "route", "status"]
+        )
+        .unwrap(),
+        request_seconds: register_histogram_vec!(
+            "http_request_duration_seconds",
+            "Time serving a request took, by the route it matched and its status.",
+            &["method", "route", "status"]
+        )
+        .unwrap(),
+        sign_ups: register_int_counter_vec!(
+            "sign_ups_total",
+            "Sign ups, by `created` or the reason they failed.",
+            &["result"]
+        )
+        .unwrap(),
+    })
+}
+
+pub fn record_sign_up(result: &Result<SignUpUseCaseResult, SignUpUseCaseError>) {
+    let label = match result {
+        Ok(_) => "created",
+        Err(SignUpUseCaseError::AlreadyExist(_)) => "already_exist",
+        Err(SignUpUseCaseError::VerifyFailed(_)) => "verify_failed",
+        Err(SignUpUseCaseError::RateLimited { .. }) => "rate_limited",
+        Err(_) => "error",
+    };
+    metrics().sign_ups.with_label_values(&[label]).inc();
+}
+
+/// Every registered metric in the Prometheus text format.
+pub fn render() -> String {
+    metrics();
+    account_driver::metrics::register();
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .expect("text encoding of gathered metrics never fails");
+    String::from_utf8(buffer).expect("text format is UTF-8")
+}
@@ -1 +1,5 @@
-
+pub mod metrics;
+pub mod problem;
+pub mod rate_limit;
+pub mod read_your_writes;
+pub mod trace;
@@ -0,0 +1,71 @@
+use axum::extract::MatchedPath;
+use axum::http::Request;
+use axum::response::Response;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tower::{Layer, Service};
+
+use crate::metrics::metrics;
+
+/// Route label of the requests no route matched, so that unknown paths add no series.
+const UNMATCHED: &str = "unmatched";
+
+#[derive(Clone, Default)]
+pub struct MetricsLayer;
+
+impl<I> Layer<I> for MetricsLayer {
+    type Service = Metrics<I>;
+
+    fn layer(&self, inner: I) -> Self::Service {
+        Metrics { inner }
+    }
+}
+
+/// Counts and times requests by method, matched route and status. Added with
+/// `Router::layer`, it runs inside the router and sees the `MatchedPath` of the route.
+#[derive(Clone)]
+pub struct Metrics<I> {
+    inner: I,
+}
+
+impl<I, B> Service<Request<B>> for Metrics<I>
+where
+    I: Service<Request<B>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    I::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let method = req.method().to_string();
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_else(|| UNMATCHED.to_string());
+
+        Box::pin(async move {
+            let start = Instant::now();
+            let res = inner.call(req).await?;
+            let status = res.status().as_u16().to_string();
+            let labels = [method.as_str(), route.as_str(), status.as_str()];
+            metrics().requests.with_label_values(&labels).inc();
+            metrics()
+                .request_seconds
+                .with_label_values(&labels)
+                .observe(start.elapsed().as_secs_f64());
+            Ok(res)
+        })
+    }
+}
@@ -0,0 +1,157 @@
+use account::model::profile::locale::Locale;
+use account::model::user_profile::UserProfileId;
+use account::repository::meta::Repository;
+use account::repository::user_profile_repository::HaveUserProfileRepository;
+use axum::body::{Bytes, HttpBody};
+use axum::http::header::{ACCEPT_LANGUAGE, CONTENT_LANGUAGE, CONTENT_LENGTH};
+use axum::http::{HeaderValue, Request};
+use axum::response::{IntoResponse, Response};
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tower_http::request_id::RequestId;
+
+use crate::error::Problem;
+use crate::i18n::{negotiate, AuthenticatedUser};
+
+/// `type` of the problems converted from error responses not rendered by `Error`, whose
+/// status code says all there is to know (RFC 7807 section 4.2).
+const ABOUT_BLANK: &str = "about:blank";
+
+/// Reads the locale stored in the profile of the authenticated user from `kernel`.
+#[derive(Clone)]
+pub struct ProblemLayer<K> {
+    kernel: K,
+}
+
+impl<K> ProblemLayer<K> {
+    pub fn new(kernel: K) -> Self {
+        Self { kernel }
+    }
+}
+
+impl<I, K: Clone> Layer<I> for ProblemLayer<K> {
+    type Service = Problems<I, K>;
+
+    fn layer(&self, inner: I) -> Self::Service {
+        Problems {
+            inner,
+            kernel: self.kernel.clone(),
+        }
+    }
+}
+
+/// Completes every error response into problem details of the request: `instance` is the
+/// request path, `request_id` the id `SetRequestIdLayer` assigned, and the messages are
+/// rendered in the language of the user (see `i18n`), from `Accept-Language` alone for server
+/// errors.
+///
+/// Error responses that are not problems yet, such as the rejections of axum extractors and
+/// unknown routes, are converted into `about:blank` problems detailed by their original body.
+#[derive(Clone)]
+pub struct Problems<I, K> {
+    inner: I,
+    kernel: K,
+}
+
+async fn stored_locale<K: HaveUserProfileRepository>(
+    kernel: &K,
+    user_id: String,
+) -> Option<Locale> {
+    kernel
+        .user_profile_repository()
+        .resolve(&UserProfileId::new(user_id))
+        .await
+        .ok()
+        .flatten()
+        .and_then(|user_profile| user_profile.profile.locale)
+}
+
+async fn read_text<B: HttpBody<Data = Bytes> + Unpin>(mut body: B) -> String {
+    let mut bytes = Vec::new();
+    while let Some(Ok(chunk)) = body.data().await {
+        bytes.extend_from_slice(&chunk);
+    }
+    String::from_utf8_lossy(&bytes).trim().to_string()
+}
+
+impl<I, K, B> Service<Request<B>> for Problems<I, K>
+where
+    I: Service<Request<B>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    I::Future: Send + 'static,
+    K: HaveUserProfileRepository + Clone + Send + Sync + 'static,
+    B: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let kernel = self.kernel.clone();
+        let accepted = req
+            .headers()
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(negotiate);
+        let authenticated = AuthenticatedUser::default();
+        req.extensions_mut().insert(authenticated.clone());
+        let instance = req.uri().path().to_string();
+        let request_id = req
+            .extensions()
+            .get::<RequestId>()
+            .and_then(|id| id.header_value().to_str().ok())
+            .map(str::to_string);
+
+        Box::pin(async move {
+            let res = inner.call(req).await?;
+            let status = res.status();
+            if !status.is_client_error() && !status.is_server_error() {
+                return Ok(res);
+            }
+            let (mut parts, body) = res.into_parts();
+            let mut problem = match parts.extensions.remove::<Problem>() {
+                Some(problem) => problem,
+                None => {
+                    let detail = read_text(body).await;
+                    let mut problem = Problem::new(
+                        ABOUT_BLANK.to_string(),
+                        status.canonical_reason().unwrap_or_default(),
+                        status,
+                    );
+                    problem.detail = (!detail.is_empty()).then_some(detail);
+                    problem
+                }
+            };
+            problem.instance = Some(instance);
+            problem.request_id = request_id;
+            // A server error may come from the database being down, which would only make
+            // the client wait for the profile as well.
+            let stored = match authenticated.get() {
+                Some(user_id) if status.is_client_error() => stored_locale(&kernel, user_id).await,
+                _ => None,
+            };
+            let locale = stored.or(accepted).unwrap_or_default();
+            let mut res = problem.localize(locale).into_response();
+            res.headers_mut().insert(
+                CONTENT_LANGUAGE,
+                HeaderValue::from_str(&String::from(&locale)).unwrap(),
+            );
+            // Keep what the error response said besides its body, e.g. `Allow` or `Retry-After`.
+            let own = res.headers().clone();
+            for (name, value) in &parts.headers {
+                if !own.contains_key(name) && name != CONTENT_LENGTH {
+                    res.headers_mut().append(name, value.clone());
+                }
+            }
+            Ok(res)
+        })
+    }
+}
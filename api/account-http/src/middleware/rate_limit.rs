@@ -0,0 +1,235 @@
+use account::effect::rate_limit_store::{
+    hit, IdentityRateLimit, RateLimitDecision, RateLimitRule, RateLimitStore,
+};
+use axum::http::Request;
+use axum::response::{IntoResponse, Response};
+use derive_more::Constructor;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use time::OffsetDateTime;
+use tower::{Layer, Service};
+use tracing::debug;
+
+use crate::error::Error;
+use crate::request_context::client_ip;
+
+/// Buckets checked for one route. Each must admit the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Constructor)]
+pub struct RouteRateLimit {
+    pub per_ip: RateLimitRule,
+    /// Checked once the bearer token is verified, by the actor extractor of the route or, on
+    /// `/sign_up` and `/verify`, by their use case.
+    pub per_identity: Option<RateLimitRule>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Constructor)]
+pub struct RateLimitConfig {
+    pub sign_up: RouteRateLimit,
+    pub verify: RouteRateLimit,
+    pub device_code: RouteRateLimit,
+    pub device_token: RouteRateLimit,
+}
+
+#[derive(Clone, Constructor)]
+pub struct RateLimitLayer<S> {
+    store: S,
+    route: &'static str,
+    limit: RouteRateLimit,
+}
+
+impl<S: Clone, I> Layer<I> for RateLimitLayer<S> {
+    type Service = RateLimit<I, S>;
+
+    fn layer(&self, inner: I) -> Self::Service {
+        RateLimit {
+            inner,
+            store: self.store.clone(),
+            route: self.route,
+            limit: self.limit,
+        }
+    }
+}
+
+/// Rejects requests with `429 Too Many Requests` once a bucket is exhausted.
+#[derive(Clone)]
+pub struct RateLimit<I, S> {
+    inner: I,
+    store: S,
+    route: &'static str,
+    limit: RouteRateLimit,
+}
+
+impl<I, S, B> Service<Request<B>> for RateLimit<I, S>
+where
+    I: Service<Request<B>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    I::Future: Send + 'static,
+    S: RateLimitStore + Clone + Send + Sync + 'static,
+    B: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let store = self.store.clone();
+        let route = self.route;
+        let limit = self.limit;
+        let ip = client_ip(req.headers(), req.extensions());
+        // Left for whoever verifies who the user is.
+        if let Some(rule) = limit.per_identity {
+            req.extensions_mut()
+                .insert(IdentityRateLimit::new(route, rule));
+        }
+
+        Box::pin(async move {
+            // Without an address, e.g. behind a Unix socket proxy that sent none, there is no
+            // bucket to count in: sharing one would let a single client lock out all of them.
+            // The identity bucket still applies.
+            match ip {
+                Some(ip) => {
+                    let now = OffsetDateTime::now_utc().unix_timestamp();
+                    let key = format!("{}:ip:{}", route, ip);
+                    if let RateLimitDecision::Limited { retry_after } =
+                        hit(&store, &key, limit.per_ip, now).await
+                    {
+                        return Ok(Error::TooManyRequests { retry_after }.into_response());
+                    }
+                }
+                None => debug!(route, "no client address to rate limit by"),
+            }
+            inner.call(req).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RateLimitLayer, RouteRateLimit};
+    use crate::actor::UserActor;
+    use crate::listener::PeerAddr;
+    use crate::mock::MockKernel;
+    use crate::tests::token;
+    use account::effect::rate_limit_store::RateLimitRule;
+    use account_driver::rate_limit_store::InMemoryRateLimitStore;
+    use axum::body::Body;
+    use axum::extract::ConnectInfo;
+    use axum::http::header::{AUTHORIZATION, RETRY_AFTER};
+    use axum::http::{Request, StatusCode};
+    use axum::routing::post;
+    use axum::{Extension, Router};
+    use tower::ServiceExt;
+
+    fn request(peer: &str, forwarded_for: &str) -> Request<Body> {
+        let mut req = Request::post("/sign_up")
+            .header("x-forwarded-for", forwarded_for)
+            .body(Body::empty())
+            .unwrap();
+        req.extensions_mut().insert(ConnectInfo(PeerAddr(Some(
+            format!("{}:40000", peer).parse().unwrap(),
+        ))));
+        req
+    }
+
+    #[tokio::test]
+    async fn rate_limit_return_429_when_ip_bucket_exhausted() {
+        let app = Router::new().route(
+            "/sign_up",
+            post(|| async { "ok" }).layer(RateLimitLayer::new(
+                InMemoryRateLimitStore::default(),
+                "sign_up",
+                RouteRateLimit::new(RateLimitRule::new(1, 60), None),
+            )),
+        );
+
+        let first = app.clone().oneshot(request("10.0.0.1", "")).await.unwrap();
+        let second = app.clone().oneshot(request("10.0.0.1", "")).await.unwrap();
+        let forged = app
+            .clone()
+            .oneshot(request("10.0.0.1", "198.51.100.7"))
+            .await
+            .unwrap();
+        let other_ip = app.oneshot(request("10.0.0.2", "")).await.unwrap();
+
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(second.headers().contains_key(RETRY_AFTER));
+        assert_eq!(forged.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(other_ip.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_skip_ip_bucket_without_client_address() {
+        let app = Router::new().route(
+            "/sign_up",
+            post(|| async { "ok" }).layer(RateLimitLayer::new(
+                InMemoryRateLimitStore::default(),
+                "sign_up",
+                RouteRateLimit::new(RateLimitRule::new(1, 60), None),
+            )),
+        );
+        // Over the Unix domain socket, without a forwarded address.
+        let unix = || {
+            let mut req = Request::post("/sign_up").body(Body::empty()).unwrap();
+            req.extensions_mut().insert(ConnectInfo(PeerAddr(None)));
+            req
+        };
+        let without_peer = || Request::post("/sign_up").body(Body::empty()).unwrap();
+
+        let first = app.clone().oneshot(unix()).await.unwrap();
+        let second = app.clone().oneshot(unix()).await.unwrap();
+        let third = app.clone().oneshot(without_peer()).await.unwrap();
+        let fourth = app.oneshot(without_peer()).await.unwrap();
+
+        for res in [first, second, third, fourth] {
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limit_key_identity_bucket_on_verified_user() {
+        let kernel = MockKernel::new("http://localhost/device".to_string());
+        kernel.seed().await.unwrap();
+        let app = Router::new()
+            .route(
+                "/sign_up",
+                post(|_: UserActor<MockKernel>| async { "ok" }).layer(RateLimitLayer::new(
+                    InMemoryRateLimitStore::default(),
+                    "sign_up",
+                    RouteRateLimit::new(
+                        RateLimitRule::new(100, 60),
+                        Some(RateLimitRule::new(1, 60)),
+                    ),
+                )),
+            )
+            .layer(Extension(kernel));
+        let send = |peer: &str, bearer: String| {
+            let mut req = request(peer, "");
+            req.headers_mut()
+                .insert(AUTHORIZATION, format!("Bearer {}", bearer).parse().unwrap());
+            app.clone().oneshot(req)
+        };
+
+        let forged = send(
+            "10.0.0.1",
+            "header.eyJzdWIiOiJhbGljZSJ9.signature".to_string(),
+        )
+        .await
+        .unwrap();
+        let first = send("10.0.0.2", token("alice")).await.unwrap();
+        let second = send("10.0.0.3", token("alice")).await.unwrap();
+        let other_user = send("10.0.0.2", token("bob")).await.unwrap();
+
+        assert_eq!(forged.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(other_user.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,81 @@
+use account_driver::replica::{has_written, read_primary, track_writes};
+use axum::http::header::HeaderName;
+use axum::http::{HeaderMap, HeaderValue, Request, Response};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use time::OffsetDateTime;
+use tower::{Layer, Service};
+
+/// Unix time, in seconds, until which the reads of the client go to the primary. Set on the
+/// response of a request that wrote, and sent back by the client on the requests after.
+pub const READ_PRIMARY_UNTIL: HeaderName = HeaderName::from_static("x-read-primary-until");
+
+/// How long a client reads from the primary after its write, longer than the replica is
+/// expected to lag behind.
+const READ_PRIMARY_SECONDS: i64 = 5;
+
+#[derive(Clone, Default)]
+pub struct ReadYourWritesLayer;
+
+impl<I> Layer<I> for ReadYourWritesLayer {
+    type Service = ReadYourWrites<I>;
+
+    fn layer(&self, inner: I) -> Self::Service {
+        ReadYourWrites { inner }
+    }
+}
+
+/// Serves each request as a unit of work of `account_driver::replica`: its reads go to the
+/// read replica until it writes, and to the primary after, so that it sees what it wrote.
+///
+/// A request that wrote answers with `X-Read-Primary-Until`. A request carrying it reads from
+/// the primary until then, so that a client sees its writes in the requests that follow too.
+#[derive(Clone)]
+pub struct ReadYourWrites<I> {
+    inner: I,
+}
+
+/// Whether `headers` ask for the primary. Values further away than `READ_PRIMARY_SECONDS` are
+/// ignored, so that a client cannot pin its reads to the primary.
+fn asks_for_primary(headers: &HeaderMap, now: i64) -> bool {
+    headers
+        .get(&READ_PRIMARY_UNTIL)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<i64>().ok())
+        .is_some_and(|until| now < until && until <= now + READ_PRIMARY_SECONDS)
+}
+
+impl<I, B, ResBody> Service<Request<B>> for ReadYourWrites<I>
+where
+    I: Service<Request<B>, Response = Response<ResBody>>,
+    I::Future: Send + 'static,
+{
+    type Response = I::Response;
+    type Error = I::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<I::Response, I::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let asked = asks_for_primary(req.headers(), now);
+        let response = self.inner.call(req);
+        Box::pin(track_writes(async move {
+            let mut response = if asked {
+                read_primary(response).await?
+            } else {
+                response.await?
+            };
+            if has_written() {
+                response.headers_mut().insert(
+                    READ_PRIMARY_UNTIL,
+                    HeaderValue::from(now + READ_PRIMARY_SECONDS),
+                );
+            }
+            Ok(response)
+        }))
+    }
+}
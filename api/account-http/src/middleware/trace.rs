@@ -0,0 +1,31 @@
+use axum::http::Request;
+use opentelemetry::global;
+use opentelemetry_http::HeaderExtractor;
+use tower_http::request_id::RequestId;
+use tracing::field::Empty;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Span of a request, entered by `TraceLayer` around everything served for it, so that every
+/// use case and repository span below is correlated by the `X-Request-Id` set by
+/// `SetRequestIdLayer`. `user_id` is recorded once an actor extractor authenticates the request.
+/// When traces are exported, the span continues the trace named by `traceparent`.
+pub fn request_span<B>(req: &Request<B>) -> Span {
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .unwrap_or_default();
+    let span = tracing::info_span!(
+        "request",
+        method = %req.method(),
+        path = %req.uri().path(),
+        request_id,
+        user_id = Empty,
+    );
+    let parent = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(req.headers()))
+    });
+    span.set_parent(parent);
+    span
+}
@@ -0,0 +1,247 @@
+//! In-memory kernel served by `account-mock`, the API the frontend preview runs against.
+//!
+//! It routes through the same handlers as `account-http`, but keeps every row in process
+//! memory and accepts `fake:<uid>` in place of a Firebase ID token, so the preview needs no
+//! database nor auth service. `MockKernel::seed` stores these users, each signed in with
+//! `fake:<id>`:
+//!
+//! | id        | roles  | status   | profile |
+//! |-----------|--------|----------|---------|
+//! | `alice`   | Viewer | Active   | `alice` |
+//! | `bob`     | Viewer | Active   | none, as right after sign up |
+//! | `root`    | Admin  | Active   | `root`  |
+//! | `mallory` | Viewer | Disabled | `mallory` |
+use account::adapter::firebase_auth::HaveFirebaseAuthDriver;
+use account::effect::audit_log::HaveAuditLog;
+use account::effect::clock::{Clock, HaveClock};
+use account::effect::config::HaveConfig;
+use account::effect::id_generator::HaveIdGenerator;
+use account::effect::rate_limit_store::HaveRateLimitStore;
+use account::effect::user_code_generator::HaveUserCodeGenerator;
+use account::effect::verified_token_cache::HaveVerifiedTokenCache;
+use account::model::login_provider::{IdInProvider, LoginProvider, ProviderKind};
+use account::model::profile::avatar::Avatar;
+use account::model::profile::display_name::DisplayName;
+use account::model::profile::entity::Profile;
+use account::model::profile::user_name::UserName;
+use account::model::role::Role;
+use account::model::user::{User, UserId, UserStatus};
+use account::model::user_profile::{UserProfile, UserProfileId};
+use account::repository::device_authorization_repository::HaveDeviceAuthorizationRepository;
+use account::repository::scoped_token_repository::HaveScopedTokenRepository;
+use account::repository::user_profile_repository::{
+    HaveUserProfileRepository, UserProfileRepository,
+};
+use account::repository::user_repository::{HaveUserRepository, UserRepository};
+use account_driver::adapter::fake_firebase_auth_adapter::FakeFirebaseAuthAdapter;
+use account_driver::audit_log::InMemoryAuditLog;
+use account_driver::config::DefaultConfig;
+use account_driver::id_generator::UUIDGenerator;
+use account_driver::rate_limit_store::InMemoryRateLimitStore;
+use account_driver::repository::in_memory_database::InMemoryDatabase;
+use account_driver::repository::in_memory_device_authorization_repository::InMemoryDeviceAuthorizationRepository;
+use account_driver::repository::in_memory_scoped_token_repository::InMemoryScopedTokenRepository;
+use account_driver::repository::in_memory_user_profile_repository::InMemoryUserProfileRepository;
+use account_driver::repository::in_memory_user_repository::InMemoryUserRepository;
+use account_driver::user_code_generator::RandomUserCodeGenerator;
+use account_driver::verified_token_cache::LruVerifiedTokenCache;
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use time::{Duration, OffsetDateTime};
+
+use crate::readiness::{Check, ReadinessKernel};
+
+/// Wall clock that can be moved forward, e.g. past the expiry of a device code. Clones share
+/// the offset.
+#[derive(Debug, Clone, Default)]
+pub struct OffsetClock(Arc<Mutex<Duration>>);
+
+impl OffsetClock {
+    pub fn advance(&self, duration: Duration) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) += duration;
+    }
+}
+
+impl Clock for OffsetClock {
+    fn now_utc(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc() + *self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[derive(Clone)]
+pub struct MockKernel {
+    config: DefaultConfig,
+    user_repo: InMemoryUserRepository,
+    user_profile_repo: InMemoryUserProfileRepository,
+    device_authorization_repo: InMemoryDeviceAuthorizationRepository,
+    scoped_token_repo: InMemoryScopedTokenRepository,
+    firebase_auth_adapter: FakeFirebaseAuthAdapter,
+    id_generator: UUIDGenerator,
+    user_code_generator: RandomUserCodeGenerator,
+    clock: OffsetClock,
+    audit_log: InMemoryAuditLog,
+    rate_limit_store: InMemoryRateLimitStore,
+    verified_token_cache: LruVerifiedTokenCache,
+}
+
+impl MockKernel {
+    pub fn new(device_verification_uri: String) -> Self {
+        let db = InMemoryDatabase::default();
+        let config = DefaultConfig::new("account-mock".to_string(), device_verification_uri);
+        MockKernel {
+            config: config.clone(),
+            user_repo: InMemoryUserRepository::new(db.clone()),
+            user_profile_repo: InMemoryUserProfileRepository::new(db.clone()),
+            device_authorization_repo: InMemoryDeviceAuthorizationRepository::new(db.clone()),
+            scoped_token_repo: InMemoryScopedTokenRepository::new(db.clone()),
+            firebase_auth_adapter: FakeFirebaseAuthAdapter::new(config),
+            id_generator: UUIDGenerator::new(),
+            user_code_generator: RandomUserCodeGenerator::new(),
+            clock: OffsetClock::default(),
+            audit_log: InMemoryAuditLog::new(db),
+            rate_limit_store: InMemoryRateLimitStore::default(),
+            verified_token_cache: LruVerifiedTokenCache::new(
+                NonZeroUsize::new(1024).expect("capacity is not zero"),
+                Duration::minutes(1),
+            ),
+        }
+    }
+
+    /// Stores the users listed in the module documentation.
+    pub async fn seed(&self) -> anyhow::Result<()> {
+        self.seed_user(google_user("alice", Role::Viewer), Some("alice"))
+            .await?;
+        self.seed_user(google_user("bob", Role::Viewer), None)
+            .await?;
+        self.seed_user(google_user("root", Role::Admin), Some("root"))
+            .await?;
+        self.seed_user(
+            google_user("mallory", Role::Viewer).with_status(UserStatus::Disabled),
+            Some("mallory"),
+        )
+        .await
+    }
+
+    async fn seed_user(&self, user: User, name: Option<&str>) -> anyhow::Result<()> {
+        self.user_repo.store(&user).await?;
+        if let Some(name) = name {
+            let profile = UserProfile::new(
+                UserProfileId::new(user.id.0.clone()),
+                Profile::new(
+                    UserName(name.to_string()),
+                    DisplayName(name.to_string()),
+                    Avatar::new(format!("https://example.com/{}.png", name)),
+                    None,
+                ),
+            );
+            self.user_profile_repo.store(&profile).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Signs in as the Firebase user `id` through `FakeFirebaseAuthAdapter`.
+fn google_user(id: &str, role: Role) -> User {
+    User::new(
+        UserId::new(id.to_string()),
+        Some(vec![LoginProvider::new(
+            ProviderKind::Google,
+            IdInProvider::new(id.to_string()),
+        )]),
+    )
+    .with_roles(vec![role])
+}
+
+/// Every dependency is in memory, so there is nothing to check.
+#[async_trait]
+impl ReadinessKernel for MockKernel {
+    async fn check_readiness(&self) -> BTreeMap<String, Check> {
+        BTreeMap::new()
+    }
+}
+
+impl HaveConfig for MockKernel {
+    type Config = DefaultConfig;
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+}
+
+impl HaveUserRepository for MockKernel {
+    type UserRepository = InMemoryUserRepository;
+    fn user_repository(&self) -> &Self::UserRepository {
+        &self.user_repo
+    }
+}
+
+impl HaveUserProfileRepository for MockKernel {
+    type UserProfileRepository = InMemoryUserProfileRepository;
+    fn user_profile_repository(&self) -> &Self::UserProfileRepository {
+        &self.user_profile_repo
+    }
+}
+
+impl HaveDeviceAuthorizationRepository for MockKernel {
+    type DeviceAuthorizationRepository = InMemoryDeviceAuthorizationRepository;
+    fn device_authorization_repository(&self) -> &Self::DeviceAuthorizationRepository {
+        &self.device_authorization_repo
+    }
+}
+
+impl HaveScopedTokenRepository for MockKernel {
+    type ScopedTokenRepository = InMemoryScopedTokenRepository;
+    fn scoped_token_repository(&self) -> &Self::ScopedTokenRepository {
+        &self.scoped_token_repo
+    }
+}
+
+impl HaveFirebaseAuthDriver for MockKernel {
+    type FirebaseAuthDriver = FakeFirebaseAuthAdapter;
+    fn firebase_auth(&self) -> &Self::FirebaseAuthDriver {
+        &self.firebase_auth_adapter
+    }
+}
+
+impl HaveIdGenerator for MockKernel {
+    type IdGenerator = UUIDGenerator;
+    fn id_generator(&self) -> &Self::IdGenerator {
+        &self.id_generator
+    }
+}
+
+impl HaveUserCodeGenerator for MockKernel {
+    type UserCodeGenerator = RandomUserCodeGenerator;
+    fn user_code_generator(&self) -> &Self::UserCodeGenerator {
+        &self.user_code_generator
+    }
+}
+
+impl HaveClock for MockKernel {
+    type Clock = OffsetClock;
+    fn clock(&self) -> &Self::Clock {
+        &self.clock
+    }
+}
+
+impl HaveAuditLog for MockKernel {
+    type AuditLog = InMemoryAuditLog;
+    fn audit_log(&self) -> &Self::AuditLog {
+        &self.audit_log
+    }
+}
+
+impl HaveVerifiedTokenCache for MockKernel {
+    type VerifiedTokenCache = LruVerifiedTokenCache;
+    fn verified_token_cache(&self) -> &Self::VerifiedTokenCache {
+        &self.verified_token_cache
+    }
+}
+
+impl HaveRateLimitStore for MockKernel {
+    type RateLimitStore = InMemoryRateLimitStore;
+    fn rate_limit_store(&self) -> &Self::RateLimitStore {
+        &self.rate_limit_store
+    }
+}
@@ -0,0 +1,163 @@
+//! OpenAPI 3 description of the routes `router` serves, generated from the handler
+//! annotations and the schemas the payload types derive.
+//!
+//! `openapi.json` at the crate root is the committed copy the frontend generates its client
+//! from; `tests::openapi` fails when it no longer matches. Regenerate it with
+//! `UPDATE_OPENAPI=1 cargo test -p account-http openapi`.
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::{RefOr, Response};
+use utoipa::{Modify, OpenApi};
+
+use crate::error::{BadRequestKind, InvalidParam, OAuthErrorKind, Problem, PROBLEM_JSON};
+use crate::handler;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "account", description = "Account API of matsunoki."),
+    paths(
+        handler::health_check::health_check_handler,
+        handler::health_check::livez_handler,
+        handler::health_check::readyz_handler,
+        handler::sign_up::sign_up_handler,
+        handler::verify::verify_handler,
+        handler::resolve_profile::resolve_profile_handler,
+        handler::update_profile::update_profile_handler,
+        handler::issue_device_code::issue_device_code_handler,
+        handler::approve_device_code::approve_device_code_handler,
+        handler::exchange_device_code::exchange_device_code_handler,
+        handler::list_audit_events::list_audit_events_handler,
+        handler::resolve_user::resolve_user_handler,
+        handler::update_role::update_role_handler,
+        handler::search_users::search_users_handler,
+        handler::update_user_status::update_user_status_handler,
+        handler::reset_user_name::reset_user_name_handler,
+    ),
+    components(schemas(
+        Problem,
+        InvalidParam,
+        BadRequestKind,
+        OAuthErrorKind,
+        handler::health_check::HealthCheckResponse,
+        handler::health_check::LivenessResponse,
+        handler::health_check::ReadinessResponse,
+        crate::readiness::Check,
+        crate::readiness::CheckStatus,
+        handler::sign_up::SignUpResponse,
+        handler::verify::VerifyResponse,
+        handler::resolve_profile::ResolveProfileResponse,
+        handler::update_profile::UpdateProfileResponse,
+        handler::issue_device_code::IssueDeviceCodeResponse,
+        handler::exchange_device_code::ExchangeDeviceCodeResponse,
+        handler::list_audit_events::ListAuditEventsResponse,
+        handler::resolve_user::ResolveUserResponse,
+        handler::update_role::UpdateRoleResponse,
+        handler::search_users::SearchUsersResponse,
+        handler::update_user_status::UpdateUserStatusResponse,
+        handler::reset_user_name::ResetUserNameResponse,
+        handler::reset_user_name::ResetUserNameParams,
+        account::model::user::User,
+        account::model::user::UserId,
+        account::model::user::UserStatus,
+        account::model::login_provider::LoginProvider,
+        account::model::login_provider::ProviderKind,
+        account::model::login_provider::IdInProvider,
+        account::model::role::Role,
+        account::model::user_profile::UserProfile,
+        account::model::user_profile::UserProfileId,
+        account::model::profile::entity::Profile,
+        account::model::profile::user_name::UserName,
+        account::model::profile::display_name::DisplayName,
+        account::model::profile::avatar::Avatar,
+        account::model::profile::locale::Locale,
+        account::model::audit_event::AuditEvent,
+        account::model::audit_event::AuditEventId,
+        account::model::audit_event::AuditAction,
+        account::model::audit_event::AuditOutcome,
+        account::model::audit_event::AuditContext,
+        account::model::device_authorization::DeviceCode,
+        account::model::device_authorization::UserCode,
+        account::model::device_authorization::Scope,
+        account::model::scoped_token::ScopedTokenValue,
+        account::usecase::sign_up::SignUpUseCaseResult,
+        account::usecase::verify::VerifyUseCaseResult,
+        account::usecase::resolve_profile::ResolveProfileUseCaseResult,
+        account::usecase::update_profile::UpdateProfileUseCaseParams,
+        account::usecase::update_profile::UpdateProfileUseCaseResult,
+        account::usecase::issue_device_code::IssueDeviceCodeUseCaseParams,
+        account::usecase::issue_device_code::IssueDeviceCodeUseCaseResult,
+        account::usecase::approve_device_code::ApproveDeviceCodeUseCaseParams,
+        account::usecase::exchange_device_code::ExchangeDeviceCodeUseCaseParams,
+        account::usecase::exchange_device_code::ExchangeDeviceCodeUseCaseResult,
+        account::usecase::list_audit_events::ListAuditEventsUseCaseResult,
+        account::usecase::resolve_user::ResolveUserUseCaseResult,
+        account::usecase::update_role::UpdateRoleUseCaseParams,
+        account::usecase::update_role::UpdateRoleUseCaseResult,
+        account::usecase::search_users::SearchUsersUseCaseResult,
+        account::usecase::update_user_status::UpdateUserStatusUseCaseParams,
+        account::usecase::update_user_status::UpdateUserStatusUseCaseResult,
+        account::usecase::reset_user_name::ResetUserNameUseCaseResult,
+    )),
+    modifiers(&BearerAuth, &ProblemContentType),
+    tags(
+        (name = "auth", description = "Sign up and token verification"),
+        (name = "profile", description = "Profile of the signed in user"),
+        (name = "device", description = "Device authorization grant (RFC 8628)"),
+        (name = "audit", description = "Audit log"),
+        (name = "admin", description = "User management, admins only"),
+        (name = "health", description = "Build information, liveness and readiness probes"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Declares the `bearer` scheme the operations refer to: a Firebase ID token, or a scoped
+/// token issued through the device flow.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .description(Some(
+                            "Firebase ID token, or a scoped token from `/device/token`",
+                        ))
+                        .build(),
+                ),
+            );
+        }
+    }
+}
+
+/// Serves the `Problem` bodies of the error responses as `application/problem+json`, the media
+/// type `Problem::into_response` sets, instead of the `application/json` utoipa assumes.
+struct ProblemContentType;
+
+impl Modify for ProblemContentType {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let operations = openapi
+            .paths
+            .paths
+            .values_mut()
+            .flat_map(|item| item.operations.values_mut());
+        for operation in operations {
+            for response in operation.responses.responses.values_mut() {
+                if let RefOr::T(Response { content, .. }) = response {
+                    *content = content
+                        .drain(..)
+                        .map(|(media_type, body)| match is_problem(&body) {
+                            true => (PROBLEM_JSON.to_string(), body),
+                            false => (media_type, body),
+                        })
+                        .collect();
+                }
+            }
+        }
+    }
+}
+
+fn is_problem(content: &utoipa::openapi::Content) -> bool {
+    matches!(&content.schema, RefOr::Ref(r) if r.ref_location.ends_with("/Problem"))
+}
@@ -0,0 +1,109 @@
+//! Dependency checks behind `/readyz`. A kernel names the checks it needs; each reports `ok`,
+//! or `fail` with the reason.
+use account_driver::adapter::firebase_auth_adapter::DefaultFirebaseAuthAdapter;
+use account_driver::db_conn::{ping, DBPool};
+use account_driver::migration::{self, MigrationState};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// Longest a single check may take, below the timeout of an orchestrator probe.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Fail,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema, TS)]
+pub struct Check {
+    pub status: CheckStatus,
+    /// Why the check failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub detail: Option<String>,
+}
+
+impl Check {
+    pub fn ok() -> Self {
+        Check {
+            status: CheckStatus::Ok,
+            detail: None,
+        }
+    }
+
+    pub fn fail(detail: impl Display) -> Self {
+        Check {
+            status: CheckStatus::Fail,
+            detail: Some(detail.to_string()),
+        }
+    }
+
+    /// Runs `check`, failed when it errs or outlasts `CHECK_TIMEOUT`.
+    async fn run<E: Display>(check: impl Future<Output = Result<(), E>>) -> Self {
+        match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+            Ok(Ok(())) => Check::ok(),
+            Ok(Err(e)) => Check::fail(e),
+            Err(_) => Check::fail("timed out"),
+        }
+    }
+}
+
+#[async_trait]
+pub trait ReadinessKernel: Clone + Send + Sync + 'static {
+    /// Result of every check, by name.
+    async fn check_readiness(&self) -> BTreeMap<String, Check>;
+}
+
+/// Logs `error` and hides it behind `reason`: `/readyz` is not authenticated, and database
+/// errors may name hosts, users or tables.
+fn logged<E: Display>(check: &'static str, reason: &'static str) -> impl FnOnce(E) -> String {
+    move |error| {
+        warn!(check, %error, "readiness check failed");
+        reason.to_string()
+    }
+}
+
+/// Whether a connection can be acquired from `pool` and the database answers.
+pub async fn check_database(pool: &DBPool) -> Check {
+    Check::run(async {
+        ping(pool)
+            .await
+            .map_err(logged("database", "database is unavailable"))
+    })
+    .await
+}
+
+/// Whether every migration compiled into the binary is applied, unchanged.
+pub async fn check_migrations(pool: &DBPool) -> Check {
+    Check::run(async {
+        let status = migration::status(pool)
+            .await
+            .map_err(logged("migrations", "migration status is unavailable"))?;
+        let count = |state| status.iter().filter(|s| s.state == state).count();
+        match (
+            count(MigrationState::Pending),
+            count(MigrationState::Modified),
+        ) {
+            (0, 0) => Ok(()),
+            (pending, modified) => Err(format!(
+                "{} pending, {} modified migrations",
+                pending, modified
+            )),
+        }
+    })
+    .await
+}
+
+/// Whether the keys Firebase ID tokens are verified with are cached, or can be fetched.
+pub async fn check_jwks(adapter: &DefaultFirebaseAuthAdapter) -> Check {
+    Check::run(async { adapter.jwks().await.map(|_| ()) }).await
+}
@@ -0,0 +1,142 @@
+use crate::listener::PeerAddr;
+use account::model::audit_event::AuditContext;
+use async_trait::async_trait;
+use axum::extract::{ConnectInfo, FromRequest, RequestParts};
+use axum::http::header::USER_AGENT;
+use axum::http::{Extensions, HeaderMap};
+use derive_more::Deref;
+use ipnet::IpNet;
+use std::convert::Infallible;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+/// Client address and user agent of the current request, recorded with audit events.
+#[derive(Debug, Clone, Default, Deref)]
+pub struct RequestContext(pub AuditContext);
+
+/// Proxies whose `X-Forwarded-For` and `X-Real-IP` are believed, from `server.trusted_proxies`.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(Arc<Vec<IpNet>>);
+
+impl TrustedProxies {
+    pub fn new(proxies: Vec<IpNet>) -> Self {
+        Self(Arc::new(proxies))
+    }
+
+    fn contains(&self, ip: &IpAddr) -> bool {
+        self.0.iter().any(|net| net.contains(ip))
+    }
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
+/// Address of the client. It is the peer of the connection, unless the peer is a trusted proxy
+/// or connected over the Unix domain socket, which only local proxies reach. Then the
+/// `X-Forwarded-For` hops are read from the nearest, and the first one that is not a trusted
+/// proxy is the client; `X-Real-IP` is read when that header is missing. Anyone else sending
+/// either header cannot pick the address they are recorded and rate limited by.
+pub fn client_ip(headers: &HeaderMap, extensions: &Extensions) -> Option<String> {
+    let trusted = extensions
+        .get::<TrustedProxies>()
+        .cloned()
+        .unwrap_or_default();
+    let ConnectInfo(PeerAddr(peer)) = extensions.get::<ConnectInfo<PeerAddr>>()?;
+    let peer = peer.map(|addr| addr.ip());
+    if let Some(ip) = peer.filter(|ip| !trusted.contains(ip)) {
+        return Some(ip.to_string());
+    }
+    let forwarded = match header_value(headers, "x-forwarded-for") {
+        Some(hops) => hops
+            .rsplit(',')
+            .map(str::trim)
+            .filter_map(|hop| hop.parse::<IpAddr>().ok())
+            .find(|ip| !trusted.contains(ip)),
+        None => header_value(headers, "x-real-ip").and_then(|ip| ip.trim().parse().ok()),
+    };
+    forwarded.or(peer).map(|ip| ip.to_string())
+}
+
+#[async_trait]
+impl<B> FromRequest<B> for RequestContext
+where
+    B: Send,
+{
+    type Rejection = Infallible;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        Ok(RequestContext(AuditContext::new(
+            client_ip(req.headers(), req.extensions()),
+            header_value(req.headers(), USER_AGENT.as_str()),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{client_ip, TrustedProxies};
+    use crate::listener::PeerAddr;
+    use axum::extract::ConnectInfo;
+    use axum::http::{Extensions, HeaderMap};
+
+    fn resolve(peer: Option<&str>, headers: &[(&'static str, &str)]) -> Option<String> {
+        let mut extensions = Extensions::new();
+        extensions.insert(TrustedProxies::new(vec![
+            "10.0.0.0/8".parse().unwrap(),
+            "192.0.2.1/32".parse().unwrap(),
+        ]));
+        extensions.insert(ConnectInfo(PeerAddr(
+            peer.map(|ip| format!("{}:40000", ip).parse().unwrap()),
+        )));
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.insert(*name, value.parse().unwrap());
+        }
+        client_ip(&map, &extensions)
+    }
+
+    #[test]
+    fn client_ip_ignore_forwarded_headers_of_untrusted_peer() {
+        let forged = [
+            ("x-forwarded-for", "198.51.100.7"),
+            ("x-real-ip", "198.51.100.8"),
+        ];
+
+        assert_eq!(
+            resolve(Some("203.0.113.5"), &forged),
+            Some("203.0.113.5".to_string())
+        );
+    }
+
+    #[test]
+    fn client_ip_read_forwarded_hops_of_trusted_proxies_from_the_nearest() {
+        let hops = [("x-forwarded-for", "198.51.100.7, 203.0.113.9, 10.1.2.3")];
+
+        assert_eq!(
+            resolve(Some("192.0.2.1"), &hops),
+            Some("203.0.113.9".to_string())
+        );
+        assert_eq!(resolve(None, &hops), Some("203.0.113.9".to_string()));
+    }
+
+    #[test]
+    fn client_ip_read_real_ip_of_trusted_proxy_without_forwarded_for() {
+        assert_eq!(
+            resolve(Some("10.0.0.2"), &[("x-real-ip", "203.0.113.9")]),
+            Some("203.0.113.9".to_string())
+        );
+        assert_eq!(resolve(Some("10.0.0.2"), &[]), Some("10.0.0.2".to_string()));
+    }
+
+    #[test]
+    fn client_ip_is_none_without_connection() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "198.51.100.7".parse().unwrap());
+
+        assert_eq!(client_ip(&headers, &Extensions::new()), None);
+    }
+}
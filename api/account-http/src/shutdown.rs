@@ -0,0 +1,121 @@
+//! Graceful shutdown. On SIGTERM or SIGINT the server stops accepting connections and
+//! `/readyz` fails, then requests in flight get `server.shutdown_timeout` to finish.
+use crate::listener::{Listener, PeerAddr};
+use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
+use axum::Router;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{oneshot, watch};
+use tower_http::add_extension::AddExtensionLayer;
+use tracing::{info, warn};
+
+/// Set once shutdown begins, read by `/readyz` so that no new traffic is routed here.
+#[derive(Debug, Clone, Default)]
+pub struct Draining(Arc<AtomicBool>);
+
+impl Draining {
+    pub fn start(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Resolves on the first SIGTERM or SIGINT.
+pub async fn signal() {
+    let interrupt = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to listen for SIGINT");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = interrupt => info!("received SIGINT"),
+        _ = terminate => info!("received SIGTERM"),
+    }
+}
+
+/// Spawns the connections of the server on Tokio, and drops those still open once the drain
+/// deadline is sent.
+#[derive(Clone)]
+struct Connections(watch::Receiver<()>);
+
+impl<F> hyper::rt::Executor<F> for Connections
+where
+    F: Future + Send + 'static,
+{
+    fn execute(&self, connection: F) {
+        let mut deadline = self.0.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = connection => {}
+                _ = deadline.changed() => {}
+            }
+        });
+    }
+}
+
+fn make_service(
+    app: Router,
+    draining: &Draining,
+) -> IntoMakeServiceWithConnectInfo<Router, PeerAddr> {
+    app.layer(AddExtensionLayer::new(draining.clone()))
+        .into_make_service_with_connect_info::<PeerAddr>()
+}
+
+/// Serves `app` on `listener` until `shutdown` resolves, then waits at most `timeout` for the
+/// requests in flight before dropping them. Returns whether they all finished.
+pub async fn serve(
+    listener: Listener,
+    app: Router,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+    timeout: Duration,
+) -> anyhow::Result<bool> {
+    let draining = Draining::default();
+    let (started, drain_started) = oneshot::channel();
+    let on_shutdown = {
+        let draining = draining.clone();
+        async move {
+            shutdown.await;
+            draining.start();
+            info!("draining requests in flight for up to {:?}", timeout);
+            let _ = started.send(());
+        }
+    };
+    let (deadline, connections) = watch::channel(());
+    let server = axum::Server::builder(listener.incoming())
+        .executor(Connections(connections))
+        .serve(make_service(app, &draining))
+        .with_graceful_shutdown(on_shutdown);
+    tokio::pin!(server);
+    tokio::select! {
+        served = &mut server => {
+            served?;
+            return Ok(true);
+        }
+        _ = drain_started => {}
+    }
+    match tokio::time::timeout(timeout, server).await {
+        Ok(served) => {
+            served?;
+            Ok(true)
+        }
+        Err(_) => {
+            warn!("requests still in flight after {:?} are dropped", timeout);
+            let _ = deadline.send(());
+            Ok(false)
+        }
+    }
+}
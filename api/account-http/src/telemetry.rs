@@ -0,0 +1,85 @@
+//! Where spans go: the log written in `log.format`, and an OTLP collector when
+//! `trace.otlp_endpoint` is set.
+//!
+//! Exported traces follow a request across services through the W3C trace context:
+//! `middleware::trace` continues the trace of an incoming `traceparent` and
+//! `account_driver::trace_context` passes it on to the services called.
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::sdk::trace::{self as sdktrace, TracerProvider};
+use opentelemetry::sdk::Resource;
+use opentelemetry::trace::{TraceError, TracerProvider as _};
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::{SpanExporterBuilder, WithExportConfig};
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+use crate::config::{LogConfig, LogFormat, TraceConfig};
+
+/// Exports spans in batches to the OTLP/HTTP collector at `endpoint`, from the Tokio runtime.
+pub fn otlp_tracer_provider(
+    endpoint: &str,
+    service_name: &str,
+) -> Result<TracerProvider, TraceError> {
+    let exporter = SpanExporterBuilder::from(
+        opentelemetry_otlp::new_exporter()
+            .http()
+            .with_endpoint(format!("{}/v1/traces", endpoint)),
+    )
+    .build_span_exporter()?;
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry::runtime::Tokio)
+        .with_config(
+            sdktrace::config().with_resource(Resource::new([KeyValue::new(
+                "service.name",
+                service_name.to_string(),
+            )])),
+        )
+        .build())
+}
+
+/// Turns the spans of `provider` into OpenTelemetry spans. The log filter does not apply, so
+/// that use case and repository spans are exported whatever the log shows.
+pub fn otlp_layer<S>(provider: &TracerProvider) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let layer: OpenTelemetryLayer<S, _> =
+        tracing_opentelemetry::layer().with_tracer(provider.tracer("account-http"));
+    layer.with_filter(LevelFilter::INFO)
+}
+
+/// Installs the global subscriber. Without `trace.otlp_endpoint` neither the OpenTelemetry
+/// layer nor a propagator is installed, so spans cost no more than the log does.
+pub fn init(log: &LogConfig, trace: &TraceConfig) -> Result<(), TraceError> {
+    let otlp = match &trace.otlp_endpoint {
+        Some(endpoint) => {
+            let provider = otlp_tracer_provider(endpoint, &trace.service_name)?;
+            let layer = otlp_layer(&provider);
+            global::set_tracer_provider(provider);
+            global::set_text_map_propagator(TraceContextPropagator::new());
+            Some(layer)
+        }
+        None => None,
+    };
+    let filter = EnvFilter::new(&log.filter);
+    let registry = tracing_subscriber::registry().with(otlp);
+    match log.format {
+        LogFormat::Text => registry
+            .with(tracing_subscriber::fmt::layer().with_filter(filter))
+            .init(),
+        LogFormat::Json => registry
+            .with(tracing_subscriber::fmt::layer().json().with_filter(filter))
+            .init(),
+    }
+    Ok(())
+}
+
+/// Exports the spans still buffered. Call before the process exits.
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}
@@ -0,0 +1,223 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use super::{token, TestApp};
+
+#[tokio::test]
+async fn admin_routes_return_403_to_non_admin() {
+    let app = TestApp::new().await;
+    let alice = token("alice");
+    let responses = [
+        app.get("/admin/resolve_user?user_id=bob", Some(&alice))
+            .await,
+        app.get("/admin/search_users", Some(&alice)).await,
+        app.post_json(
+            "/admin/update_role",
+            Some(&alice),
+            json!({"user_id": "alice", "role": "Admin", "granted": true}),
+        )
+        .await,
+        app.post_json(
+            "/admin/update_user_status",
+            Some(&alice),
+            json!({"user_id": "bob", "disabled": true}),
+        )
+        .await,
+        app.post_json(
+            "/admin/reset_user_name",
+            Some(&alice),
+            json!({"user_id": "bob"}),
+        )
+        .await,
+    ];
+
+    for res in responses {
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+    }
+}
+
+#[tokio::test]
+async fn resolve_user_return_200_with_user_and_profile() {
+    let app = TestApp::new().await;
+    let res = app
+        .get("/admin/resolve_user?user_id=alice", Some(&token("root")))
+        .await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["user"]["id"], "alice");
+    assert_eq!(res.body["user_profile"]["profile"]["name"], "alice");
+}
+
+#[tokio::test]
+async fn resolve_user_return_404_when_not_exist() {
+    let app = TestApp::new().await;
+    let res = app
+        .get("/admin/resolve_user?user_id=carol", Some(&token("root")))
+        .await;
+
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn update_role_return_200_with_granted_role() {
+    let app = TestApp::new().await;
+    let res = app
+        .post_json(
+            "/admin/update_role",
+            Some(&token("root")),
+            json!({"user_id": "alice", "role": "Editor", "granted": true}),
+        )
+        .await;
+
+    let resolved = app
+        .get("/admin/resolve_user?user_id=alice", Some(&token("root")))
+        .await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(resolved.body["user"]["roles"], json!(["Editor", "Viewer"]));
+}
+
+#[tokio::test]
+async fn update_role_return_400_when_not_grantable() {
+    let app = TestApp::new().await;
+    let not_found = app
+        .post_json(
+            "/admin/update_role",
+            Some(&token("root")),
+            json!({"user_id": "carol", "role": "Editor", "granted": true}),
+        )
+        .await;
+    let self_demotion = app
+        .post_json(
+            "/admin/update_role",
+            Some(&token("root")),
+            json!({"user_id": "root", "role": "Admin", "granted": false}),
+        )
+        .await;
+
+    assert_eq!(not_found.status, StatusCode::BAD_REQUEST);
+    assert_eq!(not_found.body["kind"], "UserNotFound");
+    assert_eq!(self_demotion.status, StatusCode::BAD_REQUEST);
+    assert_eq!(self_demotion.body["kind"], "RoleGrantError");
+}
+
+#[tokio::test]
+async fn search_users_return_200_with_page() {
+    let app = TestApp::new().await;
+    let all = app.get("/admin/search_users", Some(&token("root"))).await;
+    let prefixed = app
+        .get(
+            "/admin/search_users?name_prefix=al&limit=10",
+            Some(&token("root")),
+        )
+        .await;
+
+    assert_eq!(all.status, StatusCode::OK);
+    assert_eq!(all.body["total"], 4);
+    assert_eq!(prefixed.body["total"], 1);
+    assert_eq!(prefixed.body["users"][0]["id"], "alice");
+    assert_eq!(prefixed.body["limit"], 10);
+}
+
+#[tokio::test]
+async fn update_user_status_return_200_and_lock_user_out() {
+    let app = TestApp::new().await;
+    let before = app.get("/resolve_profile", Some(&token("alice"))).await;
+    let res = app
+        .post_json(
+            "/admin/update_user_status",
+            Some(&token("root")),
+            json!({"user_id": "alice", "disabled": true}),
+        )
+        .await;
+    let after = app.get("/resolve_profile", Some(&token("alice"))).await;
+
+    assert_eq!(before.status, StatusCode::OK);
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["user"]["status"], "Disabled");
+    assert_eq!(after.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn update_user_status_return_400_when_not_manageable() {
+    let app = TestApp::new().await;
+    let not_found = app
+        .post_json(
+            "/admin/update_user_status",
+            Some(&token("root")),
+            json!({"user_id": "carol", "disabled": true}),
+        )
+        .await;
+    let self_disable = app
+        .post_json(
+            "/admin/update_user_status",
+            Some(&token("root")),
+            json!({"user_id": "root", "disabled": true}),
+        )
+        .await;
+
+    assert_eq!(not_found.status, StatusCode::BAD_REQUEST);
+    assert_eq!(not_found.body["kind"], "UserNotFound");
+    assert_eq!(self_disable.status, StatusCode::BAD_REQUEST);
+    assert_eq!(self_disable.body["kind"], "UserManageError");
+}
+
+#[tokio::test]
+async fn reset_user_name_return_200_with_placeholder_name() {
+    let app = TestApp::new().await;
+    let res = app
+        .post_json(
+            "/admin/reset_user_name",
+            Some(&token("root")),
+            json!({"user_id": "alice"}),
+        )
+        .await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_ne!(res.body["user_profile"]["profile"]["name"], "alice");
+}
+
+#[tokio::test]
+async fn reset_user_name_return_404_when_profile_not_exist() {
+    let app = TestApp::new().await;
+    let res = app
+        .post_json(
+            "/admin/reset_user_name",
+            Some(&token("root")),
+            json!({"user_id": "carol"}),
+        )
+        .await;
+
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn reset_user_name_return_200_to_editor() {
+    let app = TestApp::new().await;
+    let viewer = app
+        .post_json(
+            "/admin/reset_user_name",
+            Some(&token("alice")),
+            json!({"user_id": "root"}),
+        )
+        .await;
+    app.post_json(
+        "/admin/update_role",
+        Some(&token("root")),
+        json!({"user_id": "alice", "role": "Editor", "granted": true}),
+    )
+    .await;
+    let editor = app
+        .post_json(
+            "/admin/reset_user_name",
+            Some(&token("alice")),
+            json!({"user_id": "root"}),
+        )
+        .await;
+    let other_admin_route = app.get("/admin/search_users", Some(&token("alice"))).await;
+
+    assert_eq!(viewer.status, StatusCode::FORBIDDEN);
+    assert_eq!(editor.status, StatusCode::OK);
+    assert_ne!(editor.body["user_profile"]["profile"]["name"], "root");
+    assert_eq!(other_admin_route.status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,62 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use super::{token, TestApp};
+
+async fn update_own_profile(app: &TestApp, id: &str) {
+    let params = json!({
+        "user_name": id,
+        "display_name": id,
+        "avatar_url": "https://example.com",
+    });
+    let res = app
+        .post_json("/update_profile", Some(&token(id)), params)
+        .await;
+    assert_eq!(res.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn list_audit_events_return_own_events_to_user() {
+    let app = TestApp::new().await;
+    update_own_profile(&app, "alice").await;
+    update_own_profile(&app, "root").await;
+    let res = app
+        .get(
+            "/audit?actor_id=root&action=ProfileUpdated",
+            Some(&token("alice")),
+        )
+        .await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["total"], 1);
+    assert_eq!(res.body["events"][0]["actor"], "alice");
+}
+
+#[tokio::test]
+async fn list_audit_events_return_any_events_to_admin() {
+    let app = TestApp::new().await;
+    update_own_profile(&app, "alice").await;
+    update_own_profile(&app, "root").await;
+    let all = app
+        .get("/audit?action=ProfileUpdated", Some(&token("root")))
+        .await;
+    let filtered = app
+        .get(
+            "/audit?actor_id=alice&action=ProfileUpdated",
+            Some(&token("root")),
+        )
+        .await;
+
+    assert_eq!(all.status, StatusCode::OK);
+    assert_eq!(all.body["total"], 2);
+    assert_eq!(filtered.body["total"], 1);
+    assert_eq!(filtered.body["events"][0]["actor"], "alice");
+}
+
+#[tokio::test]
+async fn list_audit_events_return_401_without_token() {
+    let app = TestApp::new().await;
+    let res = app.get("/audit", None).await;
+
+    assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+}
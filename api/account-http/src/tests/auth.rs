@@ -0,0 +1,181 @@
+use account::effect::rate_limit_store::RateLimitRule;
+use account_driver::adapter::fake_firebase_auth_adapter::FakeFirebaseAuthAdapter;
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::header::{AUTHORIZATION, RETRY_AFTER};
+use axum::http::{Request, StatusCode};
+use serde_json::json;
+
+use super::{token, TestApp, TestResponse};
+use crate::listener::PeerAddr;
+use crate::middleware::rate_limit::{RateLimitConfig, RouteRateLimit};
+
+#[tokio::test]
+async fn health_check_return_200() {
+    let app = TestApp::new().await;
+    let res = app.get("/hc", None).await;
+
+    assert_eq!(res.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn sign_up_return_201_and_store_user() {
+    let app = TestApp::new().await;
+    let new_token = FakeFirebaseAuthAdapter::token("carol-uid");
+    let res = app.post_json("/sign_up", Some(&new_token), json!({})).await;
+    let verified = app.post_json("/verify", Some(&new_token), json!({})).await;
+
+    assert_eq!(res.status, StatusCode::CREATED);
+    assert_eq!(res.body["name"], "carol-uid");
+    assert_eq!(verified.status, StatusCode::CREATED);
+    assert_eq!(verified.body["user"]["id"], res.body["user_id"]);
+    assert_eq!(verified.body["user"]["roles"], json!(["Viewer"]));
+}
+
+#[tokio::test]
+async fn sign_up_return_400_when_already_exist() {
+    let app = TestApp::new().await;
+    let res = app
+        .post_json("/sign_up", Some(&token("alice")), json!({}))
+        .await;
+
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.body["kind"], "AlreadyExist");
+}
+
+#[tokio::test]
+async fn sign_up_return_400_when_verify_failed() {
+    let app = TestApp::new().await;
+    let res = app.post_json("/sign_up", Some("forged"), json!({})).await;
+
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.body["kind"], "VerifyFailed");
+}
+
+#[tokio::test]
+async fn sign_up_return_429_when_rate_limited() {
+    let limit = RouteRateLimit::new(RateLimitRule::new(1, 60), None);
+    let app = TestApp::with_rate_limit(RateLimitConfig::new(limit, limit, limit, limit)).await;
+    let new_token = FakeFirebaseAuthAdapter::token("carol-uid");
+    let first = app.post_json("/sign_up", Some(&new_token), json!({})).await;
+    let second = app.post_json("/sign_up", Some(&new_token), json!({})).await;
+
+    assert_eq!(first.status, StatusCode::CREATED);
+    assert_eq!(second.status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(second.headers.contains_key(RETRY_AFTER));
+    assert_eq!(
+        second.body["type"],
+        "urn:matsunoki:problem:too_many_requests"
+    );
+    assert_eq!(
+        second.body["retry_after"].to_string(),
+        second.headers[RETRY_AFTER].to_str().unwrap()
+    );
+}
+
+/// Allows 100 requests per IP and 1 per Firebase user on both `/sign_up` and `/verify`.
+async fn identity_limited_app() -> TestApp {
+    let limit = RouteRateLimit::new(RateLimitRule::new(100, 60), Some(RateLimitRule::new(1, 60)));
+    TestApp::with_rate_limit(RateLimitConfig::new(limit, limit, limit, limit)).await
+}
+
+async fn post_from(app: &TestApp, uri: &str, token: &str, peer: [u8; 4]) -> TestResponse {
+    let mut req = Request::post(uri)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    req.extensions_mut()
+        .insert(ConnectInfo(PeerAddr(Some((peer, 40000).into()))));
+    app.send(req).await
+}
+
+#[tokio::test]
+async fn sign_up_return_429_when_identity_rate_limited_from_any_ip() {
+    let app = identity_limited_app().await;
+    let carol = FakeFirebaseAuthAdapter::token("carol-uid");
+    let forged = post_from(&app, "/sign_up", "forged", [10, 0, 0, 1]).await;
+    let first = post_from(&app, "/sign_up", &carol, [10, 0, 0, 1]).await;
+    let second = post_from(&app, "/sign_up", &carol, [10, 0, 0, 2]).await;
+    let other_user = post_from(
+        &app,
+        "/sign_up",
+        &FakeFirebaseAuthAdapter::token("dave-uid"),
+        [10, 0, 0, 2],
+    )
+    .await;
+
+    assert_eq!(forged.status, StatusCode::BAD_REQUEST);
+    assert_eq!(first.status, StatusCode::CREATED);
+    assert_eq!(second.status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(second.headers.contains_key(RETRY_AFTER));
+    assert_eq!(other_user.status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn verify_return_429_when_identity_rate_limited_from_any_ip() {
+    let app = identity_limited_app().await;
+    let first = post_from(&app, "/verify", &token("alice"), [10, 0, 0, 1]).await;
+    let second = post_from(&app, "/verify", &token("alice"), [10, 0, 0, 2]).await;
+    let other_user = post_from(&app, "/verify", &token("bob"), [10, 0, 0, 2]).await;
+
+    assert_eq!(first.status, StatusCode::CREATED);
+    assert_eq!(second.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        second.body["type"],
+        "urn:matsunoki:problem:too_many_requests"
+    );
+    assert_eq!(other_user.status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn verify_return_201_with_user() {
+    let app = TestApp::new().await;
+    let res = app
+        .post_json("/verify", Some(&token("alice")), json!({}))
+        .await;
+
+    assert_eq!(res.status, StatusCode::CREATED);
+    assert_eq!(res.body["user"]["id"], "alice");
+}
+
+#[tokio::test]
+async fn verify_return_400_when_failed() {
+    let app = TestApp::new().await;
+    let not_found = app
+        .post_json("/verify", Some(&token("carol")), json!({}))
+        .await;
+    let disabled = app
+        .post_json("/verify", Some(&token("mallory")), json!({}))
+        .await;
+    let forged = app.post_json("/verify", Some("forged"), json!({})).await;
+
+    assert_eq!(not_found.status, StatusCode::BAD_REQUEST);
+    assert_eq!(not_found.body["kind"], "UserNotFound");
+    assert_eq!(disabled.status, StatusCode::BAD_REQUEST);
+    assert_eq!(disabled.body["kind"], "UserDisabled");
+    assert_eq!(forged.status, StatusCode::BAD_REQUEST);
+    assert_eq!(forged.body["kind"], "VerifyFailed");
+}
+
+#[tokio::test]
+async fn actor_return_401_when_token_missing_or_invalid() {
+    let app = TestApp::new().await;
+    let missing = app.get("/resolve_profile", None).await;
+    let forged = app.get("/resolve_profile", Some("forged")).await;
+    let unknown = app.get("/resolve_profile", Some(&token("carol"))).await;
+    let unknown_scoped = app.get("/resolve_profile", Some("mtk_unknown")).await;
+
+    for res in [missing, forged, unknown, unknown_scoped] {
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(res.body["type"], "urn:matsunoki:problem:unauthorized");
+    }
+}
+
+#[tokio::test]
+async fn actor_return_403_when_user_disabled() {
+    let app = TestApp::new().await;
+    let res = app.get("/resolve_profile", Some(&token("mallory"))).await;
+
+    assert_eq!(res.status, StatusCode::FORBIDDEN);
+    assert_eq!(res.body["type"], "urn:matsunoki:problem:forbidden");
+}
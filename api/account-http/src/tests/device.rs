@@ -0,0 +1,218 @@
+use account::effect::clock::HaveClock;
+use account::effect::rate_limit_store::RateLimitRule;
+use account::model::device_authorization::DEVICE_CODE_GRANT_TYPE;
+use axum::http::header::{CACHE_CONTROL, RETRY_AFTER};
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use time::Duration;
+
+use super::{token, TestApp, TestResponse};
+use crate::middleware::rate_limit::{RateLimitConfig, RouteRateLimit};
+
+async fn issue(app: &TestApp) -> Value {
+    issue_with_scope(app, "profile").await
+}
+
+async fn issue_with_scope(app: &TestApp, scope: &str) -> Value {
+    let res = app
+        .post_form("/device/code", &format!("client_id=cli&scope={}", scope))
+        .await;
+    assert_eq!(res.status, StatusCode::OK);
+    res.body
+}
+
+async fn approve(app: &TestApp, user_code: &Value, approved: bool) -> TestResponse {
+    app.post_json(
+        "/device/approve",
+        Some(&token("alice")),
+        json!({"user_code": user_code, "approved": approved}),
+    )
+    .await
+}
+
+async fn exchange(app: &TestApp, grant_type: &str, device_code: &str) -> TestResponse {
+    app.post_form(
+        "/device/token",
+        &format!(
+            "grant_type={}&device_code={}&client_id=cli",
+            grant_type, device_code
+        ),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn issue_device_code_return_200_with_verification_uri() {
+    let app = TestApp::new().await;
+    let issued = issue(&app).await;
+
+    assert_eq!(issued["verification_uri"], "http://localhost/device");
+    assert_eq!(
+        issued["verification_uri_complete"],
+        format!(
+            "http://localhost/device?user_code={}",
+            issued["user_code"].as_str().unwrap()
+        )
+    );
+    assert_eq!(issued["expires_in"], 600);
+    assert_eq!(issued["interval"], 5);
+}
+
+#[tokio::test]
+async fn device_routes_return_429_when_ip_bucket_exhausted() {
+    let open = RouteRateLimit::new(RateLimitRule::new(100, 60), None);
+    let once = RouteRateLimit::new(RateLimitRule::new(1, 60), None);
+    let app = TestApp::with_rate_limit(RateLimitConfig::new(open, open, once, once)).await;
+    let issued = issue(&app).await;
+    let second_code = app
+        .post_form("/device/code", "client_id=cli&scope=profile")
+        .await;
+    let device_code = issued["device_code"].as_str().unwrap();
+    let first_poll = exchange(&app, DEVICE_CODE_GRANT_TYPE, device_code).await;
+    let second_poll = exchange(&app, DEVICE_CODE_GRANT_TYPE, device_code).await;
+
+    assert_eq!(second_code.status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(second_code.headers.contains_key(RETRY_AFTER));
+    assert_eq!(first_poll.status, StatusCode::BAD_REQUEST);
+    assert_eq!(second_poll.status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn device_flow_issue_scoped_token_usable_as_bearer() {
+    let app = TestApp::new().await;
+    let issued = issue(&app).await;
+    let approved = approve(&app, &issued["user_code"], true).await;
+    let res = exchange(
+        &app,
+        DEVICE_CODE_GRANT_TYPE,
+        issued["device_code"].as_str().unwrap(),
+    )
+    .await;
+    let access_token = res.body["access_token"].as_str().unwrap();
+    let profile = app.get("/resolve_profile", Some(access_token)).await;
+
+    assert_eq!(approved.status, StatusCode::NO_CONTENT);
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers[CACHE_CONTROL], "no-store");
+    assert_eq!(res.body["token_type"], "Bearer");
+    assert_eq!(res.body["scope"], "profile");
+    assert_eq!(profile.status, StatusCode::OK);
+    assert_eq!(profile.body["user_profile"]["id"], "alice");
+}
+
+/// Runs the device flow for `alice` and returns the scoped token granted `scope`.
+async fn scoped_token(app: &TestApp, scope: &str) -> String {
+    let issued = issue_with_scope(app, scope).await;
+    approve(app, &issued["user_code"], true).await;
+    let res = exchange(
+        app,
+        DEVICE_CODE_GRANT_TYPE,
+        issued["device_code"].as_str().unwrap(),
+    )
+    .await;
+    res.body["access_token"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn scoped_token_return_403_outside_its_scope() {
+    let app = TestApp::new().await;
+    let profile = scoped_token(&app, "profile").await;
+    let unscoped = scoped_token(&app, "").await;
+    let approve = app
+        .post_json(
+            "/device/approve",
+            Some(&profile),
+            json!({"user_code": "XXXX-XXXX", "approved": true}),
+        )
+        .await;
+    let audit = app.get("/audit", Some(&profile)).await;
+    let admin = app.get("/admin/search_users", Some(&profile)).await;
+    let unscoped_profile = app.get("/resolve_profile", Some(&unscoped)).await;
+    let scoped_profile = app.get("/resolve_profile", Some(&profile)).await;
+
+    for res in [approve, audit, admin, unscoped_profile] {
+        assert_eq!(res.status, StatusCode::FORBIDDEN);
+        assert_eq!(res.body["type"], "urn:matsunoki:problem:insufficient_scope");
+    }
+    assert_eq!(scoped_profile.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn approve_device_code_return_400_when_not_approvable() {
+    let app = TestApp::new().await;
+    let issued = issue(&app).await;
+    let unknown = approve(&app, &json!("XXXX-XXXX"), true).await;
+    let _ = approve(&app, &issued["user_code"], false).await;
+    let processed = approve(&app, &issued["user_code"], true).await;
+    let expiring = issue(&app).await;
+    app.kernel.clock().advance(Duration::seconds(600));
+    let expired = approve(&app, &expiring["user_code"], true).await;
+
+    assert_eq!(unknown.status, StatusCode::BAD_REQUEST);
+    assert_eq!(unknown.body["kind"], "DeviceCodeNotFound");
+    assert_eq!(processed.status, StatusCode::BAD_REQUEST);
+    assert_eq!(processed.body["kind"], "DeviceCodeAlreadyProcessed");
+    assert_eq!(expired.status, StatusCode::BAD_REQUEST);
+    assert_eq!(expired.body["kind"], "DeviceCodeExpired");
+}
+
+#[tokio::test]
+async fn approve_device_code_return_401_without_token() {
+    let app = TestApp::new().await;
+    let res = app
+        .post_json(
+            "/device/approve",
+            None,
+            json!({"user_code": "XXXX-XXXX", "approved": true}),
+        )
+        .await;
+
+    assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn exchange_device_code_return_oauth_errors() {
+    let app = TestApp::new().await;
+    let issued = issue(&app).await;
+    let device_code = issued["device_code"].as_str().unwrap();
+    let pending = exchange(&app, DEVICE_CODE_GRANT_TYPE, device_code).await;
+    let slow_down = exchange(&app, DEVICE_CODE_GRANT_TYPE, device_code).await;
+    let _ = approve(&app, &issued["user_code"], false).await;
+    app.kernel.clock().advance(Duration::seconds(10));
+    let denied = exchange(&app, DEVICE_CODE_GRANT_TYPE, device_code).await;
+    app.kernel.clock().advance(Duration::seconds(600));
+    let expired = exchange(&app, DEVICE_CODE_GRANT_TYPE, device_code).await;
+    let unknown = exchange(&app, DEVICE_CODE_GRANT_TYPE, "unknown").await;
+    let unsupported = exchange(&app, "password", device_code).await;
+
+    for (res, error) in [
+        (pending, "authorization_pending"),
+        (slow_down, "slow_down"),
+        (denied, "access_denied"),
+        (expired, "expired_token"),
+        (unknown, "invalid_grant"),
+        (unsupported, "unsupported_grant_type"),
+    ] {
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        assert_eq!(res.body["error"], error);
+        assert_eq!(
+            res.body["type"],
+            format!("urn:matsunoki:problem:{}", error).as_str()
+        );
+    }
+}
+
+#[tokio::test]
+async fn exchange_device_code_return_invalid_grant_once_consumed() {
+    let app = TestApp::new().await;
+    let issued = issue(&app).await;
+    let device_code = issued["device_code"].as_str().unwrap();
+    let _ = approve(&app, &issued["user_code"], true).await;
+    let first = exchange(&app, DEVICE_CODE_GRANT_TYPE, device_code).await;
+    app.kernel.clock().advance(Duration::seconds(10));
+    let second = exchange(&app, DEVICE_CODE_GRANT_TYPE, device_code).await;
+
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(second.status, StatusCode::BAD_REQUEST);
+    assert_eq!(second.body["error"], "invalid_grant");
+}
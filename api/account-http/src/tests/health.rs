@@ -0,0 +1,116 @@
+use account_driver::adapter::firebase_auth_adapter::DefaultFirebaseAuthAdapter;
+use account_driver::config::DefaultConfig;
+use account_driver::db_conn::{build_conn, ConnectRetry, DBBackend, DBConfig, DBPool, DBTimeouts};
+use account_driver::migration;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::TestApp;
+use crate::handler::health_check::ReadinessResponse;
+use crate::readiness::{check_database, check_jwks, check_migrations, Check, CheckStatus};
+
+async fn sqlite_pool() -> DBPool {
+    let config = DBConfig::new(
+        DBBackend::Sqlite,
+        "sqlite::memory:".to_string(),
+        None,
+        1,
+        1,
+        DBTimeouts::new(Duration::from_secs(1), None, None, None),
+        ConnectRetry::new(0, Duration::ZERO, Duration::ZERO),
+    );
+    build_conn(&config).await.unwrap()
+}
+
+#[tokio::test]
+async fn health_check_report_crate_version() {
+    let app = TestApp::new().await;
+    let res = app.get("/hc", None).await;
+
+    assert_eq!(res.body["version"], env!("CARGO_PKG_VERSION"));
+}
+
+#[tokio::test]
+async fn livez_return_200() {
+    let app = TestApp::new().await;
+    let res = app.get("/livez", None).await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body, json!({"status": "ok"}));
+}
+
+#[tokio::test]
+async fn readyz_return_200_when_nothing_fails() {
+    let app = TestApp::new().await;
+    let res = app.get("/readyz", None).await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body, json!({"status": "ok", "checks": {}}));
+}
+
+#[test]
+fn readiness_return_503_with_detail_of_failed_check() {
+    let checks = BTreeMap::from([
+        ("database".to_string(), Check::ok()),
+        ("jwks".to_string(), Check::fail("Get security token error")),
+    ]);
+    let res = ReadinessResponse::new(checks).into_response();
+
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn check_database_fail_when_pool_is_closed() {
+    let pool = sqlite_pool().await;
+    let open = check_database(&pool).await;
+    if let DBPool::Sqlite(p) = &pool {
+        p.close().await;
+    }
+    let closed = check_database(&pool).await;
+
+    assert_eq!(open, Check::ok());
+    assert_eq!(closed.status, CheckStatus::Fail);
+    assert_eq!(closed.detail.unwrap(), "database is unavailable");
+}
+
+#[tokio::test]
+async fn check_migrations_fail_while_pending() {
+    let pool = sqlite_pool().await;
+    let pending = check_migrations(&pool).await;
+    migration::run(&pool).await.unwrap();
+    let applied = check_migrations(&pool).await;
+
+    assert_eq!(pending.status, CheckStatus::Fail);
+    assert!(pending.detail.unwrap().contains("pending"));
+    assert_eq!(applied, Check::ok());
+}
+
+#[tokio::test]
+async fn check_migrations_fail_without_raw_database_error() {
+    let pool = sqlite_pool().await;
+    if let DBPool::Sqlite(p) = &pool {
+        p.close().await;
+    }
+    let closed = check_migrations(&pool).await;
+
+    assert_eq!(closed.status, CheckStatus::Fail);
+    assert_eq!(closed.detail.unwrap(), "migration status is unavailable");
+}
+
+#[tokio::test]
+async fn check_jwks_pass_when_cached() {
+    let cache = HashMap::from([(
+        "jwks".to_string(),
+        serde_json::from_value(json!({"keys": []})).unwrap(),
+    )]);
+    let adapter = DefaultFirebaseAuthAdapter::new(
+        DefaultConfig::new("project".to_string(), "http://localhost/device".to_string()),
+        Arc::new(Mutex::new(cache)),
+    );
+
+    assert_eq!(check_jwks(&adapter).await, Check::ok());
+}
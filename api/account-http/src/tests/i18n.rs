@@ -0,0 +1,158 @@
+use axum::body::Body;
+use axum::http::header::{ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_LANGUAGE, CONTENT_TYPE};
+use axum::http::{Request, StatusCode};
+use axum::routing::get;
+use axum::{Extension, Router};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use super::{token, TestApp, TestResponse};
+use crate::i18n::AuthenticatedUser;
+use crate::middleware::problem::ProblemLayer;
+
+async fn update_profile(app: &TestApp, language: &str, params: Value) -> TestResponse {
+    app.send(
+        Request::post("/update_profile")
+            .header(AUTHORIZATION, format!("Bearer {}", token("alice")))
+            .header(CONTENT_TYPE, "application/json")
+            .header(ACCEPT_LANGUAGE, language)
+            .body(Body::from(params.to_string()))
+            .unwrap(),
+    )
+    .await
+}
+
+fn invalid_profile() -> Value {
+    json!({"user_name": "x", "display_name": "Alice", "avatar_url": "not a url"})
+}
+
+#[tokio::test]
+async fn problem_is_japanese_by_default() {
+    let app = TestApp::new().await;
+    let res = app.get("/resolve_profile", None).await;
+
+    assert_eq!(res.headers[CONTENT_LANGUAGE], "ja");
+    assert_eq!(res.body["title"], "認証が必要です");
+}
+
+#[tokio::test]
+async fn problem_follow_accept_language() {
+    let app = TestApp::new().await;
+    let res = app
+        .send(
+            Request::get("/resolve_profile")
+                .header(ACCEPT_LANGUAGE, "fr-FR, en;q=0.8, ja;q=0.5")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+    assert_eq!(res.headers[CONTENT_LANGUAGE], "en");
+    assert_eq!(res.body["title"], "Authentication required");
+    assert_eq!(
+        res.body["detail"],
+        "Send a valid token in the Authorization header."
+    );
+}
+
+#[tokio::test]
+async fn validation_problem_explain_each_param() {
+    let app = TestApp::new().await;
+    let ja = update_profile(&app, "ja", invalid_profile()).await;
+    let en = update_profile(&app, "en", invalid_profile()).await;
+
+    assert_eq!(ja.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        ja.body["type"],
+        "urn:matsunoki:problem:profile_validation_error"
+    );
+    assert_eq!(ja.body["kind"], "ProfileValidationError");
+    assert_eq!(
+        ja.body["invalid_params"],
+        json!([
+            {
+                "name": "user_name",
+                "code": "min_length",
+                "reason": "ユーザー名は 2 文字以上で入力してください。",
+            },
+            {
+                "name": "avatar_url",
+                "code": "not_url",
+                "reason": "アバターには有効な URL を指定してください。",
+            },
+        ])
+    );
+    assert_eq!(
+        en.body["invalid_params"][0]["reason"],
+        "User name must be at least 2 characters."
+    );
+    assert_eq!(
+        en.body["invalid_params"][1]["reason"],
+        "Avatar must be a valid URL."
+    );
+}
+
+#[tokio::test]
+async fn stored_locale_take_precedence_over_accept_language() {
+    let app = TestApp::new().await;
+    let stored = update_profile(
+        &app,
+        "ja",
+        json!({
+            "user_name": "alice",
+            "display_name": "Alice",
+            "avatar_url": "https://example.com/alice.png",
+            "locale": "en",
+        }),
+    )
+    .await;
+    let res = update_profile(&app, "ja", invalid_profile()).await;
+
+    assert_eq!(stored.status, StatusCode::OK);
+    assert_eq!(stored.body["user_profile"]["profile"]["locale"], "en");
+    assert_eq!(res.headers[CONTENT_LANGUAGE], "en");
+    assert_eq!(res.body["title"], "Invalid profile");
+}
+
+#[tokio::test]
+async fn server_error_follow_accept_language_without_reading_profile() {
+    let app = TestApp::new().await;
+    update_profile(
+        &app,
+        "ja",
+        json!({
+            "user_name": "alice",
+            "display_name": "Alice",
+            "avatar_url": "https://example.com/alice.png",
+            "locale": "en",
+        }),
+    )
+    .await;
+    let fail = |status: StatusCode| {
+        move |Extension(user): Extension<AuthenticatedUser>| async move {
+            user.set("alice".to_string());
+            status
+        }
+    };
+    let router = Router::new()
+        .route("/client_error", get(fail(StatusCode::BAD_REQUEST)))
+        .route(
+            "/server_error",
+            get(fail(StatusCode::INTERNAL_SERVER_ERROR)),
+        )
+        .layer(ProblemLayer::new(app.kernel.clone()));
+    let send = |uri: &str| {
+        router.clone().oneshot(
+            Request::get(uri)
+                .header(ACCEPT_LANGUAGE, "ja")
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+
+    let client_error = send("/client_error").await.unwrap();
+    let server_error = send("/server_error").await.unwrap();
+
+    assert_eq!(client_error.headers()[CONTENT_LANGUAGE], "en");
+    assert_eq!(server_error.headers()[CONTENT_LANGUAGE], "ja");
+}
@@ -0,0 +1,168 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls;
+use tokio_rustls::TlsConnector;
+
+use super::TestApp;
+use crate::config::{ServerConfig, TlsConfig};
+use crate::listener::{Certificates, Listener};
+use crate::shutdown::serve;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "account-http-listener-{}-{}",
+        std::process::id(),
+        name
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Writes a new self-signed certificate for `localhost` and its key as `cert.pem` and
+/// `key.pem` in `dir`, and returns the certificate in DER.
+fn write_certificate(dir: &Path) -> Vec<u8> {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    std::fs::write(dir.join("cert.pem"), cert.serialize_pem().unwrap()).unwrap();
+    std::fs::write(dir.join("key.pem"), cert.serialize_private_key_pem()).unwrap();
+    cert.serialize_der().unwrap()
+}
+
+fn tls_config(dir: &Path) -> TlsConfig {
+    TlsConfig {
+        cert_path: dir.join("cert.pem"),
+        key_path: dir.join("key.pem"),
+    }
+}
+
+fn server_config(tls: Option<TlsConfig>, unix_socket: Option<PathBuf>) -> ServerConfig {
+    ServerConfig {
+        bind_address: "127.0.0.1:0".parse().unwrap(),
+        cors_origins: vec![],
+        shutdown_timeout: Duration::from_secs(1),
+        tls,
+        unix_socket,
+        trusted_proxies: vec![],
+    }
+}
+
+async fn start(listener: Listener) {
+    let app = TestApp::new().await.router;
+    tokio::spawn(serve(
+        listener,
+        app,
+        std::future::pending(),
+        Duration::from_secs(1),
+    ));
+}
+
+/// Sends a GET over `stream` and returns the raw response.
+async fn exchange(
+    mut stream: impl AsyncRead + AsyncWrite + Unpin,
+    path: &str,
+) -> std::io::Result<String> {
+    stream
+        .write_all(
+            format!(
+                "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+                path
+            )
+            .as_bytes(),
+        )
+        .await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response)
+}
+
+/// Sends a GET over HTTPS, trusting only `trusted`.
+async fn https_get(addr: SocketAddr, trusted: &[u8], path: &str) -> std::io::Result<String> {
+    let mut config = rustls::ClientConfig::new();
+    config
+        .root_store
+        .add(&rustls::Certificate(trusted.to_vec()))
+        .unwrap();
+    let domain = webpki::DNSNameRef::try_from_ascii_str("localhost").unwrap();
+    let stream = TlsConnector::from(Arc::new(config))
+        .connect(domain, TcpStream::connect(addr).await?)
+        .await?;
+    exchange(stream, path).await
+}
+
+#[tokio::test]
+async fn tls_serve_https_and_reload_renewed_certificate() {
+    let dir = temp_dir("tls");
+    let first = write_certificate(&dir);
+    let certificates = Certificates::load(&tls_config(&dir)).unwrap();
+    certificates.watch(Duration::from_millis(50));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    start(Listener::Tls(listener, certificates)).await;
+
+    let served = https_get(addr, &first, "/livez").await.unwrap();
+    let plain = exchange(TcpStream::connect(addr).await.unwrap(), "/livez").await;
+    let second = write_certificate(&dir);
+    let mut renewed = https_get(addr, &second, "/livez").await;
+    for _ in 0..100 {
+        if renewed.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        renewed = https_get(addr, &second, "/livez").await;
+    }
+    let stale = https_get(addr, &first, "/livez").await;
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(served.starts_with("HTTP/1.1 200 OK"));
+    assert!(!plain.unwrap_or_default().starts_with("HTTP/1.1 200"));
+    assert!(renewed.unwrap().starts_with("HTTP/1.1 200 OK"));
+    assert!(stale.is_err());
+}
+
+#[tokio::test]
+async fn tls_keep_certificate_in_use_when_renewal_is_invalid() {
+    let dir = temp_dir("invalid");
+    let first = write_certificate(&dir);
+    let certificates = Certificates::load(&tls_config(&dir)).unwrap();
+    certificates.watch(Duration::from_millis(50));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    start(Listener::Tls(listener, certificates)).await;
+
+    std::fs::write(dir.join("key.pem"), "not a key").unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let served = https_get(addr, &first, "/livez").await;
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(served.unwrap().starts_with("HTTP/1.1 200 OK"));
+}
+
+#[tokio::test]
+async fn bind_fail_without_certificate_files() {
+    let dir = temp_dir("missing");
+    let bound = Listener::bind(&server_config(Some(tls_config(&dir)), None)).await;
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(format!("{:#}", bound.err().unwrap()).contains("cert.pem"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn unix_socket_serve_requests_over_stale_socket_file() {
+    let dir = temp_dir("unix");
+    let path = dir.join("account.sock");
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    let listener = Listener::bind(&server_config(None, Some(path.clone())))
+        .await
+        .unwrap();
+    start(listener).await;
+
+    let stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+    let response = exchange(stream, "/hc").await.unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+}
@@ -0,0 +1,202 @@
+use account::model::device_authorization::DEVICE_CODE_GRANT_TYPE;
+use axum::body::Body;
+use axum::http::header::AUTHORIZATION;
+use axum::http::{Request, StatusCode};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use super::{token, TestApp};
+
+#[derive(Debug, Default)]
+struct Fields(BTreeMap<&'static str, String>);
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name(), format!("{:?}", value));
+    }
+}
+
+/// A closed span or an event, with the fields of every span it was in.
+#[derive(Debug)]
+struct Line {
+    target: String,
+    name: &'static str,
+    fields: BTreeMap<&'static str, String>,
+}
+
+/// Keeps every line the app logs, instead of writing it out.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<Line>>>);
+
+impl Capture {
+    fn lines(&self) -> Vec<Line> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl<S> Layer<S> for Capture
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        ctx.span(id).unwrap().extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(fields) = ctx.span(id).unwrap().extensions_mut().get_mut::<Fields>() {
+            values.record(fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        for span in ctx
+            .event_scope(event)
+            .into_iter()
+            .flat_map(|s| s.from_root())
+        {
+            if let Some(f) = span.extensions().get::<Fields>() {
+                fields.0.extend(f.0.clone());
+            }
+        }
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(Line {
+            target: event.metadata().target().to_string(),
+            name: "event",
+            fields: fields.0,
+        });
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let span = ctx.span(&id).unwrap();
+        let mut fields = Fields::default();
+        for s in span.scope().from_root() {
+            if let Some(f) = s.extensions().get::<Fields>() {
+                fields.0.extend(f.0.clone());
+            }
+        }
+        self.0.lock().unwrap().push(Line {
+            target: span.metadata().target().to_string(),
+            name: span.name(),
+            fields: fields.0,
+        });
+    }
+}
+
+fn capture() -> (Capture, tracing::subscriber::DefaultGuard) {
+    let capture = Capture::default();
+    let guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+    (capture, guard)
+}
+
+fn find<'a>(lines: &'a [Line], target: &str, name: &str) -> &'a Line {
+    lines
+        .iter()
+        .find(|l| l.target == target && l.name == name)
+        .unwrap_or_else(|| panic!("no {} {} in {:#?}", target, name, lines))
+}
+
+#[tokio::test]
+async fn use_case_and_repository_spans_carry_request_id_and_user_id() {
+    let app = TestApp::new().await;
+    let (capture, _guard) = capture();
+    let res = app
+        .send(
+            Request::get("/resolve_profile")
+                .header(AUTHORIZATION, format!("Bearer {}", token("alice")))
+                .header("x-request-id", "req-1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    let lines = capture.lines();
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers["x-request-id"], "req-1");
+    for (target, name) in [
+        ("account_http::middleware::trace", "request"),
+        ("account::usecase::verify", "execute"),
+        ("account::usecase::resolve_profile", "execute"),
+        (
+            "account_driver::repository::in_memory_user_profile_repository",
+            "resolve",
+        ),
+    ] {
+        let line = find(&lines, target, name);
+        assert_eq!(line.fields["request_id"], "req-1", "{:?}", line);
+        assert_eq!(line.fields["user_id"], "alice", "{:?}", line);
+    }
+}
+
+#[tokio::test]
+async fn request_id_is_generated_when_not_sent() {
+    let app = TestApp::new().await;
+    let (capture, _guard) = capture();
+    let res = app.get("/resolve_profile", Some(&token("alice"))).await;
+    let lines = capture.lines();
+
+    let request_id = res.headers["x-request-id"].to_str().unwrap();
+    let line = find(&lines, "account::usecase::resolve_profile", "execute");
+    assert!(!request_id.is_empty());
+    assert_eq!(line.fields["request_id"], request_id);
+}
+
+#[tokio::test]
+async fn logs_never_carry_tokens() {
+    let app = TestApp::new().await;
+    let (capture, _guard) = capture();
+    let issued = app
+        .post_form("/device/code", "client_id=cli&scope=profile")
+        .await
+        .body;
+    let device_code = issued["device_code"].as_str().unwrap();
+    app.post_json(
+        "/device/approve",
+        Some(&token("alice")),
+        json!({"user_code": issued["user_code"], "approved": true}),
+    )
+    .await;
+    let exchanged = app
+        .post_form(
+            "/device/token",
+            &format!(
+                "grant_type={}&device_code={}&client_id=cli",
+                DEVICE_CODE_GRANT_TYPE, device_code
+            ),
+        )
+        .await;
+    let scoped_token = exchanged.body["access_token"].as_str().unwrap();
+    let profile = app.get("/resolve_profile", Some(scoped_token)).await;
+    let lines = capture.lines();
+
+    assert_eq!(profile.status, StatusCode::OK);
+    find(
+        &lines,
+        "account_driver::repository::in_memory_scoped_token_repository",
+        "resolve",
+    );
+    for secret in [token("alice").as_str(), device_code, scoped_token] {
+        assert!(
+            lines
+                .iter()
+                .all(|l| l.fields.values().all(|v| !v.contains(secret))),
+            "{} is logged",
+            secret
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use account_driver::adapter::fake_firebase_auth_adapter::FakeFirebaseAuthAdapter;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::StatusCode;
+use serde_json::json;
+
+use super::{token, TestApp};
+
+/// Value of `series` in the text `exposition`, 0 when it has no sample yet.
+fn sample(exposition: &str, series: &str) -> f64 {
+    exposition
+        .lines()
+        .find_map(|line| line.strip_prefix(series)?.trim().parse().ok())
+        .unwrap_or_default()
+}
+
+async fn scrape(app: &TestApp) -> String {
+    let res = app.get("/metrics", None).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.headers[CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+    res.body.as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn metrics_count_requests_by_route_and_status() {
+    let app = TestApp::new().await;
+    let profile = r#"http_requests_total{method="GET",route="/resolve_profile",status="200"}"#;
+    let unmatched = r#"http_requests_total{method="GET",route="unmatched",status="404"}"#;
+    let before = scrape(&app).await;
+    app.get("/resolve_profile", Some(&token("alice"))).await;
+    app.get("/users/alice", None).await;
+    let after = scrape(&app).await;
+
+    assert!(sample(&after, profile) >= sample(&before, profile) + 1.0);
+    assert!(sample(&after, unmatched) >= sample(&before, unmatched) + 1.0);
+    assert!(after.contains(
+        r#"http_request_duration_seconds_count{method="GET",route="/resolve_profile",status="200"}"#
+    ));
+    assert!(!after.contains("/users/alice"));
+}
+
+#[tokio::test]
+async fn metrics_count_sign_ups_by_result() {
+    let app = TestApp::new().await;
+    let created = r#"sign_ups_total{result="created"}"#;
+    let already_exist = r#"sign_ups_total{result="already_exist"}"#;
+    let before = scrape(&app).await;
+    app.post_json(
+        "/sign_up",
+        Some(&FakeFirebaseAuthAdapter::token("dave-uid")),
+        json!({}),
+    )
+    .await;
+    app.post_json("/sign_up", Some(&token("alice")), json!({}))
+        .await;
+    let after = scrape(&app).await;
+
+    assert!(sample(&after, created) >= sample(&before, created) + 1.0);
+    assert!(sample(&after, already_exist) >= sample(&before, already_exist) + 1.0);
+}
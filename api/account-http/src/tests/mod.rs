@@ -0,0 +1,136 @@
+//! In-process tests of every route: `router` over `MockKernel`, driven with
+//! `tower::ServiceExt::oneshot`.
+mod admin;
+mod audit;
+mod auth;
+mod device;
+mod health;
+mod i18n;
+mod listener;
+mod logging;
+mod metrics;
+mod openapi;
+mod problem;
+mod profile;
+mod read_your_writes;
+mod shutdown;
+mod telemetry;
+mod typescript;
+
+use account::effect::rate_limit_store::RateLimitRule;
+use account_driver::adapter::fake_firebase_auth_adapter::FakeFirebaseAuthAdapter;
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use axum::http::{HeaderMap, Request, StatusCode};
+use axum::Router;
+use http_body::Body as _;
+use serde_json::Value;
+use std::time::Duration;
+use tower::ServiceExt;
+
+use crate::config::ServerConfig;
+use crate::listener::PeerAddr;
+use crate::middleware::rate_limit::{RateLimitConfig, RouteRateLimit};
+use crate::mock::MockKernel;
+use crate::router;
+
+/// Bearer token of the seeded user `id`.
+pub fn token(id: &str) -> String {
+    FakeFirebaseAuthAdapter::token(id)
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// Parsed JSON, or the text of a body that is not JSON.
+    pub body: Value,
+}
+
+/// The app under test, seeded with the users of `MockKernel::seed`.
+pub struct TestApp {
+    pub kernel: MockKernel,
+    router: Router,
+}
+
+impl TestApp {
+    pub async fn new() -> Self {
+        let limit = RouteRateLimit::new(RateLimitRule::new(100, 60), None);
+        Self::with_rate_limit(RateLimitConfig::new(limit, limit, limit, limit)).await
+    }
+
+    pub async fn with_rate_limit(rate_limit: RateLimitConfig) -> Self {
+        let kernel = MockKernel::new("http://localhost/device".to_string());
+        kernel.seed().await.unwrap();
+        let server = ServerConfig {
+            bind_address: "127.0.0.1:0".parse().unwrap(),
+            cors_origins: vec![],
+            shutdown_timeout: Duration::from_secs(1),
+            tls: None,
+            unix_socket: None,
+            trusted_proxies: vec![],
+        };
+        let router = router(kernel.clone(), rate_limit, &server);
+        TestApp { kernel, router }
+    }
+
+    /// Sends `req` as if from a client at `127.0.0.1`, unless it sets `ConnectInfo` itself.
+    pub async fn send(&self, mut req: Request<Body>) -> TestResponse {
+        if req.extensions().get::<ConnectInfo<PeerAddr>>().is_none() {
+            req.extensions_mut()
+                .insert(ConnectInfo(PeerAddr(Some(([127, 0, 0, 1], 40000).into()))));
+        }
+        let res = self.router.clone().oneshot(req).await.unwrap();
+        let status = res.status();
+        let headers = res.headers().clone();
+        let mut body = res.into_body();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = body.data().await {
+            bytes.extend_from_slice(&chunk.unwrap());
+        }
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()))
+        };
+        TestResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    pub async fn get(&self, uri: &str, token: Option<&str>) -> TestResponse {
+        self.send(request("GET", uri, token).body(Body::empty()).unwrap())
+            .await
+    }
+
+    pub async fn post_json(&self, uri: &str, token: Option<&str>, body: Value) -> TestResponse {
+        self.send(
+            request("POST", uri, token)
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+    }
+
+    pub async fn post_form(&self, uri: &str, body: &str) -> TestResponse {
+        self.send(
+            request("POST", uri, None)
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+    }
+}
+
+fn request(method: &str, uri: &str, token: Option<&str>) -> axum::http::request::Builder {
+    let builder = Request::builder().method(method).uri(uri);
+    match token {
+        Some(token) => builder.header(AUTHORIZATION, format!("Bearer {}", token)),
+        None => builder,
+    }
+}
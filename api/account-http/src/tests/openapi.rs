@@ -0,0 +1,125 @@
+use axum::http::header::{CONTENT_SECURITY_POLICY, CONTENT_TYPE};
+use axum::http::StatusCode;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use utoipa::OpenApi;
+
+use super::TestApp;
+use crate::handler::api_doc::{DOCS_PAGE, DOCS_POLICY};
+use crate::openapi::ApiDoc;
+
+const REGENERATE: &str = "UPDATE_OPENAPI=1 cargo test -p account-http openapi";
+
+fn refs<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(r)) = map.get("$ref") {
+                found.push(r);
+            }
+            map.values().for_each(|v| refs(v, found));
+        }
+        Value::Array(items) => items.iter().for_each(|v| refs(v, found)),
+        _ => {}
+    }
+}
+
+#[test]
+fn committed_spec_is_up_to_date() {
+    let spec = ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("openapi.json");
+    if std::env::var_os("UPDATE_OPENAPI").is_some() {
+        std::fs::write(&path, &spec).unwrap();
+    }
+    let committed = std::fs::read_to_string(&path).unwrap_or_default();
+
+    assert!(
+        committed == spec,
+        "{} is out of date, regenerate it with `{}`",
+        path.display(),
+        REGENERATE
+    );
+}
+
+#[test]
+fn spec_declare_every_referenced_schema() {
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let mut found = Vec::new();
+    refs(&spec, &mut found);
+
+    let missing = found
+        .into_iter()
+        .filter(|r| spec.pointer(r.trim_start_matches('#')).is_none())
+        .collect::<Vec<_>>();
+    assert!(missing.is_empty(), "undeclared schemas: {:?}", missing);
+}
+
+#[test]
+fn spec_document_every_route() {
+    let spec = ApiDoc::openapi();
+
+    for path in [
+        "/hc",
+        "/livez",
+        "/readyz",
+        "/sign_up",
+        "/verify",
+        "/resolve_profile",
+        "/update_profile",
+        "/device/code",
+        "/device/approve",
+        "/device/token",
+        "/audit",
+        "/admin/resolve_user",
+        "/admin/update_role",
+        "/admin/search_users",
+        "/admin/update_user_status",
+        "/admin/reset_user_name",
+    ] {
+        assert!(
+            spec.paths.paths.contains_key(path),
+            "{} is undocumented",
+            path
+        );
+    }
+}
+
+#[tokio::test]
+async fn openapi_return_200_and_spec() {
+    let app = TestApp::new().await;
+    let res = app.get("/openapi.json", None).await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body, serde_json::to_value(ApiDoc::openapi()).unwrap());
+}
+
+#[tokio::test]
+async fn docs_return_200_and_html() {
+    let app = TestApp::new().await;
+    let res = app.get("/docs", None).await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.headers[CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/html"));
+    assert!(res.body.as_str().unwrap().contains("openapi.json"));
+    assert_eq!(res.headers[CONTENT_SECURITY_POLICY], DOCS_POLICY);
+}
+
+#[test]
+fn docs_policy_allow_inline_script_and_pinned_swagger_ui_only() {
+    let script = DOCS_PAGE
+        .split("<script>")
+        .nth(1)
+        .and_then(|s| s.split("</script>").next())
+        .unwrap();
+    let hash = base64::encode(Sha256::digest(script.as_bytes()));
+
+    assert!(DOCS_POLICY.contains(&format!("'sha256-{}'", hash)));
+    assert!(!DOCS_PAGE.contains("swagger-ui-dist@5/"));
+    for src in DOCS_PAGE.split("https://").skip(1) {
+        let url = src.split('"').next().unwrap();
+        assert!(DOCS_POLICY.contains(url), "{} is not allowed", url);
+    }
+}
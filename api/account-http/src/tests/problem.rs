@@ -0,0 +1,96 @@
+use axum::body::Body;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{Request, StatusCode};
+use serde_json::json;
+
+use super::{token, TestApp};
+use crate::error::PROBLEM_JSON;
+
+#[tokio::test]
+async fn error_return_problem_with_instance_and_request_id() {
+    let app = TestApp::new().await;
+    let res = app.get("/resolve_profile", None).await;
+
+    assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(res.headers[CONTENT_TYPE], PROBLEM_JSON);
+    assert_eq!(res.body["type"], "urn:matsunoki:problem:unauthorized");
+    assert_eq!(res.body["title"], "認証が必要です");
+    assert_eq!(res.body["status"], 401);
+    assert_eq!(res.body["instance"], "/resolve_profile");
+    assert_eq!(
+        res.body["request_id"],
+        res.headers["x-request-id"].to_str().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn bad_request_problem_carry_kind_and_key() {
+    let app = TestApp::new().await;
+    let res = app
+        .post_json("/verify", Some(&token("carol")), json!({}))
+        .await;
+
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.body["type"], "urn:matsunoki:problem:user_not_found");
+    assert_eq!(res.body["title"], "ユーザーが見つかりません");
+    assert_eq!(res.body["kind"], "UserNotFound");
+    assert!(res.body["key"].as_str().is_some());
+}
+
+#[tokio::test]
+async fn request_id_sent_by_client_is_kept() {
+    let app = TestApp::new().await;
+    let res = app
+        .send(
+            Request::get("/resolve_profile")
+                .header("x-request-id", "req-1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+    assert_eq!(res.headers["x-request-id"], "req-1");
+    assert_eq!(res.body["request_id"], "req-1");
+}
+
+#[tokio::test]
+async fn success_response_carry_request_id_but_no_problem() {
+    let app = TestApp::new().await;
+    let res = app.get("/resolve_profile", Some(&token("alice"))).await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.headers.contains_key("x-request-id"));
+    assert_eq!(res.headers[CONTENT_TYPE], "application/json");
+}
+
+#[tokio::test]
+async fn extractor_rejection_become_about_blank_problem() {
+    let app = TestApp::new().await;
+    let res = app
+        .send(
+            Request::post("/update_profile")
+                .header("authorization", format!("Bearer {}", token("alice")))
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from("{"))
+                .unwrap(),
+        )
+        .await;
+
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.headers[CONTENT_TYPE], PROBLEM_JSON);
+    assert_eq!(res.body["type"], "about:blank");
+    assert_eq!(res.body["title"], "リクエストが不正です");
+    assert!(res.body["detail"].as_str().is_some_and(|d| !d.is_empty()));
+    assert_eq!(res.body["instance"], "/update_profile");
+}
+
+#[tokio::test]
+async fn unknown_route_return_404_problem() {
+    let app = TestApp::new().await;
+    let res = app.get("/unknown", None).await;
+
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+    assert_eq!(res.headers[CONTENT_TYPE], PROBLEM_JSON);
+    assert_eq!(res.body["type"], "about:blank");
+    assert_eq!(res.body["instance"], "/unknown");
+}
@@ -0,0 +1,71 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use super::{token, TestApp};
+
+#[tokio::test]
+async fn resolve_profile_return_200_with_profile() {
+    let app = TestApp::new().await;
+    let res = app.get("/resolve_profile", Some(&token("alice"))).await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["user_profile"]["id"], "alice");
+    assert_eq!(res.body["user_profile"]["profile"]["name"], "alice");
+}
+
+#[tokio::test]
+async fn resolve_profile_return_500_when_profile_is_broken() {
+    let app = TestApp::new().await;
+    let res = app.get("/resolve_profile", Some(&token("bob"))).await;
+
+    assert_eq!(res.status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        res.body["type"],
+        "urn:matsunoki:problem:internal_server_error"
+    );
+    assert_eq!(
+        res.body["detail"],
+        "時間をおいてもう一度お試しください。解決しない場合は request_id を添えてお問い合わせください。"
+    );
+}
+
+#[tokio::test]
+async fn update_profile_return_200_and_store_profile() {
+    let app = TestApp::new().await;
+    let params = json!({
+        "user_name": "alice_2",
+        "display_name": "Alice",
+        "avatar_url": "https://example.com/alice.png",
+    });
+    let res = app
+        .post_json("/update_profile", Some(&token("alice")), params)
+        .await;
+    let resolved = app.get("/resolve_profile", Some(&token("alice"))).await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(resolved.body["user_profile"]["profile"]["name"], "alice_2");
+}
+
+#[tokio::test]
+async fn update_profile_return_400_when_invalid() {
+    let app = TestApp::new().await;
+    let params = json!({
+        "user_name": "Alice",
+        "display_name": "Alice",
+        "avatar_url": "https://example.com/alice.png",
+    });
+    let res = app
+        .post_json("/update_profile", Some(&token("alice")), params)
+        .await;
+
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.body["kind"], "ProfileValidationError");
+}
+
+#[tokio::test]
+async fn update_profile_return_401_without_token() {
+    let app = TestApp::new().await;
+    let res = app.post_json("/update_profile", None, json!({})).await;
+
+    assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,84 @@
+use account_driver::replica::{reads_from_primary, wrote};
+use axum::body::Body;
+use axum::http::{Request, Response};
+use std::convert::Infallible;
+use time::OffsetDateTime;
+use tower::{service_fn, Layer, ServiceExt};
+
+use crate::middleware::read_your_writes::{ReadYourWritesLayer, READ_PRIMARY_UNTIL};
+
+/// Reports, in the response extensions, whether reads went to the primary before and after
+/// the request wrote, when it does.
+async fn handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let before = reads_from_primary();
+    if req.uri().path() == "/write" {
+        wrote();
+    }
+    let mut res = Response::new(Body::empty());
+    res.extensions_mut().insert((before, reads_from_primary()));
+    Ok(res)
+}
+
+fn request(path: &str, read_primary_until: Option<i64>) -> Request<Body> {
+    let builder = Request::get(path);
+    let builder = match read_primary_until {
+        Some(until) => builder.header(READ_PRIMARY_UNTIL, until),
+        None => builder,
+    };
+    builder.body(Body::empty()).unwrap()
+}
+
+fn reads(res: &Response<Body>) -> (bool, bool) {
+    *res.extensions().get::<(bool, bool)>().unwrap()
+}
+
+#[tokio::test]
+async fn read_your_writes_route_reads_to_primary_after_write_within_request() {
+    let service = ReadYourWritesLayer.layer(service_fn(handler));
+    let write = service
+        .clone()
+        .oneshot(request("/write", None))
+        .await
+        .unwrap();
+    let read = service.oneshot(request("/read", None)).await.unwrap();
+
+    assert_eq!(reads(&write), (false, true));
+    assert!(write.headers().contains_key(READ_PRIMARY_UNTIL));
+    assert_eq!(reads(&read), (false, false));
+    assert!(!read.headers().contains_key(READ_PRIMARY_UNTIL));
+}
+
+#[tokio::test]
+async fn read_your_writes_route_reads_to_primary_in_next_request_after_write() {
+    let service = ReadYourWritesLayer.layer(service_fn(handler));
+    let write = service
+        .clone()
+        .oneshot(request("/write", None))
+        .await
+        .unwrap();
+    let until = write.headers()[READ_PRIMARY_UNTIL]
+        .to_str()
+        .unwrap()
+        .parse::<i64>()
+        .unwrap();
+    let next = service
+        .clone()
+        .oneshot(request("/read", Some(until)))
+        .await
+        .unwrap();
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let expired = service
+        .clone()
+        .oneshot(request("/read", Some(now - 1)))
+        .await
+        .unwrap();
+    let too_far = service
+        .oneshot(request("/read", Some(now + 3600)))
+        .await
+        .unwrap();
+
+    assert_eq!(reads(&next), (true, true));
+    assert!(!next.headers().contains_key(READ_PRIMARY_UNTIL));
+    assert_eq!(reads(&expired), (false, false));
+    assert_eq!(reads(&too_far), (false, false));
+}
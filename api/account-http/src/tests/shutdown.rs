@@ -0,0 +1,108 @@
+use axum::http::StatusCode;
+use axum::routing::get;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::{oneshot, Notify};
+use tower_http::add_extension::AddExtensionLayer;
+
+use super::TestApp;
+use crate::listener::Listener;
+use crate::shutdown::{serve, Draining};
+
+/// Sends a GET over a new connection and returns the raw response.
+async fn request(addr: SocketAddr, path: &str) -> std::io::Result<String> {
+    let mut stream = TcpStream::connect(addr).await?;
+    stream
+        .write_all(
+            format!(
+                "GET {} HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\r\n",
+                path
+            )
+            .as_bytes(),
+        )
+        .await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response)
+}
+
+/// Starts the app with a `/slow` route that answers `delay` after it is entered, and returns
+/// the address, a notification of the slow request being entered, the trigger of shutdown
+/// and the server task.
+async fn start(
+    delay: Duration,
+    timeout: Duration,
+) -> (
+    SocketAddr,
+    Arc<Notify>,
+    oneshot::Sender<()>,
+    tokio::task::JoinHandle<anyhow::Result<bool>>,
+) {
+    let entered = Arc::new(Notify::new());
+    let slow = {
+        let entered = entered.clone();
+        move || async move {
+            entered.notify_one();
+            tokio::time::sleep(delay).await;
+            "done"
+        }
+    };
+    let app = TestApp::new().await.router.route("/slow", get(slow));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (trigger, signal) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve(
+        Listener::Tcp(listener),
+        app,
+        async {
+            signal.await.ok();
+        },
+        timeout,
+    ));
+    (addr, entered, trigger, server)
+}
+
+#[tokio::test]
+async fn shutdown_drain_request_in_flight_and_refuse_new_connections() {
+    let (addr, entered, trigger, server) =
+        start(Duration::from_millis(500), Duration::from_secs(5)).await;
+    let slow = tokio::spawn(request(addr, "/slow"));
+    entered.notified().await;
+    trigger.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let refused = request(addr, "/livez").await;
+    let response = slow.await.unwrap().unwrap();
+
+    assert!(refused.is_err());
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("done"));
+    assert!(server.await.unwrap().unwrap());
+}
+
+#[tokio::test]
+async fn shutdown_drop_requests_in_flight_after_timeout() {
+    let (addr, entered, trigger, server) =
+        start(Duration::from_secs(60), Duration::from_millis(100)).await;
+    let slow = tokio::spawn(request(addr, "/slow"));
+    entered.notified().await;
+    trigger.send(()).unwrap();
+    let drained = tokio::time::timeout(Duration::from_secs(5), server).await;
+
+    assert!(!drained.unwrap().unwrap().unwrap());
+    assert!(slow.await.unwrap().unwrap_or_default().is_empty());
+}
+
+#[tokio::test]
+async fn readyz_fail_while_draining() {
+    let draining = Draining::default();
+    draining.start();
+    let mut app = TestApp::new().await;
+    app.router = app.router.layer(AddExtensionLayer::new(draining));
+    let res = app.get("/readyz", None).await;
+
+    assert_eq!(res.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.body["checks"]["shutdown"]["detail"], "shutting down");
+}
@@ -0,0 +1,178 @@
+use account_driver::trace_context::trace_headers;
+use axum::body::{Body, Bytes};
+use axum::http::header::AUTHORIZATION;
+use axum::http::{HeaderMap, Request, StatusCode};
+use axum::routing::post;
+use axum::{Extension, Router};
+use opentelemetry::global;
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::sdk::trace::TracerProvider;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_http::HeaderExtractor;
+use prost::Message;
+use std::sync::{Arc, Mutex};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+
+use super::{token, TestApp};
+use crate::telemetry::{otlp_layer, otlp_tracer_provider};
+
+const TRACE_ID: &str = "0af7651916cd43dd8448eb211c80319c";
+const PARENT_ID: &str = "b7ad6b7169203331";
+
+fn traceparent() -> String {
+    format!("00-{}-{}-01", TRACE_ID, PARENT_ID)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// The part of the OTLP messages (opentelemetry/proto/collector/trace/v1) the collector reads.
+
+#[derive(Clone, PartialEq, Message)]
+struct ExportTraceServiceRequest {
+    #[prost(message, repeated, tag = "1")]
+    resource_spans: Vec<ResourceSpans>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ResourceSpans {
+    #[prost(message, repeated, tag = "2")]
+    instrumentation_library_spans: Vec<InstrumentationLibrarySpans>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct InstrumentationLibrarySpans {
+    #[prost(message, repeated, tag = "2")]
+    spans: Vec<Span>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Span {
+    #[prost(bytes = "vec", tag = "1")]
+    trace_id: Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    parent_span_id: Vec<u8>,
+    #[prost(string, tag = "5")]
+    name: String,
+    #[prost(message, repeated, tag = "9")]
+    attributes: Vec<KeyValue>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct KeyValue {
+    #[prost(string, tag = "1")]
+    key: String,
+    #[prost(message, optional, tag = "2")]
+    value: Option<AnyValue>,
+}
+
+/// Only the `string_value` of the `value` oneof.
+#[derive(Clone, PartialEq, Message)]
+struct AnyValue {
+    #[prost(string, tag = "1")]
+    string_value: String,
+}
+
+fn attribute(span: &Span, key: &str) -> Option<String> {
+    span.attributes
+        .iter()
+        .find(|a| a.key == key)
+        .and_then(|a| a.value.as_ref())
+        .map(|v| v.string_value.clone())
+}
+
+type Received = Arc<Mutex<Vec<Span>>>;
+
+async fn export(Extension(received): Extension<Received>, body: Bytes) -> StatusCode {
+    let request = ExportTraceServiceRequest::decode(body).unwrap();
+    received.lock().unwrap().extend(
+        request
+            .resource_spans
+            .into_iter()
+            .flat_map(|r| r.instrumentation_library_spans)
+            .flat_map(|l| l.spans),
+    );
+    StatusCode::OK
+}
+
+/// In-process stand-in for an OTLP/HTTP collector, keeping the spans exported to it.
+fn collector() -> (String, Received) {
+    let received = Received::default();
+    let app = Router::new()
+        .route("/v1/traces", post(export))
+        .layer(Extension(received.clone()));
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    (endpoint, received)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn export_spans_of_request_continuing_incoming_trace() {
+    let (endpoint, received) = collector();
+    let provider = otlp_tracer_provider(&endpoint, "account-http").unwrap();
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let app = TestApp::new().await;
+    let guard = tracing::subscriber::set_default(
+        tracing_subscriber::registry().with(otlp_layer(&provider)),
+    );
+    let res = app
+        .send(
+            Request::get("/resolve_profile")
+                .header(AUTHORIZATION, format!("Bearer {}", token("alice")))
+                .header("traceparent", traceparent())
+                .header("x-request-id", "req-1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    drop(guard);
+    tokio::task::spawn_blocking(move || provider.force_flush())
+        .await
+        .unwrap();
+    let spans = std::mem::take(&mut *received.lock().unwrap());
+
+    assert_eq!(res.status, StatusCode::OK);
+    let request = spans.iter().find(|s| s.name == "request").unwrap();
+    assert_eq!(hex(&request.trace_id), TRACE_ID);
+    assert_eq!(hex(&request.parent_span_id), PARENT_ID);
+    assert_eq!(attribute(request, "request_id").as_deref(), Some("req-1"));
+    assert_eq!(attribute(request, "user_id").as_deref(), Some("alice"));
+    for name in ["execute", "resolve"] {
+        let span = spans.iter().find(|s| s.name == name).unwrap();
+        assert_eq!(hex(&span.trace_id), TRACE_ID, "{}", name);
+    }
+}
+
+#[tokio::test]
+async fn trace_headers_continue_current_trace() {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let provider = TracerProvider::builder().build();
+    let _guard = tracing::subscriber::set_default(
+        tracing_subscriber::registry().with(otlp_layer(&provider)),
+    );
+    let mut incoming = HeaderMap::new();
+    incoming.insert("traceparent", traceparent().parse().unwrap());
+    let span = tracing::info_span!("request");
+    span.set_parent(global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(&incoming))
+    }));
+    let headers = span.in_scope(trace_headers);
+
+    let outgoing = headers["traceparent"].to_str().unwrap();
+    assert!(outgoing.starts_with(&format!("00-{}-", TRACE_ID)));
+    assert!(!outgoing.contains(PARENT_ID));
+}
+
+#[test]
+fn trace_headers_are_empty_when_traces_are_not_exported() {
+    let span = tracing::info_span!("request");
+
+    assert!(span.in_scope(trace_headers).is_empty());
+}
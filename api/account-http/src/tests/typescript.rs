@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+use crate::error::Problem;
+use crate::handler::exchange_device_code::ExchangeDeviceCodeResponse;
+use crate::handler::health_check::{HealthCheckResponse, LivenessResponse, ReadinessResponse};
+use crate::handler::issue_device_code::IssueDeviceCodeResponse;
+use crate::handler::reset_user_name::ResetUserNameParams;
+use account::usecase::approve_device_code::ApproveDeviceCodeUseCaseParams;
+use account::usecase::list_audit_events::ListAuditEventsUseCaseResult;
+use account::usecase::reset_user_name::ResetUserNameUseCaseResult;
+use account::usecase::resolve_profile::ResolveProfileUseCaseResult;
+use account::usecase::resolve_user::ResolveUserUseCaseResult;
+use account::usecase::search_users::SearchUsersUseCaseResult;
+use account::usecase::sign_up::SignUpUseCaseResult;
+use account::usecase::update_profile::{UpdateProfileUseCaseParams, UpdateProfileUseCaseResult};
+use account::usecase::update_role::{UpdateRoleUseCaseParams, UpdateRoleUseCaseResult};
+use account::usecase::update_user_status::{
+    UpdateUserStatusUseCaseParams, UpdateUserStatusUseCaseResult,
+};
+use account::usecase::verify::VerifyUseCaseResult;
+
+const REGENERATE: &str = "UPDATE_TS_TYPES=1 cargo test -p account-http typescript";
+
+fn generated_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../web/api-client/src/generated")
+}
+
+/// Writes every payload type and the types they refer to into `dir`, one module per type,
+/// with an `index.ts` re-exporting them all.
+fn export(dir: &Path) {
+    macro_rules! export_all {
+        ($($t:ty),* $(,)?) => {
+            $(<$t>::export_all_to(dir).unwrap();)*
+        };
+    }
+    export_all!(
+        SignUpUseCaseResult,
+        VerifyUseCaseResult,
+        ResolveProfileUseCaseResult,
+        UpdateProfileUseCaseParams,
+        UpdateProfileUseCaseResult,
+        IssueDeviceCodeResponse,
+        ApproveDeviceCodeUseCaseParams,
+        ExchangeDeviceCodeResponse,
+        ListAuditEventsUseCaseResult,
+        ResolveUserUseCaseResult,
+        UpdateRoleUseCaseParams,
+        UpdateRoleUseCaseResult,
+        SearchUsersUseCaseResult,
+        UpdateUserStatusUseCaseParams,
+        UpdateUserStatusUseCaseResult,
+        ResetUserNameParams,
+        ResetUserNameUseCaseResult,
+        HealthCheckResponse,
+        LivenessResponse,
+        ReadinessResponse,
+        Problem,
+    );
+    let mut index = String::from(
+        "// This file was generated by `account-http` tests. Do not edit this file manually.\n\n",
+    );
+    for name in read_modules(dir).keys().filter(|name| *name != "index") {
+        index.push_str(&format!("export type {{ {0} }} from \"./{0}\";\n", name));
+    }
+    fs::write(dir.join("index.ts"), index).unwrap();
+}
+
+/// `.ts` files of `dir` by module name.
+fn read_modules(dir: &Path) -> BTreeMap<String, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return BTreeMap::new(),
+    };
+    entries
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == "ts"))
+        .map(|path| {
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            (name, fs::read_to_string(&path).unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn committed_types_are_up_to_date() {
+    let committed = generated_dir();
+    if std::env::var_os("UPDATE_TS_TYPES").is_some() {
+        for path in read_modules(&committed).keys() {
+            fs::remove_file(committed.join(format!("{}.ts", path))).unwrap();
+        }
+        fs::create_dir_all(&committed).unwrap();
+        export(&committed);
+    }
+    let expected = std::env::temp_dir().join(format!("account-http-ts-{}", std::process::id()));
+    export(&expected);
+    let expected_modules = read_modules(&expected);
+    fs::remove_dir_all(&expected).unwrap();
+
+    assert!(
+        read_modules(&committed) == expected_modules,
+        "{} is out of date, regenerate it with `{}`",
+        committed.display(),
+        REGENERATE
+    );
+}
@@ -0,0 +1,6 @@
+use crate::effect::audit_log::AuditEventCondition;
+
+pub trait AuditViewer {
+    /// Narrows a requested condition down to the events this viewer may see.
+    fn restrict(&self, condition: AuditEventCondition) -> AuditEventCondition;
+}
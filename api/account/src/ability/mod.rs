@@ -1 +1,5 @@
+pub mod audit_viewer;
+pub mod name_moderator;
 pub mod profile_creator;
+pub mod role_granter;
+pub mod user_manager;
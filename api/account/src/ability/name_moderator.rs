@@ -0,0 +1,8 @@
+use crate::model::user::UserId;
+use crate::model::user_profile::UserProfile;
+
+pub trait NameModerator {
+    /// Id of the moderator, recorded against every reset.
+    fn moderator_id(&self) -> UserId;
+    fn reset_user_name(&self, target: &mut UserProfile);
+}
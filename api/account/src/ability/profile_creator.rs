@@ -1,4 +1,5 @@
 use crate::model::profile::entity::{Profile, ProfileInvalidity};
+use crate::model::profile::locale::Locale;
 use semval::prelude::ValidationContext;
 
 pub trait ProfileCreator {
@@ -7,5 +8,6 @@ pub trait ProfileCreator {
         name: String,
         display_name: String,
         avatar: String,
+        locale: Option<Locale>,
     ) -> Result<Profile, ValidationContext<ProfileInvalidity>>;
 }
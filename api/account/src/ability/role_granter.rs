@@ -0,0 +1,14 @@
+use crate::model::role::Role;
+use crate::model::user::User;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum RoleGrantError {
+    #[error("Admin cannot revoke own admin role.")]
+    SelfDemotion,
+}
+
+pub trait RoleGranter {
+    fn grant_role(&self, target: &mut User, role: Role) -> Result<(), RoleGrantError>;
+    fn revoke_role(&self, target: &mut User, role: Role) -> Result<(), RoleGrantError>;
+}
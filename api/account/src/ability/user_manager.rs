@@ -0,0 +1,15 @@
+use crate::model::user::{User, UserId};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum UserManageError {
+    #[error("Operator cannot disable own account.")]
+    SelfDisable,
+}
+
+pub trait UserManager {
+    /// Id of the operator, recorded against every management action.
+    fn operator_id(&self) -> UserId;
+    fn disable_user(&self, target: &mut User) -> Result<(), UserManageError>;
+    fn enable_user(&self, target: &mut User) -> Result<(), UserManageError>;
+}
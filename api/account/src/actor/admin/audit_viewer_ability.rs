@@ -0,0 +1,9 @@
+use super::Admin;
+use crate::ability::audit_viewer::AuditViewer;
+use crate::effect::audit_log::AuditEventCondition;
+
+impl AuditViewer for Admin {
+    fn restrict(&self, condition: AuditEventCondition) -> AuditEventCondition {
+        condition
+    }
+}
@@ -0,0 +1,33 @@
+use super::user::UserId;
+use crate::ability::profile_creator::ProfileCreator;
+use crate::model::profile::entity::{Profile, ProfileInvalidity};
+use crate::model::profile::locale::Locale;
+use derive_more::Constructor;
+use semval::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Constructor, Default)]
+pub struct Admin(pub UserId);
+
+impl From<&Admin> for super::user::User {
+    fn from(a: &Admin) -> Self {
+        super::user::User::new(a.0.clone())
+    }
+}
+
+impl ProfileCreator for Admin {
+    fn create_profile(
+        &self,
+        name: String,
+        display_name: String,
+        avatar: String,
+        locale: Option<Locale>,
+    ) -> Result<Profile, ValidationContext<ProfileInvalidity>> {
+        super::user::User::from(self).create_profile(name, display_name, avatar, locale)
+    }
+}
+
+pub mod audit_viewer_ability;
+pub mod name_moderator_ability;
+pub mod role_granter_ability;
+pub mod user_manager_ability;
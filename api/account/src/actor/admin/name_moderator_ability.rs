@@ -0,0 +1,15 @@
+use super::Admin;
+use crate::ability::name_moderator::NameModerator;
+use crate::actor::editor::Editor;
+use crate::model::user::UserId;
+use crate::model::user_profile::UserProfile;
+
+impl NameModerator for Admin {
+    fn moderator_id(&self) -> UserId {
+        UserId::new(self.0 .0.clone())
+    }
+
+    fn reset_user_name(&self, target: &mut UserProfile) {
+        Editor::new(self.0.clone()).reset_user_name(target)
+    }
+}
@@ -0,0 +1,59 @@
+use super::Admin;
+use crate::ability::role_granter::{RoleGrantError, RoleGranter};
+use crate::model::role::Role;
+use crate::model::user::User;
+
+impl RoleGranter for Admin {
+    fn grant_role(&self, target: &mut User, role: Role) -> Result<(), RoleGrantError> {
+        if !target.has_role(role) {
+            target.roles.push(role);
+        }
+        Ok(())
+    }
+
+    fn revoke_role(&self, target: &mut User, role: Role) -> Result<(), RoleGrantError> {
+        if role == Role::Admin && target.id.0 == self.0 .0 {
+            return Err(RoleGrantError::SelfDemotion);
+        }
+        target.roles.retain(|r| *r != role);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Admin;
+    use crate::ability::role_granter::{RoleGrantError, RoleGranter};
+    use crate::actor::user::UserId as ActorUserId;
+    use crate::model::role::Role;
+    use crate::model::user::{User, UserId};
+
+    #[test]
+    fn grant_role_is_idempotent() {
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let mut target = User::new(UserId::new("foo".to_string()), None);
+        admin.grant_role(&mut target, Role::Editor).unwrap();
+        admin.grant_role(&mut target, Role::Editor).unwrap();
+        assert_eq!(target.roles, vec![Role::Editor]);
+    }
+
+    #[test]
+    fn revoke_role_removes_role() {
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let mut target =
+            User::new(UserId::new("foo".to_string()), None).with_roles(vec![Role::Editor]);
+        admin.revoke_role(&mut target, Role::Editor).unwrap();
+        assert!(target.roles.is_empty());
+    }
+
+    #[test]
+    fn revoke_role_is_err_when_admin_demotes_self() {
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let mut target =
+            User::new(UserId::new("admin".to_string()), None).with_roles(vec![Role::Admin]);
+        assert_eq!(
+            admin.revoke_role(&mut target, Role::Admin),
+            Err(RoleGrantError::SelfDemotion)
+        );
+    }
+}
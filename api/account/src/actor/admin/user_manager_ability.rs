@@ -0,0 +1,50 @@
+use super::Admin;
+use crate::ability::user_manager::{UserManageError, UserManager};
+use crate::model::user::{User, UserId, UserStatus};
+
+impl UserManager for Admin {
+    fn operator_id(&self) -> UserId {
+        UserId::new(self.0 .0.clone())
+    }
+
+    fn disable_user(&self, target: &mut User) -> Result<(), UserManageError> {
+        if target.id.0 == self.0 .0 {
+            return Err(UserManageError::SelfDisable);
+        }
+        target.status = UserStatus::Disabled;
+        Ok(())
+    }
+
+    fn enable_user(&self, target: &mut User) -> Result<(), UserManageError> {
+        target.status = UserStatus::Active;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Admin;
+    use crate::ability::user_manager::{UserManageError, UserManager};
+    use crate::actor::user::UserId as ActorUserId;
+    use crate::model::user::{User, UserId, UserStatus};
+
+    #[test]
+    fn disable_user_then_enable_user() {
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let mut target = User::new(UserId::new("foo".to_string()), None);
+        admin.disable_user(&mut target).unwrap();
+        assert_eq!(target.status, UserStatus::Disabled);
+        admin.enable_user(&mut target).unwrap();
+        assert_eq!(target.status, UserStatus::Active);
+    }
+
+    #[test]
+    fn disable_user_is_err_when_admin_disables_self() {
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let mut target = User::new(UserId::new("admin".to_string()), None);
+        assert_eq!(
+            admin.disable_user(&mut target),
+            Err(UserManageError::SelfDisable)
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use super::user::UserId;
+use crate::ability::profile_creator::ProfileCreator;
+use crate::model::profile::entity::{Profile, ProfileInvalidity};
+use crate::model::profile::locale::Locale;
+use derive_more::Constructor;
+use semval::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Constructor, Default)]
+pub struct Editor(pub UserId);
+
+impl From<&Editor> for super::user::User {
+    fn from(e: &Editor) -> Self {
+        super::user::User::new(e.0.clone())
+    }
+}
+
+impl ProfileCreator for Editor {
+    fn create_profile(
+        &self,
+        name: String,
+        display_name: String,
+        avatar: String,
+        locale: Option<Locale>,
+    ) -> Result<Profile, ValidationContext<ProfileInvalidity>> {
+        super::user::User::from(self).create_profile(name, display_name, avatar, locale)
+    }
+}
+
+pub mod name_moderator_ability;
@@ -0,0 +1,15 @@
+use super::Editor;
+use crate::ability::name_moderator::NameModerator;
+use crate::model::profile::user_name::UserName;
+use crate::model::user::UserId;
+use crate::model::user_profile::UserProfile;
+
+impl NameModerator for Editor {
+    fn moderator_id(&self) -> UserId {
+        UserId::new(self.0 .0.clone())
+    }
+
+    fn reset_user_name(&self, target: &mut UserProfile) {
+        target.profile.name = UserName::placeholder(&target.id.0);
+    }
+}
@@ -1 +1,3 @@
+pub mod admin;
+pub mod editor;
 pub mod user;
@@ -0,0 +1,34 @@
+use super::User;
+use crate::ability::audit_viewer::AuditViewer;
+use crate::effect::audit_log::AuditEventCondition;
+use crate::model::user::UserId;
+
+impl AuditViewer for User {
+    fn restrict(&self, condition: AuditEventCondition) -> AuditEventCondition {
+        AuditEventCondition {
+            actor: Some(UserId::new(self.0 .0.clone())),
+            ..condition
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::User;
+    use crate::ability::audit_viewer::AuditViewer;
+    use crate::actor::user::UserId as ActorUserId;
+    use crate::effect::audit_log::AuditEventCondition;
+    use crate::model::audit_event::AuditAction;
+    use crate::model::user::UserId;
+
+    #[test]
+    fn restrict_replace_actor_with_self() {
+        let user = User::new(ActorUserId::new("foo".to_string()));
+        let condition = user.restrict(AuditEventCondition::new(
+            Some(UserId::new("bar".to_string())),
+            Some(AuditAction::ProfileUpdated),
+        ));
+        assert_eq!(condition.actor, Some(UserId::new("foo".to_string())));
+        assert_eq!(condition.action, Some(AuditAction::ProfileUpdated));
+    }
+}
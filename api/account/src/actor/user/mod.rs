@@ -7,4 +7,5 @@ pub struct UserId(pub String);
 #[derive(Debug, Clone, Serialize, Deserialize, Constructor, Default)]
 pub struct User(pub UserId);
 
+pub mod audit_viewer_ability;
 pub mod profile_creator_ability;
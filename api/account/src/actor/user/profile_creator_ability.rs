@@ -3,6 +3,7 @@ use crate::ability::profile_creator::ProfileCreator;
 use crate::model::profile::avatar::Avatar;
 use crate::model::profile::display_name::DisplayName;
 use crate::model::profile::entity::{Profile, ProfileInvalidity};
+use crate::model::profile::locale::Locale;
 use crate::model::profile::user_name::UserName;
 use semval::prelude::*;
 
@@ -12,11 +13,13 @@ impl ProfileCreator for User {
         name: String,
         display_name: String,
         avatar: String,
+        locale: Option<Locale>,
     ) -> Result<Profile, ValidationContext<ProfileInvalidity>> {
         let profile = Profile {
             name: UserName(name),
             display_name: DisplayName(display_name),
             avatar: Avatar { url: avatar },
+            locale,
         };
 
         profile.validate()?;
@@ -35,7 +38,8 @@ mod tests {
         let profile_result = user.create_profile(
             "xxx".to_string(),
             "yyyy".to_string(),
-            "https://lh3.googleusercontent.com/a-/AOh14GiibtsjDIo7GRGVpJQxg3pD0azpzNvYaeI7v9Lldg=s288-p-no".to_string()
+            "https://lh3.googleusercontent.com/a-/AOh14GiibtsjDIo7GRGVpJQxg3pD0azpzNvYaeI7v9Lldg=s288-p-no".to_string(),
+            None,
         );
         assert!(profile_result.is_ok());
     }
@@ -47,6 +51,7 @@ mod tests {
             "xxx".to_string(),
             "yyyy".to_string(),
             "adfasdfasdasd".to_string(),
+            None,
         );
         assert!(profile_result.is_err());
     }
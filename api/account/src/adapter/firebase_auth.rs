@@ -1,6 +1,8 @@
 use async_trait::async_trait;
 use derive_more::{Constructor, Deref, Display};
+use std::fmt;
 use thiserror::Error;
+use time::OffsetDateTime;
 
 use crate::effect::config::HaveConfig;
 
@@ -37,6 +39,8 @@ pub enum VerifyError {
 pub struct VerifyResult {
     pub uid: LocalId,
     pub full_name: FullName,
+    /// `exp` claim of the verified token.
+    pub expires_at: Option<OffsetDateTime>,
 }
 
 #[derive(Debug, Constructor, Clone, PartialEq, Eq, Deref, Display, Default)]
@@ -48,9 +52,16 @@ pub struct FederatedId(pub String);
 #[derive(Debug, Constructor, Clone, PartialEq, Eq, Deref, Display, Default)]
 pub struct FullName(pub String);
 
-#[derive(Debug, Constructor, Clone, PartialEq, Eq, Deref)]
+#[derive(Constructor, Clone, PartialEq, Eq, Deref)]
 pub struct AccessToken(pub String);
 
+/// Redacted, so that spans and logs never carry the token.
+impl fmt::Debug for AccessToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AccessToken(<redacted>)")
+    }
+}
+
 #[async_trait]
 pub trait FirebaseAuthDriver: HaveConfig {
     async fn verify(&self, token: AccessToken) -> Result<VerifyResult, VerifyError>;
@@ -0,0 +1,85 @@
+use crate::effect::clock::{Clock, HaveClock};
+use crate::effect::id_generator::{HaveIdGenerator, IdGenerator};
+use crate::model::audit_event::{
+    AuditAction, AuditContext, AuditEvent, AuditEventId, AuditOutcome,
+};
+use crate::model::user::UserId;
+use crate::repository::meta::{Page, Pagination};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use thiserror::Error;
+use tracing::warn;
+
+#[derive(Error, Debug)]
+pub enum AuditLogError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// Filters for `AuditLog::search`. Every `None` field matches all events.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Constructor)]
+pub struct AuditEventCondition {
+    pub actor: Option<UserId>,
+    pub action: Option<AuditAction>,
+}
+
+/// Append-only sink for security relevant events.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait AuditLog {
+    async fn record(&self, event: &AuditEvent) -> Result<(), AuditLogError>;
+    /// Newest events first.
+    async fn search(
+        &self,
+        condition: &AuditEventCondition,
+        pagination: &Pagination,
+    ) -> Result<Page<AuditEvent>, AuditLogError>;
+}
+
+#[cfg_attr(test, mockall::automock(type AuditLog = MockAuditLog;))]
+pub trait HaveAuditLog {
+    type AuditLog: AuditLog + Send + Sync + 'static;
+    fn audit_log(&self) -> &Self::AuditLog;
+}
+
+/// Builds an `AuditEvent` stamped with a fresh id and the current time and records it.
+#[async_trait]
+pub trait AuditRecorder: HaveAuditLog + HaveIdGenerator + HaveClock {
+    async fn audit(
+        &self,
+        context: &AuditContext,
+        actor: Option<UserId>,
+        action: AuditAction,
+        target: Option<String>,
+        outcome: AuditOutcome,
+    ) -> Result<(), AuditLogError> {
+        let event = AuditEvent::new(
+            AuditEventId::new(self.id_generator().generate()),
+            actor,
+            action,
+            target,
+            context.clone(),
+            outcome,
+            self.clock().now_utc(),
+        );
+        self.audit_log().record(&event).await
+    }
+
+    /// Same as `audit`, for an event about a change already stored or a request already
+    /// answered: failing to record it is logged, rather than failing a use case that can no
+    /// longer be undone.
+    async fn audit_or_warn(
+        &self,
+        context: &AuditContext,
+        actor: Option<UserId>,
+        action: AuditAction,
+        target: Option<String>,
+        outcome: AuditOutcome,
+    ) {
+        if let Err(e) = self.audit(context, actor, action, target, outcome).await {
+            warn!(?action, "failed to record audit event: {}", e);
+        }
+    }
+}
+
+impl<T: HaveAuditLog + HaveIdGenerator + HaveClock> AuditRecorder for T {}
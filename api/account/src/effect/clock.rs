@@ -1,20 +1,22 @@
 use derive_more::Constructor;
 use time::OffsetDateTime;
 
-trait Clock {
-    fn now_utc() -> OffsetDateTime;
+#[cfg_attr(test, mockall::automock)]
+pub trait Clock {
+    fn now_utc(&self) -> OffsetDateTime;
 }
 
 #[derive(Debug, Clone, Copy, Constructor)]
 pub struct DefaultClock;
 
 impl Clock for DefaultClock {
-    fn now_utc() -> OffsetDateTime {
+    fn now_utc(&self) -> OffsetDateTime {
         OffsetDateTime::now_utc()
     }
 }
 
-trait HaveClock {
-    type Clock: Clock;
-    fn clock(&self) -> Self::Clock;
+#[cfg_attr(test, mockall::automock(type Clock = MockClock;))]
+pub trait HaveClock {
+    type Clock: Clock + Send + Sync + 'static;
+    fn clock(&self) -> &Self::Clock;
 }
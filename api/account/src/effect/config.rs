@@ -1,7 +1,7 @@
 #[cfg_attr(test, mockall::automock)]
 pub trait Config {
     fn firebase_project_id(&self) -> &str;
-    fn max_connections(&self) -> &u32;
+    fn device_verification_uri(&self) -> &str;
 }
 
 #[cfg_attr(test, mockall::automock(type Config = MockConfig;))]
@@ -1,3 +1,7 @@
+pub mod audit_log;
 pub mod clock;
 pub mod config;
 pub mod id_generator;
+pub mod rate_limit_store;
+pub mod user_code_generator;
+pub mod verified_token_cache;
@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+use derive_more::Constructor;
+use thiserror::Error;
+use tracing::warn;
+
+#[derive(Error, Debug)]
+pub enum RateLimitStoreError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// At most `limit` hits per fixed window of `window_seconds`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Constructor)]
+pub struct RateLimitRule {
+    pub limit: i64,
+    pub window_seconds: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    Allowed,
+    /// Rejected until the current window closes, `retry_after` seconds from now.
+    Limited {
+        retry_after: i64,
+    },
+}
+
+impl RateLimitRule {
+    /// Start of the window containing `now`, in unix seconds.
+    pub fn window_start(&self, now: i64) -> i64 {
+        now - now.rem_euclid(self.window_seconds)
+    }
+
+    pub fn decide(&self, count: i64, now: i64) -> RateLimitDecision {
+        if count <= self.limit {
+            return RateLimitDecision::Allowed;
+        }
+        RateLimitDecision::Limited {
+            retry_after: self.window_start(now) + self.window_seconds - now,
+        }
+    }
+}
+
+/// Counter of hits per key and window, shared by every replica that uses the same store.
+#[async_trait]
+pub trait RateLimitStore {
+    /// Adds one hit to `key` in the window starting at `window_start` and returns the hits so far.
+    async fn increment(
+        &self,
+        key: &str,
+        window_start: i64,
+        window_seconds: i64,
+    ) -> Result<i64, RateLimitStoreError>;
+}
+
+#[cfg(test)]
+mockall::mock! {
+    pub RateLimitStore {}
+
+    impl Clone for RateLimitStore {
+        fn clone(&self) -> Self;
+    }
+
+    #[async_trait]
+    impl RateLimitStore for RateLimitStore {
+        async fn increment(
+            &self,
+            key: &str,
+            window_start: i64,
+            window_seconds: i64,
+        ) -> Result<i64, RateLimitStoreError>;
+    }
+}
+
+pub trait HaveRateLimitStore {
+    type RateLimitStore: RateLimitStore + Clone + Send + Sync + 'static;
+    fn rate_limit_store(&self) -> &Self::RateLimitStore;
+}
+
+/// Counts a hit of `key` under `rule` at `now`, in unix seconds.
+pub async fn hit<S>(store: &S, key: &str, rule: RateLimitRule, now: i64) -> RateLimitDecision
+where
+    S: RateLimitStore + Sync + ?Sized,
+{
+    match store
+        .increment(key, rule.window_start(now), rule.window_seconds)
+        .await
+    {
+        Ok(count) => rule.decide(count, now),
+        Err(e) => {
+            // Fail open: an unavailable store must not lock every user out.
+            warn!("rate limit store failed: {}", e);
+            RateLimitDecision::Allowed
+        }
+    }
+}
+
+/// Bucket of a route counting the requests of one verified identity, so that a user is limited
+/// whatever address the requests come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Constructor)]
+pub struct IdentityRateLimit {
+    pub route: &'static str,
+    pub rule: RateLimitRule,
+}
+
+impl IdentityRateLimit {
+    /// Counts a request of `identity`, which must be verified so that nobody can drain the
+    /// bucket of another user.
+    pub async fn check<S>(&self, store: &S, identity: &str, now: i64) -> RateLimitDecision
+    where
+        S: RateLimitStore + Sync + ?Sized,
+    {
+        hit(
+            store,
+            &format!("{}:id:{}", self.route, identity),
+            self.rule,
+            now,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdentityRateLimit, MockRateLimitStore, RateLimitDecision, RateLimitRule};
+    use anyhow::anyhow;
+
+    #[test]
+    fn decide_allow_up_to_limit() {
+        let rule = RateLimitRule::new(2, 60);
+        assert_eq!(rule.decide(2, 130), RateLimitDecision::Allowed);
+    }
+
+    #[test]
+    fn decide_limit_until_window_closes() {
+        let rule = RateLimitRule::new(2, 60);
+        assert_eq!(rule.window_start(130), 120);
+        assert_eq!(
+            rule.decide(3, 130),
+            RateLimitDecision::Limited { retry_after: 50 }
+        );
+    }
+
+    #[tokio::test]
+    async fn identity_rate_limit_key_bucket_on_route_and_identity() {
+        let mut store = MockRateLimitStore::new();
+        store
+            .expect_increment()
+            .withf(|key, window_start, window_seconds| {
+                key == "verify:id:alice" && *window_start == 120 && *window_seconds == 60
+            })
+            .returning(|_, _, _| Ok(2));
+        let limit = IdentityRateLimit::new("verify", RateLimitRule::new(1, 60));
+
+        assert_eq!(
+            limit.check(&store, "alice", 130).await,
+            RateLimitDecision::Limited { retry_after: 50 }
+        );
+    }
+
+    #[tokio::test]
+    async fn identity_rate_limit_allow_when_store_fails() {
+        let mut store = MockRateLimitStore::new();
+        store
+            .expect_increment()
+            .returning(|_, _, _| Err(anyhow!("unavailable").into()));
+        let limit = IdentityRateLimit::new("verify", RateLimitRule::new(1, 60));
+
+        assert_eq!(
+            limit.check(&store, "alice", 130).await,
+            RateLimitDecision::Allowed
+        );
+    }
+}
@@ -0,0 +1,10 @@
+#[cfg_attr(test, mockall::automock)]
+pub trait UserCodeGenerator {
+    fn generate(&self) -> String;
+}
+
+#[cfg_attr(test, mockall::automock(type UserCodeGenerator = MockUserCodeGenerator;))]
+pub trait HaveUserCodeGenerator {
+    type UserCodeGenerator: UserCodeGenerator;
+    fn user_code_generator(&self) -> &Self::UserCodeGenerator;
+}
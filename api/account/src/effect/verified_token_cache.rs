@@ -0,0 +1,28 @@
+use crate::model::user::{User, UserId};
+use time::OffsetDateTime;
+
+/// Users resolved from recently verified tokens, so repeated requests with the same token skip
+/// signature checks and the user lookup.
+///
+/// Entries are local to a process. `invalidate_user` only reaches the replica it is called on,
+/// so other replicas may serve a stale user until their entries expire.
+#[cfg_attr(test, mockall::automock)]
+pub trait VerifiedTokenCache {
+    fn get(&self, token: &str, now: OffsetDateTime) -> Option<User>;
+    /// Keeps `user` until the token expires or the cache ttl elapses, whichever comes first.
+    fn put(
+        &self,
+        token: &str,
+        user: &User,
+        token_expires_at: Option<OffsetDateTime>,
+        now: OffsetDateTime,
+    );
+    /// Drops every cached token resolving to `user_id`.
+    fn invalidate_user(&self, user_id: &UserId);
+}
+
+#[cfg_attr(test, mockall::automock(type VerifiedTokenCache = MockVerifiedTokenCache;))]
+pub trait HaveVerifiedTokenCache {
+    type VerifiedTokenCache: VerifiedTokenCache + Send + Sync + 'static;
+    fn verified_token_cache(&self) -> &Self::VerifiedTokenCache;
+}
@@ -0,0 +1,138 @@
+use crate::model::meta::{AggregateRoot, Entity, Identifier};
+use crate::model::user::UserId;
+use derive_more::{Constructor, Deref};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use time::OffsetDateTime;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Deref, Constructor, Default, Serialize, Deserialize, ToSchema, TS,
+)]
+pub struct AuditEventId(pub String);
+
+impl Identifier for AuditEventId {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, TS)]
+pub enum AuditAction {
+    SignedUp,
+    ProviderLinked,
+    Authenticated,
+    ProfileUpdated,
+    RoleGranted,
+    RoleRevoked,
+    UserDisabled,
+    UserEnabled,
+    UserNameReset,
+}
+
+#[derive(Error, Debug, Constructor)]
+#[error("Failed audit action convert. source: {action}")]
+pub struct AuditActionConvertError {
+    action: String,
+}
+
+impl TryFrom<String> for AuditAction {
+    type Error = AuditActionConvertError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "SignedUp" => Ok(AuditAction::SignedUp),
+            "ProviderLinked" => Ok(AuditAction::ProviderLinked),
+            "Authenticated" => Ok(AuditAction::Authenticated),
+            "ProfileUpdated" => Ok(AuditAction::ProfileUpdated),
+            "RoleGranted" => Ok(AuditAction::RoleGranted),
+            "RoleRevoked" => Ok(AuditAction::RoleRevoked),
+            "UserDisabled" => Ok(AuditAction::UserDisabled),
+            "UserEnabled" => Ok(AuditAction::UserEnabled),
+            "UserNameReset" => Ok(AuditAction::UserNameReset),
+            _ => Err(AuditActionConvertError::new(value)),
+        }
+    }
+}
+
+impl From<&AuditAction> for String {
+    fn from(a: &AuditAction) -> Self {
+        match a {
+            AuditAction::SignedUp => "SignedUp",
+            AuditAction::ProviderLinked => "ProviderLinked",
+            AuditAction::Authenticated => "Authenticated",
+            AuditAction::ProfileUpdated => "ProfileUpdated",
+            AuditAction::RoleGranted => "RoleGranted",
+            AuditAction::RoleRevoked => "RoleRevoked",
+            AuditAction::UserDisabled => "UserDisabled",
+            AuditAction::UserEnabled => "UserEnabled",
+            AuditAction::UserNameReset => "UserNameReset",
+        }
+        .to_string()
+    }
+}
+
+/// Whether the audited action took effect. A failure carries the reason it was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, TS)]
+#[serde(tag = "status", content = "reason")]
+pub enum AuditOutcome {
+    Success,
+    Failure(String),
+}
+
+/// Where a request came from, as seen by the transport layer.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Constructor, Serialize, Deserialize, ToSchema, TS,
+)]
+pub struct AuditContext {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Constructor, Serialize, ToSchema, TS)]
+pub struct AuditEvent {
+    pub id: AuditEventId,
+    pub actor: Option<UserId>,
+    pub action: AuditAction,
+    pub target: Option<String>,
+    #[serde(flatten)]
+    pub context: AuditContext,
+    pub outcome: AuditOutcome,
+    #[serde(with = "time::serde::rfc3339")]
+    #[ts(type = "string")]
+    pub occurred_at: OffsetDateTime,
+}
+
+impl Entity<AuditEventId> for AuditEvent {
+    fn id(&self) -> &AuditEventId {
+        &self.id
+    }
+}
+
+impl AggregateRoot<AuditEventId> for AuditEvent {}
+
+#[cfg(test)]
+mod tests {
+    use super::AuditAction;
+
+    #[test]
+    fn audit_action_round_trip_string() {
+        for action in [
+            AuditAction::SignedUp,
+            AuditAction::ProviderLinked,
+            AuditAction::Authenticated,
+            AuditAction::ProfileUpdated,
+            AuditAction::RoleGranted,
+            AuditAction::RoleRevoked,
+            AuditAction::UserDisabled,
+            AuditAction::UserEnabled,
+            AuditAction::UserNameReset,
+        ] {
+            assert_eq!(
+                AuditAction::try_from(String::from(&action)).unwrap(),
+                action
+            );
+        }
+    }
+
+    #[test]
+    fn audit_action_is_err_when_unknown() {
+        assert!(AuditAction::try_from("Unknown".to_string()).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+use crate::model::meta::{AggregateRoot, Entity, Identifier};
+use crate::model::user::UserId;
+use derive_more::{Constructor, Deref, Display};
+use serde::Serialize;
+use std::fmt;
+use thiserror::Error;
+use time::{Duration, OffsetDateTime};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// Lifetime of an issued device code in seconds (RFC 8628 `expires_in`).
+pub const DEVICE_CODE_EXPIRES_IN: i64 = 600;
+/// Seconds an expired device code is kept, so that a client polling late is told it expired
+/// rather than that it is unknown.
+pub const DEVICE_CODE_RETENTION: i64 = DEVICE_CODE_EXPIRES_IN;
+/// Minimum polling interval in seconds (RFC 8628 `interval`).
+pub const DEVICE_CODE_INTERVAL: i64 = 5;
+/// Seconds added to the interval each time a client polls too fast.
+pub const SLOW_DOWN_STEP: i64 = 5;
+/// `grant_type` value a device uses when polling the token endpoint.
+pub const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+#[derive(Clone, PartialEq, Eq, Deref, Constructor, Default, Serialize, Display, ToSchema, TS)]
+pub struct DeviceCode(pub String);
+
+/// Redacted, so that spans and logs never carry the device code.
+impl fmt::Debug for DeviceCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DeviceCode(<redacted>)")
+    }
+}
+
+impl Identifier for DeviceCode {}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Deref, Constructor, Default, Serialize, Display, ToSchema, TS,
+)]
+pub struct UserCode(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Deref, Constructor, Default, Serialize, Display)]
+pub struct ClientId(pub String);
+
+/// Space-delimited scope string as defined in RFC 6749 section 3.3.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Deref, Constructor, Default, Serialize, Display, ToSchema, TS,
+)]
+pub struct Scope(pub String);
+
+impl Scope {
+    /// Whether `scope` is one of the space-delimited scopes granted.
+    pub fn grants(&self, scope: &str) -> bool {
+        self.0.split(' ').any(|granted| granted == scope)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum DeviceAuthorizationStatus {
+    Pending,
+    Approved(UserId),
+    Denied,
+    Consumed,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ApproveError {
+    #[error("Device code expired.")]
+    Expired,
+    #[error("Device code is already processed.")]
+    AlreadyProcessed,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum PollError {
+    #[error("Authorization pending.")]
+    AuthorizationPending,
+    #[error("Polling too fast. (interval: {0})")]
+    SlowDown(i64),
+    #[error("Access denied.")]
+    AccessDenied,
+    #[error("Device code expired.")]
+    Expired,
+    #[error("Device code is already consumed.")]
+    AlreadyConsumed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceAuthorization {
+    pub device_code: DeviceCode,
+    pub user_code: UserCode,
+    pub client_id: ClientId,
+    pub scope: Scope,
+    pub status: DeviceAuthorizationStatus,
+    pub interval: i64,
+    pub expires_at: OffsetDateTime,
+    pub last_polled_at: Option<OffsetDateTime>,
+}
+
+impl DeviceAuthorization {
+    pub fn issue(
+        device_code: DeviceCode,
+        user_code: UserCode,
+        client_id: ClientId,
+        scope: Scope,
+        now: OffsetDateTime,
+    ) -> Self {
+        Self {
+            device_code,
+            user_code,
+            client_id,
+            scope,
+            status: DeviceAuthorizationStatus::Pending,
+            interval: DEVICE_CODE_INTERVAL,
+            expires_at: now + Duration::seconds(DEVICE_CODE_EXPIRES_IN),
+            last_polled_at: None,
+        }
+    }
+
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expires_at <= now
+    }
+
+    pub fn approve(&mut self, user_id: UserId, now: OffsetDateTime) -> Result<(), ApproveError> {
+        self.decide(DeviceAuthorizationStatus::Approved(user_id), now)
+    }
+
+    pub fn deny(&mut self, now: OffsetDateTime) -> Result<(), ApproveError> {
+        self.decide(DeviceAuthorizationStatus::Denied, now)
+    }
+
+    fn decide(
+        &mut self,
+        status: DeviceAuthorizationStatus,
+        now: OffsetDateTime,
+    ) -> Result<(), ApproveError> {
+        if self.is_expired(now) {
+            return Err(ApproveError::Expired);
+        }
+        if self.status != DeviceAuthorizationStatus::Pending {
+            return Err(ApproveError::AlreadyProcessed);
+        }
+        self.status = status;
+        Ok(())
+    }
+
+    /// Records a token request from the device and returns the approving user once.
+    ///
+    /// Polling faster than `interval` is rejected with `SlowDown` and widens the
+    /// interval, as required by RFC 8628 section 3.5.
+    pub fn poll(&mut self, now: OffsetDateTime) -> Result<UserId, PollError> {
+        if self.is_expired(now) {
+            return Err(PollError::Expired);
+        }
+        let too_fast = self
+            .last_polled_at
+            .map(|last| now < last + Duration::seconds(self.interval))
+            .unwrap_or(false);
+        self.last_polled_at = Some(now);
+        if too_fast {
+            self.interval += SLOW_DOWN_STEP;
+            return Err(PollError::SlowDown(self.interval));
+        }
+        match &self.status {
+            DeviceAuthorizationStatus::Pending => Err(PollError::AuthorizationPending),
+            DeviceAuthorizationStatus::Denied => Err(PollError::AccessDenied),
+            DeviceAuthorizationStatus::Consumed => Err(PollError::AlreadyConsumed),
+            DeviceAuthorizationStatus::Approved(user_id) => {
+                let user_id = user_id.clone();
+                self.status = DeviceAuthorizationStatus::Consumed;
+                Ok(user_id)
+            }
+        }
+    }
+}
+
+impl Entity<DeviceCode> for DeviceAuthorization {
+    fn id(&self) -> &DeviceCode {
+        &self.device_code
+    }
+}
+
+impl AggregateRoot<DeviceCode> for DeviceAuthorization {}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ApproveError, ClientId, DeviceAuthorization, DeviceAuthorizationStatus, DeviceCode,
+        PollError, Scope, UserCode, DEVICE_CODE_EXPIRES_IN, DEVICE_CODE_INTERVAL, SLOW_DOWN_STEP,
+    };
+    use crate::model::user::UserId;
+    use time::{Duration, OffsetDateTime};
+
+    fn issued(now: OffsetDateTime) -> DeviceAuthorization {
+        DeviceAuthorization::issue(
+            DeviceCode::new("device".to_string()),
+            UserCode::new("BCDF-GHJK".to_string()),
+            ClientId::new("cli".to_string()),
+            Scope::new("profile".to_string()),
+            now,
+        )
+    }
+
+    #[test]
+    fn poll_is_pending_before_approval() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let mut authorization = issued(now);
+        assert_eq!(
+            authorization.poll(now),
+            Err(PollError::AuthorizationPending)
+        );
+    }
+
+    #[test]
+    fn poll_return_to_user_id_once_after_approval() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let mut authorization = issued(now);
+        authorization
+            .approve(UserId::new("foo".to_string()), now)
+            .unwrap();
+        assert_eq!(authorization.poll(now), Ok(UserId::new("foo".to_string())));
+        assert_eq!(authorization.status, DeviceAuthorizationStatus::Consumed);
+
+        let later = now + Duration::seconds(DEVICE_CODE_INTERVAL);
+        assert_eq!(authorization.poll(later), Err(PollError::AlreadyConsumed));
+    }
+
+    #[test]
+    fn poll_is_slow_down_when_polling_faster_than_interval() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let mut authorization = issued(now);
+        let _ = authorization.poll(now);
+        assert_eq!(
+            authorization.poll(now + Duration::seconds(1)),
+            Err(PollError::SlowDown(DEVICE_CODE_INTERVAL + SLOW_DOWN_STEP))
+        );
+    }
+
+    #[test]
+    fn poll_is_access_denied_when_denied() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let mut authorization = issued(now);
+        authorization.deny(now).unwrap();
+        assert_eq!(authorization.poll(now), Err(PollError::AccessDenied));
+    }
+
+    #[test]
+    fn poll_and_approve_is_expired_after_expires_in() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let expired = now + Duration::seconds(DEVICE_CODE_EXPIRES_IN);
+        let mut authorization = issued(now);
+        assert_eq!(authorization.poll(expired), Err(PollError::Expired));
+        assert_eq!(
+            authorization.approve(UserId::new("foo".to_string()), expired),
+            Err(ApproveError::Expired)
+        );
+    }
+
+    #[test]
+    fn scope_grants_each_space_delimited_scope() {
+        let scope = Scope::new("profile audit".to_string());
+
+        assert!(scope.grants("profile"));
+        assert!(scope.grants("audit"));
+        assert!(!scope.grants("prof"));
+        assert!(!Scope::default().grants("profile"));
+    }
+
+    #[test]
+    fn approve_is_err_when_already_processed() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let mut authorization = issued(now);
+        authorization.deny(now).unwrap();
+        assert_eq!(
+            authorization.approve(UserId::new("foo".to_string()), now),
+            Err(ApproveError::AlreadyProcessed)
+        );
+    }
+}
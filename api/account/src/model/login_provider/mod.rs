@@ -1,9 +1,12 @@
 use derive_more::Constructor;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, ToSchema, TS)]
 pub enum ProviderKind {
+    #[default]
     Google,
 }
 
@@ -31,16 +34,10 @@ impl From<&ProviderKind> for String {
     }
 }
 
-impl Default for ProviderKind {
-    fn default() -> Self {
-        Self::Google
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Constructor, Default, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Constructor, Default, Serialize, ToSchema, TS)]
 pub struct IdInProvider(pub String);
 
-#[derive(Debug, Clone, PartialEq, Eq, Constructor, Default, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Constructor, Default, Serialize, ToSchema, TS)]
 pub struct LoginProvider {
     pub kind: ProviderKind,
     pub id_in_provider: IdInProvider,
@@ -9,6 +9,7 @@ pub trait Entity<T: Identifier> {
 pub trait AggregateRoot<T: Identifier>: Entity<T> {}
 
 #[cfg(test)]
+#[allow(dead_code)]
 mod tests {
     use super::{AggregateRoot, Entity, Identifier};
     use derive_more::Deref;
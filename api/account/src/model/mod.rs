@@ -1,5 +1,9 @@
+pub mod audit_event;
+pub mod device_authorization;
 pub mod login_provider;
 pub mod meta;
 pub mod profile;
+pub mod role;
+pub mod scoped_token;
 pub mod user;
 pub mod user_profile;
@@ -1,9 +1,11 @@
 use derive_more::Constructor;
 use semval::prelude::*;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 use url::Url;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, Constructor)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, Constructor, ToSchema, TS)]
 pub struct Avatar {
     pub url: String,
 }
@@ -1,5 +1,7 @@
 use derive_more::{Constructor, Deref};
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
 
-#[derive(Constructor, Deref, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Constructor, Deref, Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema, TS)]
 pub struct DisplayName(pub String);
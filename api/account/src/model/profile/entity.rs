@@ -1,15 +1,21 @@
 use super::avatar::{Avatar, AvatarInvalidity};
 use super::display_name::DisplayName;
+use super::locale::Locale;
 use super::user_name::{UserName, UserNameInvalidity};
 use derive_more::Constructor;
 use semval::prelude::*;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Constructor, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Constructor, PartialEq, Eq, ToSchema, TS)]
 pub struct Profile {
     pub name: UserName,
     pub display_name: DisplayName,
     pub avatar: Avatar,
+    /// Chosen by the user; `None` leaves the language to the `Accept-Language` of each request.
+    #[serde(default)]
+    pub locale: Option<Locale>,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
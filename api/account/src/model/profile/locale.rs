@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// Language the user reads messages in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    Ja,
+    En,
+}
+
+impl Locale {
+    /// Locale of a BCP 47 language tag such as `ja-JP`, matched on its primary language subtag.
+    pub fn from_language_tag(tag: &str) -> Option<Self> {
+        let primary = tag.trim().split(['-', '_']).next()?;
+        if primary.eq_ignore_ascii_case("ja") {
+            Some(Locale::Ja)
+        } else if primary.eq_ignore_ascii_case("en") {
+            Some(Locale::En)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Failed locale convert. source: {0}")]
+pub struct LocaleConvertError(String);
+
+impl TryFrom<String> for Locale {
+    type Error = LocaleConvertError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "ja" => Ok(Locale::Ja),
+            "en" => Ok(Locale::En),
+            _ => Err(LocaleConvertError(value)),
+        }
+    }
+}
+
+impl From<&Locale> for String {
+    fn from(l: &Locale) -> Self {
+        match l {
+            Locale::Ja => "ja".to_string(),
+            Locale::En => "en".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Locale;
+
+    #[test]
+    fn from_language_tag_match_primary_subtag() {
+        assert_eq!(Locale::from_language_tag("ja"), Some(Locale::Ja));
+        assert_eq!(Locale::from_language_tag("ja-JP"), Some(Locale::Ja));
+        assert_eq!(Locale::from_language_tag("EN-us"), Some(Locale::En));
+        assert_eq!(Locale::from_language_tag("fr-FR"), None);
+        assert_eq!(Locale::from_language_tag("*"), None);
+    }
+
+    #[test]
+    fn try_from_string_round_trip() {
+        for locale in [Locale::Ja, Locale::En] {
+            assert_eq!(Locale::try_from(String::from(&locale)).unwrap(), locale);
+        }
+        assert!(Locale::try_from("fr".to_string()).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 pub mod avatar;
 pub mod display_name;
 pub mod entity;
+pub mod locale;
 pub mod user_name;
@@ -1,8 +1,10 @@
 use derive_more::{Constructor, Deref};
 use semval::prelude::*;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
 
-#[derive(Deref, Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Constructor)]
+#[derive(Deref, Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Constructor, ToSchema, TS)]
 pub struct UserName(pub String);
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -12,12 +14,17 @@ pub enum UserNameInvalidity {
     Format,
 }
 
+impl UserName {
+    pub const MIN_LENGTH: usize = 2;
+    pub const MAX_LENGTH: usize = 20;
+}
+
 impl Validate for UserName {
     type Invalidity = UserNameInvalidity;
     fn validate(&self) -> ValidationResult<Self::Invalidity> {
         ValidationContext::new()
-            .invalidate_if(self.len() < 2, Self::Invalidity::MinLength)
-            .invalidate_if(20 < self.len(), Self::Invalidity::MaxLength)
+            .invalidate_if(self.len() < Self::MIN_LENGTH, Self::Invalidity::MinLength)
+            .invalidate_if(Self::MAX_LENGTH < self.len(), Self::Invalidity::MaxLength)
             .invalidate_if(
                 self.chars().filter(|c| c.is_ascii_uppercase()).count() != 0,
                 Self::Invalidity::Format,
@@ -33,6 +40,19 @@ impl Validate for UserName {
     }
 }
 
+impl UserName {
+    /// Neutral name derived from the owner's id, used when an admin resets an abusive name.
+    pub fn placeholder(owner_id: &str) -> Self {
+        let suffix: String = owner_id
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .take(15)
+            .collect();
+        UserName(format!("user_{}", suffix))
+    }
+}
+
 impl TryFrom<String> for UserName {
     type Error = (UserName, ValidationContext<UserNameInvalidity>);
     fn try_from(value: String) -> Result<Self, Self::Error> {
@@ -143,6 +163,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_placeholder_user_name_is_valid() {
+        let name = UserName::placeholder("5F1C8E2A-0B3d-4c5e-9f00-aaaaaaaaaaaa");
+        assert_eq!(name, UserName("user_5f1c8e2a0b3d4c5".to_string()));
+        assert!(UserName::try_from(name.0).is_ok());
+    }
+
     #[test]
     fn test_try_from_string_user_name_is_err_when_21() {
         let name_result = UserName::try_from("vavavavavavavavavavaa".to_string());
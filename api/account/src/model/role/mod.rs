@@ -0,0 +1,57 @@
+use derive_more::Constructor;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema, TS)]
+pub enum Role {
+    Admin,
+    Editor,
+    Viewer,
+}
+
+#[derive(Error, Debug, Constructor)]
+#[error("Failed role convert. source: {role}")]
+pub struct RoleConvertError {
+    role: String,
+}
+
+impl TryFrom<String> for Role {
+    type Error = RoleConvertError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "Admin" => Ok(Role::Admin),
+            "Editor" => Ok(Role::Editor),
+            "Viewer" => Ok(Role::Viewer),
+            _ => Err(RoleConvertError::new(value)),
+        }
+    }
+}
+
+impl From<&Role> for String {
+    fn from(r: &Role) -> Self {
+        match r {
+            Role::Admin => "Admin".to_string(),
+            Role::Editor => "Editor".to_string(),
+            Role::Viewer => "Viewer".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Role;
+
+    #[test]
+    fn role_round_trip_through_string() {
+        for role in [Role::Admin, Role::Editor, Role::Viewer] {
+            assert_eq!(Role::try_from(String::from(&role)).unwrap(), role);
+        }
+    }
+
+    #[test]
+    fn role_try_from_unknown_string_is_err() {
+        assert!(Role::try_from("Owner".to_string()).is_err());
+    }
+}
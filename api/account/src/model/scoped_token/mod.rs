@@ -0,0 +1,69 @@
+use crate::model::device_authorization::Scope;
+use crate::model::meta::{AggregateRoot, Entity, Identifier};
+use crate::model::user::UserId;
+use derive_more::{Constructor, Deref};
+use serde::Serialize;
+use std::fmt;
+use time::{Duration, OffsetDateTime};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// Prefix that tells scoped tokens apart from Firebase ID tokens.
+pub const SCOPED_TOKEN_PREFIX: &str = "mtk_";
+/// Scope of a token allowed to read and update the profile of its user.
+pub const PROFILE_SCOPE: &str = "profile";
+/// Lifetime of a scoped token in seconds.
+pub const SCOPED_TOKEN_EXPIRES_IN: i64 = 60 * 60 * 24 * 30;
+
+#[derive(Clone, PartialEq, Eq, Deref, Constructor, Default, Serialize, ToSchema, TS)]
+pub struct ScopedTokenValue(pub String);
+
+/// Redacted, so that spans and logs never carry the token.
+impl fmt::Debug for ScopedTokenValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ScopedTokenValue(<redacted>)")
+    }
+}
+
+impl ScopedTokenValue {
+    pub fn is_scoped_token(token: &str) -> bool {
+        token.starts_with(SCOPED_TOKEN_PREFIX)
+    }
+}
+
+impl Identifier for ScopedTokenValue {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Constructor)]
+pub struct ScopedToken {
+    pub token: ScopedTokenValue,
+    pub user_id: UserId,
+    pub scope: Scope,
+    pub expires_at: OffsetDateTime,
+}
+
+impl ScopedToken {
+    pub fn issue(raw: String, user_id: UserId, scope: Scope, now: OffsetDateTime) -> Self {
+        Self::new(
+            ScopedTokenValue::new(format!("{}{}", SCOPED_TOKEN_PREFIX, raw)),
+            user_id,
+            scope,
+            now + Duration::seconds(SCOPED_TOKEN_EXPIRES_IN),
+        )
+    }
+
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expires_at <= now
+    }
+
+    pub fn expires_in(&self, now: OffsetDateTime) -> i64 {
+        (self.expires_at - now).whole_seconds().max(0)
+    }
+}
+
+impl Entity<ScopedTokenValue> for ScopedToken {
+    fn id(&self) -> &ScopedTokenValue {
+        &self.token
+    }
+}
+
+impl AggregateRoot<ScopedTokenValue> for ScopedToken {}
@@ -1,23 +1,86 @@
 use crate::model::meta::{AggregateRoot, Entity, Identifier};
 use derive_more::{Constructor, Deref};
 use serde::Serialize;
+use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
 
 use super::login_provider::LoginProvider;
+use super::role::Role;
 
-#[derive(Debug, Clone, PartialEq, Eq, Deref, Constructor, Default, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deref, Constructor, Default, Serialize, ToSchema, TS)]
 pub struct UserId(pub String);
 
 impl Identifier for UserId {}
 
-#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, ToSchema, TS)]
+pub enum UserStatus {
+    #[default]
+    Active,
+    Disabled,
+}
+
+#[derive(Error, Debug, Constructor)]
+#[error("Failed user status convert. source: {status}")]
+pub struct UserStatusConvertError {
+    status: String,
+}
+
+impl TryFrom<String> for UserStatus {
+    type Error = UserStatusConvertError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "Active" => Ok(UserStatus::Active),
+            "Disabled" => Ok(UserStatus::Disabled),
+            _ => Err(UserStatusConvertError::new(value)),
+        }
+    }
+}
+
+impl From<&UserStatus> for String {
+    fn from(s: &UserStatus) -> Self {
+        match s {
+            UserStatus::Active => "Active".to_string(),
+            UserStatus::Disabled => "Disabled".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, ToSchema, TS)]
 pub struct User {
     pub id: UserId,
     pub providers: Vec<LoginProvider>,
+    pub roles: Vec<Role>,
+    pub status: UserStatus,
+}
+
+impl From<User> for crate::actor::user::User {
+    fn from(u: User) -> Self {
+        crate::actor::user::User::new(crate::actor::user::UserId::new(u.id.0))
+    }
+}
+
+impl TryFrom<User> for crate::actor::admin::Admin {
+    type Error = User;
+    fn try_from(u: User) -> Result<Self, Self::Error> {
+        if !u.has_role(Role::Admin) {
+            return Err(u);
+        }
+        Ok(crate::actor::admin::Admin::new(
+            crate::actor::user::UserId::new(u.id.0),
+        ))
+    }
 }
 
-impl Into<crate::actor::user::User> for User {
-    fn into(self) -> crate::actor::user::User {
-        crate::actor::user::User::new(crate::actor::user::UserId::new(self.id.0))
+impl TryFrom<User> for crate::actor::editor::Editor {
+    type Error = User;
+    fn try_from(u: User) -> Result<Self, Self::Error> {
+        if !u.has_role(Role::Admin) && !u.has_role(Role::Editor) {
+            return Err(u);
+        }
+        Ok(crate::actor::editor::Editor::new(
+            crate::actor::user::UserId::new(u.id.0),
+        ))
     }
 }
 
@@ -26,8 +89,26 @@ impl User {
         User {
             id,
             providers: providers.unwrap_or_else(|| vec![] as Vec<LoginProvider>),
+            roles: vec![],
+            status: UserStatus::Active,
         }
     }
+
+    pub fn with_status(self, status: UserStatus) -> Self {
+        User { status, ..self }
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.status == UserStatus::Disabled
+    }
+
+    pub fn with_roles(self, roles: Vec<Role>) -> Self {
+        User { roles, ..self }
+    }
+
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles.contains(&role)
+    }
 }
 
 impl Entity<UserId> for User {
@@ -37,3 +118,31 @@ impl Entity<UserId> for User {
 }
 
 impl AggregateRoot<UserId> for User {}
+
+#[cfg(test)]
+mod tests {
+    use super::{User, UserId};
+    use crate::actor::admin::Admin;
+    use crate::actor::editor::Editor;
+    use crate::model::role::Role;
+
+    #[test]
+    fn try_into_admin_is_ok_when_user_has_admin_role() {
+        let user = User::new(UserId::new("foo".to_string()), None).with_roles(vec![Role::Admin]);
+        assert!(Admin::try_from(user.clone()).is_ok());
+        assert!(Editor::try_from(user).is_ok());
+    }
+
+    #[test]
+    fn try_into_admin_is_err_when_user_is_editor() {
+        let user = User::new(UserId::new("foo".to_string()), None).with_roles(vec![Role::Editor]);
+        assert!(Admin::try_from(user.clone()).is_err());
+        assert!(Editor::try_from(user).is_ok());
+    }
+
+    #[test]
+    fn try_into_editor_is_err_when_user_is_viewer() {
+        let user = User::new(UserId::new("foo".to_string()), None).with_roles(vec![Role::Viewer]);
+        assert!(Editor::try_from(user).is_err());
+    }
+}
@@ -3,9 +3,22 @@ use crate::model::meta::{AggregateRoot, Entity, Identifier};
 use crate::model::profile::entity::Profile;
 use derive_more::{Constructor, Deref, From};
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
 
 #[derive(
-    Debug, Clone, PartialEq, Eq, Deref, Constructor, Default, Serialize, Deserialize, From,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Deref,
+    Constructor,
+    Default,
+    Serialize,
+    Deserialize,
+    From,
+    ToSchema,
+    TS,
 )]
 pub struct UserProfileId(pub String);
 
@@ -17,7 +30,7 @@ impl From<UserId> for UserProfileId {
 
 impl Identifier for UserProfileId {}
 
-#[derive(Debug, Clone, Serialize, Deserialize, Constructor, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Constructor, PartialEq, Eq, ToSchema, TS)]
 pub struct UserProfile {
     pub id: UserProfileId,
     pub profile: Profile,
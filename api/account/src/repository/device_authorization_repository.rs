@@ -0,0 +1,67 @@
+use crate::model::device_authorization::{
+    DeviceAuthorization, DeviceAuthorizationStatus, DeviceCode, UserCode,
+};
+use crate::repository::meta::Repository;
+#[cfg(test)]
+use crate::repository::meta::ResolveError;
+use async_trait::async_trait;
+use thiserror::Error;
+use time::OffsetDateTime;
+
+#[cfg(test)]
+use mockall::mock;
+
+#[derive(Error, Debug)]
+pub enum StoreError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum FindByUserCodeError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[async_trait]
+pub trait DeviceAuthorizationRepository: Repository<DeviceCode, DeviceAuthorization> {
+    async fn store(&self, authorization: &DeviceAuthorization) -> Result<(), StoreError>;
+    /// Stores `authorization` only if its stored status is still `expected`, and returns
+    /// whether it did, so that of two requests deciding one device code only the first wins.
+    async fn store_if(
+        &self,
+        authorization: &DeviceAuthorization,
+        expected: &DeviceAuthorizationStatus,
+    ) -> Result<bool, StoreError>;
+    /// Only a digest of the device code is stored, so the authorization found has an empty
+    /// `device_code`. `store_if` finds the stored authorization by its user code.
+    async fn find_by_user_code(
+        &self,
+        user_code: &UserCode,
+    ) -> Result<Option<DeviceAuthorization>, FindByUserCodeError>;
+    /// Deletes the authorizations that expired before `before`, and returns how many.
+    async fn delete_expired(&self, before: OffsetDateTime) -> Result<u64, StoreError>;
+}
+
+pub trait HaveDeviceAuthorizationRepository {
+    type DeviceAuthorizationRepository: DeviceAuthorizationRepository + Send + Sync + 'static;
+    fn device_authorization_repository(&self) -> &Self::DeviceAuthorizationRepository;
+}
+
+#[cfg(test)]
+mock! {
+    pub DeviceAuthorizationRepository {}
+
+    #[async_trait]
+    impl Repository<DeviceCode, DeviceAuthorization> for DeviceAuthorizationRepository {
+        async fn resolve(&self, id: &DeviceCode) -> Result<Option<DeviceAuthorization>, ResolveError>;
+    }
+
+    #[async_trait]
+    impl DeviceAuthorizationRepository for DeviceAuthorizationRepository {
+        async fn store(&self, authorization: &DeviceAuthorization) -> Result<(), StoreError>;
+        async fn store_if(&self, authorization: &DeviceAuthorization, expected: &DeviceAuthorizationStatus) -> Result<bool, StoreError>;
+        async fn find_by_user_code(&self, user_code: &UserCode) -> Result<Option<DeviceAuthorization>, FindByUserCodeError>;
+        async fn delete_expired(&self, before: OffsetDateTime) -> Result<u64, StoreError>;
+    }
+}
@@ -1,8 +1,44 @@
 use crate::model::meta::{AggregateRoot, Identifier};
 use async_trait::async_trait;
+use derive_more::Constructor;
+use serde::{Deserialize, Serialize};
 
 use thiserror::Error;
 
+/// Upper bound of `Pagination::limit` accepted by paged queries.
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Constructor, Serialize, Deserialize)]
+pub struct Pagination {
+    pub offset: i64,
+    pub limit: i64,
+}
+
+impl Pagination {
+    /// Clamps client supplied values into `0..` and `1..=MAX_PAGE_SIZE`.
+    pub fn clamped(self) -> Self {
+        Self {
+            offset: self.offset.max(0),
+            limit: self.limit.clamp(1, MAX_PAGE_SIZE),
+        }
+    }
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            limit: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Constructor, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+}
+
 #[derive(Error, Debug)]
 pub enum ResolveError {
     #[error(transparent)]
@@ -1,3 +1,5 @@
+pub mod device_authorization_repository;
 pub mod meta;
+pub mod scoped_token_repository;
 pub mod user_profile_repository;
 pub mod user_repository;
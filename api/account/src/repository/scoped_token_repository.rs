@@ -0,0 +1,40 @@
+use crate::model::scoped_token::{ScopedToken, ScopedTokenValue};
+use crate::repository::meta::Repository;
+#[cfg(test)]
+use crate::repository::meta::ResolveError;
+use async_trait::async_trait;
+use thiserror::Error;
+
+#[cfg(test)]
+use mockall::mock;
+
+#[derive(Error, Debug)]
+pub enum StoreError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+#[async_trait]
+pub trait ScopedTokenRepository: Repository<ScopedTokenValue, ScopedToken> {
+    async fn store(&self, token: &ScopedToken) -> Result<(), StoreError>;
+}
+
+pub trait HaveScopedTokenRepository {
+    type ScopedTokenRepository: ScopedTokenRepository + Send + Sync + 'static;
+    fn scoped_token_repository(&self) -> &Self::ScopedTokenRepository;
+}
+
+#[cfg(test)]
+mock! {
+    pub ScopedTokenRepository {}
+
+    #[async_trait]
+    impl Repository<ScopedTokenValue, ScopedToken> for ScopedTokenRepository {
+        async fn resolve(&self, id: &ScopedTokenValue) -> Result<Option<ScopedToken>, ResolveError>;
+    }
+
+    #[async_trait]
+    impl ScopedTokenRepository for ScopedTokenRepository {
+        async fn store(&self, token: &ScopedToken) -> Result<(), StoreError>;
+    }
+}
@@ -1,7 +1,5 @@
 use crate::model::user_profile::{UserProfile, UserProfileId};
-use crate::repository::meta::Repository;
-#[cfg(test)]
-use crate::repository::meta::ResolveError;
+use crate::repository::meta::{Repository, ResolveError};
 use async_trait::async_trait;
 use thiserror::Error;
 
@@ -16,6 +14,12 @@ pub enum StoreError {
 
 #[async_trait]
 pub trait UserProfileRepository: Repository<UserProfileId, UserProfile> {
+    /// Like `resolve`, but never served by a read replica, for a profile about to be changed
+    /// and stored back, so that a lagging replica does not undo newer writes.
+    async fn resolve_for_update(
+        &self,
+        id: &UserProfileId,
+    ) -> Result<Option<UserProfile>, ResolveError>;
     async fn store(&self, profile: &UserProfile) -> Result<(), StoreError>;
 }
 
@@ -35,6 +39,7 @@ mock! {
 
     #[async_trait]
     impl UserProfileRepository for UserProfileRepository {
+        async fn resolve_for_update(&self, id: &UserProfileId) -> Result<Option<UserProfile>, ResolveError>;
         async fn store(&self, u: &UserProfile) -> Result<(), StoreError>;
     }
 }
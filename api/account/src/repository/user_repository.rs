@@ -1,13 +1,14 @@
-use crate::model::login_provider::IdInProvider;
+use crate::model::login_provider::{IdInProvider, ProviderKind};
 use crate::model::user::{User, UserId};
-use crate::repository::meta::Repository;
-#[cfg(test)]
-use crate::repository::meta::ResolveError;
+use crate::repository::meta::{Page, Pagination, Repository, ResolveError};
 
 use async_trait::async_trait;
+use derive_more::Constructor;
 #[cfg(test)]
 use mockall::mock;
+use serde::Deserialize;
 use thiserror::Error;
+use time::OffsetDateTime;
 
 #[derive(Error, Debug)]
 pub enum StoreError {
@@ -23,13 +24,38 @@ pub enum FilterByIdInProviderError {
     Unexpected(#[from] anyhow::Error),
 }
 
+#[derive(Error, Debug)]
+pub enum SearchError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+/// Filters for `UserRepository::search`. Every `None` field matches all users.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Constructor, Deserialize)]
+pub struct UserSearchCondition {
+    pub provider_kind: Option<ProviderKind>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub created_after: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub created_before: Option<OffsetDateTime>,
+    pub name_prefix: Option<String>,
+}
+
 #[async_trait]
 pub trait UserRepository: Repository<UserId, User> {
+    /// Like `resolve`, but never served by a read replica, for a user about to be changed and
+    /// stored back, so that a lagging replica does not undo newer writes.
+    async fn resolve_for_update(&self, id: &UserId) -> Result<Option<User>, ResolveError>;
     async fn store(&self, u: &User) -> Result<(), StoreError>;
     async fn find_by_id_in_provider(
         &self,
         id_in_provider: &IdInProvider,
     ) -> Result<Option<User>, FilterByIdInProviderError>;
+    async fn search(
+        &self,
+        condition: &UserSearchCondition,
+        pagination: &Pagination,
+    ) -> Result<Page<User>, SearchError>;
 }
 
 pub trait HaveUserRepository {
@@ -48,7 +74,9 @@ mock! {
 
     #[async_trait]
     impl UserRepository for UserRepository {
+        async fn resolve_for_update(&self, id: &UserId) -> Result<Option<User>, ResolveError>;
         async fn find_by_id_in_provider(&self, id_in_provider: &IdInProvider) -> Result<Option<User>, FilterByIdInProviderError>;
         async fn store(&self, u: &User) -> Result<(), StoreError>;
+        async fn search(&self, condition: &UserSearchCondition, pagination: &Pagination) -> Result<Page<User>, SearchError>;
     }
 }
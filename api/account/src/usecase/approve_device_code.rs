@@ -0,0 +1,209 @@
+use crate::actor::user::User;
+use crate::effect::clock::{Clock, HaveClock};
+use crate::model::device_authorization::{ApproveError, DeviceAuthorizationStatus, UserCode};
+use crate::model::user::UserId;
+use crate::repository::device_authorization_repository::{
+    DeviceAuthorizationRepository, FindByUserCodeError, HaveDeviceAuthorizationRepository,
+    StoreError,
+};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Debug, Constructor, Serialize, Deserialize, ToSchema, TS)]
+pub struct ApproveDeviceCodeUseCaseParams {
+    pub user_code: String,
+    pub approved: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum ApproveDeviceCodeUseCaseError {
+    #[error(transparent)]
+    FindError(#[from] FindByUserCodeError),
+    #[error(transparent)]
+    StoreError(#[from] StoreError),
+    #[error(transparent)]
+    ApproveError(#[from] ApproveError),
+    #[error("Device code is not found. (user_code: {0})")]
+    NotFound(String),
+}
+
+#[async_trait]
+pub trait ApproveDeviceCodeUseCase: HaveDeviceAuthorizationRepository + HaveClock {
+    #[tracing::instrument(skip(self, actor), fields(user_id = %actor.0 .0))]
+    async fn execute(
+        &self,
+        actor: &User,
+        params: ApproveDeviceCodeUseCaseParams,
+    ) -> Result<(), ApproveDeviceCodeUseCaseError> {
+        let user_code = UserCode::new(params.user_code.to_uppercase());
+        let mut authorization = match self
+            .device_authorization_repository()
+            .find_by_user_code(&user_code)
+            .await?
+        {
+            Some(a) => a,
+            None => return Err(ApproveDeviceCodeUseCaseError::NotFound(user_code.0)),
+        };
+        let now = self.clock().now_utc();
+        if params.approved {
+            authorization.approve(UserId::new(actor.0 .0.clone()), now)?;
+        } else {
+            authorization.deny(now)?;
+        }
+        let stored = self
+            .device_authorization_repository()
+            .store_if(&authorization, &DeviceAuthorizationStatus::Pending)
+            .await?;
+        if !stored {
+            return Err(ApproveError::AlreadyProcessed.into());
+        }
+        Ok(())
+    }
+}
+
+impl<T: HaveDeviceAuthorizationRepository + HaveClock> ApproveDeviceCodeUseCase for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ApproveDeviceCodeUseCase, ApproveDeviceCodeUseCaseError, ApproveDeviceCodeUseCaseParams,
+    };
+    use crate::actor::user::{User, UserId};
+    use crate::effect::clock::{HaveClock, MockClock};
+    use crate::model::device_authorization::{
+        ApproveError, ClientId, DeviceAuthorization, DeviceAuthorizationStatus, DeviceCode, Scope,
+        UserCode, DEVICE_CODE_EXPIRES_IN,
+    };
+    use crate::repository::device_authorization_repository::{
+        HaveDeviceAuthorizationRepository, MockDeviceAuthorizationRepository,
+    };
+    use derive_more::Constructor;
+    use time::{Duration, OffsetDateTime};
+
+    #[derive(Constructor)]
+    struct UC {
+        repo: MockDeviceAuthorizationRepository,
+        clock: MockClock,
+    }
+
+    impl HaveDeviceAuthorizationRepository for UC {
+        type DeviceAuthorizationRepository = MockDeviceAuthorizationRepository;
+        fn device_authorization_repository(&self) -> &Self::DeviceAuthorizationRepository {
+            &self.repo
+        }
+    }
+
+    impl HaveClock for UC {
+        type Clock = MockClock;
+        fn clock(&self) -> &Self::Clock {
+            &self.clock
+        }
+    }
+
+    fn issued() -> DeviceAuthorization {
+        DeviceAuthorization::issue(
+            DeviceCode::new("device".to_string()),
+            UserCode::new("BCDF-GHJK".to_string()),
+            ClientId::new("cli".to_string()),
+            Scope::default(),
+            OffsetDateTime::UNIX_EPOCH,
+        )
+    }
+
+    fn clock_at(seconds: i64) -> MockClock {
+        let mut clock = MockClock::new();
+        clock
+            .expect_now_utc()
+            .returning(move || OffsetDateTime::UNIX_EPOCH + Duration::seconds(seconds));
+        clock
+    }
+
+    #[tokio::test]
+    async fn approve_device_code_store_approved_authorization() {
+        let mut repo = MockDeviceAuthorizationRepository::new();
+        repo.expect_find_by_user_code()
+            .returning(|_| Ok(Some(issued())));
+        repo.expect_store_if()
+            .withf(|a, expected| {
+                a.status
+                    == DeviceAuthorizationStatus::Approved(crate::model::user::UserId::new(
+                        "foo".to_string(),
+                    ))
+                    && expected == &DeviceAuthorizationStatus::Pending
+            })
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        let user = User::new(UserId::new("foo".to_string()));
+        let result = UC::new(repo, clock_at(0))
+            .execute(
+                &user,
+                ApproveDeviceCodeUseCaseParams::new("bcdf-ghjk".to_string(), true),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn approve_device_code_is_err_when_not_found() {
+        let mut repo = MockDeviceAuthorizationRepository::new();
+        repo.expect_find_by_user_code().returning(|_| Ok(None));
+
+        let result = UC::new(repo, clock_at(0))
+            .execute(
+                &User::default(),
+                ApproveDeviceCodeUseCaseParams::new("BCDF-GHJK".to_string(), true),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(ApproveDeviceCodeUseCaseError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn approve_device_code_is_err_when_expired() {
+        let mut repo = MockDeviceAuthorizationRepository::new();
+        repo.expect_find_by_user_code()
+            .returning(|_| Ok(Some(issued())));
+        repo.expect_store_if().never();
+
+        let result = UC::new(repo, clock_at(DEVICE_CODE_EXPIRES_IN))
+            .execute(
+                &User::default(),
+                ApproveDeviceCodeUseCaseParams::new("BCDF-GHJK".to_string(), true),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(ApproveDeviceCodeUseCaseError::ApproveError(
+                ApproveError::Expired
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn approve_device_code_is_err_when_decided_concurrently() {
+        let mut repo = MockDeviceAuthorizationRepository::new();
+        repo.expect_find_by_user_code()
+            .returning(|_| Ok(Some(issued())));
+        repo.expect_store_if().times(1).returning(|_, _| Ok(false));
+
+        let result = UC::new(repo, clock_at(0))
+            .execute(
+                &User::default(),
+                ApproveDeviceCodeUseCaseParams::new("BCDF-GHJK".to_string(), false),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(ApproveDeviceCodeUseCaseError::ApproveError(
+                ApproveError::AlreadyProcessed
+            ))
+        ));
+    }
+}
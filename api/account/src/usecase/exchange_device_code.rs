@@ -0,0 +1,547 @@
+use crate::effect::clock::{Clock, HaveClock};
+use crate::effect::id_generator::{HaveIdGenerator, IdGenerator};
+use crate::model::device_authorization::{DeviceCode, PollError, Scope, DEVICE_CODE_GRANT_TYPE};
+use crate::model::scoped_token::{ScopedToken, ScopedTokenValue};
+use crate::repository::device_authorization_repository::{
+    DeviceAuthorizationRepository, HaveDeviceAuthorizationRepository,
+    StoreError as DeviceAuthorizationStoreError,
+};
+use crate::repository::meta::{Repository, ResolveError};
+use crate::repository::scoped_token_repository::{
+    HaveScopedTokenRepository, ScopedTokenRepository, StoreError as ScopedTokenStoreError,
+};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Debug, Constructor, Serialize, ToSchema, TS)]
+pub struct ExchangeDeviceCodeUseCaseResult {
+    pub access_token: ScopedTokenValue,
+    #[ts(type = "number")]
+    pub expires_in: i64,
+    pub scope: Scope,
+}
+
+#[derive(Debug, Constructor, Serialize, Deserialize, ToSchema, TS)]
+pub struct ExchangeDeviceCodeUseCaseParams {
+    pub grant_type: String,
+    pub device_code: String,
+    pub client_id: String,
+}
+
+#[derive(Error, Debug)]
+pub enum ExchangeDeviceCodeUseCaseError {
+    #[error(transparent)]
+    Poll(#[from] PollError),
+    #[error("Unsupported grant type. ({0})")]
+    UnsupportedGrantType(String),
+    #[error("Device code is not issued for this client.")]
+    InvalidGrant,
+    #[error(transparent)]
+    ResolveError(#[from] ResolveError),
+    #[error(transparent)]
+    DeviceAuthorizationStoreError(#[from] DeviceAuthorizationStoreError),
+    #[error(transparent)]
+    ScopedTokenStoreError(#[from] ScopedTokenStoreError),
+}
+
+#[async_trait]
+pub trait ExchangeDeviceCodeUseCase:
+    HaveDeviceAuthorizationRepository + HaveScopedTokenRepository + HaveIdGenerator + HaveClock
+{
+    #[tracing::instrument(skip(self, params), fields(client_id = %params.client_id))]
+    async fn execute(
+        &self,
+        params: ExchangeDeviceCodeUseCaseParams,
+    ) -> Result<ExchangeDeviceCodeUseCaseResult, ExchangeDeviceCodeUseCaseError> {
+        if params.grant_type != DEVICE_CODE_GRANT_TYPE {
+            return Err(ExchangeDeviceCodeUseCaseError::UnsupportedGrantType(
+                params.grant_type,
+            ));
+        }
+        let mut authorization = match self
+            .device_authorization_repository()
+            .resolve(&DeviceCode::new(params.device_code))
+            .await?
+        {
+            Some(a) if a.client_id.0 == params.client_id => a,
+            _ => return Err(ExchangeDeviceCodeUseCaseError::InvalidGrant),
+        };
+
+        let now = self.clock().now_utc();
+        let polled = authorization.status.clone();
+        let poll_result = authorization.poll(now);
+        let stored = self
+            .device_authorization_repository()
+            .store_if(&authorization, &polled)
+            .await?;
+        let user_id = poll_result?;
+        if !stored {
+            // Another poll consumed the approval between resolve and store.
+            return Err(PollError::AlreadyConsumed.into());
+        }
+
+        let token = ScopedToken::issue(
+            self.id_generator().generate(),
+            user_id,
+            authorization.scope,
+            now,
+        );
+        self.scoped_token_repository().store(&token).await?;
+        Ok(ExchangeDeviceCodeUseCaseResult::new(
+            token.token.clone(),
+            token.expires_in(now),
+            token.scope,
+        ))
+    }
+}
+
+impl<T> ExchangeDeviceCodeUseCase for T where
+    T: HaveDeviceAuthorizationRepository + HaveScopedTokenRepository + HaveIdGenerator + HaveClock
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ExchangeDeviceCodeUseCase, ExchangeDeviceCodeUseCaseError, ExchangeDeviceCodeUseCaseParams,
+    };
+    use crate::actor::user::{User as UserActor, UserId as ActorUserId};
+    use crate::adapter::firebase_auth::{HaveFirebaseAuthDriver, MockFirebaseAuthDriver};
+    use crate::effect::audit_log::{AuditEventCondition, AuditLog, AuditLogError, HaveAuditLog};
+    use crate::effect::clock::{Clock, HaveClock};
+    use crate::effect::id_generator::{HaveIdGenerator, IdGenerator};
+    use crate::effect::user_code_generator::{HaveUserCodeGenerator, UserCodeGenerator};
+    use crate::model::audit_event::{AuditContext, AuditEvent};
+    use crate::model::device_authorization::{
+        DeviceAuthorization, DeviceAuthorizationStatus, DeviceCode, PollError, UserCode,
+        DEVICE_CODE_GRANT_TYPE, DEVICE_CODE_INTERVAL,
+    };
+    use crate::model::login_provider::IdInProvider;
+    use crate::model::scoped_token::{ScopedToken, ScopedTokenValue, SCOPED_TOKEN_PREFIX};
+    use crate::model::user::{User, UserId};
+    use crate::repository::device_authorization_repository::{
+        DeviceAuthorizationRepository, FindByUserCodeError, HaveDeviceAuthorizationRepository,
+        StoreError as DeviceAuthorizationStoreError,
+    };
+    use crate::repository::meta::{Page, Pagination, Repository, ResolveError};
+    use crate::repository::scoped_token_repository::{
+        HaveScopedTokenRepository, ScopedTokenRepository, StoreError as ScopedTokenStoreError,
+    };
+    use crate::repository::user_repository::{
+        FilterByIdInProviderError, HaveUserRepository, SearchError, StoreError as UserStoreError,
+        UserRepository, UserSearchCondition,
+    };
+    use crate::usecase::approve_device_code::{
+        ApproveDeviceCodeUseCase, ApproveDeviceCodeUseCaseParams,
+    };
+    use crate::usecase::issue_device_code::{IssueDeviceCodeUseCase, IssueDeviceCodeUseCaseParams};
+    use crate::usecase::verify_scoped_token::VerifyScopedTokenUseCase;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Mutex;
+    use time::{Duration, OffsetDateTime};
+
+    /// Drives the whole device flow against in-process stores.
+    #[derive(Default)]
+    struct Fixture {
+        authorizations: Mutex<HashMap<String, DeviceAuthorization>>,
+        tokens: Mutex<HashMap<String, ScopedToken>>,
+        events: Mutex<Vec<AuditEvent>>,
+        elapsed: AtomicI64,
+        firebase_auth: MockFirebaseAuthDriver,
+    }
+
+    impl Fixture {
+        fn advance(&self, seconds: i64) {
+            self.elapsed.fetch_add(seconds, Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl Repository<DeviceCode, DeviceAuthorization> for Fixture {
+        async fn resolve(
+            &self,
+            id: &DeviceCode,
+        ) -> Result<Option<DeviceAuthorization>, ResolveError> {
+            let resolved = self.authorizations.lock().unwrap().get(&id.0).cloned();
+            // Lets a concurrent request resolve the same code before this one stores it.
+            tokio::task::yield_now().await;
+            Ok(resolved)
+        }
+    }
+
+    #[async_trait]
+    impl DeviceAuthorizationRepository for Fixture {
+        async fn store(
+            &self,
+            a: &DeviceAuthorization,
+        ) -> Result<(), DeviceAuthorizationStoreError> {
+            self.authorizations
+                .lock()
+                .unwrap()
+                .insert(a.device_code.0.clone(), a.clone());
+            Ok(())
+        }
+        async fn store_if(
+            &self,
+            a: &DeviceAuthorization,
+            expected: &DeviceAuthorizationStatus,
+        ) -> Result<bool, DeviceAuthorizationStoreError> {
+            let mut authorizations = self.authorizations.lock().unwrap();
+            match authorizations
+                .values_mut()
+                .find(|stored| stored.user_code == a.user_code)
+            {
+                Some(stored) if &stored.status == expected => {
+                    stored.status = a.status.clone();
+                    stored.interval = a.interval;
+                    stored.last_polled_at = a.last_polled_at;
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        }
+        async fn find_by_user_code(
+            &self,
+            user_code: &UserCode,
+        ) -> Result<Option<DeviceAuthorization>, FindByUserCodeError> {
+            let found = self
+                .authorizations
+                .lock()
+                .unwrap()
+                .values()
+                .find(|a| &a.user_code == user_code)
+                .map(|a| DeviceAuthorization {
+                    device_code: DeviceCode::default(),
+                    ..a.clone()
+                });
+            tokio::task::yield_now().await;
+            Ok(found)
+        }
+        async fn delete_expired(
+            &self,
+            before: OffsetDateTime,
+        ) -> Result<u64, DeviceAuthorizationStoreError> {
+            let mut authorizations = self.authorizations.lock().unwrap();
+            let len = authorizations.len();
+            authorizations.retain(|_, a| a.expires_at >= before);
+            Ok((len - authorizations.len()) as u64)
+        }
+    }
+
+    #[async_trait]
+    impl Repository<ScopedTokenValue, ScopedToken> for Fixture {
+        async fn resolve(
+            &self,
+            id: &ScopedTokenValue,
+        ) -> Result<Option<ScopedToken>, ResolveError> {
+            Ok(self.tokens.lock().unwrap().get(&id.0).cloned())
+        }
+    }
+
+    #[async_trait]
+    impl ScopedTokenRepository for Fixture {
+        async fn store(&self, t: &ScopedToken) -> Result<(), ScopedTokenStoreError> {
+            self.tokens
+                .lock()
+                .unwrap()
+                .insert(t.token.0.clone(), t.clone());
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Repository<UserId, User> for Fixture {
+        async fn resolve(&self, id: &UserId) -> Result<Option<User>, ResolveError> {
+            Ok(Some(User::new(id.clone(), None)))
+        }
+    }
+
+    #[async_trait]
+    impl UserRepository for Fixture {
+        async fn resolve_for_update(&self, id: &UserId) -> Result<Option<User>, ResolveError> {
+            self.resolve(id).await
+        }
+        async fn store(&self, _: &User) -> Result<(), UserStoreError> {
+            Ok(())
+        }
+        async fn find_by_id_in_provider(
+            &self,
+            _: &IdInProvider,
+        ) -> Result<Option<User>, FilterByIdInProviderError> {
+            Ok(None)
+        }
+        async fn search(
+            &self,
+            _: &UserSearchCondition,
+            _: &Pagination,
+        ) -> Result<Page<User>, SearchError> {
+            Ok(Page::new(vec![], 0))
+        }
+    }
+
+    #[async_trait]
+    impl AuditLog for Fixture {
+        async fn record(&self, event: &AuditEvent) -> Result<(), AuditLogError> {
+            self.events.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+        async fn search(
+            &self,
+            _: &AuditEventCondition,
+            _: &Pagination,
+        ) -> Result<Page<AuditEvent>, AuditLogError> {
+            let events = self.events.lock().unwrap().clone();
+            let total = events.len() as i64;
+            Ok(Page::new(events, total))
+        }
+    }
+
+    impl Clock for Fixture {
+        fn now_utc(&self) -> OffsetDateTime {
+            OffsetDateTime::UNIX_EPOCH + Duration::seconds(self.elapsed.load(Ordering::SeqCst))
+        }
+    }
+
+    impl IdGenerator for Fixture {
+        fn generate(&self) -> String {
+            format!("id{}", self.tokens.lock().unwrap().len())
+        }
+    }
+
+    impl UserCodeGenerator for Fixture {
+        fn generate(&self) -> String {
+            "BCDF-GHJK".to_string()
+        }
+    }
+
+    macro_rules! have_self {
+        ($($have:ident :: $assoc:ident => $getter:ident),* $(,)?) => {
+            $(
+                impl $have for Fixture {
+                    type $assoc = Fixture;
+                    fn $getter(&self) -> &Self::$assoc {
+                        self
+                    }
+                }
+            )*
+        };
+    }
+
+    have_self! {
+        HaveDeviceAuthorizationRepository::DeviceAuthorizationRepository => device_authorization_repository,
+        HaveScopedTokenRepository::ScopedTokenRepository => scoped_token_repository,
+        HaveUserRepository::UserRepository => user_repository,
+        HaveClock::Clock => clock,
+        HaveIdGenerator::IdGenerator => id_generator,
+        HaveUserCodeGenerator::UserCodeGenerator => user_code_generator,
+        HaveAuditLog::AuditLog => audit_log,
+    }
+
+    impl HaveFirebaseAuthDriver for Fixture {
+        type FirebaseAuthDriver = MockFirebaseAuthDriver;
+        fn firebase_auth(&self) -> &Self::FirebaseAuthDriver {
+            &self.firebase_auth
+        }
+    }
+
+    fn exchange_params(device_code: &DeviceCode) -> ExchangeDeviceCodeUseCaseParams {
+        ExchangeDeviceCodeUseCaseParams::new(
+            DEVICE_CODE_GRANT_TYPE.to_string(),
+            device_code.0.clone(),
+            "cli".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn device_flow_issue_approve_and_exchange_return_to_usable_token() {
+        let fixture = Fixture::default();
+        let issued = IssueDeviceCodeUseCase::execute(
+            &fixture,
+            IssueDeviceCodeUseCaseParams::new("cli".to_string(), Some("profile".to_string())),
+        )
+        .await
+        .unwrap();
+
+        let pending =
+            ExchangeDeviceCodeUseCase::execute(&fixture, exchange_params(&issued.device_code))
+                .await;
+        assert!(matches!(
+            pending,
+            Err(ExchangeDeviceCodeUseCaseError::Poll(
+                PollError::AuthorizationPending
+            ))
+        ));
+
+        let approver = UserActor::new(ActorUserId::new("approver".to_string()));
+        ApproveDeviceCodeUseCase::execute(
+            &fixture,
+            &approver,
+            ApproveDeviceCodeUseCaseParams::new(issued.user_code.0.clone(), true),
+        )
+        .await
+        .unwrap();
+
+        fixture.advance(DEVICE_CODE_INTERVAL);
+        let exchanged =
+            ExchangeDeviceCodeUseCase::execute(&fixture, exchange_params(&issued.device_code))
+                .await
+                .unwrap();
+        assert!(exchanged.access_token.0.starts_with(SCOPED_TOKEN_PREFIX));
+        assert_eq!(exchanged.scope.0, "profile");
+
+        let verified = VerifyScopedTokenUseCase::execute(
+            &fixture,
+            &exchanged.access_token.0,
+            &AuditContext::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(verified.user.id, UserId::new("approver".to_string()));
+
+        fixture.advance(DEVICE_CODE_INTERVAL);
+        let replayed =
+            ExchangeDeviceCodeUseCase::execute(&fixture, exchange_params(&issued.device_code))
+                .await;
+        assert!(matches!(
+            replayed,
+            Err(ExchangeDeviceCodeUseCaseError::Poll(
+                PollError::AlreadyConsumed
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn device_flow_exchange_mint_one_token_when_polled_concurrently() {
+        let fixture = Fixture::default();
+        let issued = IssueDeviceCodeUseCase::execute(
+            &fixture,
+            IssueDeviceCodeUseCaseParams::new("cli".to_string(), None),
+        )
+        .await
+        .unwrap();
+        ApproveDeviceCodeUseCase::execute(
+            &fixture,
+            &UserActor::new(ActorUserId::new("approver".to_string())),
+            ApproveDeviceCodeUseCaseParams::new(issued.user_code.0.clone(), true),
+        )
+        .await
+        .unwrap();
+
+        let (first, second) = tokio::join!(
+            ExchangeDeviceCodeUseCase::execute(&fixture, exchange_params(&issued.device_code)),
+            ExchangeDeviceCodeUseCase::execute(&fixture, exchange_params(&issued.device_code)),
+        );
+
+        let (won, lost) = if first.is_ok() {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        assert!(won.is_ok());
+        assert!(matches!(
+            lost,
+            Err(ExchangeDeviceCodeUseCaseError::Poll(
+                PollError::AlreadyConsumed
+            ))
+        ));
+        assert_eq!(fixture.tokens.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn device_flow_approve_decide_once_when_approved_concurrently() {
+        let fixture = Fixture::default();
+        let issued = IssueDeviceCodeUseCase::execute(
+            &fixture,
+            IssueDeviceCodeUseCaseParams::new("cli".to_string(), None),
+        )
+        .await
+        .unwrap();
+        let (first_user, second_user) = (
+            UserActor::new(ActorUserId::new("first".to_string())),
+            UserActor::new(ActorUserId::new("second".to_string())),
+        );
+        let params = || ApproveDeviceCodeUseCaseParams::new(issued.user_code.0.clone(), true);
+
+        let (first, second) = tokio::join!(
+            ApproveDeviceCodeUseCase::execute(&fixture, &first_user, params()),
+            ApproveDeviceCodeUseCase::execute(&fixture, &second_user, params()),
+        );
+
+        let winner = if first.is_ok() { "first" } else { "second" };
+        assert!(first.is_ok() != second.is_ok());
+        assert_eq!(
+            fixture.authorizations.lock().unwrap()[&issued.device_code.0].status,
+            DeviceAuthorizationStatus::Approved(UserId::new(winner.to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn device_flow_exchange_is_slow_down_when_polling_too_fast() {
+        let fixture = Fixture::default();
+        let issued = IssueDeviceCodeUseCase::execute(
+            &fixture,
+            IssueDeviceCodeUseCaseParams::new("cli".to_string(), None),
+        )
+        .await
+        .unwrap();
+
+        let _ = ExchangeDeviceCodeUseCase::execute(&fixture, exchange_params(&issued.device_code))
+            .await;
+        let result =
+            ExchangeDeviceCodeUseCase::execute(&fixture, exchange_params(&issued.device_code))
+                .await;
+        assert!(matches!(
+            result,
+            Err(ExchangeDeviceCodeUseCaseError::Poll(PollError::SlowDown(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn device_flow_exchange_is_invalid_grant_when_client_mismatch() {
+        let fixture = Fixture::default();
+        let issued = IssueDeviceCodeUseCase::execute(
+            &fixture,
+            IssueDeviceCodeUseCaseParams::new("cli".to_string(), None),
+        )
+        .await
+        .unwrap();
+
+        let result = ExchangeDeviceCodeUseCase::execute(
+            &fixture,
+            ExchangeDeviceCodeUseCaseParams::new(
+                DEVICE_CODE_GRANT_TYPE.to_string(),
+                issued.device_code.0,
+                "other".to_string(),
+            ),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(ExchangeDeviceCodeUseCaseError::InvalidGrant)
+        ));
+    }
+
+    #[tokio::test]
+    async fn device_flow_exchange_is_err_when_unsupported_grant_type() {
+        let fixture = Fixture::default();
+        let result = ExchangeDeviceCodeUseCase::execute(
+            &fixture,
+            ExchangeDeviceCodeUseCaseParams::new(
+                "password".to_string(),
+                "device".to_string(),
+                "cli".to_string(),
+            ),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(ExchangeDeviceCodeUseCaseError::UnsupportedGrantType(_))
+        ));
+    }
+}
@@ -0,0 +1,210 @@
+use crate::effect::clock::{Clock, HaveClock};
+use crate::effect::id_generator::{HaveIdGenerator, IdGenerator};
+use crate::effect::user_code_generator::{HaveUserCodeGenerator, UserCodeGenerator};
+use crate::model::device_authorization::{
+    ClientId, DeviceAuthorization, DeviceCode, Scope, UserCode, DEVICE_CODE_RETENTION,
+};
+use crate::repository::device_authorization_repository::{
+    DeviceAuthorizationRepository, HaveDeviceAuthorizationRepository, StoreError,
+};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use time::Duration;
+use tracing::warn;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Debug, Constructor, Serialize, ToSchema, TS)]
+pub struct IssueDeviceCodeUseCaseResult {
+    pub device_code: DeviceCode,
+    pub user_code: UserCode,
+    #[ts(type = "number")]
+    pub expires_in: i64,
+    #[ts(type = "number")]
+    pub interval: i64,
+}
+
+#[derive(Debug, Constructor, Serialize, Deserialize, ToSchema, TS)]
+pub struct IssueDeviceCodeUseCaseParams {
+    pub client_id: String,
+    pub scope: Option<String>,
+}
+
+#[derive(Error, Debug)]
+pub enum IssueDeviceCodeUseCaseError {
+    #[error(transparent)]
+    StoreError(#[from] StoreError),
+}
+
+#[async_trait]
+pub trait IssueDeviceCodeUseCase:
+    HaveDeviceAuthorizationRepository + HaveIdGenerator + HaveUserCodeGenerator + HaveClock
+{
+    #[tracing::instrument(skip(self))]
+    async fn execute(
+        &self,
+        params: IssueDeviceCodeUseCaseParams,
+    ) -> Result<IssueDeviceCodeUseCaseResult, IssueDeviceCodeUseCaseError> {
+        let now = self.clock().now_utc();
+        // Expired codes are swept as new ones are issued. A failed sweep is retried by the next.
+        if let Err(e) = self
+            .device_authorization_repository()
+            .delete_expired(now - Duration::seconds(DEVICE_CODE_RETENTION))
+            .await
+        {
+            warn!("failed to delete expired device authorizations: {}", e);
+        }
+        let authorization = DeviceAuthorization::issue(
+            DeviceCode::new(self.id_generator().generate()),
+            UserCode::new(self.user_code_generator().generate()),
+            ClientId::new(params.client_id),
+            Scope::new(params.scope.unwrap_or_default()),
+            now,
+        );
+        self.device_authorization_repository()
+            .store(&authorization)
+            .await?;
+        Ok(IssueDeviceCodeUseCaseResult::new(
+            authorization.device_code,
+            authorization.user_code,
+            (authorization.expires_at - now).whole_seconds(),
+            authorization.interval,
+        ))
+    }
+}
+
+impl<T> IssueDeviceCodeUseCase for T where
+    T: HaveDeviceAuthorizationRepository + HaveIdGenerator + HaveUserCodeGenerator + HaveClock
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IssueDeviceCodeUseCase, IssueDeviceCodeUseCaseParams};
+    use crate::effect::clock::{HaveClock, MockClock};
+    use crate::effect::id_generator::{HaveIdGenerator, MockIdGenerator};
+    use crate::effect::user_code_generator::{HaveUserCodeGenerator, MockUserCodeGenerator};
+    use crate::model::device_authorization::{
+        DeviceCode, UserCode, DEVICE_CODE_EXPIRES_IN, DEVICE_CODE_INTERVAL, DEVICE_CODE_RETENTION,
+    };
+    use crate::repository::device_authorization_repository::{
+        HaveDeviceAuthorizationRepository, MockDeviceAuthorizationRepository, StoreError,
+    };
+    use derive_more::Constructor;
+    use time::{Duration, OffsetDateTime};
+
+    #[derive(Constructor)]
+    struct UC {
+        repo: MockDeviceAuthorizationRepository,
+        id_gen: MockIdGenerator,
+        user_code_gen: MockUserCodeGenerator,
+        clock: MockClock,
+    }
+
+    impl HaveDeviceAuthorizationRepository for UC {
+        type DeviceAuthorizationRepository = MockDeviceAuthorizationRepository;
+        fn device_authorization_repository(&self) -> &Self::DeviceAuthorizationRepository {
+            &self.repo
+        }
+    }
+
+    impl HaveIdGenerator for UC {
+        type IdGenerator = MockIdGenerator;
+        fn id_generator(&self) -> &Self::IdGenerator {
+            &self.id_gen
+        }
+    }
+
+    impl HaveUserCodeGenerator for UC {
+        type UserCodeGenerator = MockUserCodeGenerator;
+        fn user_code_generator(&self) -> &Self::UserCodeGenerator {
+            &self.user_code_gen
+        }
+    }
+
+    impl HaveClock for UC {
+        type Clock = MockClock;
+        fn clock(&self) -> &Self::Clock {
+            &self.clock
+        }
+    }
+
+    fn mocks() -> (MockIdGenerator, MockUserCodeGenerator, MockClock) {
+        let mut id_gen = MockIdGenerator::new();
+        let mut user_code_gen = MockUserCodeGenerator::new();
+        let mut clock = MockClock::new();
+        id_gen.expect_generate().returning(|| "device".to_string());
+        user_code_gen
+            .expect_generate()
+            .returning(|| "BCDF-GHJK".to_string());
+        clock
+            .expect_now_utc()
+            .returning(|| OffsetDateTime::UNIX_EPOCH);
+        (id_gen, user_code_gen, clock)
+    }
+
+    #[tokio::test]
+    async fn issue_device_code_return_to_codes() {
+        let mut repo = MockDeviceAuthorizationRepository::new();
+        repo.expect_delete_expired().returning(|_| Ok(0));
+        repo.expect_store().returning(|_| Ok(()));
+        let (id_gen, user_code_gen, clock) = mocks();
+
+        let result = UC::new(repo, id_gen, user_code_gen, clock)
+            .execute(IssueDeviceCodeUseCaseParams::new("cli".to_string(), None))
+            .await
+            .unwrap();
+        assert_eq!(result.device_code, DeviceCode::new("device".to_string()));
+        assert_eq!(result.user_code, UserCode::new("BCDF-GHJK".to_string()));
+        assert_eq!(result.expires_in, DEVICE_CODE_EXPIRES_IN);
+        assert_eq!(result.interval, DEVICE_CODE_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn issue_device_code_is_err_when_store_error() {
+        let mut repo = MockDeviceAuthorizationRepository::new();
+        repo.expect_delete_expired().returning(|_| Ok(0));
+        repo.expect_store()
+            .returning(|_| Err(StoreError::Unexpected(anyhow::anyhow!("foo"))));
+        let (id_gen, user_code_gen, clock) = mocks();
+
+        let result = UC::new(repo, id_gen, user_code_gen, clock)
+            .execute(IssueDeviceCodeUseCaseParams::new("cli".to_string(), None))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn issue_device_code_delete_codes_expired_before_retention() {
+        let mut repo = MockDeviceAuthorizationRepository::new();
+        repo.expect_delete_expired()
+            .withf(|before| {
+                *before == OffsetDateTime::UNIX_EPOCH - Duration::seconds(DEVICE_CODE_RETENTION)
+            })
+            .times(1)
+            .returning(|_| Ok(1));
+        repo.expect_store().times(1).returning(|_| Ok(()));
+        let (id_gen, user_code_gen, clock) = mocks();
+
+        let result = UC::new(repo, id_gen, user_code_gen, clock)
+            .execute(IssueDeviceCodeUseCaseParams::new("cli".to_string(), None))
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn issue_device_code_is_ok_when_delete_expired_error() {
+        let mut repo = MockDeviceAuthorizationRepository::new();
+        repo.expect_delete_expired()
+            .returning(|_| Err(StoreError::Unexpected(anyhow::anyhow!("foo"))));
+        repo.expect_store().times(1).returning(|_| Ok(()));
+        let (id_gen, user_code_gen, clock) = mocks();
+
+        let result = UC::new(repo, id_gen, user_code_gen, clock)
+            .execute(IssueDeviceCodeUseCaseParams::new("cli".to_string(), None))
+            .await;
+        assert!(result.is_ok());
+    }
+}
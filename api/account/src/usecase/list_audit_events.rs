@@ -0,0 +1,112 @@
+use crate::ability::audit_viewer::AuditViewer;
+use crate::effect::audit_log::{AuditEventCondition, AuditLog, AuditLogError, HaveAuditLog};
+use crate::model::audit_event::AuditEvent;
+use crate::repository::meta::{Page, Pagination};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use serde::Serialize;
+use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Debug, Constructor, Serialize, ToSchema, TS)]
+pub struct ListAuditEventsUseCaseResult {
+    pub events: Vec<AuditEvent>,
+    #[ts(type = "number")]
+    pub total: i64,
+    #[ts(type = "number")]
+    pub offset: i64,
+    #[ts(type = "number")]
+    pub limit: i64,
+}
+
+#[derive(Error, Debug)]
+pub enum ListAuditEventsUseCaseError {
+    #[error(transparent)]
+    AuditLogError(#[from] AuditLogError),
+}
+
+#[async_trait]
+pub trait ListAuditEventsUseCase: HaveAuditLog {
+    #[tracing::instrument(skip(self, actor))]
+    async fn execute<A: AuditViewer + Sync>(
+        &self,
+        actor: &A,
+        condition: AuditEventCondition,
+        pagination: Pagination,
+    ) -> Result<ListAuditEventsUseCaseResult, ListAuditEventsUseCaseError> {
+        let condition = actor.restrict(condition);
+        let pagination = pagination.clamped();
+        let Page { items, total } = self.audit_log().search(&condition, &pagination).await?;
+        Ok(ListAuditEventsUseCaseResult::new(
+            items,
+            total,
+            pagination.offset,
+            pagination.limit,
+        ))
+    }
+}
+
+impl<T: HaveAuditLog> ListAuditEventsUseCase for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::ListAuditEventsUseCase;
+    use crate::actor::admin::Admin;
+    use crate::actor::user::{User, UserId as ActorUserId};
+    use crate::effect::audit_log::{AuditEventCondition, HaveAuditLog, MockAuditLog};
+    use crate::model::user::UserId;
+    use crate::repository::meta::{Page, Pagination};
+    use derive_more::Constructor;
+
+    #[derive(Constructor)]
+    struct UC {
+        audit_log: MockAuditLog,
+    }
+
+    impl HaveAuditLog for UC {
+        type AuditLog = MockAuditLog;
+        fn audit_log(&self) -> &Self::AuditLog {
+            &self.audit_log
+        }
+    }
+
+    #[tokio::test]
+    async fn list_audit_events_restrict_user_to_own_events() {
+        let mut audit_log = MockAuditLog::new();
+        audit_log
+            .expect_search()
+            .withf(|c, _| c.actor == Some(UserId::new("foo".to_string())))
+            .times(1)
+            .returning(|_, _| Ok(Page::new(vec![], 0)));
+
+        let user = User::new(ActorUserId::new("foo".to_string()));
+        let result = UC::new(audit_log)
+            .execute(
+                &user,
+                AuditEventCondition::new(Some(UserId::new("bar".to_string())), None),
+                Pagination::default(),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn list_audit_events_let_admin_see_all_events() {
+        let mut audit_log = MockAuditLog::new();
+        audit_log
+            .expect_search()
+            .withf(|c, _| c.actor.is_none())
+            .times(1)
+            .returning(|_, _| Ok(Page::new(vec![], 0)));
+
+        let result = UC::new(audit_log)
+            .execute(
+                &Admin::default(),
+                AuditEventCondition::default(),
+                Pagination::default(),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+}
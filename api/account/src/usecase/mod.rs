@@ -1,4 +1,14 @@
+pub mod approve_device_code;
+pub mod exchange_device_code;
+pub mod issue_device_code;
+pub mod list_audit_events;
+pub mod reset_user_name;
 pub mod resolve_profile;
+pub mod resolve_user;
+pub mod search_users;
 pub mod sign_up;
 pub mod update_profile;
+pub mod update_role;
+pub mod update_user_status;
 pub mod verify;
+pub mod verify_scoped_token;
@@ -0,0 +1,249 @@
+use crate::ability::name_moderator::NameModerator;
+use crate::effect::audit_log::AuditRecorder;
+use crate::model::audit_event::{AuditAction, AuditContext, AuditOutcome};
+use crate::model::user_profile::{UserProfile, UserProfileId};
+use crate::repository::meta::ResolveError;
+use crate::repository::user_profile_repository::{
+    HaveUserProfileRepository, StoreError, UserProfileRepository,
+};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use serde::Serialize;
+use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Debug, Constructor, Serialize, ToSchema, TS)]
+pub struct ResetUserNameUseCaseResult {
+    pub user_profile: UserProfile,
+}
+
+#[derive(Error, Debug)]
+pub enum ResetUserNameUseCaseError {
+    #[error(transparent)]
+    ResolveError(#[from] ResolveError),
+    #[error(transparent)]
+    StoreError(#[from] StoreError),
+    #[error("Profile is not found. {0}")]
+    ProfileNotFound(String),
+}
+
+#[async_trait]
+pub trait ResetUserNameUseCase: HaveUserProfileRepository + AuditRecorder {
+    #[tracing::instrument(skip(self, actor), fields(user_id = %actor.moderator_id().0))]
+    async fn execute<A: NameModerator + Sync>(
+        &self,
+        actor: &A,
+        user_id: String,
+        context: &AuditContext,
+    ) -> Result<ResetUserNameUseCaseResult, ResetUserNameUseCaseError> {
+        let mut user_profile = match self
+            .user_profile_repository()
+            .resolve_for_update(&UserProfileId::new(user_id.clone()))
+            .await?
+        {
+            Some(p) => p,
+            None => return Err(ResetUserNameUseCaseError::ProfileNotFound(user_id)),
+        };
+        actor.reset_user_name(&mut user_profile);
+        self.user_profile_repository().store(&user_profile).await?;
+        self.audit_or_warn(
+            context,
+            Some(actor.moderator_id()),
+            AuditAction::UserNameReset,
+            Some(user_id),
+            AuditOutcome::Success,
+        )
+        .await;
+        Ok(ResetUserNameUseCaseResult::new(user_profile))
+    }
+}
+
+impl<T: HaveUserProfileRepository + AuditRecorder> ResetUserNameUseCase for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResetUserNameUseCase, ResetUserNameUseCaseError};
+    use crate::actor::admin::Admin;
+    use crate::actor::editor::Editor;
+    use crate::actor::user::UserId as ActorUserId;
+    use crate::effect::audit_log::{AuditLogError, HaveAuditLog, MockAuditLog};
+    use crate::effect::clock::{HaveClock, MockClock};
+    use crate::effect::id_generator::{HaveIdGenerator, MockIdGenerator};
+    use crate::model::audit_event::{AuditAction, AuditContext};
+    use crate::model::profile::avatar::Avatar;
+    use crate::model::profile::display_name::DisplayName;
+    use crate::model::profile::entity::Profile;
+    use crate::model::profile::user_name::UserName;
+    use crate::model::user_profile::UserProfile;
+    use crate::repository::user_profile_repository::{
+        HaveUserProfileRepository, MockUserProfileRepository,
+    };
+    use anyhow::anyhow;
+    use derive_more::Constructor;
+    use time::OffsetDateTime;
+
+    #[derive(Constructor)]
+    struct UC {
+        user_profile_repo: MockUserProfileRepository,
+        audit_log: MockAuditLog,
+        id_generator: MockIdGenerator,
+        clock: MockClock,
+    }
+
+    impl HaveUserProfileRepository for UC {
+        type UserProfileRepository = MockUserProfileRepository;
+        fn user_profile_repository(&self) -> &Self::UserProfileRepository {
+            &self.user_profile_repo
+        }
+    }
+
+    impl HaveAuditLog for UC {
+        type AuditLog = MockAuditLog;
+        fn audit_log(&self) -> &Self::AuditLog {
+            &self.audit_log
+        }
+    }
+
+    impl HaveIdGenerator for UC {
+        type IdGenerator = MockIdGenerator;
+        fn id_generator(&self) -> &Self::IdGenerator {
+            &self.id_generator
+        }
+    }
+
+    impl HaveClock for UC {
+        type Clock = MockClock;
+        fn clock(&self) -> &Self::Clock {
+            &self.clock
+        }
+    }
+
+    fn uc(user_profile_repo: MockUserProfileRepository, audit_log: MockAuditLog) -> UC {
+        let mut id_generator = MockIdGenerator::new();
+        id_generator
+            .expect_generate()
+            .returning(|| "event".to_string());
+        let mut clock = MockClock::new();
+        clock
+            .expect_now_utc()
+            .returning(|| OffsetDateTime::UNIX_EPOCH);
+        UC::new(user_profile_repo, audit_log, id_generator, clock)
+    }
+
+    #[tokio::test]
+    async fn reset_user_name_store_placeholder_name() {
+        let mut user_profile_repo = MockUserProfileRepository::new();
+        user_profile_repo
+            .expect_resolve_for_update()
+            .returning(|id| {
+                Ok(Some(UserProfile::new(
+                    id.clone(),
+                    Profile::new(
+                        UserName::new("abusive".to_string()),
+                        DisplayName::new("Abusive".to_string()),
+                        Avatar::new("https://example.com/avatar.png".to_string()),
+                        None,
+                    ),
+                )))
+            });
+        user_profile_repo
+            .expect_store()
+            .withf(|p| p.profile.name == UserName::placeholder("foo"))
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit_log = MockAuditLog::new();
+        audit_log
+            .expect_record()
+            .withf(|e| e.action == AuditAction::UserNameReset)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let editor = Editor::new(ActorUserId::new("editor".to_string()));
+        let result = uc(user_profile_repo, audit_log)
+            .execute(&editor, "foo".to_string(), &AuditContext::default())
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn reset_user_name_return_ok_when_audit_log_fails_after_store() {
+        let mut user_profile_repo = MockUserProfileRepository::new();
+        user_profile_repo
+            .expect_resolve_for_update()
+            .returning(|id| {
+                Ok(Some(UserProfile::new(
+                    id.clone(),
+                    Profile::new(
+                        UserName::new("abusive".to_string()),
+                        DisplayName::new("Abusive".to_string()),
+                        Avatar::new("https://example.com/avatar.png".to_string()),
+                        None,
+                    ),
+                )))
+            });
+        user_profile_repo
+            .expect_store()
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit_log = MockAuditLog::new();
+        audit_log
+            .expect_record()
+            .returning(|_| Err(AuditLogError::Unexpected(anyhow!("audit log is down"))));
+
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let result = uc(user_profile_repo, audit_log)
+            .execute(&admin, "foo".to_string(), &AuditContext::default())
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn reset_user_name_is_err_when_profile_not_found() {
+        let mut user_profile_repo = MockUserProfileRepository::new();
+        user_profile_repo
+            .expect_resolve_for_update()
+            .returning(|_| Ok(None));
+
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let result = uc(user_profile_repo, MockAuditLog::new())
+            .execute(&admin, "foo".to_string(), &AuditContext::default())
+            .await;
+        assert!(matches!(
+            result,
+            Err(ResetUserNameUseCaseError::ProfileNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn reset_user_name_keep_profile_the_replica_has_not_seen_yet() {
+        let profile = |display_name: &str| {
+            Profile::new(
+                UserName::new("abusive".to_string()),
+                DisplayName::new(display_name.to_string()),
+                Avatar::new("https://example.com/avatar.png".to_string()),
+                None,
+            )
+        };
+        let mut user_profile_repo = MockUserProfileRepository::new();
+        user_profile_repo
+            .expect_resolve()
+            .returning(move |id| Ok(Some(UserProfile::new(id.clone(), profile("Stale")))));
+        user_profile_repo
+            .expect_resolve_for_update()
+            .returning(move |id| Ok(Some(UserProfile::new(id.clone(), profile("Current")))));
+        user_profile_repo
+            .expect_store()
+            .withf(|p| p.profile.display_name == DisplayName::new("Current".to_string()))
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit_log = MockAuditLog::new();
+        audit_log.expect_record().returning(|_| Ok(()));
+
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let result = uc(user_profile_repo, audit_log)
+            .execute(&admin, "foo".to_string(), &AuditContext::default())
+            .await;
+        assert!(result.is_ok());
+    }
+}
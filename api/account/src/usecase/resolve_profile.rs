@@ -9,11 +9,13 @@ use async_trait::async_trait;
 use derive_more::Constructor;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
 
 #[cfg(test)]
 use mockall::mock;
 
-#[derive(Debug, Constructor, Serialize, Deserialize)]
+#[derive(Debug, Constructor, Serialize, Deserialize, ToSchema, TS)]
 pub struct ResolveProfileUseCaseResult {
     pub user_profile: UserProfile,
 }
@@ -28,6 +30,7 @@ pub enum ResolveProfileUseCaseError {
 
 #[async_trait]
 pub trait ResolveProfileUseCase: HaveUserProfileRepository {
+    #[tracing::instrument(skip(self, actor), fields(user_id = %actor.0 .0))]
     async fn execute(
         &self,
         actor: &User,
@@ -90,6 +93,7 @@ mod tests {
                     UserName::new("foo".to_string()),
                     DisplayName::new("fooo".to_string()),
                     Avatar::new("avatar".to_string()),
+                    None,
                 ),
             )))
         });
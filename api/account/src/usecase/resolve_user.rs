@@ -0,0 +1,115 @@
+use crate::ability::user_manager::UserManager;
+use crate::model::user::{User, UserId};
+use crate::model::user_profile::{UserProfile, UserProfileId};
+use crate::repository::meta::{Repository, ResolveError};
+use crate::repository::user_profile_repository::HaveUserProfileRepository;
+use crate::repository::user_repository::HaveUserRepository;
+use async_trait::async_trait;
+use derive_more::Constructor;
+use serde::Serialize;
+use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Debug, Constructor, Serialize, ToSchema, TS)]
+pub struct ResolveUserUseCaseResult {
+    pub user: User,
+    pub user_profile: Option<UserProfile>,
+}
+
+#[derive(Error, Debug)]
+pub enum ResolveUserUseCaseError {
+    #[error(transparent)]
+    ResolveError(#[from] ResolveError),
+    #[error("User is not found. {0}")]
+    UserNotFound(String),
+}
+
+#[async_trait]
+pub trait ResolveUserUseCase: HaveUserRepository + HaveUserProfileRepository {
+    #[tracing::instrument(skip(self, _actor), fields(user_id = %_actor.operator_id().0))]
+    async fn execute<A: UserManager + Sync>(
+        &self,
+        _actor: &A,
+        user_id: String,
+    ) -> Result<ResolveUserUseCaseResult, ResolveUserUseCaseError> {
+        let user = match self
+            .user_repository()
+            .resolve(&UserId::new(user_id.clone()))
+            .await?
+        {
+            Some(u) => u,
+            None => return Err(ResolveUserUseCaseError::UserNotFound(user_id)),
+        };
+        let user_profile = self
+            .user_profile_repository()
+            .resolve(&UserProfileId::new(user_id))
+            .await?;
+        Ok(ResolveUserUseCaseResult::new(user, user_profile))
+    }
+}
+
+impl<T: HaveUserRepository + HaveUserProfileRepository> ResolveUserUseCase for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResolveUserUseCase, ResolveUserUseCaseError};
+    use crate::actor::admin::Admin;
+    use crate::model::user::{User, UserId};
+    use crate::repository::user_profile_repository::{
+        HaveUserProfileRepository, MockUserProfileRepository,
+    };
+    use crate::repository::user_repository::{HaveUserRepository, MockUserRepository};
+    use derive_more::Constructor;
+
+    #[derive(Constructor)]
+    struct UC {
+        user_repo: MockUserRepository,
+        user_profile_repo: MockUserProfileRepository,
+    }
+
+    impl HaveUserRepository for UC {
+        type UserRepository = MockUserRepository;
+        fn user_repository(&self) -> &Self::UserRepository {
+            &self.user_repo
+        }
+    }
+
+    impl HaveUserProfileRepository for UC {
+        type UserProfileRepository = MockUserProfileRepository;
+        fn user_profile_repository(&self) -> &Self::UserProfileRepository {
+            &self.user_profile_repo
+        }
+    }
+
+    #[tokio::test]
+    async fn resolve_user_return_to_user_without_profile() {
+        let mut user_repo = MockUserRepository::new();
+        let mut user_profile_repo = MockUserProfileRepository::new();
+        user_repo
+            .expect_resolve()
+            .returning(|id| Ok(Some(User::new(id.clone(), None))));
+        user_profile_repo.expect_resolve().returning(|_| Ok(None));
+
+        let result = UC::new(user_repo, user_profile_repo)
+            .execute(&Admin::default(), "foo".to_string())
+            .await
+            .unwrap();
+        assert_eq!(result.user.id, UserId::new("foo".to_string()));
+        assert!(result.user_profile.is_none());
+    }
+
+    #[tokio::test]
+    async fn resolve_user_is_err_when_not_found() {
+        let mut user_repo = MockUserRepository::new();
+        user_repo.expect_resolve().returning(|_| Ok(None));
+
+        let result = UC::new(user_repo, MockUserProfileRepository::new())
+            .execute(&Admin::default(), "foo".to_string())
+            .await;
+        assert!(matches!(
+            result,
+            Err(ResolveUserUseCaseError::UserNotFound(_))
+        ));
+    }
+}
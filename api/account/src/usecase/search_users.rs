@@ -0,0 +1,105 @@
+use crate::ability::user_manager::UserManager;
+use crate::model::user::User;
+use crate::repository::meta::{Page, Pagination};
+use crate::repository::user_repository::{
+    HaveUserRepository, SearchError, UserRepository, UserSearchCondition,
+};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use serde::Serialize;
+use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Debug, Constructor, Serialize, ToSchema, TS)]
+pub struct SearchUsersUseCaseResult {
+    pub users: Vec<User>,
+    #[ts(type = "number")]
+    pub total: i64,
+    #[ts(type = "number")]
+    pub offset: i64,
+    #[ts(type = "number")]
+    pub limit: i64,
+}
+
+#[derive(Error, Debug)]
+pub enum SearchUsersUseCaseError {
+    #[error(transparent)]
+    SearchError(#[from] SearchError),
+}
+
+#[async_trait]
+pub trait SearchUsersUseCase: HaveUserRepository {
+    #[tracing::instrument(skip(self, _actor), fields(user_id = %_actor.operator_id().0))]
+    async fn execute<A: UserManager + Sync>(
+        &self,
+        _actor: &A,
+        condition: UserSearchCondition,
+        pagination: Pagination,
+    ) -> Result<SearchUsersUseCaseResult, SearchUsersUseCaseError> {
+        let pagination = pagination.clamped();
+        let Page { items, total } = self
+            .user_repository()
+            .search(&condition, &pagination)
+            .await?;
+        Ok(SearchUsersUseCaseResult::new(
+            items,
+            total,
+            pagination.offset,
+            pagination.limit,
+        ))
+    }
+}
+
+impl<T: HaveUserRepository> SearchUsersUseCase for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::SearchUsersUseCase;
+    use crate::actor::admin::Admin;
+    use crate::model::user::{User, UserId};
+    use crate::repository::meta::{Page, Pagination, MAX_PAGE_SIZE};
+    use crate::repository::user_repository::{
+        HaveUserRepository, MockUserRepository, UserSearchCondition,
+    };
+    use derive_more::Constructor;
+
+    #[derive(Constructor)]
+    struct UC {
+        user_repo: MockUserRepository,
+    }
+
+    impl HaveUserRepository for UC {
+        type UserRepository = MockUserRepository;
+        fn user_repository(&self) -> &Self::UserRepository {
+            &self.user_repo
+        }
+    }
+
+    #[tokio::test]
+    async fn search_users_clamp_page_size() {
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_search()
+            .withf(|_, p| p.limit == MAX_PAGE_SIZE)
+            .times(1)
+            .returning(|_, _| {
+                Ok(Page::new(
+                    vec![User::new(UserId::new("foo".to_string()), None)],
+                    1,
+                ))
+            });
+
+        let result = UC::new(user_repo)
+            .execute(
+                &Admin::default(),
+                UserSearchCondition::default(),
+                Pagination::new(0, 1000),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.limit, MAX_PAGE_SIZE);
+        assert_eq!(result.users.len(), 1);
+    }
+}
@@ -3,10 +3,21 @@ use crate::adapter::firebase_auth::MockFirebaseAuthDriver;
 use crate::adapter::firebase_auth::{
     AccessToken, FirebaseAuthDriver, HaveFirebaseAuthDriver, VerifyError,
 };
+use crate::effect::audit_log::AuditRecorder;
 #[cfg(test)]
-use crate::effect::id_generator::MockIdGenerator;
-use crate::effect::id_generator::{HaveIdGenerator, IdGenerator};
+use crate::effect::audit_log::{HaveAuditLog, MockAuditLog};
+use crate::effect::clock::Clock;
+#[cfg(test)]
+use crate::effect::clock::{HaveClock, MockClock};
+use crate::effect::id_generator::IdGenerator;
+#[cfg(test)]
+use crate::effect::id_generator::{HaveIdGenerator, MockIdGenerator};
+#[cfg(test)]
+use crate::effect::rate_limit_store::MockRateLimitStore;
+use crate::effect::rate_limit_store::{HaveRateLimitStore, IdentityRateLimit, RateLimitDecision};
+use crate::model::audit_event::{AuditAction, AuditContext, AuditOutcome};
 use crate::model::login_provider::{IdInProvider, LoginProvider, ProviderKind};
+use crate::model::role::Role;
 use crate::model::user::{User, UserId};
 #[cfg(test)]
 use crate::repository::user_repository::MockUserRepository;
@@ -17,9 +28,11 @@ use async_trait::async_trait;
 use derive_more::Constructor;
 use serde::Serialize;
 use thiserror::Error;
-use tracing::info;
+use tracing::{info, Span};
+use ts_rs::TS;
+use utoipa::ToSchema;
 
-#[derive(Debug, Constructor, Serialize)]
+#[derive(Debug, Constructor, Serialize, ToSchema, TS)]
 pub struct SignUpUseCaseResult {
     user_id: UserId,
     name: String,
@@ -35,23 +48,42 @@ pub enum SignUpUseCaseError {
     StoreError(#[from] StoreError),
     #[error("User is already exist. (id: {0})")]
     AlreadyExist(String),
+    #[error("Too many sign ups. Retry after {retry_after} seconds")]
+    RateLimited { retry_after: i64 },
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
 
 #[async_trait]
-pub trait SignUpUseCase: HaveUserRepository + HaveFirebaseAuthDriver + HaveIdGenerator {
-    #[tracing::instrument(skip(self, token))]
-    async fn execute(&self, token: String) -> Result<SignUpUseCaseResult, SignUpUseCaseError> {
+pub trait SignUpUseCase:
+    HaveUserRepository + HaveFirebaseAuthDriver + HaveRateLimitStore + AuditRecorder
+{
+    /// `identity_limit` is counted against the Firebase user of the verified token.
+    #[tracing::instrument(skip(self, token), fields(user_id))]
+    async fn execute(
+        &self,
+        token: String,
+        context: &AuditContext,
+        identity_limit: Option<IdentityRateLimit>,
+    ) -> Result<SignUpUseCaseResult, SignUpUseCaseError> {
         let verify_result = self.firebase_auth().verify(AccessToken::new(token)).await?;
         let id_in_provider = IdInProvider::new(verify_result.uid.0);
+        if let Some(limit) = identity_limit {
+            let now = self.clock().now_utc().unix_timestamp();
+            if let RateLimitDecision::Limited { retry_after } = limit
+                .check(self.rate_limit_store(), &id_in_provider.0, now)
+                .await
+            {
+                return Err(SignUpUseCaseError::RateLimited { retry_after });
+            }
+        }
 
         let user_opt = self
             .user_repository()
             .find_by_id_in_provider(&id_in_provider)
             .await?;
-        if user_opt.is_some() {
-            info!("user(id:{:?}) is alread exist", user_opt.unwrap().id);
+        if let Some(user) = user_opt {
+            info!("user(id:{:?}) is alread exist", user.id);
             return Err(SignUpUseCaseError::AlreadyExist(id_in_provider.0.clone()));
         }
 
@@ -61,16 +93,39 @@ pub trait SignUpUseCase: HaveUserRepository + HaveFirebaseAuthDriver + HaveIdGen
                 ProviderKind::Google,
                 id_in_provider,
             )]),
-        );
+        )
+        .with_roles(vec![Role::Viewer]);
+        Span::current().record("user_id", &sign_up_user.id.0.as_str());
 
         self.user_repository().store(&sign_up_user).await?;
+        self.audit_or_warn(
+            context,
+            Some(sign_up_user.id.clone()),
+            AuditAction::SignedUp,
+            Some(sign_up_user.id.0.clone()),
+            AuditOutcome::Success,
+        )
+        .await;
+        for provider in sign_up_user.providers.iter() {
+            self.audit_or_warn(
+                context,
+                Some(sign_up_user.id.clone()),
+                AuditAction::ProviderLinked,
+                Some(String::from(&provider.kind)),
+                AuditOutcome::Success,
+            )
+            .await;
+        }
         Ok(SignUpUseCaseResult::new(
             sign_up_user.id,
             verify_result.full_name.0,
         ))
     }
 }
-impl<T: HaveUserRepository + HaveFirebaseAuthDriver + HaveIdGenerator> SignUpUseCase for T {}
+impl<T: HaveUserRepository + HaveFirebaseAuthDriver + HaveRateLimitStore + AuditRecorder>
+    SignUpUseCase for T
+{
+}
 
 #[cfg(test)]
 mockall::mock! {
@@ -90,26 +145,81 @@ mockall::mock! {
         type IdGenerator = MockIdGenerator;
         fn id_generator(&self) -> &MockIdGenerator;
     }
+
+    impl HaveAuditLog for SignUpUseCase {
+        type AuditLog = MockAuditLog;
+        fn audit_log(&self) -> &MockAuditLog;
+    }
+
+    impl HaveClock for SignUpUseCase {
+        type Clock = MockClock;
+        fn clock(&self) -> &MockClock;
+    }
+
+    impl HaveRateLimitStore for SignUpUseCase {
+        type RateLimitStore = MockRateLimitStore;
+        fn rate_limit_store(&self) -> &MockRateLimitStore;
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::SignUpUseCase;
+    use super::{SignUpUseCase, SignUpUseCaseError};
     use crate::adapter::firebase_auth::{
         FullName, HaveFirebaseAuthDriver, LocalId, MockFirebaseAuthDriver, VerifyError,
         VerifyResult,
     };
+    use crate::effect::audit_log::{AuditLogError, HaveAuditLog, MockAuditLog};
+    use crate::effect::clock::{HaveClock, MockClock};
     use crate::effect::id_generator::{HaveIdGenerator, MockIdGenerator};
-    use crate::model::user::{User, UserId};
+    use crate::effect::rate_limit_store::{
+        HaveRateLimitStore, IdentityRateLimit, MockRateLimitStore, RateLimitRule,
+    };
+    use crate::model::audit_event::{AuditAction, AuditContext};
+    use crate::model::user::User;
     use crate::repository::user_repository::{HaveUserRepository, MockUserRepository};
+    use anyhow::anyhow;
+    use time::OffsetDateTime;
 
-    use derive_more::Constructor;
-
-    #[derive(Constructor)]
     struct UC {
         user_repo: MockUserRepository,
         firebase_auth: MockFirebaseAuthDriver,
         id_gen: MockIdGenerator,
+        audit_log: MockAuditLog,
+        clock: MockClock,
+        rate_limit_store: MockRateLimitStore,
+    }
+
+    impl UC {
+        fn new(
+            user_repo: MockUserRepository,
+            firebase_auth: MockFirebaseAuthDriver,
+            id_gen: MockIdGenerator,
+        ) -> Self {
+            let mut audit_log = MockAuditLog::new();
+            audit_log.expect_record().returning(|_| Ok(()));
+            Self::with_audit_log(user_repo, firebase_auth, id_gen, audit_log)
+        }
+
+        fn with_audit_log(
+            user_repo: MockUserRepository,
+            firebase_auth: MockFirebaseAuthDriver,
+            id_gen: MockIdGenerator,
+            audit_log: MockAuditLog,
+        ) -> Self {
+            let mut clock = MockClock::new();
+            clock
+                .expect_now_utc()
+                .returning(|| OffsetDateTime::UNIX_EPOCH);
+            UC {
+                user_repo,
+                firebase_auth,
+                id_gen,
+                audit_log,
+                clock,
+                rate_limit_store: MockRateLimitStore::new(),
+            }
+        }
     }
     impl HaveUserRepository for UC {
         type UserRepository = MockUserRepository;
@@ -132,6 +242,27 @@ mod tests {
         }
     }
 
+    impl HaveAuditLog for UC {
+        type AuditLog = MockAuditLog;
+        fn audit_log(&self) -> &Self::AuditLog {
+            &self.audit_log
+        }
+    }
+
+    impl HaveClock for UC {
+        type Clock = MockClock;
+        fn clock(&self) -> &Self::Clock {
+            &self.clock
+        }
+    }
+
+    impl HaveRateLimitStore for UC {
+        type RateLimitStore = MockRateLimitStore;
+        fn rate_limit_store(&self) -> &Self::RateLimitStore {
+            &self.rate_limit_store
+        }
+    }
+
     #[tokio::test]
     async fn sign_up_return_ok_when_verify_ok_and_user_repository_return_empty() {
         let mut user_repo = MockUserRepository::new();
@@ -146,15 +277,45 @@ mod tests {
             Ok(VerifyResult::new(
                 LocalId::new("DUMMY".to_string()),
                 FullName::new("FULL NAME".to_string()),
+                None,
             ))
         });
         id_gen.expect_generate().returning(|| "xxxx".to_string());
         assert!(UC::new(user_repo, firebase_auth, id_gen)
-            .execute("xxxx".to_string())
+            .execute("xxxx".to_string(), &AuditContext::default(), None)
             .await
             .is_ok())
     }
 
+    #[tokio::test]
+    async fn sign_up_return_ok_when_audit_log_fails_after_store() {
+        let mut user_repo = MockUserRepository::new();
+        let mut firebase_auth = MockFirebaseAuthDriver::new();
+        let mut id_gen = MockIdGenerator::new();
+        let mut audit_log = MockAuditLog::new();
+
+        user_repo
+            .expect_find_by_id_in_provider()
+            .returning(|_| Ok(None));
+        user_repo.expect_store().times(1).returning(|_| Ok(()));
+        firebase_auth.expect_verify().returning(|_| {
+            Ok(VerifyResult::new(
+                LocalId::new("DUMMY".to_string()),
+                FullName::new("FULL NAME".to_string()),
+                None,
+            ))
+        });
+        id_gen.expect_generate().returning(|| "xxxx".to_string());
+        audit_log
+            .expect_record()
+            .returning(|_| Err(AuditLogError::Unexpected(anyhow!("audit log is down"))));
+
+        let result = UC::with_audit_log(user_repo, firebase_auth, id_gen, audit_log)
+            .execute("xxxx".to_string(), &AuditContext::default(), None)
+            .await;
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn sign_up_return_err_when_token_expire() {
         let mut user_repo = MockUserRepository::new();
@@ -170,7 +331,7 @@ mod tests {
             .returning(|_| Err(VerifyError::TokenExpired));
         id_gen.expect_generate().returning(|| "xxxx".to_string());
         let usecase_result = UC::new(user_repo, firebase_auth, id_gen)
-            .execute("xxxx".to_string())
+            .execute("xxxx".to_string(), &AuditContext::default(), None)
             .await;
         assert!(usecase_result.is_err());
         assert_eq!(
@@ -195,7 +356,7 @@ mod tests {
         id_gen.expect_generate().returning(|| "xxxx".to_string());
 
         let usecase_result = UC::new(user_repo, firebase_auth, id_gen)
-            .execute("xxxx".to_string())
+            .execute("xxxx".to_string(), &AuditContext::default(), None)
             .await;
         assert!(usecase_result.is_err());
         assert_eq!(
@@ -220,7 +381,7 @@ mod tests {
         id_gen.expect_generate().returning(|| "xxxx".to_string());
 
         let usecase_result = UC::new(user_repo, firebase_auth, id_gen)
-            .execute("xxxx".to_string())
+            .execute("xxxx".to_string(), &AuditContext::default(), None)
             .await;
         assert!(usecase_result.is_err());
         assert_eq!(
@@ -245,7 +406,7 @@ mod tests {
         id_gen.expect_generate().returning(|| "xxxx".to_string());
 
         let usecase_result = UC::new(user_repo, firebase_auth, id_gen)
-            .execute("xxxx".to_string())
+            .execute("xxxx".to_string(), &AuditContext::default(), None)
             .await;
         assert!(usecase_result.is_err());
         assert_eq!(
@@ -270,7 +431,7 @@ mod tests {
         id_gen.expect_generate().returning(|| "xxxx".to_string());
 
         let usecase_result = UC::new(user_repo, firebase_auth, id_gen)
-            .execute("xxxx".to_string())
+            .execute("xxxx".to_string(), &AuditContext::default(), None)
             .await;
         assert!(usecase_result.is_err());
         assert_eq!(
@@ -278,4 +439,77 @@ mod tests {
             "User is already exist. (id: )".to_string()
         );
     }
+
+    #[tokio::test]
+    async fn sign_up_record_sign_up_and_provider_link() {
+        let mut user_repo = MockUserRepository::new();
+        let mut firebase_auth = MockFirebaseAuthDriver::new();
+        let mut id_gen = MockIdGenerator::new();
+        let mut audit_log = MockAuditLog::new();
+
+        user_repo
+            .expect_find_by_id_in_provider()
+            .returning(|_| Ok(None));
+        user_repo.expect_store().returning(|_| Ok(()));
+        firebase_auth.expect_verify().returning(|_| {
+            Ok(VerifyResult::new(
+                LocalId::new("DUMMY".to_string()),
+                FullName::new("FULL NAME".to_string()),
+                None,
+            ))
+        });
+        id_gen.expect_generate().returning(|| "xxxx".to_string());
+        audit_log
+            .expect_record()
+            .withf(|e| e.action == AuditAction::SignedUp)
+            .times(1)
+            .returning(|_| Ok(()));
+        audit_log
+            .expect_record()
+            .withf(|e| {
+                e.action == AuditAction::ProviderLinked && e.target == Some("Google".to_string())
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+
+        assert!(
+            UC::with_audit_log(user_repo, firebase_auth, id_gen, audit_log)
+                .execute("xxxx".to_string(), &AuditContext::default(), None)
+                .await
+                .is_ok()
+        )
+    }
+
+    #[tokio::test]
+    async fn sign_up_return_err_when_identity_rate_limited() {
+        let user_repo = MockUserRepository::new();
+        let mut firebase_auth = MockFirebaseAuthDriver::new();
+        let id_gen = MockIdGenerator::new();
+
+        firebase_auth.expect_verify().returning(|_| {
+            Ok(VerifyResult::new(
+                LocalId::new("DUMMY".to_string()),
+                FullName::new("FULL NAME".to_string()),
+                None,
+            ))
+        });
+        let mut uc = UC::new(user_repo, firebase_auth, id_gen);
+        uc.rate_limit_store
+            .expect_increment()
+            .withf(|key, _, _| key == "sign_up:id:DUMMY")
+            .returning(|_, _, _| Ok(2));
+
+        let result = uc
+            .execute(
+                "xxxx".to_string(),
+                &AuditContext::default(),
+                Some(IdentityRateLimit::new("sign_up", RateLimitRule::new(1, 60))),
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(SignUpUseCaseError::RateLimited { retry_after: 60 })
+        ));
+    }
 }
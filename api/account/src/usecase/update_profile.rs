@@ -1,6 +1,16 @@
 use crate::ability::profile_creator::ProfileCreator;
 use crate::actor::user::User;
+use crate::effect::audit_log::AuditRecorder;
+#[cfg(test)]
+use crate::effect::audit_log::{HaveAuditLog, MockAuditLog};
+#[cfg(test)]
+use crate::effect::clock::{HaveClock, MockClock};
+#[cfg(test)]
+use crate::effect::id_generator::{HaveIdGenerator, MockIdGenerator};
+use crate::model::audit_event::{AuditAction, AuditContext, AuditOutcome};
 use crate::model::profile::entity::ProfileInvalidity;
+use crate::model::profile::locale::Locale;
+use crate::model::user::UserId;
 use crate::model::user_profile::{UserProfile, UserProfileId};
 #[cfg(test)]
 use crate::repository::user_profile_repository::MockUserProfileRepository;
@@ -14,20 +24,24 @@ use derive_more::Constructor;
 use semval::prelude::*;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
 
 #[cfg(test)]
 use mockall::mock;
 
-#[derive(Debug, Constructor, Serialize, Deserialize)]
+#[derive(Debug, Constructor, Serialize, Deserialize, ToSchema, TS)]
 pub struct UpdateProfileUseCaseResult {
     pub user_profile: UserProfile,
 }
 
-#[derive(Debug, Constructor, Serialize, Deserialize)]
+#[derive(Debug, Constructor, Serialize, Deserialize, ToSchema, TS)]
 pub struct UpdateProfileUseCaseParams {
     user_name: String,
     display_name: String,
     avatar_url: String,
+    #[serde(default)]
+    locale: Option<Locale>,
 }
 
 #[derive(Debug, Error)]
@@ -39,22 +53,37 @@ pub enum UpdateProfileUseCaseError {
 }
 
 #[async_trait]
-pub trait UpdateProfileUseCase: HaveUserProfileRepository {
+pub trait UpdateProfileUseCase: HaveUserProfileRepository + AuditRecorder {
+    #[tracing::instrument(skip(self, actor, params), fields(user_id = %actor.0 .0))]
     async fn execute(
         &self,
         actor: &User,
         params: UpdateProfileUseCaseParams,
+        context: &AuditContext,
     ) -> Result<UpdateProfileUseCaseResult, UpdateProfileUseCaseError> {
         let profile = actor
-            .create_profile(params.user_name, params.display_name, params.avatar_url)
-            .map_err(|e| UpdateProfileUseCaseError::ProfileValidationError(e))?;
+            .create_profile(
+                params.user_name,
+                params.display_name,
+                params.avatar_url,
+                params.locale,
+            )
+            .map_err(UpdateProfileUseCaseError::ProfileValidationError)?;
         let user_profile = UserProfile::new(UserProfileId::from(actor.0.clone()), profile);
         self.user_profile_repository().store(&user_profile).await?;
+        self.audit_or_warn(
+            context,
+            Some(UserId::new(actor.0 .0.clone())),
+            AuditAction::ProfileUpdated,
+            Some(user_profile.id.0.clone()),
+            AuditOutcome::Success,
+        )
+        .await;
         Ok(UpdateProfileUseCaseResult::new(user_profile))
     }
 }
 
-impl<T: HaveUserProfileRepository> UpdateProfileUseCase for T {}
+impl<T: HaveUserProfileRepository + AuditRecorder> UpdateProfileUseCase for T {}
 
 #[cfg(test)]
 mock! {
@@ -64,24 +93,66 @@ mock! {
         type UserProfileRepository = MockUserProfileRepository;
         fn user_profile_repository(&self) -> &MockUserProfileRepository;
     }
+
+    impl HaveAuditLog for UpdateProfileUseCase {
+        type AuditLog = MockAuditLog;
+        fn audit_log(&self) -> &MockAuditLog;
+    }
+
+    impl HaveIdGenerator for UpdateProfileUseCase {
+        type IdGenerator = MockIdGenerator;
+        fn id_generator(&self) -> &MockIdGenerator;
+    }
+
+    impl HaveClock for UpdateProfileUseCase {
+        type Clock = MockClock;
+        fn clock(&self) -> &MockClock;
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{
-        UpdateProfileUseCase, UpdateProfileUseCaseError, UpdateProfileUseCaseParams,
-        UpdateProfileUseCaseResult,
-    };
+    use super::{UpdateProfileUseCase, UpdateProfileUseCaseParams};
     use crate::actor::user::*;
+    use crate::effect::audit_log::{HaveAuditLog, MockAuditLog};
+    use crate::effect::clock::{HaveClock, MockClock};
+    use crate::effect::id_generator::{HaveIdGenerator, MockIdGenerator};
+    use crate::model::audit_event::{AuditAction, AuditContext};
     use crate::model::user_profile::UserProfileId;
     use crate::repository::user_profile_repository::{
         HaveUserProfileRepository, MockUserProfileRepository, StoreError,
     };
-    use derive_more::Constructor;
+    use time::OffsetDateTime;
 
-    #[derive(Constructor)]
     struct UC {
         user_profile_repository: MockUserProfileRepository,
+        audit_log: MockAuditLog,
+        id_generator: MockIdGenerator,
+        clock: MockClock,
+    }
+
+    impl UC {
+        fn new(user_profile_repository: MockUserProfileRepository) -> Self {
+            let mut audit_log = MockAuditLog::new();
+            audit_log
+                .expect_record()
+                .withf(|e| e.action == AuditAction::ProfileUpdated)
+                .returning(|_| Ok(()));
+            let mut id_generator = MockIdGenerator::new();
+            id_generator
+                .expect_generate()
+                .returning(|| "event".to_string());
+            let mut clock = MockClock::new();
+            clock
+                .expect_now_utc()
+                .returning(|| OffsetDateTime::UNIX_EPOCH);
+            UC {
+                user_profile_repository,
+                audit_log,
+                id_generator,
+                clock,
+            }
+        }
     }
 
     impl HaveUserProfileRepository for UC {
@@ -91,6 +162,27 @@ mod tests {
         }
     }
 
+    impl HaveAuditLog for UC {
+        type AuditLog = MockAuditLog;
+        fn audit_log(&self) -> &Self::AuditLog {
+            &self.audit_log
+        }
+    }
+
+    impl HaveIdGenerator for UC {
+        type IdGenerator = MockIdGenerator;
+        fn id_generator(&self) -> &Self::IdGenerator {
+            &self.id_generator
+        }
+    }
+
+    impl HaveClock for UC {
+        type Clock = MockClock;
+        fn clock(&self) -> &Self::Clock {
+            &self.clock
+        }
+    }
+
     #[tokio::test]
     async fn update_profile_usecase_return_to_profile() {
         let mut user_profile_repository = MockUserProfileRepository::new();
@@ -105,7 +197,9 @@ mod tests {
                     "xxxx".to_string(),
                     "XXXX".to_string(),
                     "https://example.com".to_string(),
+                    None,
                 ),
+                &AuditContext::default(),
             )
             .await;
         assert!(result.is_ok());
@@ -131,7 +225,9 @@ mod tests {
                     "xxxx".to_string(),
                     "XXXX".to_string(),
                     "https://example.com".to_string(),
+                    None,
                 ),
+                &AuditContext::default(),
             )
             .await;
         assert!(result.is_err());
@@ -151,7 +247,9 @@ mod tests {
                     "x".to_string(),
                     "XXXX".to_string(),
                     "https://example.com".to_string(),
+                    None,
                 ),
+                &AuditContext::default(),
             )
             .await;
         assert!(result.is_err());
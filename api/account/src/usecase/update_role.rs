@@ -0,0 +1,292 @@
+use crate::ability::role_granter::{RoleGrantError, RoleGranter};
+use crate::ability::user_manager::UserManager;
+use crate::effect::audit_log::AuditRecorder;
+use crate::effect::verified_token_cache::{HaveVerifiedTokenCache, VerifiedTokenCache};
+use crate::model::audit_event::{AuditAction, AuditContext, AuditOutcome};
+use crate::model::role::Role;
+use crate::model::user::{User, UserId};
+use crate::repository::meta::ResolveError;
+use crate::repository::user_repository::{HaveUserRepository, StoreError, UserRepository};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Debug, Constructor, Serialize, ToSchema, TS)]
+pub struct UpdateRoleUseCaseResult {
+    pub user: User,
+}
+
+#[derive(Debug, Constructor, Serialize, Deserialize, ToSchema, TS)]
+pub struct UpdateRoleUseCaseParams {
+    pub user_id: String,
+    pub role: Role,
+    pub granted: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum UpdateRoleUseCaseError {
+    #[error(transparent)]
+    ResolveError(#[from] ResolveError),
+    #[error(transparent)]
+    StoreError(#[from] StoreError),
+    #[error(transparent)]
+    RoleGrantError(#[from] RoleGrantError),
+    #[error("User is not found. {0}")]
+    UserNotFound(String),
+}
+
+#[async_trait]
+pub trait UpdateRoleUseCase: HaveUserRepository + HaveVerifiedTokenCache + AuditRecorder {
+    #[tracing::instrument(skip(self, actor), fields(user_id = %actor.operator_id().0))]
+    async fn execute<A: RoleGranter + UserManager + Sync>(
+        &self,
+        actor: &A,
+        params: UpdateRoleUseCaseParams,
+        context: &AuditContext,
+    ) -> Result<UpdateRoleUseCaseResult, UpdateRoleUseCaseError> {
+        let mut user = match self
+            .user_repository()
+            .resolve_for_update(&UserId::new(params.user_id.clone()))
+            .await?
+        {
+            Some(u) => u,
+            None => return Err(UpdateRoleUseCaseError::UserNotFound(params.user_id)),
+        };
+        let action = if params.granted {
+            actor.grant_role(&mut user, params.role)?;
+            AuditAction::RoleGranted
+        } else {
+            actor.revoke_role(&mut user, params.role)?;
+            AuditAction::RoleRevoked
+        };
+        self.user_repository().store(&user).await?;
+        self.verified_token_cache().invalidate_user(&user.id);
+        self.audit_or_warn(
+            context,
+            Some(actor.operator_id()),
+            action,
+            Some(format!("{}:{}", user.id.0, String::from(&params.role))),
+            AuditOutcome::Success,
+        )
+        .await;
+        Ok(UpdateRoleUseCaseResult::new(user))
+    }
+}
+
+impl<T: HaveUserRepository + HaveVerifiedTokenCache + AuditRecorder> UpdateRoleUseCase for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::{UpdateRoleUseCase, UpdateRoleUseCaseError, UpdateRoleUseCaseParams};
+    use crate::actor::admin::Admin;
+    use crate::actor::user::UserId as ActorUserId;
+    use crate::effect::audit_log::{AuditLogError, HaveAuditLog, MockAuditLog};
+    use crate::effect::clock::{HaveClock, MockClock};
+    use crate::effect::id_generator::{HaveIdGenerator, MockIdGenerator};
+    use crate::effect::verified_token_cache::{HaveVerifiedTokenCache, MockVerifiedTokenCache};
+    use crate::model::audit_event::{AuditAction, AuditContext};
+    use crate::model::role::Role;
+    use crate::model::user::User;
+    use crate::repository::user_repository::{HaveUserRepository, MockUserRepository};
+    use anyhow::anyhow;
+    use derive_more::Constructor;
+    use time::OffsetDateTime;
+
+    #[derive(Constructor)]
+    struct UC {
+        user_repo: MockUserRepository,
+        audit_log: MockAuditLog,
+        id_generator: MockIdGenerator,
+        clock: MockClock,
+        verified_token_cache: MockVerifiedTokenCache,
+    }
+
+    impl HaveUserRepository for UC {
+        type UserRepository = MockUserRepository;
+        fn user_repository(&self) -> &Self::UserRepository {
+            &self.user_repo
+        }
+    }
+
+    impl HaveAuditLog for UC {
+        type AuditLog = MockAuditLog;
+        fn audit_log(&self) -> &Self::AuditLog {
+            &self.audit_log
+        }
+    }
+
+    impl HaveIdGenerator for UC {
+        type IdGenerator = MockIdGenerator;
+        fn id_generator(&self) -> &Self::IdGenerator {
+            &self.id_generator
+        }
+    }
+
+    impl HaveClock for UC {
+        type Clock = MockClock;
+        fn clock(&self) -> &Self::Clock {
+            &self.clock
+        }
+    }
+
+    impl HaveVerifiedTokenCache for UC {
+        type VerifiedTokenCache = MockVerifiedTokenCache;
+        fn verified_token_cache(&self) -> &Self::VerifiedTokenCache {
+            &self.verified_token_cache
+        }
+    }
+
+    fn uc(user_repo: MockUserRepository, audit_log: MockAuditLog) -> UC {
+        let mut id_generator = MockIdGenerator::new();
+        id_generator
+            .expect_generate()
+            .returning(|| "event".to_string());
+        let mut clock = MockClock::new();
+        clock
+            .expect_now_utc()
+            .returning(|| OffsetDateTime::UNIX_EPOCH);
+        let mut verified_token_cache = MockVerifiedTokenCache::new();
+        verified_token_cache
+            .expect_invalidate_user()
+            .returning(|_| ());
+        UC::new(
+            user_repo,
+            audit_log,
+            id_generator,
+            clock,
+            verified_token_cache,
+        )
+    }
+
+    #[tokio::test]
+    async fn update_role_store_user_with_granted_role() {
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_resolve_for_update()
+            .returning(|id| Ok(Some(User::new(id.clone(), None))));
+        user_repo
+            .expect_store()
+            .withf(|u| u.roles == vec![Role::Editor])
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit_log = MockAuditLog::new();
+        audit_log
+            .expect_record()
+            .withf(|e| {
+                e.action == AuditAction::RoleGranted && e.target == Some("foo:Editor".to_string())
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let result = uc(user_repo, audit_log)
+            .execute(
+                &admin,
+                UpdateRoleUseCaseParams::new("foo".to_string(), Role::Editor, true),
+                &AuditContext::default(),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn update_role_return_ok_when_audit_log_fails_after_store() {
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_resolve_for_update()
+            .returning(|id| Ok(Some(User::new(id.clone(), None))));
+        user_repo.expect_store().times(1).returning(|_| Ok(()));
+        let mut audit_log = MockAuditLog::new();
+        audit_log
+            .expect_record()
+            .returning(|_| Err(AuditLogError::Unexpected(anyhow!("audit log is down"))));
+
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let result = uc(user_repo, audit_log)
+            .execute(
+                &admin,
+                UpdateRoleUseCaseParams::new("foo".to_string(), Role::Admin, true),
+                &AuditContext::default(),
+            )
+            .await;
+        assert_eq!(result.unwrap().user.roles, vec![Role::Admin]);
+    }
+
+    #[tokio::test]
+    async fn update_role_is_err_when_user_not_found() {
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_resolve_for_update()
+            .returning(|_| Ok(None));
+
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let result = uc(user_repo, MockAuditLog::new())
+            .execute(
+                &admin,
+                UpdateRoleUseCaseParams::new("foo".to_string(), Role::Editor, true),
+                &AuditContext::default(),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(UpdateRoleUseCaseError::UserNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn update_role_is_err_when_admin_demotes_self() {
+        let mut user_repo = MockUserRepository::new();
+        user_repo.expect_resolve_for_update().returning(|id| {
+            Ok(Some(
+                User::new(id.clone(), None).with_roles(vec![Role::Admin]),
+            ))
+        });
+        user_repo.expect_store().never();
+
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let result = uc(user_repo, MockAuditLog::new())
+            .execute(
+                &admin,
+                UpdateRoleUseCaseParams::new("admin".to_string(), Role::Admin, false),
+                &AuditContext::default(),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(UpdateRoleUseCaseError::RoleGrantError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn update_role_keep_roles_the_replica_has_not_seen_yet() {
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_resolve()
+            .returning(|id| Ok(Some(User::new(id.clone(), None))));
+        user_repo.expect_resolve_for_update().returning(|id| {
+            Ok(Some(
+                User::new(id.clone(), None).with_roles(vec![Role::Admin]),
+            ))
+        });
+        user_repo
+            .expect_store()
+            .withf(|u| u.roles.contains(&Role::Admin) && u.roles.contains(&Role::Editor))
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit_log = MockAuditLog::new();
+        audit_log.expect_record().returning(|_| Ok(()));
+
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let result = uc(user_repo, audit_log)
+            .execute(
+                &admin,
+                UpdateRoleUseCaseParams::new("foo".to_string(), Role::Editor, true),
+                &AuditContext::default(),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,281 @@
+use crate::ability::user_manager::{UserManageError, UserManager};
+use crate::effect::audit_log::AuditRecorder;
+use crate::effect::verified_token_cache::{HaveVerifiedTokenCache, VerifiedTokenCache};
+use crate::model::audit_event::{AuditAction, AuditContext, AuditOutcome};
+use crate::model::user::{User, UserId};
+use crate::repository::meta::ResolveError;
+use crate::repository::user_repository::{HaveUserRepository, StoreError, UserRepository};
+use async_trait::async_trait;
+use derive_more::Constructor;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Debug, Constructor, Serialize, ToSchema, TS)]
+pub struct UpdateUserStatusUseCaseResult {
+    pub user: User,
+}
+
+#[derive(Debug, Constructor, Serialize, Deserialize, ToSchema, TS)]
+pub struct UpdateUserStatusUseCaseParams {
+    pub user_id: String,
+    pub disabled: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum UpdateUserStatusUseCaseError {
+    #[error(transparent)]
+    ResolveError(#[from] ResolveError),
+    #[error(transparent)]
+    StoreError(#[from] StoreError),
+    #[error(transparent)]
+    UserManageError(#[from] UserManageError),
+    #[error("User is not found. {0}")]
+    UserNotFound(String),
+}
+
+#[async_trait]
+pub trait UpdateUserStatusUseCase:
+    HaveUserRepository + HaveVerifiedTokenCache + AuditRecorder
+{
+    #[tracing::instrument(skip(self, actor), fields(user_id = %actor.operator_id().0))]
+    async fn execute<A: UserManager + Sync>(
+        &self,
+        actor: &A,
+        params: UpdateUserStatusUseCaseParams,
+        context: &AuditContext,
+    ) -> Result<UpdateUserStatusUseCaseResult, UpdateUserStatusUseCaseError> {
+        let mut user = match self
+            .user_repository()
+            .resolve_for_update(&UserId::new(params.user_id.clone()))
+            .await?
+        {
+            Some(u) => u,
+            None => return Err(UpdateUserStatusUseCaseError::UserNotFound(params.user_id)),
+        };
+        let action = if params.disabled {
+            actor.disable_user(&mut user)?;
+            AuditAction::UserDisabled
+        } else {
+            actor.enable_user(&mut user)?;
+            AuditAction::UserEnabled
+        };
+        self.user_repository().store(&user).await?;
+        self.verified_token_cache().invalidate_user(&user.id);
+        self.audit_or_warn(
+            context,
+            Some(actor.operator_id()),
+            action,
+            Some(user.id.0.clone()),
+            AuditOutcome::Success,
+        )
+        .await;
+        Ok(UpdateUserStatusUseCaseResult::new(user))
+    }
+}
+
+impl<T: HaveUserRepository + HaveVerifiedTokenCache + AuditRecorder> UpdateUserStatusUseCase for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        UpdateUserStatusUseCase, UpdateUserStatusUseCaseError, UpdateUserStatusUseCaseParams,
+    };
+    use crate::actor::admin::Admin;
+    use crate::actor::user::UserId as ActorUserId;
+    use crate::effect::audit_log::{AuditLogError, HaveAuditLog, MockAuditLog};
+    use crate::effect::clock::{HaveClock, MockClock};
+    use crate::effect::id_generator::{HaveIdGenerator, MockIdGenerator};
+    use crate::effect::verified_token_cache::{HaveVerifiedTokenCache, MockVerifiedTokenCache};
+    use crate::model::audit_event::{AuditAction, AuditContext};
+    use crate::model::role::Role;
+    use crate::model::user::{User, UserStatus};
+    use crate::repository::user_repository::{HaveUserRepository, MockUserRepository};
+    use anyhow::anyhow;
+    use derive_more::Constructor;
+    use time::OffsetDateTime;
+
+    #[derive(Constructor)]
+    struct UC {
+        user_repo: MockUserRepository,
+        audit_log: MockAuditLog,
+        id_generator: MockIdGenerator,
+        clock: MockClock,
+        verified_token_cache: MockVerifiedTokenCache,
+    }
+
+    impl HaveUserRepository for UC {
+        type UserRepository = MockUserRepository;
+        fn user_repository(&self) -> &Self::UserRepository {
+            &self.user_repo
+        }
+    }
+
+    impl HaveAuditLog for UC {
+        type AuditLog = MockAuditLog;
+        fn audit_log(&self) -> &Self::AuditLog {
+            &self.audit_log
+        }
+    }
+
+    impl HaveIdGenerator for UC {
+        type IdGenerator = MockIdGenerator;
+        fn id_generator(&self) -> &Self::IdGenerator {
+            &self.id_generator
+        }
+    }
+
+    impl HaveClock for UC {
+        type Clock = MockClock;
+        fn clock(&self) -> &Self::Clock {
+            &self.clock
+        }
+    }
+
+    impl HaveVerifiedTokenCache for UC {
+        type VerifiedTokenCache = MockVerifiedTokenCache;
+        fn verified_token_cache(&self) -> &Self::VerifiedTokenCache {
+            &self.verified_token_cache
+        }
+    }
+
+    fn uc(user_repo: MockUserRepository, audit_log: MockAuditLog) -> UC {
+        let mut id_generator = MockIdGenerator::new();
+        id_generator
+            .expect_generate()
+            .returning(|| "event".to_string());
+        let mut clock = MockClock::new();
+        clock
+            .expect_now_utc()
+            .returning(|| OffsetDateTime::UNIX_EPOCH);
+        let mut verified_token_cache = MockVerifiedTokenCache::new();
+        verified_token_cache
+            .expect_invalidate_user()
+            .returning(|_| ());
+        UC::new(
+            user_repo,
+            audit_log,
+            id_generator,
+            clock,
+            verified_token_cache,
+        )
+    }
+
+    #[tokio::test]
+    async fn update_user_status_store_disabled_user_and_record_event() {
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_resolve_for_update()
+            .returning(|id| Ok(Some(User::new(id.clone(), None))));
+        user_repo
+            .expect_store()
+            .withf(|u| u.status == UserStatus::Disabled)
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit_log = MockAuditLog::new();
+        audit_log
+            .expect_record()
+            .withf(|e| {
+                e.action == AuditAction::UserDisabled
+                    && e.actor.as_ref().map(|a| a.0.as_str()) == Some("admin")
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut verified_token_cache = MockVerifiedTokenCache::new();
+        verified_token_cache
+            .expect_invalidate_user()
+            .withf(|id| id.0 == "foo")
+            .times(1)
+            .returning(|_| ());
+
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let mut uc = uc(user_repo, audit_log);
+        uc.verified_token_cache = verified_token_cache;
+        let result = uc
+            .execute(
+                &admin,
+                UpdateUserStatusUseCaseParams::new("foo".to_string(), true),
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        assert!(result.user.is_disabled());
+    }
+
+    #[tokio::test]
+    async fn update_user_status_return_ok_when_audit_log_fails_after_store() {
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_resolve_for_update()
+            .returning(|id| Ok(Some(User::new(id.clone(), None))));
+        user_repo.expect_store().times(1).returning(|_| Ok(()));
+        let mut audit_log = MockAuditLog::new();
+        audit_log
+            .expect_record()
+            .returning(|_| Err(AuditLogError::Unexpected(anyhow!("audit log is down"))));
+
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let result = uc(user_repo, audit_log)
+            .execute(
+                &admin,
+                UpdateUserStatusUseCaseParams::new("foo".to_string(), true),
+                &AuditContext::default(),
+            )
+            .await;
+        assert!(result.unwrap().user.is_disabled());
+    }
+
+    #[tokio::test]
+    async fn update_user_status_is_err_when_admin_disables_self() {
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_resolve_for_update()
+            .returning(|id| Ok(Some(User::new(id.clone(), None))));
+        user_repo.expect_store().never();
+
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let result = uc(user_repo, MockAuditLog::new())
+            .execute(
+                &admin,
+                UpdateUserStatusUseCaseParams::new("admin".to_string(), true),
+                &AuditContext::default(),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(UpdateUserStatusUseCaseError::UserManageError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn update_user_status_keep_roles_the_replica_has_not_seen_yet() {
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_resolve()
+            .returning(|id| Ok(Some(User::new(id.clone(), None))));
+        user_repo.expect_resolve_for_update().returning(|id| {
+            Ok(Some(
+                User::new(id.clone(), None).with_roles(vec![Role::Editor]),
+            ))
+        });
+        user_repo
+            .expect_store()
+            .withf(|u| u.status == UserStatus::Disabled && u.roles == vec![Role::Editor])
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit_log = MockAuditLog::new();
+        audit_log.expect_record().returning(|_| Ok(()));
+
+        let admin = Admin::new(ActorUserId::new("admin".to_string()));
+        let result = uc(user_repo, audit_log)
+            .execute(
+                &admin,
+                UpdateUserStatusUseCaseParams::new("foo".to_string(), true),
+                &AuditContext::default(),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+}
@@ -1,8 +1,13 @@
 use crate::adapter::firebase_auth::{
     AccessToken, FirebaseAuthDriver, HaveFirebaseAuthDriver, VerifyError,
 };
+use crate::effect::audit_log::AuditRecorder;
+use crate::effect::clock::{Clock, HaveClock};
+use crate::effect::rate_limit_store::{HaveRateLimitStore, IdentityRateLimit, RateLimitDecision};
+use crate::effect::verified_token_cache::{HaveVerifiedTokenCache, VerifiedTokenCache};
+use crate::model::audit_event::{AuditAction, AuditContext, AuditOutcome};
 use crate::model::login_provider::IdInProvider;
-use crate::model::user::User;
+use crate::model::user::{User, UserId};
 use crate::repository::user_repository::{
     FilterByIdInProviderError, HaveUserRepository, UserRepository,
 };
@@ -10,13 +15,27 @@ use async_trait::async_trait;
 use derive_more::Constructor;
 use serde::Serialize;
 use thiserror::Error;
+use time::OffsetDateTime;
+use tracing::Span;
+use ts_rs::TS;
+use utoipa::ToSchema;
 
 #[cfg(test)]
 use crate::adapter::firebase_auth::MockFirebaseAuthDriver;
 #[cfg(test)]
+use crate::effect::audit_log::{HaveAuditLog, MockAuditLog};
+#[cfg(test)]
+use crate::effect::clock::MockClock;
+#[cfg(test)]
+use crate::effect::id_generator::{HaveIdGenerator, MockIdGenerator};
+#[cfg(test)]
+use crate::effect::rate_limit_store::MockRateLimitStore;
+#[cfg(test)]
+use crate::effect::verified_token_cache::MockVerifiedTokenCache;
+#[cfg(test)]
 use crate::repository::user_repository::MockUserRepository;
 
-#[derive(Debug, Constructor, Serialize)]
+#[derive(Debug, Constructor, Serialize, ToSchema, TS)]
 pub struct VerifyUseCaseResult {
     pub user: User,
 }
@@ -29,29 +48,134 @@ pub enum VerifyUseCaseError {
     FilterError(#[from] FilterByIdInProviderError),
     #[error("User is not found. {0}")]
     UserNotFound(String),
+    #[error("User is disabled. {0}")]
+    UserDisabled(String),
+    #[error("Too many verifications. Retry after {retry_after} seconds")]
+    RateLimited { retry_after: i64 },
+}
+
+async fn admit<T>(
+    uc: &T,
+    identity_limit: Option<IdentityRateLimit>,
+    provider_id: &IdInProvider,
+    now: OffsetDateTime,
+) -> Result<(), VerifyUseCaseError>
+where
+    T: HaveRateLimitStore + Sync + ?Sized,
+{
+    let limit = match identity_limit {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+    match limit
+        .check(uc.rate_limit_store(), &provider_id.0, now.unix_timestamp())
+        .await
+    {
+        RateLimitDecision::Allowed => Ok(()),
+        RateLimitDecision::Limited { retry_after } => {
+            Err(VerifyUseCaseError::RateLimited { retry_after })
+        }
+    }
+}
+
+async fn verify_user<T>(
+    uc: &T,
+    token: &str,
+    identity_limit: Option<IdentityRateLimit>,
+) -> Result<User, VerifyUseCaseError>
+where
+    T: HaveUserRepository
+        + HaveFirebaseAuthDriver
+        + HaveVerifiedTokenCache
+        + HaveRateLimitStore
+        + HaveClock
+        + Sync
+        + ?Sized,
+{
+    let now = uc.clock().now_utc();
+    if let Some(user) = uc.verified_token_cache().get(token, now) {
+        // Users are cached as found by the provider the token was verified to, which is
+        // therefore their only provider.
+        if let Some(provider) = user.providers.first() {
+            admit(uc, identity_limit, &provider.id_in_provider, now).await?;
+        }
+        return Ok(user);
+    }
+    let verify_result = uc
+        .firebase_auth()
+        .verify(AccessToken::new(token.to_string()))
+        .await?;
+    let provider_id = IdInProvider::new(verify_result.uid.0);
+    admit(uc, identity_limit, &provider_id, now).await?;
+    let user = match uc
+        .user_repository()
+        .find_by_id_in_provider(&provider_id)
+        .await?
+    {
+        Some(u) => u,
+        None => return Err(VerifyUseCaseError::UserNotFound(provider_id.0)),
+    };
+    if user.is_disabled() {
+        return Err(VerifyUseCaseError::UserDisabled(user.id.0));
+    }
+    uc.verified_token_cache()
+        .put(token, &user, verify_result.expires_at, now);
+    Ok(user)
 }
 
 #[async_trait]
-pub trait VerifyUseCase: HaveUserRepository + HaveFirebaseAuthDriver {
-    async fn execute(&self, token: &str) -> Result<VerifyUseCaseResult, VerifyUseCaseError> {
-        let verify_result = self
-            .firebase_auth()
-            .verify(AccessToken::new(token.to_string()))
-            .await?;
-        let provider_id = IdInProvider::new(verify_result.uid.0);
-        let user = match self
-            .user_repository()
-            .find_by_id_in_provider(&provider_id)
-            .await?
-        {
-            Some(u) => u,
-            None => return Err(VerifyUseCaseError::UserNotFound(provider_id.0)),
-        };
-        Ok(VerifyUseCaseResult::new(user))
+pub trait VerifyUseCase:
+    HaveUserRepository
+    + HaveFirebaseAuthDriver
+    + HaveVerifiedTokenCache
+    + HaveRateLimitStore
+    + AuditRecorder
+{
+    /// `identity_limit` is counted against the Firebase user of the verified token.
+    #[tracing::instrument(skip(self, token), fields(user_id))]
+    async fn execute(
+        &self,
+        token: &str,
+        context: &AuditContext,
+        identity_limit: Option<IdentityRateLimit>,
+    ) -> Result<VerifyUseCaseResult, VerifyUseCaseError> {
+        match verify_user(self, token, identity_limit).await {
+            Ok(user) => {
+                Span::current().record("user_id", &user.id.0.as_str());
+                Ok(VerifyUseCaseResult::new(user))
+            }
+            // Not audited, so that a flood of rejected requests or garbage tokens writes
+            // nothing: neither names a verified user.
+            Err(
+                e @ (VerifyUseCaseError::RateLimited { .. } | VerifyUseCaseError::VerifyFailed(_)),
+            ) => Err(e),
+            Err(e) => {
+                let actor = match &e {
+                    VerifyUseCaseError::UserDisabled(id) => Some(UserId::new(id.clone())),
+                    _ => None,
+                };
+                self.audit_or_warn(
+                    context,
+                    actor,
+                    AuditAction::Authenticated,
+                    None,
+                    AuditOutcome::Failure(e.to_string()),
+                )
+                .await;
+                Err(e)
+            }
+        }
     }
 }
 
-impl<T: HaveUserRepository + HaveFirebaseAuthDriver> VerifyUseCase for T {}
+impl<T> VerifyUseCase for T where
+    T: HaveUserRepository
+        + HaveFirebaseAuthDriver
+        + HaveVerifiedTokenCache
+        + HaveRateLimitStore
+        + AuditRecorder
+{
+}
 
 #[cfg(test)]
 mockall::mock! {
@@ -66,23 +190,96 @@ mockall::mock! {
         type FirebaseAuthDriver = MockFirebaseAuthDriver;
         fn firebase_auth(&self) -> &MockFirebaseAuthDriver;
     }
+
+    impl HaveAuditLog for VerifyUseCase {
+        type AuditLog = MockAuditLog;
+        fn audit_log(&self) -> &MockAuditLog;
+    }
+
+    impl HaveIdGenerator for VerifyUseCase {
+        type IdGenerator = MockIdGenerator;
+        fn id_generator(&self) -> &MockIdGenerator;
+    }
+
+    impl HaveClock for VerifyUseCase {
+        type Clock = MockClock;
+        fn clock(&self) -> &MockClock;
+    }
+
+    impl HaveVerifiedTokenCache for VerifyUseCase {
+        type VerifiedTokenCache = MockVerifiedTokenCache;
+        fn verified_token_cache(&self) -> &MockVerifiedTokenCache;
+    }
+
+    impl HaveRateLimitStore for VerifyUseCase {
+        type RateLimitStore = MockRateLimitStore;
+        fn rate_limit_store(&self) -> &MockRateLimitStore;
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::VerifyUseCase;
+    use super::{VerifyUseCase, VerifyUseCaseError};
 
     use crate::adapter::firebase_auth::{
-        HaveFirebaseAuthDriver, MockFirebaseAuthDriver, VerifyError, VerifyResult,
+        HaveFirebaseAuthDriver, LocalId, MockFirebaseAuthDriver, VerifyError, VerifyResult,
+    };
+    use crate::effect::audit_log::{HaveAuditLog, MockAuditLog};
+    use crate::effect::clock::{HaveClock, MockClock};
+    use crate::effect::id_generator::{HaveIdGenerator, MockIdGenerator};
+    use crate::effect::rate_limit_store::{
+        HaveRateLimitStore, IdentityRateLimit, MockRateLimitStore, RateLimitRule,
     };
-    use crate::model::user::User;
+    use crate::effect::verified_token_cache::{HaveVerifiedTokenCache, MockVerifiedTokenCache};
+    use crate::model::audit_event::{AuditAction, AuditContext, AuditOutcome};
+    use crate::model::login_provider::{IdInProvider, LoginProvider, ProviderKind};
+    use crate::model::user::{User, UserId, UserStatus};
     use crate::repository::user_repository::{HaveUserRepository, MockUserRepository};
-    use derive_more::Constructor;
+    use time::OffsetDateTime;
 
-    #[derive(Constructor)]
     struct UC {
         user_repo: MockUserRepository,
         firebase_auth: MockFirebaseAuthDriver,
+        audit_log: MockAuditLog,
+        id_generator: MockIdGenerator,
+        clock: MockClock,
+        verified_token_cache: MockVerifiedTokenCache,
+        rate_limit_store: MockRateLimitStore,
+    }
+
+    impl UC {
+        fn new(user_repo: MockUserRepository, firebase_auth: MockFirebaseAuthDriver) -> Self {
+            let mut audit_log = MockAuditLog::new();
+            audit_log.expect_record().returning(|_| Ok(()));
+            Self::with_audit_log(user_repo, firebase_auth, audit_log)
+        }
+
+        fn with_audit_log(
+            user_repo: MockUserRepository,
+            firebase_auth: MockFirebaseAuthDriver,
+            audit_log: MockAuditLog,
+        ) -> Self {
+            let mut id_generator = MockIdGenerator::new();
+            id_generator
+                .expect_generate()
+                .returning(|| "event".to_string());
+            let mut clock = MockClock::new();
+            clock
+                .expect_now_utc()
+                .returning(|| OffsetDateTime::UNIX_EPOCH);
+            let mut verified_token_cache = MockVerifiedTokenCache::new();
+            verified_token_cache.expect_get().returning(|_, _| None);
+            verified_token_cache.expect_put().returning(|_, _, _, _| ());
+            UC {
+                user_repo,
+                firebase_auth,
+                audit_log,
+                id_generator,
+                clock,
+                verified_token_cache,
+                rate_limit_store: MockRateLimitStore::new(),
+            }
+        }
     }
     impl HaveUserRepository for UC {
         type UserRepository = MockUserRepository;
@@ -98,6 +295,41 @@ mod tests {
         }
     }
 
+    impl HaveAuditLog for UC {
+        type AuditLog = MockAuditLog;
+        fn audit_log(&self) -> &Self::AuditLog {
+            &self.audit_log
+        }
+    }
+
+    impl HaveIdGenerator for UC {
+        type IdGenerator = MockIdGenerator;
+        fn id_generator(&self) -> &Self::IdGenerator {
+            &self.id_generator
+        }
+    }
+
+    impl HaveClock for UC {
+        type Clock = MockClock;
+        fn clock(&self) -> &Self::Clock {
+            &self.clock
+        }
+    }
+
+    impl HaveVerifiedTokenCache for UC {
+        type VerifiedTokenCache = MockVerifiedTokenCache;
+        fn verified_token_cache(&self) -> &Self::VerifiedTokenCache {
+            &self.verified_token_cache
+        }
+    }
+
+    impl HaveRateLimitStore for UC {
+        type RateLimitStore = MockRateLimitStore;
+        fn rate_limit_store(&self) -> &Self::RateLimitStore {
+            &self.rate_limit_store
+        }
+    }
+
     #[tokio::test]
     async fn verify_use_case_return_to_user_when_ok() {
         let mut user_repository = MockUserRepository::new();
@@ -111,7 +343,61 @@ mod tests {
             .returning(|_| Ok(VerifyResult::default()));
 
         assert!(UC::new(user_repository, firebase_auth)
-            .execute("xxx")
+            .execute("xxx", &AuditContext::default(), None)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn verify_use_case_return_to_cached_user_without_verify() {
+        let mut user_repository = MockUserRepository::new();
+        let mut firebase_auth = MockFirebaseAuthDriver::new();
+        let mut verified_token_cache = MockVerifiedTokenCache::new();
+
+        user_repository.expect_find_by_id_in_provider().never();
+        firebase_auth.expect_verify().never();
+        verified_token_cache
+            .expect_get()
+            .withf(|token, _| token == "xxx")
+            .returning(|_, _| Some(User::new(UserId::new("cached".to_string()), None)));
+        verified_token_cache.expect_put().never();
+
+        let mut uc = UC::new(user_repository, firebase_auth);
+        uc.verified_token_cache = verified_token_cache;
+        let result = uc
+            .execute("xxx", &AuditContext::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(result.user.id, UserId::new("cached".to_string()));
+    }
+
+    #[tokio::test]
+    async fn verify_use_case_cache_user_until_token_expires() {
+        let mut user_repository = MockUserRepository::new();
+        let mut firebase_auth = MockFirebaseAuthDriver::new();
+        let mut verified_token_cache = MockVerifiedTokenCache::new();
+        let expires_at = OffsetDateTime::from_unix_timestamp(3600).unwrap();
+
+        user_repository
+            .expect_find_by_id_in_provider()
+            .returning(|_| Ok(Some(User::default())));
+        firebase_auth.expect_verify().returning(move |_| {
+            Ok(VerifyResult {
+                expires_at: Some(expires_at),
+                ..VerifyResult::default()
+            })
+        });
+        verified_token_cache.expect_get().returning(|_, _| None);
+        verified_token_cache
+            .expect_put()
+            .withf(move |token, _, exp, _| token == "xxx" && *exp == Some(expires_at))
+            .times(1)
+            .returning(|_, _, _, _| ());
+
+        let mut uc = UC::new(user_repository, firebase_auth);
+        uc.verified_token_cache = verified_token_cache;
+        assert!(uc
+            .execute("xxx", &AuditContext::default(), None)
             .await
             .is_ok());
     }
@@ -128,7 +414,9 @@ mod tests {
             .expect_verify()
             .returning(|_| Ok(VerifyResult::default()));
 
-        let usecase_result = UC::new(user_repository, firebase_auth).execute("xxx").await;
+        let usecase_result = UC::new(user_repository, firebase_auth)
+            .execute("xxx", &AuditContext::default(), None)
+            .await;
         assert!(usecase_result.is_err());
         assert_eq!(
             usecase_result.unwrap_err().to_string(),
@@ -148,11 +436,154 @@ mod tests {
             .expect_verify()
             .returning(|_| Err(VerifyError::TokenExpired));
 
-        let usecase_result = UC::new(user_repository, firebase_auth).execute("xxx").await;
+        let usecase_result = UC::new(user_repository, firebase_auth)
+            .execute("xxx", &AuditContext::default(), None)
+            .await;
         assert!(usecase_result.is_err());
         assert_eq!(
             usecase_result.unwrap_err().to_string(),
             "Token expired.".to_string()
         )
     }
+
+    #[tokio::test]
+    async fn verify_use_case_return_to_err_when_user_disabled() {
+        let mut user_repository = MockUserRepository::new();
+        let mut firebase_auth = MockFirebaseAuthDriver::new();
+
+        user_repository
+            .expect_find_by_id_in_provider()
+            .returning(|_| Ok(Some(User::default().with_status(UserStatus::Disabled))));
+        firebase_auth
+            .expect_verify()
+            .returning(|_| Ok(VerifyResult::default()));
+
+        let usecase_result = UC::new(user_repository, firebase_auth)
+            .execute("xxx", &AuditContext::default(), None)
+            .await;
+        assert!(matches!(
+            usecase_result,
+            Err(super::VerifyUseCaseError::UserDisabled(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_use_case_skip_audit_when_verify_error() {
+        let mut user_repository = MockUserRepository::new();
+        let mut firebase_auth = MockFirebaseAuthDriver::new();
+        let mut audit_log = MockAuditLog::new();
+
+        user_repository.expect_find_by_id_in_provider().never();
+        firebase_auth
+            .expect_verify()
+            .returning(|_| Err(VerifyError::TokenExpired));
+        audit_log.expect_record().never();
+
+        let context = AuditContext::new(Some("127.0.0.1".to_string()), None);
+        let usecase_result = UC::with_audit_log(user_repository, firebase_auth, audit_log)
+            .execute("xxx", &context, None)
+            .await;
+        assert!(matches!(
+            usecase_result,
+            Err(VerifyUseCaseError::VerifyFailed(VerifyError::TokenExpired))
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_use_case_record_failure_when_user_not_found() {
+        let mut user_repository = MockUserRepository::new();
+        let mut firebase_auth = MockFirebaseAuthDriver::new();
+        let mut audit_log = MockAuditLog::new();
+
+        user_repository
+            .expect_find_by_id_in_provider()
+            .returning(|_| Ok(None));
+        firebase_auth
+            .expect_verify()
+            .returning(|_| Ok(VerifyResult::default()));
+        audit_log
+            .expect_record()
+            .withf(|e| {
+                e.action == AuditAction::Authenticated
+                    && matches!(e.outcome, AuditOutcome::Failure(_))
+                    && e.context.ip_address == Some("127.0.0.1".to_string())
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let context = AuditContext::new(Some("127.0.0.1".to_string()), None);
+        let usecase_result = UC::with_audit_log(user_repository, firebase_auth, audit_log)
+            .execute("xxx", &context, None)
+            .await;
+        assert!(matches!(
+            usecase_result,
+            Err(VerifyUseCaseError::UserNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_use_case_return_to_err_when_identity_rate_limited() {
+        let user_repository = MockUserRepository::new();
+        let mut firebase_auth = MockFirebaseAuthDriver::new();
+        let mut audit_log = MockAuditLog::new();
+
+        firebase_auth.expect_verify().returning(|_| {
+            Ok(VerifyResult {
+                uid: LocalId::new("alice".to_string()),
+                ..VerifyResult::default()
+            })
+        });
+        audit_log.expect_record().never();
+        let mut uc = UC::with_audit_log(user_repository, firebase_auth, audit_log);
+        uc.rate_limit_store
+            .expect_increment()
+            .withf(|key, _, _| key == "verify:id:alice")
+            .returning(|_, _, _| Ok(2));
+
+        let result = uc
+            .execute(
+                "xxx",
+                &AuditContext::default(),
+                Some(IdentityRateLimit::new("verify", RateLimitRule::new(1, 60))),
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(VerifyUseCaseError::RateLimited { retry_after: 60 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_use_case_count_identity_of_cached_user() {
+        let user_repository = MockUserRepository::new();
+        let firebase_auth = MockFirebaseAuthDriver::new();
+        let mut verified_token_cache = MockVerifiedTokenCache::new();
+
+        verified_token_cache.expect_get().returning(|_, _| {
+            Some(User::new(
+                UserId::new("cached".to_string()),
+                Some(vec![LoginProvider::new(
+                    ProviderKind::Google,
+                    IdInProvider::new("alice".to_string()),
+                )]),
+            ))
+        });
+        let mut uc = UC::new(user_repository, firebase_auth);
+        uc.verified_token_cache = verified_token_cache;
+        uc.rate_limit_store
+            .expect_increment()
+            .withf(|key, _, _| key == "verify:id:alice")
+            .times(1)
+            .returning(|_, _, _| Ok(1));
+
+        assert!(uc
+            .execute(
+                "xxx",
+                &AuditContext::default(),
+                Some(IdentityRateLimit::new("verify", RateLimitRule::new(1, 60))),
+            )
+            .await
+            .is_ok());
+    }
 }
@@ -0,0 +1,274 @@
+use crate::effect::audit_log::AuditRecorder;
+use crate::effect::clock::{Clock, HaveClock};
+use crate::model::audit_event::{AuditAction, AuditContext, AuditOutcome};
+use crate::model::device_authorization::Scope;
+use crate::model::scoped_token::ScopedTokenValue;
+use crate::model::user::{User, UserId};
+use crate::repository::meta::{Repository, ResolveError};
+use crate::repository::scoped_token_repository::HaveScopedTokenRepository;
+use crate::repository::user_repository::HaveUserRepository;
+use async_trait::async_trait;
+use derive_more::Constructor;
+use thiserror::Error;
+use tracing::Span;
+
+/// The user a scoped token acts for, and the scope it was granted.
+#[derive(Debug, Constructor)]
+pub struct VerifyScopedTokenUseCaseResult {
+    pub user: User,
+    pub scope: Scope,
+}
+
+#[derive(Error, Debug)]
+pub enum VerifyScopedTokenUseCaseError {
+    #[error(transparent)]
+    ResolveError(#[from] ResolveError),
+    #[error("Scoped token is invalid.")]
+    InvalidToken,
+    #[error("Scoped token expired.")]
+    TokenExpired,
+    #[error("User is not found. {0}")]
+    UserNotFound(String),
+    #[error("User is disabled. {0}")]
+    UserDisabled(String),
+}
+
+async fn verify_scoped_token<T>(
+    uc: &T,
+    token: &str,
+) -> Result<VerifyScopedTokenUseCaseResult, VerifyScopedTokenUseCaseError>
+where
+    T: HaveScopedTokenRepository + HaveUserRepository + HaveClock + Sync + ?Sized,
+{
+    let scoped_token = match uc
+        .scoped_token_repository()
+        .resolve(&ScopedTokenValue::new(token.to_string()))
+        .await?
+    {
+        Some(t) => t,
+        None => return Err(VerifyScopedTokenUseCaseError::InvalidToken),
+    };
+    if scoped_token.is_expired(uc.clock().now_utc()) {
+        return Err(VerifyScopedTokenUseCaseError::TokenExpired);
+    }
+    match uc.user_repository().resolve(&scoped_token.user_id).await? {
+        Some(u) if u.is_disabled() => Err(VerifyScopedTokenUseCaseError::UserDisabled(u.id.0)),
+        Some(u) => Ok(VerifyScopedTokenUseCaseResult::new(u, scoped_token.scope)),
+        None => Err(VerifyScopedTokenUseCaseError::UserNotFound(
+            scoped_token.user_id.0,
+        )),
+    }
+}
+
+#[async_trait]
+pub trait VerifyScopedTokenUseCase:
+    HaveScopedTokenRepository + HaveUserRepository + AuditRecorder
+{
+    #[tracing::instrument(skip(self, token), fields(user_id))]
+    async fn execute(
+        &self,
+        token: &str,
+        context: &AuditContext,
+    ) -> Result<VerifyScopedTokenUseCaseResult, VerifyScopedTokenUseCaseError> {
+        match verify_scoped_token(self, token).await {
+            Ok(result) => {
+                Span::current().record("user_id", &result.user.id.0.as_str());
+                Ok(result)
+            }
+            // Not audited, so that a flood of garbage tokens writes nothing.
+            Err(e @ VerifyScopedTokenUseCaseError::InvalidToken) => Err(e),
+            Err(e) => {
+                let actor = match &e {
+                    VerifyScopedTokenUseCaseError::UserDisabled(id)
+                    | VerifyScopedTokenUseCaseError::UserNotFound(id) => {
+                        Some(UserId::new(id.clone()))
+                    }
+                    _ => None,
+                };
+                self.audit_or_warn(
+                    context,
+                    actor,
+                    AuditAction::Authenticated,
+                    None,
+                    AuditOutcome::Failure(e.to_string()),
+                )
+                .await;
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<T: HaveScopedTokenRepository + HaveUserRepository + AuditRecorder> VerifyScopedTokenUseCase
+    for T
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VerifyScopedTokenUseCase, VerifyScopedTokenUseCaseError};
+    use crate::effect::audit_log::{HaveAuditLog, MockAuditLog};
+    use crate::effect::clock::{HaveClock, MockClock};
+    use crate::effect::id_generator::{HaveIdGenerator, MockIdGenerator};
+    use crate::model::audit_event::AuditContext;
+    use crate::model::device_authorization::Scope;
+    use crate::model::scoped_token::{ScopedToken, SCOPED_TOKEN_EXPIRES_IN};
+    use crate::model::user::{User, UserId, UserStatus};
+    use crate::repository::scoped_token_repository::{
+        HaveScopedTokenRepository, MockScopedTokenRepository,
+    };
+    use crate::repository::user_repository::{HaveUserRepository, MockUserRepository};
+    use time::{Duration, OffsetDateTime};
+
+    struct UC {
+        token_repo: MockScopedTokenRepository,
+        user_repo: MockUserRepository,
+        clock: MockClock,
+        audit_log: MockAuditLog,
+        id_generator: MockIdGenerator,
+    }
+
+    impl UC {
+        fn new(
+            token_repo: MockScopedTokenRepository,
+            user_repo: MockUserRepository,
+            clock: MockClock,
+        ) -> Self {
+            let mut audit_log = MockAuditLog::new();
+            audit_log.expect_record().returning(|_| Ok(()));
+            let mut id_generator = MockIdGenerator::new();
+            id_generator
+                .expect_generate()
+                .returning(|| "event".to_string());
+            UC {
+                token_repo,
+                user_repo,
+                clock,
+                audit_log,
+                id_generator,
+            }
+        }
+    }
+
+    impl HaveScopedTokenRepository for UC {
+        type ScopedTokenRepository = MockScopedTokenRepository;
+        fn scoped_token_repository(&self) -> &Self::ScopedTokenRepository {
+            &self.token_repo
+        }
+    }
+
+    impl HaveUserRepository for UC {
+        type UserRepository = MockUserRepository;
+        fn user_repository(&self) -> &Self::UserRepository {
+            &self.user_repo
+        }
+    }
+
+    impl HaveClock for UC {
+        type Clock = MockClock;
+        fn clock(&self) -> &Self::Clock {
+            &self.clock
+        }
+    }
+
+    impl HaveAuditLog for UC {
+        type AuditLog = MockAuditLog;
+        fn audit_log(&self) -> &Self::AuditLog {
+            &self.audit_log
+        }
+    }
+
+    impl HaveIdGenerator for UC {
+        type IdGenerator = MockIdGenerator;
+        fn id_generator(&self) -> &Self::IdGenerator {
+            &self.id_generator
+        }
+    }
+
+    fn token() -> ScopedToken {
+        ScopedToken::issue(
+            "xxxx".to_string(),
+            UserId::new("foo".to_string()),
+            Scope::new("profile".to_string()),
+            OffsetDateTime::UNIX_EPOCH,
+        )
+    }
+
+    fn clock_at(seconds: i64) -> MockClock {
+        let mut clock = MockClock::new();
+        clock
+            .expect_now_utc()
+            .returning(move || OffsetDateTime::UNIX_EPOCH + Duration::seconds(seconds));
+        clock
+    }
+
+    #[tokio::test]
+    async fn verify_scoped_token_return_to_user() {
+        let mut token_repo = MockScopedTokenRepository::new();
+        let mut user_repo = MockUserRepository::new();
+        token_repo.expect_resolve().returning(|_| Ok(Some(token())));
+        user_repo
+            .expect_resolve()
+            .returning(|id| Ok(Some(User::new(id.clone(), None))));
+
+        let result = UC::new(token_repo, user_repo, clock_at(0))
+            .execute("mtk_xxxx", &AuditContext::default())
+            .await
+            .unwrap();
+        assert_eq!(result.user.id, UserId::new("foo".to_string()));
+        assert_eq!(result.scope, Scope::new("profile".to_string()));
+    }
+
+    #[tokio::test]
+    async fn verify_scoped_token_is_err_when_unknown() {
+        let mut token_repo = MockScopedTokenRepository::new();
+        token_repo.expect_resolve().returning(|_| Ok(None));
+        let mut uc = UC::new(token_repo, MockUserRepository::new(), clock_at(0));
+        uc.audit_log = MockAuditLog::new();
+        uc.audit_log.expect_record().never();
+
+        let result = uc.execute("mtk_xxxx", &AuditContext::default()).await;
+        assert!(matches!(
+            result,
+            Err(VerifyScopedTokenUseCaseError::InvalidToken)
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_scoped_token_is_err_when_expired() {
+        let mut token_repo = MockScopedTokenRepository::new();
+        token_repo.expect_resolve().returning(|_| Ok(Some(token())));
+
+        let result = UC::new(
+            token_repo,
+            MockUserRepository::new(),
+            clock_at(SCOPED_TOKEN_EXPIRES_IN),
+        )
+        .execute("mtk_xxxx", &AuditContext::default())
+        .await;
+        assert!(matches!(
+            result,
+            Err(VerifyScopedTokenUseCaseError::TokenExpired)
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_scoped_token_is_err_when_user_disabled() {
+        let mut token_repo = MockScopedTokenRepository::new();
+        let mut user_repo = MockUserRepository::new();
+        token_repo.expect_resolve().returning(|_| Ok(Some(token())));
+        user_repo.expect_resolve().returning(|id| {
+            Ok(Some(
+                User::new(id.clone(), None).with_status(UserStatus::Disabled),
+            ))
+        });
+
+        let result = UC::new(token_repo, user_repo, clock_at(0))
+            .execute("mtk_xxxx", &AuditContext::default())
+            .await;
+        assert!(matches!(
+            result,
+            Err(VerifyScopedTokenUseCaseError::UserDisabled(_))
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+import { z } from "zod";
 import { Config, UnknownError } from "./shared";
 
 export const buildUnknownError = (e: any): UnknownError => {
@@ -19,3 +20,13 @@ export const buildHeader = (config: Config): Record<string, string> => {
     Authorization: `Bearer ${config.authorizationToken}`,
   };
 };
+
+/** Members of the problem details (RFC 7807) every error response carries. */
+export const problemSchema = z.object({
+  type: z.string(),
+  title: z.string(),
+  status: z.number(),
+});
+
+export const problemType = (name: string): string =>
+  `urn:matsunoki:problem:${name}`;
@@ -1,7 +1,13 @@
 import { Err, Ok, Result } from "ts-results";
 import { z } from "zod";
 import { Config, UnknownError } from "./shared";
-import { buildURL, buildHeader, buildUnknownError } from "./internal";
+import {
+  buildURL,
+  buildHeader,
+  buildUnknownError,
+  problemSchema,
+  problemType,
+} from "./internal";
 
 export type SignUpError = {
   kind: "AlreadyExist";
@@ -38,12 +44,8 @@ export const responseErrorHandler = (
   a: any
 ): Result<never, SignUpError | UnknownError> => {
   try {
-    const schema = z.object({
-      kind: z.string(),
-      key: z.string(),
-    });
-    const parsed = schema.parse(a);
-    if (parsed.kind === "already_exist") {
+    const parsed = problemSchema.parse(a);
+    if (parsed.type === problemType("already_exist")) {
       return Err({ kind: "AlreadyExist" });
     }
     return Err(buildUnknownError(a));
@@ -16,11 +16,16 @@ describe("responseHandler", () => {
 
 describe("responseErrorHandler", () => {
   it("error", () => {
-    expect(
-      responseErrorHandler({ kind: "user_not_found", key: "" }).val
-    ).toStrictEqual({ kind: "UserNotFound" });
-    expect(
-      responseErrorHandler({ kind: "verify_failed", key: "" }).val
-    ).toStrictEqual({ kind: "VerifyError" });
+    const problem = (name: string) => ({
+      type: `urn:matsunoki:problem:${name}`,
+      title: "",
+      status: 400,
+    });
+    expect(responseErrorHandler(problem("user_not_found")).val).toStrictEqual({
+      kind: "UserNotFound",
+    });
+    expect(responseErrorHandler(problem("verify_failed")).val).toStrictEqual({
+      kind: "VerifyError",
+    });
   });
 });
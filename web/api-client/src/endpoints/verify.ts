@@ -1,7 +1,13 @@
 import { Err, Ok, Result } from "ts-results";
 import { z } from "zod";
 import { Config, UnknownError } from "./shared";
-import { buildURL, buildHeader, buildUnknownError } from "./internal";
+import {
+  buildURL,
+  buildHeader,
+  buildUnknownError,
+  problemSchema,
+  problemType,
+} from "./internal";
 
 export type VerifyError =
   | {
@@ -39,15 +45,11 @@ export const responseErrorHandler = (
   a: any
 ): Result<never, VerifyError | UnknownError> => {
   try {
-    const schema = z.object({
-      kind: z.string(),
-      key: z.string(),
-    });
-    const parsed = schema.parse(a);
-    switch (parsed.kind) {
-      case "verify_failed":
+    const parsed = problemSchema.parse(a);
+    switch (parsed.type) {
+      case problemType("verify_failed"):
         return Err({ kind: "VerifyError" });
-      case "user_not_found":
+      case problemType("user_not_found"):
         return Err({ kind: "UserNotFound" });
       default:
         return Err(buildUnknownError(parsed));
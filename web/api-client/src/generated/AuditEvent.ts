@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AuditAction } from "./AuditAction";
+import type { AuditEventId } from "./AuditEventId";
+import type { AuditOutcome } from "./AuditOutcome";
+import type { UserId } from "./UserId";
+
+export type AuditEvent = { id: AuditEventId, actor: UserId | null, action: AuditAction, target: string | null, outcome: AuditOutcome, occurred_at: string, ip_address: string | null, user_agent: string | null, };
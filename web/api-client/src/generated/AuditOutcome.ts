@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Whether the audited action took effect. A failure carries the reason it was rejected.
+ */
+export type AuditOutcome = { "status": "Success" } | { "status": "Failure", "reason": string };
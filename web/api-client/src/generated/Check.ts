@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CheckStatus } from "./CheckStatus";
+
+export type Check = { status: CheckStatus, 
+/**
+ * Why the check failed.
+ */
+detail?: string, };
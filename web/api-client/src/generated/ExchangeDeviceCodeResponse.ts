@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Scope } from "./Scope";
+import type { ScopedTokenValue } from "./ScopedTokenValue";
+
+export type ExchangeDeviceCodeResponse = { token_type: "Bearer", access_token: ScopedTokenValue, expires_in: number, scope: Scope, };
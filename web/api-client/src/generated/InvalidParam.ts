@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A request parameter a `profile_validation_error` rejected.
+ */
+export type InvalidParam = { 
+/**
+ * Name of the parameter in the request body.
+ */
+name: string, 
+/**
+ * The check that failed, stable across languages.
+ */
+code: string, 
+/**
+ * Why the value is rejected, in the language of the response.
+ */
+reason: string, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DeviceCode } from "./DeviceCode";
+import type { UserCode } from "./UserCode";
+
+export type IssueDeviceCodeResponse = { verification_uri: string, verification_uri_complete: string, device_code: DeviceCode, user_code: UserCode, expires_in: number, interval: number, };
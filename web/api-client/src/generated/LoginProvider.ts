@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IdInProvider } from "./IdInProvider";
+import type { ProviderKind } from "./ProviderKind";
+
+export type LoginProvider = { kind: ProviderKind, id_in_provider: IdInProvider, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Error codes of the OAuth 2.0 token endpoint (RFC 6749 section 5.2, RFC 8628 section 3.5).
+ */
+export type OAuthErrorKind = "authorization_pending" | "slow_down" | "access_denied" | "expired_token" | "invalid_grant" | "unsupported_grant_type";
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BadRequestKind } from "./BadRequestKind";
+import type { InvalidParam } from "./InvalidParam";
+import type { OAuthErrorKind } from "./OAuthErrorKind";
+
+/**
+ * Problem details (RFC 7807), the body of every error response.
+ *
+ * `title`, `detail` and the `reason` of `invalid_params` are written in the language
+ * `middleware::problem` picked for the request (see `i18n`), which also fills in `instance` and
+ * `request_id`. The members after them are extensions set by some problems only: `kind` and
+ * `key` by bad requests, `error` and `error_description` by the token endpoint, as RFC 6749
+ * expects, `retry_after` by rate limited requests and `invalid_params` by profile validation.
+ */
+export type Problem = { type: string, title: string, status: number, detail?: string, instance?: string, 
+/**
+ * Also sent as `X-Request-Id`; quote it when reporting an internal server error.
+ */
+request_id?: string, kind?: BadRequestKind, key?: string, error?: OAuthErrorKind, error_description?: string, retry_after?: number, invalid_params?: Array<InvalidParam>, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Avatar } from "./Avatar";
+import type { DisplayName } from "./DisplayName";
+import type { Locale } from "./Locale";
+import type { UserName } from "./UserName";
+
+export type Profile = { name: UserName, display_name: DisplayName, avatar: Avatar, 
+/**
+ * Chosen by the user; `None` leaves the language to the `Accept-Language` of each request.
+ */
+locale: Locale | null, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Check } from "./Check";
+import type { CheckStatus } from "./CheckStatus";
+
+export type ReadinessResponse = { 
+/**
+ * `fail` when any check failed.
+ */
+status: CheckStatus, checks: { [key in string]?: Check }, };
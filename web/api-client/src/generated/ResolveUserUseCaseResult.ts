@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { User } from "./User";
+import type { UserProfile } from "./UserProfile";
+
+export type ResolveUserUseCaseResult = { user: User, user_profile: UserProfile | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Space-delimited scope string as defined in RFC 6749 section 3.3.
+ */
+export type Scope = string;
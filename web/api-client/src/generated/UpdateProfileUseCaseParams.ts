@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Locale } from "./Locale";
+
+export type UpdateProfileUseCaseParams = { user_name: string, display_name: string, avatar_url: string, locale: Locale | null, };
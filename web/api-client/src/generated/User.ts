@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LoginProvider } from "./LoginProvider";
+import type { Role } from "./Role";
+import type { UserId } from "./UserId";
+import type { UserStatus } from "./UserStatus";
+
+export type User = { id: UserId, providers: Array<LoginProvider>, roles: Array<Role>, status: UserStatus, };
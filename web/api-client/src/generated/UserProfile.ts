@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Profile } from "./Profile";
+import type { UserProfileId } from "./UserProfileId";
+
+export type UserProfile = { id: UserProfileId, profile: Profile, };
@@ -0,0 +1,50 @@
+// This file was generated by `account-http` tests. Do not edit this file manually.
+
+export type { ApproveDeviceCodeUseCaseParams } from "./ApproveDeviceCodeUseCaseParams";
+export type { AuditAction } from "./AuditAction";
+export type { AuditEvent } from "./AuditEvent";
+export type { AuditEventId } from "./AuditEventId";
+export type { AuditOutcome } from "./AuditOutcome";
+export type { Avatar } from "./Avatar";
+export type { BadRequestKind } from "./BadRequestKind";
+export type { Check } from "./Check";
+export type { CheckStatus } from "./CheckStatus";
+export type { DeviceCode } from "./DeviceCode";
+export type { DisplayName } from "./DisplayName";
+export type { ExchangeDeviceCodeResponse } from "./ExchangeDeviceCodeResponse";
+export type { HealthCheckResponse } from "./HealthCheckResponse";
+export type { IdInProvider } from "./IdInProvider";
+export type { InvalidParam } from "./InvalidParam";
+export type { IssueDeviceCodeResponse } from "./IssueDeviceCodeResponse";
+export type { ListAuditEventsUseCaseResult } from "./ListAuditEventsUseCaseResult";
+export type { LivenessResponse } from "./LivenessResponse";
+export type { Locale } from "./Locale";
+export type { LoginProvider } from "./LoginProvider";
+export type { OAuthErrorKind } from "./OAuthErrorKind";
+export type { Problem } from "./Problem";
+export type { Profile } from "./Profile";
+export type { ProviderKind } from "./ProviderKind";
+export type { ReadinessResponse } from "./ReadinessResponse";
+export type { ResetUserNameParams } from "./ResetUserNameParams";
+export type { ResetUserNameUseCaseResult } from "./ResetUserNameUseCaseResult";
+export type { ResolveProfileUseCaseResult } from "./ResolveProfileUseCaseResult";
+export type { ResolveUserUseCaseResult } from "./ResolveUserUseCaseResult";
+export type { Role } from "./Role";
+export type { Scope } from "./Scope";
+export type { ScopedTokenValue } from "./ScopedTokenValue";
+export type { SearchUsersUseCaseResult } from "./SearchUsersUseCaseResult";
+export type { SignUpUseCaseResult } from "./SignUpUseCaseResult";
+export type { UpdateProfileUseCaseParams } from "./UpdateProfileUseCaseParams";
+export type { UpdateProfileUseCaseResult } from "./UpdateProfileUseCaseResult";
+export type { UpdateRoleUseCaseParams } from "./UpdateRoleUseCaseParams";
+export type { UpdateRoleUseCaseResult } from "./UpdateRoleUseCaseResult";
+export type { UpdateUserStatusUseCaseParams } from "./UpdateUserStatusUseCaseParams";
+export type { UpdateUserStatusUseCaseResult } from "./UpdateUserStatusUseCaseResult";
+export type { User } from "./User";
+export type { UserCode } from "./UserCode";
+export type { UserId } from "./UserId";
+export type { UserName } from "./UserName";
+export type { UserProfile } from "./UserProfile";
+export type { UserProfileId } from "./UserProfileId";
+export type { UserStatus } from "./UserStatus";
+export type { VerifyUseCaseResult } from "./VerifyUseCaseResult";
@@ -1 +1,2 @@
 export * from "./endpoints/index";
+export * from "./generated/index";